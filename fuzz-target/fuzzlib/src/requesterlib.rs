@@ -30,6 +30,10 @@ pub fn req_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
         aead_algo: SpdmAeadAlgo::AES_256_GCM,
         req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
         key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        secured_message_version: [
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+        ],
         ..Default::default()
     };
 
@@ -79,6 +83,8 @@ pub fn req_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
         my_cert_chain: None,
         peer_cert_chain_data: Some(peer_cert_chain_data),
         peer_cert_chain_root_hash: None,
+        psk: None,
+        psk_hint: None,
     };
 
     (config_info, provision_info)
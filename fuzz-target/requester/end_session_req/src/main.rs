@@ -2,10 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{
-    spdmlib::session::{SpdmSession, SpdmSessionState},
-    *,
-};
+use fuzzlib::{spdmlib::session::SpdmSessionState, *};
 
 fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
     let (rsp_config_info, rsp_provision_info) = rsp_create_info();
@@ -53,7 +50,7 @@ fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
 
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -63,7 +60,8 @@ fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
         );
         requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
 
-        let _ = requester.send_receive_spdm_end_session(4294901758);
+        let _ = requester
+            .send_receive_spdm_end_session(4294901758, SpdmEndSessionRequestAttributes::empty());
     }
 
     {
@@ -105,7 +103,7 @@ fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
 
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -115,7 +113,8 @@ fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
         );
         requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
 
-        let _ = requester.send_receive_spdm_end_session(4294901758);
+        let _ = requester
+            .send_receive_spdm_end_session(4294901758, SpdmEndSessionRequestAttributes::empty());
     }
 }
 
@@ -2,10 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{
-    spdmlib::session::{SpdmSession, SpdmSessionState},
-    *,
-};
+use fuzzlib::{spdmlib::session::SpdmSessionState, *};
 
 fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
     let (rsp_config_info, rsp_provision_info) = rsp_create_info();
@@ -50,7 +47,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         responder.common.reset_runtime_info();
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -92,7 +89,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         requester.common.reset_runtime_info();
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -146,7 +143,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         responder.common.reset_runtime_info();
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -186,7 +183,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         requester.common.reset_runtime_info();
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -232,7 +229,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         responder.common.reset_runtime_info();
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -272,7 +269,7 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
 
         requester.common.reset_runtime_info();
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
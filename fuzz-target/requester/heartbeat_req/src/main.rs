@@ -2,10 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{
-    spdmlib::session::{SpdmSession, SpdmSessionState},
-    *,
-};
+use fuzzlib::{spdmlib::session::SpdmSessionState, *};
 
 fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
     let (rsp_config_info, rsp_provision_info) = rsp_create_info();
@@ -31,7 +28,7 @@ fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
 
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
 
@@ -54,7 +51,7 @@ fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
 
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -84,7 +81,7 @@ fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
 
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
 
@@ -107,7 +104,7 @@ fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
 
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -2,10 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{
-    spdmlib::session::{SpdmSession, SpdmSessionState},
-    *,
-};
+use fuzzlib::{spdmlib::session::SpdmSessionState, *};
 
 fn fuzz_send_receive_spdm_key_update(fuzzdata: &[u8]) {
     let (rsp_config_info, rsp_provision_info) = rsp_create_info();
@@ -50,7 +47,7 @@ fn fuzz_send_receive_spdm_key_update(fuzzdata: &[u8]) {
             req_config_info,
             req_provision_info,
         );
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -99,7 +96,7 @@ fn fuzz_send_receive_spdm_key_update(fuzzdata: &[u8]) {
             req_provision_info1,
         );
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -147,7 +144,7 @@ fn fuzz_send_receive_spdm_key_update(fuzzdata: &[u8]) {
             req_provision_info2,
         );
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
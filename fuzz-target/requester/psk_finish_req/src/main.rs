@@ -2,10 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{
-    spdmlib::session::{SpdmSession, SpdmSessionState},
-    *,
-};
+use fuzzlib::{spdmlib::session::SpdmSessionState, *};
 
 fn fuzz_send_receive_spdm_psk_finish(fuzzdata: &[u8]) {
     let (rsp_config_info, rsp_provision_info) = rsp_create_info();
@@ -57,7 +54,7 @@ fn fuzz_send_receive_spdm_psk_finish(fuzzdata: &[u8]) {
     requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
     requester.common.negotiate_info.req_asym_sel = SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048;
 
-    requester.common.session = [SpdmSession::new(); 4];
+    requester.common.session = Default::default();
     requester.common.session[0].setup(4294901758).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -3,10 +3,7 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use fuzzlib::{
-    spdmlib::{
-        config,
-        session::{SpdmSession, SpdmSessionState},
-    },
+    spdmlib::{config, session::SpdmSessionState},
     *,
 };
 
@@ -37,7 +34,7 @@ fn fuzz_handle_spdm_psk_finish(data: &[u8]) {
         );
 
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -63,7 +60,7 @@ fn fuzz_handle_spdm_psk_finish(data: &[u8]) {
             provision_info2,
         );
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -92,7 +89,7 @@ fn fuzz_handle_spdm_psk_finish(data: &[u8]) {
             provision_info3,
         );
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_512;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -117,7 +114,7 @@ fn fuzz_handle_spdm_psk_finish(data: &[u8]) {
         );
 
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA3_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
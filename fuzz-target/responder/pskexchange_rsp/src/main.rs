@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use fuzzlib::{spdmlib::session::SpdmSession, *};
+use fuzzlib::*;
 
 fn fuzz_handle_spdm_psk_exchange(data: &[u8]) {
     let (config_info, provision_info) = rsp_create_info();
@@ -63,7 +63,7 @@ fn fuzz_handle_spdm_psk_exchange(data: &[u8]) {
         context.common.negotiate_info.key_schedule_sel = SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE;
 
         context.common.reset_runtime_info();
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[1].setup(4294901758).unwrap();
         context.common.session[2].setup(4294901758).unwrap();
@@ -0,0 +1,926 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::{MctpMessageHeader, MctpMessageType};
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const MCTP_CONTROL_INSTANCE_ID_MASK: u8 = 0x1F;
+pub const MCTP_UUID_SIZE: usize = 16;
+pub const MCTP_MAX_VERSION_COUNT: usize = 4;
+pub const MCTP_MAX_MESSAGE_TYPE_COUNT: usize = 8;
+pub const MCTP_MAX_CONTROL_MESSAGE_SIZE: usize = 64;
+
+/// Message type number used in Get MCTP Version Support for the base specification.
+pub const MCTP_BASE_SPECIFICATION_MESSAGE_TYPE: u8 = 0xFF;
+
+enum_builder! {
+    @U8
+    EnumName: MctpControlCommandCode;
+    EnumVal{
+        MctpControlSetEndpointId => 0x01,
+        MctpControlGetEndpointId => 0x02,
+        MctpControlGetEndpointUuid => 0x03,
+        MctpControlGetVersionSupport => 0x04,
+        MctpControlGetMessageTypeSupport => 0x05
+    }
+}
+
+enum_builder! {
+    @U8
+    EnumName: MctpControlCompletionCode;
+    EnumVal{
+        MctpControlSuccess => 0x00,
+        MctpControlError => 0x01,
+        MctpControlErrorInvalidData => 0x02,
+        MctpControlErrorInvalidLength => 0x03,
+        MctpControlErrorNotReady => 0x04,
+        MctpControlErrorUnsupportedCmd => 0x05,
+        // Get MCTP Version Support
+        MctpControlMessageTypeNotSupported => 0x80
+    }
+}
+
+enum_builder! {
+    @U8
+    EnumName: MctpControlSetEndpointIdOperation;
+    EnumVal{
+        MctpControlSetEid => 0x00,
+        MctpControlForceEid => 0x01,
+        MctpControlResetEid => 0x02,
+        MctpControlSetDiscoveredFlag => 0x03
+    }
+}
+
+/// Rq, D and Instance ID, followed by the command code.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MctpControlHeader {
+    pub request: bool,
+    pub datagram: bool,
+    pub instance_id: u8,
+    pub command_code: MctpControlCommandCode,
+}
+
+impl Codec for MctpControlHeader {
+    fn encode(&self, bytes: &mut Writer) {
+        let mut value = self.instance_id & MCTP_CONTROL_INSTANCE_ID_MASK;
+        if self.request {
+            value |= 0x80;
+        }
+        if self.datagram {
+            value |= 0x40;
+        }
+        value.encode(bytes);
+        self.command_code.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlHeader> {
+        let value = u8::read(r)?;
+        let command_code = MctpControlCommandCode::read(r)?;
+        Some(MctpControlHeader {
+            request: (value & 0x80) != 0,
+            datagram: (value & 0x40) != 0,
+            instance_id: value & MCTP_CONTROL_INSTANCE_ID_MASK,
+            command_code,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlSetEndpointIdRequest {
+    pub operation: MctpControlSetEndpointIdOperation,
+    pub eid: u8,
+}
+
+impl Codec for MctpControlSetEndpointIdRequest {
+    fn encode(&self, bytes: &mut Writer) {
+        self.operation.encode(bytes);
+        self.eid.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlSetEndpointIdRequest> {
+        let operation = MctpControlSetEndpointIdOperation::read(r)?;
+        let eid = u8::read(r)?;
+        Some(MctpControlSetEndpointIdRequest { operation, eid })
+    }
+}
+
+/// Response fields follow the completion code only on success.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlSetEndpointIdResponse {
+    pub completion_code: MctpControlCompletionCode,
+    pub eid_assignment_rejected: bool,
+    pub eid_setting: u8,
+    pub eid_pool_size: u8,
+}
+
+impl Codec for MctpControlSetEndpointIdResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.completion_code.encode(bytes);
+        if self.completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return;
+        }
+        let status: u8 = if self.eid_assignment_rejected {
+            0x10
+        } else {
+            0x00
+        };
+        status.encode(bytes);
+        self.eid_setting.encode(bytes);
+        self.eid_pool_size.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlSetEndpointIdResponse> {
+        let completion_code = MctpControlCompletionCode::read(r)?;
+        if completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return Some(MctpControlSetEndpointIdResponse {
+                completion_code,
+                ..Default::default()
+            });
+        }
+        let status = u8::read(r)?;
+        let eid_setting = u8::read(r)?;
+        let eid_pool_size = u8::read(r)?;
+        Some(MctpControlSetEndpointIdResponse {
+            completion_code,
+            eid_assignment_rejected: (status & 0x30) == 0x10,
+            eid_setting,
+            eid_pool_size,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlGetEndpointIdResponse {
+    pub completion_code: MctpControlCompletionCode,
+    pub eid: u8,
+    pub endpoint_type: u8,
+    pub medium_specific: u8,
+}
+
+impl Codec for MctpControlGetEndpointIdResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.completion_code.encode(bytes);
+        if self.completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return;
+        }
+        self.eid.encode(bytes);
+        self.endpoint_type.encode(bytes);
+        self.medium_specific.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlGetEndpointIdResponse> {
+        let completion_code = MctpControlCompletionCode::read(r)?;
+        if completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return Some(MctpControlGetEndpointIdResponse {
+                completion_code,
+                ..Default::default()
+            });
+        }
+        let eid = u8::read(r)?;
+        let endpoint_type = u8::read(r)?;
+        let medium_specific = u8::read(r)?;
+        Some(MctpControlGetEndpointIdResponse {
+            completion_code,
+            eid,
+            endpoint_type,
+            medium_specific,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlGetEndpointUuidResponse {
+    pub completion_code: MctpControlCompletionCode,
+    pub uuid: [u8; MCTP_UUID_SIZE],
+}
+
+impl Codec for MctpControlGetEndpointUuidResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.completion_code.encode(bytes);
+        if self.completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return;
+        }
+        for d in self.uuid.iter() {
+            d.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlGetEndpointUuidResponse> {
+        let completion_code = MctpControlCompletionCode::read(r)?;
+        let mut uuid = [0u8; MCTP_UUID_SIZE];
+        if completion_code == MctpControlCompletionCode::MctpControlSuccess {
+            for d in uuid.iter_mut() {
+                *d = u8::read(r)?;
+            }
+        }
+        Some(MctpControlGetEndpointUuidResponse {
+            completion_code,
+            uuid,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlGetVersionSupportRequest {
+    pub message_type: u8,
+}
+
+impl Codec for MctpControlGetVersionSupportRequest {
+    fn encode(&self, bytes: &mut Writer) {
+        self.message_type.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlGetVersionSupportRequest> {
+        let message_type = u8::read(r)?;
+        Some(MctpControlGetVersionSupportRequest { message_type })
+    }
+}
+
+/// Version number entry, each field is BCD encoded with 0xF in the high nibble, for example F1 F3 F1 00 is 1.3.1.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MctpVersionNumber {
+    pub major: u8,
+    pub minor: u8,
+    pub update: u8,
+    pub alpha: u8,
+}
+
+impl Codec for MctpVersionNumber {
+    fn encode(&self, bytes: &mut Writer) {
+        self.major.encode(bytes);
+        self.minor.encode(bytes);
+        self.update.encode(bytes);
+        self.alpha.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpVersionNumber> {
+        let major = u8::read(r)?;
+        let minor = u8::read(r)?;
+        let update = u8::read(r)?;
+        let alpha = u8::read(r)?;
+        Some(MctpVersionNumber {
+            major,
+            minor,
+            update,
+            alpha,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlGetVersionSupportResponse {
+    pub completion_code: MctpControlCompletionCode,
+    pub version_count: u8,
+    pub versions: [MctpVersionNumber; MCTP_MAX_VERSION_COUNT],
+}
+
+impl Codec for MctpControlGetVersionSupportResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.completion_code.encode(bytes);
+        if self.completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return;
+        }
+        self.version_count.encode(bytes);
+        for version in self.versions.iter().take(self.version_count as usize) {
+            version.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlGetVersionSupportResponse> {
+        let completion_code = MctpControlCompletionCode::read(r)?;
+        if completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return Some(MctpControlGetVersionSupportResponse {
+                completion_code,
+                ..Default::default()
+            });
+        }
+        let version_count = u8::read(r)?;
+        if version_count as usize > MCTP_MAX_VERSION_COUNT {
+            return None;
+        }
+        let mut versions = [MctpVersionNumber::default(); MCTP_MAX_VERSION_COUNT];
+        for version in versions.iter_mut().take(version_count as usize) {
+            *version = MctpVersionNumber::read(r)?;
+        }
+        Some(MctpControlGetVersionSupportResponse {
+            completion_code,
+            version_count,
+            versions,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MctpControlGetMessageTypeSupportResponse {
+    pub completion_code: MctpControlCompletionCode,
+    pub message_type_count: u8,
+    pub message_types: [MctpMessageType; MCTP_MAX_MESSAGE_TYPE_COUNT],
+}
+
+impl MctpControlGetMessageTypeSupportResponse {
+    pub fn is_supported(&self, message_type: MctpMessageType) -> bool {
+        self.message_types
+            .iter()
+            .take(self.message_type_count as usize)
+            .any(|t| *t == message_type)
+    }
+}
+
+impl Codec for MctpControlGetMessageTypeSupportResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.completion_code.encode(bytes);
+        if self.completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return;
+        }
+        self.message_type_count.encode(bytes);
+        for message_type in self
+            .message_types
+            .iter()
+            .take(self.message_type_count as usize)
+        {
+            message_type.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpControlGetMessageTypeSupportResponse> {
+        let completion_code = MctpControlCompletionCode::read(r)?;
+        if completion_code != MctpControlCompletionCode::MctpControlSuccess {
+            return Some(MctpControlGetMessageTypeSupportResponse {
+                completion_code,
+                ..Default::default()
+            });
+        }
+        let message_type_count = u8::read(r)?;
+        if message_type_count as usize > MCTP_MAX_MESSAGE_TYPE_COUNT {
+            return None;
+        }
+        let mut message_types = [MctpMessageType::default(); MCTP_MAX_MESSAGE_TYPE_COUNT];
+        for message_type in message_types.iter_mut().take(message_type_count as usize) {
+            *message_type = MctpMessageType::read(r)?;
+        }
+        Some(MctpControlGetMessageTypeSupportResponse {
+            completion_code,
+            message_type_count,
+            message_types,
+        })
+    }
+}
+
+/// MCTP 1.3.1, reported for the base specification and the control protocol.
+pub const MCTP_BASE_VERSION: MctpVersionNumber = MctpVersionNumber {
+    major: 0xF1,
+    minor: 0xF3,
+    update: 0xF1,
+    alpha: 0x00,
+};
+
+/// Version 1.0.0, reported for the other supported message types.
+pub const MCTP_MESSAGE_TYPE_VERSION: MctpVersionNumber = MctpVersionNumber {
+    major: 0xF1,
+    minor: 0xF0,
+    update: 0xF0,
+    alpha: 0x00,
+};
+
+/// Endpoint side of the MCTP control protocol.
+#[derive(Debug, Copy, Clone)]
+pub struct MctpControlResponder {
+    pub eid: u8,
+    pub uuid: [u8; MCTP_UUID_SIZE],
+    pub discovered: bool,
+    message_type_count: usize,
+    message_types: [MctpMessageType; MCTP_MAX_MESSAGE_TYPE_COUNT],
+}
+
+impl MctpControlResponder {
+    /// message_types lists the types besides MCTP control, for example SPDM and secured messages.
+    pub fn new(eid: u8, uuid: [u8; MCTP_UUID_SIZE], message_types: &[MctpMessageType]) -> Self {
+        let message_type_count = message_types.len().min(MCTP_MAX_MESSAGE_TYPE_COUNT);
+        let mut responder = MctpControlResponder {
+            eid,
+            uuid,
+            discovered: false,
+            message_type_count,
+            message_types: [MctpMessageType::default(); MCTP_MAX_MESSAGE_TYPE_COUNT],
+        };
+        responder.message_types[..message_type_count]
+            .copy_from_slice(&message_types[..message_type_count]);
+        responder
+    }
+
+    fn get_message_types(&self) -> &[MctpMessageType] {
+        &self.message_types[..self.message_type_count]
+    }
+
+    /// Process a control message (starting with the MCTP message type) and write the response.
+    pub fn process_message(&mut self, request: &[u8], response: &mut [u8]) -> SpdmResult<usize> {
+        let mut reader = Reader::init(request);
+        match MctpMessageHeader::read(&mut reader) {
+            Some(mctp_header)
+                if mctp_header.r#type == MctpMessageType::MctpMessageTypeMctpControl => {}
+            Some(_) => return spdm_result_err!(EINVAL),
+            None => return spdm_result_err!(EIO),
+        }
+        let control_header = MctpControlHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if !control_header.request {
+            return spdm_result_err!(EINVAL);
+        }
+
+        let mut writer = Writer::init(response);
+        MctpMessageHeader {
+            r#type: MctpMessageType::MctpMessageTypeMctpControl,
+        }
+        .encode(&mut writer);
+        MctpControlHeader {
+            request: false,
+            ..control_header
+        }
+        .encode(&mut writer);
+
+        match control_header.command_code {
+            MctpControlCommandCode::MctpControlSetEndpointId => {
+                self.write_set_endpoint_id_response(&mut reader, &mut writer)
+            }
+            MctpControlCommandCode::MctpControlGetEndpointId => MctpControlGetEndpointIdResponse {
+                completion_code: MctpControlCompletionCode::MctpControlSuccess,
+                eid: self.eid,
+                // simple endpoint, dynamic EID
+                endpoint_type: 0x00,
+                medium_specific: 0x00,
+            }
+            .encode(&mut writer),
+            MctpControlCommandCode::MctpControlGetEndpointUuid => {
+                MctpControlGetEndpointUuidResponse {
+                    completion_code: MctpControlCompletionCode::MctpControlSuccess,
+                    uuid: self.uuid,
+                }
+                .encode(&mut writer)
+            }
+            MctpControlCommandCode::MctpControlGetVersionSupport => {
+                self.write_get_version_support_response(&mut reader, &mut writer)
+            }
+            MctpControlCommandCode::MctpControlGetMessageTypeSupport => {
+                let mut response = MctpControlGetMessageTypeSupportResponse {
+                    completion_code: MctpControlCompletionCode::MctpControlSuccess,
+                    message_type_count: self.message_type_count as u8,
+                    ..Default::default()
+                };
+                response.message_types[..self.message_type_count]
+                    .copy_from_slice(self.get_message_types());
+                response.encode(&mut writer)
+            }
+            MctpControlCommandCode::Unknown(_) => {
+                MctpControlCompletionCode::MctpControlErrorUnsupportedCmd.encode(&mut writer)
+            }
+        }
+        Ok(writer.used())
+    }
+
+    fn write_set_endpoint_id_response(&mut self, reader: &mut Reader, writer: &mut Writer) {
+        let set_eid = match MctpControlSetEndpointIdRequest::read(reader) {
+            Some(set_eid) => set_eid,
+            None => {
+                MctpControlCompletionCode::MctpControlErrorInvalidLength.encode(writer);
+                return;
+            }
+        };
+        match set_eid.operation {
+            MctpControlSetEndpointIdOperation::MctpControlSetEid
+            | MctpControlSetEndpointIdOperation::MctpControlForceEid => {
+                if set_eid.eid == crate::MCTP_NULL_EID || set_eid.eid == crate::MCTP_BROADCAST_EID {
+                    MctpControlCompletionCode::MctpControlErrorInvalidData.encode(writer);
+                    return;
+                }
+                self.eid = set_eid.eid;
+                self.discovered = true;
+            }
+            MctpControlSetEndpointIdOperation::MctpControlSetDiscoveredFlag => {
+                self.discovered = true;
+            }
+            // the EID is not static, there is nothing to reset to.
+            MctpControlSetEndpointIdOperation::MctpControlResetEid
+            | MctpControlSetEndpointIdOperation::Unknown(_) => {
+                MctpControlCompletionCode::MctpControlErrorInvalidData.encode(writer);
+                return;
+            }
+        }
+        MctpControlSetEndpointIdResponse {
+            completion_code: MctpControlCompletionCode::MctpControlSuccess,
+            eid_assignment_rejected: false,
+            eid_setting: self.eid,
+            eid_pool_size: 0,
+        }
+        .encode(writer);
+    }
+
+    fn write_get_version_support_response(&mut self, reader: &mut Reader, writer: &mut Writer) {
+        let get_version = match MctpControlGetVersionSupportRequest::read(reader) {
+            Some(get_version) => get_version,
+            None => {
+                MctpControlCompletionCode::MctpControlErrorInvalidLength.encode(writer);
+                return;
+            }
+        };
+        let version = if get_version.message_type == MCTP_BASE_SPECIFICATION_MESSAGE_TYPE
+            || get_version.message_type == MctpMessageType::MctpMessageTypeMctpControl.get_u8()
+        {
+            MCTP_BASE_VERSION
+        } else if self
+            .get_message_types()
+            .iter()
+            .any(|t| t.get_u8() == get_version.message_type)
+        {
+            MCTP_MESSAGE_TYPE_VERSION
+        } else {
+            MctpControlCompletionCode::MctpControlMessageTypeNotSupported.encode(writer);
+            return;
+        };
+        let mut response = MctpControlGetVersionSupportResponse {
+            completion_code: MctpControlCompletionCode::MctpControlSuccess,
+            version_count: 1,
+            ..Default::default()
+        };
+        response.versions[0] = version;
+        response.encode(writer);
+    }
+}
+
+/// SpdmDeviceIo that answers MCTP control requests with responder,
+/// and passes the other messages to the SPDM stack.
+pub struct MctpControlDeviceIo<'a> {
+    device_io: &'a mut dyn SpdmDeviceIo,
+    pub responder: MctpControlResponder,
+}
+
+impl<'a> MctpControlDeviceIo<'a> {
+    pub fn new(device_io: &'a mut dyn SpdmDeviceIo, responder: MctpControlResponder) -> Self {
+        MctpControlDeviceIo {
+            device_io,
+            responder,
+        }
+    }
+}
+
+impl<'a> SpdmDeviceIo for MctpControlDeviceIo<'a> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.device_io.send(buffer)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        loop {
+            let used = self.device_io.receive(buffer)?;
+            if used == 0
+                || MctpMessageType::read_bytes(&buffer[..1])
+                    != Some(MctpMessageType::MctpMessageTypeMctpControl)
+            {
+                return Ok(used);
+            }
+            let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+            // malformed control messages and responses are dropped.
+            if let Ok(response_size) = self
+                .responder
+                .process_message(&buffer[..used], &mut response)
+            {
+                if self.device_io.send(&response[..response_size]).is_err() {
+                    return Err(0);
+                }
+            }
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.device_io.flush_all()
+    }
+}
+
+/// Bus owner side of the MCTP control protocol.
+pub struct MctpControlRequester<'a> {
+    device_io: &'a mut dyn SpdmDeviceIo,
+    instance_id: u8,
+}
+
+impl<'a> MctpControlRequester<'a> {
+    pub fn new(device_io: &'a mut dyn SpdmDeviceIo) -> Self {
+        MctpControlRequester {
+            device_io,
+            instance_id: 0,
+        }
+    }
+
+    fn send_receive(
+        &mut self,
+        command_code: MctpControlCommandCode,
+        request_data: &[u8],
+        response: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let instance_id = self.instance_id;
+        self.instance_id = (self.instance_id + 1) & MCTP_CONTROL_INSTANCE_ID_MASK;
+
+        let mut request = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let mut writer = Writer::init(&mut request);
+        MctpMessageHeader {
+            r#type: MctpMessageType::MctpMessageTypeMctpControl,
+        }
+        .encode(&mut writer);
+        let control_header = MctpControlHeader {
+            request: true,
+            datagram: false,
+            instance_id,
+            command_code,
+        };
+        control_header.encode(&mut writer);
+        writer
+            .extend_from_slice(request_data)
+            .ok_or(spdm_err!(ENOMEM))?;
+        let used = writer.used();
+        self.device_io.send(&request[..used])?;
+
+        let used = self
+            .device_io
+            .receive(response)
+            .map_err(|_| spdm_err!(EIO))?;
+        let mut reader = Reader::init(&response[..used]);
+        match MctpMessageHeader::read(&mut reader) {
+            Some(mctp_header)
+                if mctp_header.r#type == MctpMessageType::MctpMessageTypeMctpControl => {}
+            Some(_) => return spdm_result_err!(EINVAL),
+            None => return spdm_result_err!(EIO),
+        }
+        let response_header = MctpControlHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if response_header
+            != (MctpControlHeader {
+                request: false,
+                ..control_header
+            })
+        {
+            return spdm_result_err!(EINVAL);
+        }
+        let header_size = reader.used();
+        response.copy_within(header_size..used, 0);
+        Ok(used - header_size)
+    }
+
+    fn check_completion_code(completion_code: MctpControlCompletionCode) -> SpdmResult {
+        if completion_code == MctpControlCompletionCode::MctpControlSuccess {
+            Ok(())
+        } else {
+            spdm_result_err!(EFAULT)
+        }
+    }
+
+    pub fn set_endpoint_id(
+        &mut self,
+        operation: MctpControlSetEndpointIdOperation,
+        eid: u8,
+    ) -> SpdmResult<MctpControlSetEndpointIdResponse> {
+        let mut request = [0u8; 2];
+        MctpControlSetEndpointIdRequest { operation, eid }.encode(&mut Writer::init(&mut request));
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let used = self.send_receive(
+            MctpControlCommandCode::MctpControlSetEndpointId,
+            &request,
+            &mut response,
+        )?;
+        let response = MctpControlSetEndpointIdResponse::read(&mut Reader::init(&response[..used]))
+            .ok_or(spdm_err!(EFAULT))?;
+        Self::check_completion_code(response.completion_code)?;
+        Ok(response)
+    }
+
+    pub fn get_endpoint_id(&mut self) -> SpdmResult<MctpControlGetEndpointIdResponse> {
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let used = self.send_receive(
+            MctpControlCommandCode::MctpControlGetEndpointId,
+            &[],
+            &mut response,
+        )?;
+        let response = MctpControlGetEndpointIdResponse::read(&mut Reader::init(&response[..used]))
+            .ok_or(spdm_err!(EFAULT))?;
+        Self::check_completion_code(response.completion_code)?;
+        Ok(response)
+    }
+
+    pub fn get_endpoint_uuid(&mut self) -> SpdmResult<[u8; MCTP_UUID_SIZE]> {
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let used = self.send_receive(
+            MctpControlCommandCode::MctpControlGetEndpointUuid,
+            &[],
+            &mut response,
+        )?;
+        let response =
+            MctpControlGetEndpointUuidResponse::read(&mut Reader::init(&response[..used]))
+                .ok_or(spdm_err!(EFAULT))?;
+        Self::check_completion_code(response.completion_code)?;
+        Ok(response.uuid)
+    }
+
+    /// message_type is an MCTP message type, or MCTP_BASE_SPECIFICATION_MESSAGE_TYPE.
+    pub fn get_version_support(
+        &mut self,
+        message_type: u8,
+    ) -> SpdmResult<MctpControlGetVersionSupportResponse> {
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let used = self.send_receive(
+            MctpControlCommandCode::MctpControlGetVersionSupport,
+            &[message_type],
+            &mut response,
+        )?;
+        let response =
+            MctpControlGetVersionSupportResponse::read(&mut Reader::init(&response[..used]))
+                .ok_or(spdm_err!(EFAULT))?;
+        Self::check_completion_code(response.completion_code)?;
+        Ok(response)
+    }
+
+    pub fn get_message_type_support(
+        &mut self,
+    ) -> SpdmResult<MctpControlGetMessageTypeSupportResponse> {
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+        let used = self.send_receive(
+            MctpControlCommandCode::MctpControlGetMessageTypeSupport,
+            &[],
+            &mut response,
+        )?;
+        let response =
+            MctpControlGetMessageTypeSupportResponse::read(&mut Reader::init(&response[..used]))
+                .ok_or(spdm_err!(EFAULT))?;
+        Self::check_completion_code(response.completion_code)?;
+        Ok(response)
+    }
+
+    /// Check that the endpoint lists the SPDM message type, before GET_VERSION is sent.
+    pub fn check_spdm_support(&mut self) -> SpdmResult<bool> {
+        Ok(self
+            .get_message_type_support()?
+            .is_supported(MctpMessageType::MctpMessageTypeSpdm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_UUID: [u8; MCTP_UUID_SIZE] = [0x5a; MCTP_UUID_SIZE];
+
+    /// Delivers every message sent by the requester to the responder, and returns its response.
+    struct FakeMctpControlIo<'a> {
+        responder: &'a mut MctpControlResponder,
+        response: [u8; MCTP_MAX_CONTROL_MESSAGE_SIZE],
+        response_size: usize,
+    }
+
+    impl<'a> SpdmDeviceIo for FakeMctpControlIo<'a> {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            self.response_size = self.responder.process_message(buffer, &mut self.response)?;
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+            buffer[..self.response_size].copy_from_slice(&self.response[..self.response_size]);
+            Ok(self.response_size)
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_case0_mctp_control_header() {
+        let u8_slice = &mut [0u8; 2];
+        let mut writer = Writer::init(u8_slice);
+        let value = MctpControlHeader {
+            request: true,
+            datagram: false,
+            instance_id: 0x21,
+            command_code: MctpControlCommandCode::MctpControlGetMessageTypeSupport,
+        };
+        value.encode(&mut writer);
+        assert_eq!(u8_slice, &[0x81, 0x05]);
+        let mut reader = Reader::init(u8_slice);
+        let header = MctpControlHeader::read(&mut reader).unwrap();
+        assert!(header.request);
+        assert_eq!(header.instance_id, 0x01);
+        assert_eq!(
+            header.command_code,
+            MctpControlCommandCode::MctpControlGetMessageTypeSupport
+        );
+    }
+    #[test]
+    fn test_case0_mctp_control_get_message_type_support_response() {
+        let u8_slice = &mut [0u8; 8];
+        let mut writer = Writer::init(u8_slice);
+        let mut value = MctpControlGetMessageTypeSupportResponse {
+            completion_code: MctpControlCompletionCode::MctpControlSuccess,
+            message_type_count: 2,
+            ..Default::default()
+        };
+        value.message_types[0] = MctpMessageType::MctpMessageTypeSpdm;
+        value.message_types[1] = MctpMessageType::MctpMessageTypeSecuredMctp;
+        value.encode(&mut writer);
+        assert_eq!(writer.used(), 4);
+        let mut reader = Reader::init(u8_slice);
+        let response = MctpControlGetMessageTypeSupportResponse::read(&mut reader).unwrap();
+        assert!(response.is_supported(MctpMessageType::MctpMessageTypeSpdm));
+        assert!(!response.is_supported(MctpMessageType::MctpMessageTypePldm));
+
+        // only the completion code on error
+        let u8_slice = &mut [0u8; 8];
+        let mut writer = Writer::init(u8_slice);
+        value.completion_code = MctpControlCompletionCode::MctpControlErrorNotReady;
+        value.encode(&mut writer);
+        assert_eq!(writer.used(), 1);
+
+        // too many message types
+        let u8_slice = &mut [0x00u8, (MCTP_MAX_MESSAGE_TYPE_COUNT + 1) as u8];
+        let mut reader = Reader::init(u8_slice);
+        assert!(MctpControlGetMessageTypeSupportResponse::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_mctp_control_requester() {
+        let mut responder = MctpControlResponder::new(
+            crate::MCTP_NULL_EID,
+            TEST_UUID,
+            &[
+                MctpMessageType::MctpMessageTypeSpdm,
+                MctpMessageType::MctpMessageTypeSecuredMctp,
+            ],
+        );
+        let mut device_io = FakeMctpControlIo {
+            responder: &mut responder,
+            response: [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE],
+            response_size: 0,
+        };
+        let mut requester = MctpControlRequester::new(&mut device_io);
+
+        let set_eid = requester
+            .set_endpoint_id(MctpControlSetEndpointIdOperation::MctpControlSetEid, 0x10)
+            .unwrap();
+        assert!(!set_eid.eid_assignment_rejected);
+        assert_eq!(set_eid.eid_setting, 0x10);
+        assert!(requester
+            .set_endpoint_id(
+                MctpControlSetEndpointIdOperation::MctpControlSetEid,
+                crate::MCTP_BROADCAST_EID
+            )
+            .is_err());
+        assert_eq!(requester.get_endpoint_id().unwrap().eid, 0x10);
+        assert_eq!(requester.get_endpoint_uuid().unwrap(), TEST_UUID);
+
+        let versions = requester
+            .get_version_support(MCTP_BASE_SPECIFICATION_MESSAGE_TYPE)
+            .unwrap();
+        assert_eq!(versions.version_count, 1);
+        assert_eq!(versions.versions[0], MCTP_BASE_VERSION);
+        assert!(requester
+            .get_version_support(MctpMessageType::MctpMessageTypeSpdm.get_u8())
+            .is_ok());
+        assert!(requester
+            .get_version_support(MctpMessageType::MctpMessageTypePldm.get_u8())
+            .is_err());
+
+        assert!(requester.check_spdm_support().unwrap());
+    }
+    #[test]
+    fn test_case1_mctp_control_requester() {
+        let mut responder =
+            MctpControlResponder::new(0x10, TEST_UUID, &[MctpMessageType::MctpMessageTypePldm]);
+        let mut device_io = FakeMctpControlIo {
+            responder: &mut responder,
+            response: [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE],
+            response_size: 0,
+        };
+        let mut requester = MctpControlRequester::new(&mut device_io);
+        assert!(!requester.check_spdm_support().unwrap());
+    }
+    #[test]
+    fn test_case0_mctp_control_responder() {
+        let mut responder = MctpControlResponder::new(0x10, TEST_UUID, &[]);
+        let mut response = [0u8; MCTP_MAX_CONTROL_MESSAGE_SIZE];
+
+        // unknown command
+        let used = responder
+            .process_message(&[0x00, 0x83, 0x7F], &mut response)
+            .unwrap();
+        assert_eq!(&response[..used], &[0x00, 0x03, 0x7F, 0x05]);
+
+        // truncated Set Endpoint ID
+        let used = responder
+            .process_message(&[0x00, 0x80, 0x01, 0x00], &mut response)
+            .unwrap();
+        assert_eq!(&response[..used], &[0x00, 0x00, 0x01, 0x03]);
+
+        // not a request, not a control message
+        assert!(responder
+            .process_message(&[0x00, 0x00, 0x02], &mut response)
+            .is_err());
+        assert!(responder
+            .process_message(&[0x05, 0x80, 0x02], &mut response)
+            .is_err());
+    }
+}
@@ -8,6 +8,11 @@ use spdmlib::common::SpdmTransportEncap;
 use spdmlib::error::SpdmResult;
 use spdmlib::{spdm_err, spdm_result_err};
 
+/// Secured MCTP messages (DSP0276) carry the low 2 bytes of the sequence number
+/// and up to 32 bytes of random data.
+pub const MCTP_SEQUENCE_NUMBER_COUNT: u8 = 2;
+pub const MCTP_MAX_RANDOM_COUNT: u16 = 32;
+
 enum_builder! {
     @U8
     EnumName: MctpMessageType;
@@ -24,6 +29,20 @@ enum_builder! {
     }
 }
 
+impl MctpMessageType {
+    /// Whether the type may be carried as an application message inside a
+    /// secured MCTP message, e.g. PLDM, NVMe-MI or a vendor defined message.
+    /// MCTP control, SPDM and secured messages are not application messages.
+    pub fn is_app_message_type(&self) -> bool {
+        !matches!(
+            self,
+            MctpMessageType::MctpMessageTypeMctpControl
+                | MctpMessageType::MctpMessageTypeSpdm
+                | MctpMessageType::MctpMessageTypeSecuredMctp
+        )
+    }
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct MctpMessageHeader {
     pub r#type: MctpMessageType,
@@ -83,6 +102,7 @@ impl SpdmTransportEncap for MctpTransportEncap {
                 MctpMessageType::MctpMessageTypeSecuredMctp => {
                     secured_message = true;
                 }
+                // MCTP control messages are answered by MctpControlDeviceIo before decap.
                 _ => return spdm_result_err!(EINVAL),
             },
             None => return spdm_result_err!(EIO),
@@ -101,17 +121,21 @@ impl SpdmTransportEncap for MctpTransportEncap {
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
         let payload_len = spdm_buffer.len();
         let mut writer = Writer::init(&mut *app_buffer);
-        let mctp_header = if is_app_message {
-            MctpMessageHeader {
-                r#type: MctpMessageType::MctpMessageTypePldm,
-            }
-        } else {
-            MctpMessageHeader {
+        let mctp_header = match app_message_type {
+            None => MctpMessageHeader {
                 r#type: MctpMessageType::MctpMessageTypeSpdm,
+            },
+            Some(app_message_type) => {
+                let r#type =
+                    MctpMessageType::read_bytes(&[app_message_type]).ok_or(spdm_err!(EINVAL))?;
+                if !r#type.is_app_message_type() {
+                    return spdm_result_err!(EINVAL);
+                }
+                MctpMessageHeader { r#type }
             }
         };
         mctp_header.encode(&mut writer);
@@ -127,19 +151,16 @@ impl SpdmTransportEncap for MctpTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         let mut reader = Reader::init(app_buffer);
-        let mut is_app_mesaage = false;
-        match MctpMessageHeader::read(&mut reader) {
+        let app_message_type = match MctpMessageHeader::read(&mut reader) {
             Some(mctp_header) => match mctp_header.r#type {
-                MctpMessageType::MctpMessageTypeSpdm => {}
-                MctpMessageType::MctpMessageTypePldm => {
-                    is_app_mesaage = true;
-                }
+                MctpMessageType::MctpMessageTypeSpdm => None,
+                r#type if r#type.is_app_message_type() => Some(r#type.get_u8()),
                 _ => return spdm_result_err!(EINVAL),
             },
             None => return spdm_result_err!(EIO),
-        }
+        };
         let header_size = reader.used();
         let payload_size = app_buffer.len() - header_size;
         if spdm_buffer.len() < payload_size {
@@ -147,14 +168,14 @@ impl SpdmTransportEncap for MctpTransportEncap {
         }
         let payload = &app_buffer[header_size..];
         spdm_buffer[..payload_size].copy_from_slice(payload);
-        Ok((payload_size, is_app_mesaage))
+        Ok((payload_size, app_message_type))
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
-        2
+        MCTP_SEQUENCE_NUMBER_COUNT
     }
     fn get_max_random_count(&mut self) -> u16 {
-        32
+        MCTP_MAX_RANDOM_COUNT
     }
 }
 
@@ -247,14 +268,14 @@ mod tests {
         let spdm_buffer = [0u8; 10];
 
         let status = mctp_transport_encap
-            .encap_app(&spdm_buffer, &mut app_buffer, false)
+            .encap_app(&spdm_buffer, &mut app_buffer, None)
             .is_ok();
         assert!(status);
 
         let spdm_buffer = [100u8; 1024];
 
         let status = mctp_transport_encap
-            .encap_app(&spdm_buffer, &mut app_buffer, false)
+            .encap_app(&spdm_buffer, &mut app_buffer, None)
             .is_err();
         assert!(status);
     }
@@ -283,6 +304,47 @@ mod tests {
         assert!(status);
     }
     #[test]
+    fn test_case1_encap_decap_app() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let mut app_buffer = [0u8; 16];
+        let mut buffer = [0u8; 16];
+        let message = [0x80u8, 0x00, 0x02];
+
+        for app_message_type in [0x01u8, 0x04, 0x7E, 0x7F].iter() {
+            let used = mctp_transport_encap
+                .encap_app(&message, &mut app_buffer, Some(*app_message_type))
+                .unwrap();
+            assert_eq!(app_buffer[0], *app_message_type);
+            let (size, decoded_type) = mctp_transport_encap
+                .decap_app(&app_buffer[..used], &mut buffer)
+                .unwrap();
+            assert_eq!(decoded_type, Some(*app_message_type));
+            assert_eq!(&buffer[..size], &message);
+        }
+
+        let used = mctp_transport_encap
+            .encap_app(&message, &mut app_buffer, None)
+            .unwrap();
+        assert_eq!(app_buffer[0], 0x05);
+        let (_, decoded_type) = mctp_transport_encap
+            .decap_app(&app_buffer[..used], &mut buffer)
+            .unwrap();
+        assert_eq!(decoded_type, None);
+
+        // control, SPDM and secured messages are not application messages
+        for app_message_type in [0x00u8, 0x05, 0x06].iter() {
+            assert!(mctp_transport_encap
+                .encap_app(&message, &mut app_buffer, Some(*app_message_type))
+                .is_err());
+            app_buffer[0] = *app_message_type;
+            if *app_message_type != 0x05 {
+                assert!(mctp_transport_encap
+                    .decap_app(&app_buffer[..used], &mut buffer)
+                    .is_err());
+            }
+        }
+    }
+    #[test]
     fn test_case0_get_sequence_number_count() {
         let mut mctp_transport_encap = MctpTransportEncap {};
         assert_eq!(mctp_transport_encap.get_sequence_number_count(), 2);
@@ -3,7 +3,19 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 #![forbid(unsafe_code)]
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 mod header;
 pub use header::*;
+
+mod packet;
+pub use packet::*;
+
+mod control;
+pub use control::*;
+
+mod serial;
+pub use serial::*;
+
+mod smbus;
+pub use smbus::*;
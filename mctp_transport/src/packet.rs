@@ -0,0 +1,691 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::config;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const MCTP_TRANSPORT_HEADER_VERSION: u8 = 0x1;
+pub const MCTP_TRANSPORT_HEADER_SIZE: usize = 4;
+
+/// Baseline transmission unit, the MTU every MCTP endpoint supports.
+pub const MCTP_BASELINE_TRANSMISSION_UNIT: usize = 64;
+pub const MCTP_MAX_TRANSMISSION_UNIT: usize = 1024;
+pub const MCTP_MAX_PACKET_SIZE: usize = MCTP_TRANSPORT_HEADER_SIZE + MCTP_MAX_TRANSMISSION_UNIT;
+
+pub const MCTP_NULL_EID: u8 = 0x00;
+pub const MCTP_BROADCAST_EID: u8 = 0xFF;
+
+pub const MCTP_MESSAGE_TAG_MASK: u8 = 0x7;
+pub const MCTP_PACKET_SEQUENCE_MASK: u8 = 0x3;
+
+/// MT4 in DSP0236, the maximum time between the packets of one message.
+pub const MCTP_DEFAULT_REASSEMBLY_TIMEOUT_MS: u64 = 100;
+
+/// MCTP transport header, at the start of every MCTP packet.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MctpTransportHeader {
+    pub version: u8,
+    pub dest_eid: u8,
+    pub source_eid: u8,
+    pub som: bool,
+    pub eom: bool,
+    pub pkt_seq: u8,
+    pub tag_owner: bool,
+    pub msg_tag: u8,
+}
+
+impl Codec for MctpTransportHeader {
+    fn encode(&self, bytes: &mut Writer) {
+        (self.version & 0xF).encode(bytes);
+        self.dest_eid.encode(bytes);
+        self.source_eid.encode(bytes);
+        let mut flags = (self.pkt_seq & MCTP_PACKET_SEQUENCE_MASK) << 4;
+        flags |= self.msg_tag & MCTP_MESSAGE_TAG_MASK;
+        if self.som {
+            flags |= 0x80;
+        }
+        if self.eom {
+            flags |= 0x40;
+        }
+        if self.tag_owner {
+            flags |= 0x08;
+        }
+        flags.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpTransportHeader> {
+        let version = u8::read(r)? & 0xF;
+        let dest_eid = u8::read(r)?;
+        let source_eid = u8::read(r)?;
+        let flags = u8::read(r)?;
+        Some(MctpTransportHeader {
+            version,
+            dest_eid,
+            source_eid,
+            som: (flags & 0x80) != 0,
+            eom: (flags & 0x40) != 0,
+            pkt_seq: (flags >> 4) & MCTP_PACKET_SEQUENCE_MASK,
+            tag_owner: (flags & 0x08) != 0,
+            msg_tag: flags & MCTP_MESSAGE_TAG_MASK,
+        })
+    }
+}
+
+/// Split MCTP messages into packets of at most MTU payload bytes.
+#[derive(Debug, Copy, Clone)]
+pub struct MctpPacketizer {
+    pub local_eid: u8,
+    pub remote_eid: u8,
+    mtu: usize,
+    next_msg_tag: u8,
+}
+
+impl MctpPacketizer {
+    pub fn new(local_eid: u8, remote_eid: u8) -> Self {
+        MctpPacketizer {
+            local_eid,
+            remote_eid,
+            mtu: MCTP_BASELINE_TRANSMISSION_UNIT,
+            next_msg_tag: 0,
+        }
+    }
+
+    pub fn get_mtu(&self) -> usize {
+        self.mtu
+    }
+
+    /// The MTU must be between the baseline transmission unit and MCTP_MAX_TRANSMISSION_UNIT.
+    pub fn set_mtu(&mut self, mtu: usize) -> SpdmResult {
+        if !(MCTP_BASELINE_TRANSMISSION_UNIT..=MCTP_MAX_TRANSMISSION_UNIT).contains(&mtu) {
+            return spdm_result_err!(EINVAL);
+        }
+        self.mtu = mtu;
+        Ok(())
+    }
+
+    /// Return a new tag for a request, the tag owner keeps it until the response arrives.
+    pub fn alloc_msg_tag(&mut self) -> u8 {
+        let msg_tag = self.next_msg_tag;
+        self.next_msg_tag = (self.next_msg_tag + 1) & MCTP_MESSAGE_TAG_MASK;
+        msg_tag
+    }
+
+    pub fn get_packet_count(&self, message_size: usize) -> usize {
+        (message_size + self.mtu - 1) / self.mtu
+    }
+
+    /// Packetize message (starting with the MCTP message type) and hand each packet to send_packet.
+    pub fn packetize<F>(
+        &self,
+        message: &[u8],
+        tag_owner: bool,
+        msg_tag: u8,
+        mut send_packet: F,
+    ) -> SpdmResult
+    where
+        F: FnMut(&[u8]) -> SpdmResult,
+    {
+        if message.is_empty() {
+            return spdm_result_err!(EINVAL);
+        }
+        let packet_count = self.get_packet_count(message.len());
+        let mut packet = [0u8; MCTP_MAX_PACKET_SIZE];
+        for (index, payload) in message.chunks(self.mtu).enumerate() {
+            let header = MctpTransportHeader {
+                version: MCTP_TRANSPORT_HEADER_VERSION,
+                dest_eid: self.remote_eid,
+                source_eid: self.local_eid,
+                som: index == 0,
+                eom: index == packet_count - 1,
+                pkt_seq: (index as u8) & MCTP_PACKET_SEQUENCE_MASK,
+                tag_owner,
+                msg_tag,
+            };
+            let mut writer = Writer::init(&mut packet);
+            header.encode(&mut writer);
+            writer.extend_from_slice(payload).ok_or(spdm_err!(ENOMEM))?;
+            let used = writer.used();
+            send_packet(&packet[..used])?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild MCTP messages from the packets sent to local_eid.
+///
+/// Only one message is assembled at a time. A new SOM packet discards the message in progress,
+/// packets with another source, tag owner or tag are discarded as stale,
+/// and a sequence error or timeout drops the message in progress.
+pub struct MctpReassembler {
+    pub local_eid: u8,
+    timeout_ms: u64,
+    in_progress: bool,
+    header: MctpTransportHeader,
+    expected_pkt_seq: u8,
+    last_timestamp_ms: u64,
+    message_size: usize,
+    message: [u8; config::MAX_SPDM_TRANSPORT_SIZE],
+}
+
+impl MctpReassembler {
+    pub fn new(local_eid: u8) -> Self {
+        MctpReassembler {
+            local_eid,
+            timeout_ms: MCTP_DEFAULT_REASSEMBLY_TIMEOUT_MS,
+            in_progress: false,
+            header: MctpTransportHeader::default(),
+            expected_pkt_seq: 0,
+            last_timestamp_ms: 0,
+            message_size: 0,
+            message: [0u8; config::MAX_SPDM_TRANSPORT_SIZE],
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout_ms: u64) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    pub fn reset(&mut self) {
+        self.in_progress = false;
+        self.message_size = 0;
+    }
+
+    pub fn is_in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    /// The SOM header of the last message, used to address and tag the response.
+    pub fn get_message_header(&self) -> MctpTransportHeader {
+        self.header
+    }
+
+    /// The last complete message.
+    pub fn get_message(&self) -> &[u8] {
+        &self.message[..self.message_size]
+    }
+
+    /// Feed one packet. timestamp_ms is a monotonic time provided by the caller.
+    /// Return Some(message size) once the message is complete, None if more packets are needed
+    /// or the packet was discarded.
+    pub fn receive_packet(
+        &mut self,
+        packet: &[u8],
+        timestamp_ms: u64,
+    ) -> SpdmResult<Option<usize>> {
+        let mut reader = Reader::init(packet);
+        let header = MctpTransportHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if header.version != MCTP_TRANSPORT_HEADER_VERSION {
+            return spdm_result_err!(EINVAL);
+        }
+        if header.dest_eid != self.local_eid
+            && header.dest_eid != MCTP_NULL_EID
+            && header.dest_eid != MCTP_BROADCAST_EID
+        {
+            return Ok(None);
+        }
+        let payload = &packet[MCTP_TRANSPORT_HEADER_SIZE..];
+
+        if self.in_progress && timestamp_ms.wrapping_sub(self.last_timestamp_ms) > self.timeout_ms {
+            self.reset();
+        }
+
+        if header.som {
+            self.reset();
+            self.header = header;
+            self.in_progress = true;
+        } else {
+            if !self.in_progress {
+                return Ok(None);
+            }
+            if header.source_eid != self.header.source_eid
+                || header.tag_owner != self.header.tag_owner
+                || header.msg_tag != self.header.msg_tag
+            {
+                // stale packet from another message
+                return Ok(None);
+            }
+            if header.pkt_seq != self.expected_pkt_seq {
+                self.reset();
+                return Ok(None);
+            }
+        }
+
+        let message_size = self.message_size + payload.len();
+        if message_size > self.message.len() {
+            self.reset();
+            return spdm_result_err!(ENOMEM);
+        }
+        self.message[self.message_size..message_size].copy_from_slice(payload);
+        self.message_size = message_size;
+        self.expected_pkt_seq = (header.pkt_seq + 1) & MCTP_PACKET_SEQUENCE_MASK;
+        self.last_timestamp_ms = timestamp_ms;
+
+        if header.eom {
+            self.in_progress = false;
+            Ok(Some(self.message_size))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// SpdmDeviceIo for a raw MCTP link, where link carries one MCTP packet per send/receive.
+///
+/// The messages are the output of MctpTransportEncap: the MCTP message type followed by the body.
+/// Requests are sent as tag owner with a new tag, responses reuse the tag of the last request.
+/// Only the packets of the peer are received: a requester takes the response to its outstanding
+/// request, a responder takes requests.
+pub struct MctpPacketDeviceIo<'a> {
+    link: &'a mut dyn SpdmDeviceIo,
+    packetizer: MctpPacketizer,
+    reassembler: MctpReassembler,
+    is_requester: bool,
+    outstanding_msg_tag: Option<u8>,
+    time_source: Option<fn() -> u64>,
+}
+
+impl<'a> MctpPacketDeviceIo<'a> {
+    pub fn new(
+        link: &'a mut dyn SpdmDeviceIo,
+        local_eid: u8,
+        remote_eid: u8,
+        is_requester: bool,
+    ) -> Self {
+        MctpPacketDeviceIo {
+            link,
+            packetizer: MctpPacketizer::new(local_eid, remote_eid),
+            reassembler: MctpReassembler::new(local_eid),
+            is_requester,
+            outstanding_msg_tag: None,
+            time_source: None,
+        }
+    }
+
+    pub fn set_mtu(&mut self, mtu: usize) -> SpdmResult {
+        self.packetizer.set_mtu(mtu)
+    }
+
+    pub fn set_reassembly_timeout(&mut self, timeout_ms: u64) {
+        self.reassembler.set_timeout(timeout_ms)
+    }
+
+    /// Register a monotonic millisecond clock. Without it, reassembly never times out.
+    pub fn register_time_source(&mut self, time_source: fn() -> u64) {
+        self.time_source = Some(time_source);
+    }
+
+    /// Check every packet, not only the SOM one, so that a packet of another
+    /// endpoint or message never disturbs the message in progress.
+    fn is_expected_packet(&self, packet: &[u8]) -> bool {
+        let mut reader = Reader::init(packet);
+        let header = match MctpTransportHeader::read(&mut reader) {
+            Some(header) => header,
+            None => return false,
+        };
+        if header.source_eid != self.packetizer.remote_eid {
+            return false;
+        }
+        if self.is_requester {
+            !header.tag_owner && Some(header.msg_tag) == self.outstanding_msg_tag
+        } else {
+            header.tag_owner
+        }
+    }
+}
+
+impl<'a> SpdmDeviceIo for MctpPacketDeviceIo<'a> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        let (tag_owner, msg_tag) = if self.is_requester {
+            let msg_tag = self.packetizer.alloc_msg_tag();
+            self.outstanding_msg_tag = Some(msg_tag);
+            (true, msg_tag)
+        } else {
+            (false, self.reassembler.get_message_header().msg_tag)
+        };
+        let link = &mut self.link;
+        self.packetizer
+            .packetize(buffer, tag_owner, msg_tag, |packet| link.send(packet))
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        let mut packet = [0u8; MCTP_MAX_PACKET_SIZE];
+        loop {
+            let used = self.link.receive(&mut packet)?;
+            let timestamp_ms = self.time_source.map_or(0, |time_source| time_source());
+            if !self.is_expected_packet(&packet[..used]) {
+                continue;
+            }
+            // malformed packets are dropped, as on a real link.
+            if let Ok(Some(message_size)) = self
+                .reassembler
+                .receive_packet(&packet[..used], timestamp_ms)
+            {
+                if buffer.len() < message_size {
+                    return Err(0);
+                }
+                buffer[..message_size].copy_from_slice(self.reassembler.get_message());
+                return Ok(message_size);
+            }
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.reassembler.reset();
+        self.link.flush_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PACKET_COUNT: usize = 32;
+
+    struct FakeMctpLink {
+        packets: [[u8; MCTP_MAX_PACKET_SIZE]; TEST_PACKET_COUNT],
+        sizes: [usize; TEST_PACKET_COUNT],
+        head: usize,
+        tail: usize,
+    }
+
+    impl FakeMctpLink {
+        fn new() -> Self {
+            FakeMctpLink {
+                packets: [[0u8; MCTP_MAX_PACKET_SIZE]; TEST_PACKET_COUNT],
+                sizes: [0; TEST_PACKET_COUNT],
+                head: 0,
+                tail: 0,
+            }
+        }
+
+        fn push(&mut self, packet: &[u8]) -> SpdmResult {
+            if self.tail == TEST_PACKET_COUNT {
+                return spdm_result_err!(ENOMEM);
+            }
+            self.packets[self.tail][..packet.len()].copy_from_slice(packet);
+            self.sizes[self.tail] = packet.len();
+            self.tail += 1;
+            Ok(())
+        }
+
+        fn get(&self, index: usize) -> &[u8] {
+            &self.packets[index][..self.sizes[index]]
+        }
+    }
+
+    impl SpdmDeviceIo for FakeMctpLink {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            self.push(buffer)
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+            if self.head == self.tail {
+                return Err(0);
+            }
+            let used = self.sizes[self.head];
+            buffer[..used].copy_from_slice(&self.packets[self.head][..used]);
+            self.head += 1;
+            Ok(used)
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+
+    fn test_message(size: usize) -> [u8; 1024] {
+        let mut message = [0u8; 1024];
+        for (i, d) in message.iter_mut().take(size).enumerate() {
+            *d = i as u8;
+        }
+        message[0] = 0x05;
+        message
+    }
+
+    #[test]
+    fn test_case0_mctp_transport_header() {
+        let u8_slice = &mut [0u8; 4];
+        let mut writer = Writer::init(u8_slice);
+        let value = MctpTransportHeader {
+            version: MCTP_TRANSPORT_HEADER_VERSION,
+            dest_eid: 0x10,
+            source_eid: 0x08,
+            som: true,
+            eom: false,
+            pkt_seq: 2,
+            tag_owner: true,
+            msg_tag: 5,
+        };
+        value.encode(&mut writer);
+        assert_eq!(u8_slice, &[0x01, 0x10, 0x08, 0xAD]);
+
+        let mut reader = Reader::init(u8_slice);
+        assert_eq!(MctpTransportHeader::read(&mut reader).unwrap(), value);
+        assert_eq!(0, reader.left());
+    }
+    #[test]
+    fn test_case0_packetize() {
+        let mut packetizer = MctpPacketizer::new(0x08, 0x10);
+        let mut link = FakeMctpLink::new();
+        let message = test_message(200);
+
+        packetizer
+            .packetize(&message[..200], true, 3, |packet| link.push(packet))
+            .unwrap();
+        assert_eq!(link.tail, 4);
+        for i in 0..4 {
+            let mut reader = Reader::init(link.get(i));
+            let header = MctpTransportHeader::read(&mut reader).unwrap();
+            assert_eq!(header.dest_eid, 0x10);
+            assert_eq!(header.source_eid, 0x08);
+            assert_eq!(header.som, i == 0);
+            assert_eq!(header.eom, i == 3);
+            assert_eq!(header.pkt_seq, i as u8);
+            assert!(header.tag_owner);
+            assert_eq!(header.msg_tag, 3);
+        }
+        assert_eq!(link.get(3).len(), MCTP_TRANSPORT_HEADER_SIZE + 200 - 3 * 64);
+
+        assert!(packetizer
+            .set_mtu(MCTP_BASELINE_TRANSMISSION_UNIT - 1)
+            .is_err());
+        assert!(packetizer.set_mtu(MCTP_MAX_TRANSMISSION_UNIT + 1).is_err());
+        assert!(packetizer.set_mtu(256).is_ok());
+        let mut link = FakeMctpLink::new();
+        packetizer
+            .packetize(&message[..200], true, 3, |packet| link.push(packet))
+            .unwrap();
+        assert_eq!(link.tail, 1);
+
+        assert!(packetizer.packetize(&[], true, 3, |_| Ok(())).is_err());
+
+        for i in 0..9 {
+            assert_eq!(packetizer.alloc_msg_tag(), i & MCTP_MESSAGE_TAG_MASK);
+        }
+    }
+    #[test]
+    fn test_case0_reassemble() {
+        // 10 packets, the packet sequence number wraps twice
+        let packetizer = MctpPacketizer::new(0x08, 0x10);
+        let mut link = FakeMctpLink::new();
+        let message = test_message(600);
+        packetizer
+            .packetize(&message[..600], true, 1, |packet| link.push(packet))
+            .unwrap();
+        assert_eq!(link.tail, 10);
+
+        let mut reassembler = MctpReassembler::new(0x10);
+        for i in 0..9 {
+            assert_eq!(reassembler.receive_packet(link.get(i), 0).unwrap(), None);
+        }
+        assert_eq!(
+            reassembler.receive_packet(link.get(9), 0).unwrap(),
+            Some(600)
+        );
+        assert_eq!(reassembler.get_message(), &message[..600]);
+        assert_eq!(reassembler.get_message_header().msg_tag, 1);
+        assert!(!reassembler.is_in_progress());
+
+        // not for this endpoint
+        let mut reassembler = MctpReassembler::new(0x20);
+        assert_eq!(reassembler.receive_packet(link.get(0), 0).unwrap(), None);
+        assert!(!reassembler.is_in_progress());
+
+        // bad header
+        assert!(reassembler.receive_packet(&[0x01, 0x20], 0).is_err());
+        assert!(reassembler
+            .receive_packet(&[0x02, 0x20, 0x08, 0xC0, 0x05], 0)
+            .is_err());
+    }
+    #[test]
+    fn test_case1_reassemble() {
+        let packetizer = MctpPacketizer::new(0x08, 0x10);
+        let message = test_message(200);
+        let mut link = FakeMctpLink::new();
+        packetizer
+            .packetize(&message[..200], true, 1, |packet| link.push(packet))
+            .unwrap();
+        let mut stale_link = FakeMctpLink::new();
+        packetizer
+            .packetize(&[0x05u8; 200], true, 2, |packet| stale_link.push(packet))
+            .unwrap();
+
+        // packets with another tag are discarded
+        let mut reassembler = MctpReassembler::new(0x10);
+        assert_eq!(reassembler.receive_packet(link.get(0), 0).unwrap(), None);
+        assert_eq!(
+            reassembler.receive_packet(stale_link.get(1), 0).unwrap(),
+            None
+        );
+        assert_eq!(reassembler.receive_packet(link.get(1), 0).unwrap(), None);
+        assert_eq!(
+            reassembler.receive_packet(stale_link.get(2), 0).unwrap(),
+            None
+        );
+        assert_eq!(reassembler.receive_packet(link.get(2), 0).unwrap(), None);
+        assert_eq!(
+            reassembler.receive_packet(link.get(3), 0).unwrap(),
+            Some(200)
+        );
+        assert_eq!(reassembler.get_message(), &message[..200]);
+
+        // a missing packet drops the message
+        assert_eq!(reassembler.receive_packet(link.get(0), 0).unwrap(), None);
+        assert_eq!(reassembler.receive_packet(link.get(2), 0).unwrap(), None);
+        assert!(!reassembler.is_in_progress());
+        assert_eq!(reassembler.receive_packet(link.get(3), 0).unwrap(), None);
+
+        // a new SOM restarts the message
+        assert_eq!(
+            reassembler.receive_packet(stale_link.get(0), 0).unwrap(),
+            None
+        );
+        assert_eq!(reassembler.receive_packet(link.get(0), 0).unwrap(), None);
+        for i in 1..3 {
+            assert_eq!(reassembler.receive_packet(link.get(i), 0).unwrap(), None);
+        }
+        assert_eq!(
+            reassembler.receive_packet(link.get(3), 0).unwrap(),
+            Some(200)
+        );
+        assert_eq!(reassembler.get_message(), &message[..200]);
+    }
+    #[test]
+    fn test_case2_reassemble() {
+        let packetizer = MctpPacketizer::new(0x08, 0x10);
+        let message = test_message(100);
+        let mut link = FakeMctpLink::new();
+        packetizer
+            .packetize(&message[..100], false, 0, |packet| link.push(packet))
+            .unwrap();
+
+        let mut reassembler = MctpReassembler::new(0x10);
+        reassembler.set_timeout(50);
+        assert_eq!(reassembler.receive_packet(link.get(0), 1000).unwrap(), None);
+        assert_eq!(reassembler.receive_packet(link.get(1), 1051).unwrap(), None);
+        assert!(!reassembler.is_in_progress());
+
+        assert_eq!(reassembler.receive_packet(link.get(0), 2000).unwrap(), None);
+        assert_eq!(
+            reassembler.receive_packet(link.get(1), 2050).unwrap(),
+            Some(100)
+        );
+    }
+    #[test]
+    fn test_case0_mctp_packet_device_io() {
+        let mut link = FakeMctpLink::new();
+        let message = test_message(300);
+        {
+            let mut requester_io = MctpPacketDeviceIo::new(&mut link, 0x08, 0x10, true);
+            assert!(requester_io.send(&message[..300]).is_ok());
+        }
+        assert_eq!(link.tail, 5);
+
+        let mut responder_io = MctpPacketDeviceIo::new(&mut link, 0x10, 0x08, false);
+        let mut buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        assert_eq!(responder_io.receive(&mut buffer), Ok(300));
+        assert_eq!(&buffer[..300], &message[..300]);
+
+        // the response reuses the request tag
+        assert!(responder_io.send(&message[..10]).is_ok());
+        let mut reader = Reader::init(link.get(5));
+        let header = MctpTransportHeader::read(&mut reader).unwrap();
+        assert_eq!(header.dest_eid, 0x08);
+        assert!(!header.tag_owner);
+        assert_eq!(header.msg_tag, 0);
+    }
+    #[test]
+    fn test_case1_mctp_packet_device_io() {
+        let message = test_message(150);
+        let responder = MctpPacketizer::new(0x10, 0x08);
+        let mut response = FakeMctpLink::new();
+        responder
+            .packetize(&message[..150], false, 0, |packet| response.push(packet))
+            .unwrap();
+        assert_eq!(response.tail, 3);
+        // another endpoint, another tag, the tag owner set
+        let mut stale = FakeMctpLink::new();
+        MctpPacketizer::new(0x11, 0x08)
+            .packetize(&message[..150], false, 0, |packet| stale.push(packet))
+            .unwrap();
+        responder
+            .packetize(&message[..150], false, 1, |packet| stale.push(packet))
+            .unwrap();
+        responder
+            .packetize(&message[..150], true, 0, |packet| stale.push(packet))
+            .unwrap();
+
+        let mut link = FakeMctpLink::new();
+        let mut buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        {
+            // no request is outstanding
+            let mut requester_io = MctpPacketDeviceIo::new(&mut link, 0x08, 0x10, true);
+            for index in 0..response.tail {
+                requester_io.link.send(response.get(index)).unwrap();
+            }
+            assert!(requester_io.receive(&mut buffer).is_err());
+        }
+
+        let mut requester_io = MctpPacketDeviceIo::new(&mut link, 0x08, 0x10, true);
+        assert!(requester_io.send(&message[..10]).is_ok());
+        let mut packet = [0u8; MCTP_MAX_PACKET_SIZE];
+        assert!(requester_io.link.receive(&mut packet).is_ok());
+
+        // the stale packets come in between the packets of the response
+        requester_io.link.send(response.get(0)).unwrap();
+        for index in 0..stale.tail {
+            requester_io.link.send(stale.get(index)).unwrap();
+        }
+        requester_io.link.send(response.get(1)).unwrap();
+        requester_io.link.send(stale.get(0)).unwrap();
+        requester_io.link.send(stale.get(4)).unwrap();
+        requester_io.link.send(response.get(2)).unwrap();
+        assert_eq!(requester_io.receive(&mut buffer), Ok(150));
+        assert_eq!(&buffer[..150], &message[..150]);
+        assert!(requester_io.receive(&mut buffer).is_err());
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! MCTP serial transport binding, see DSP0253.
+//!
+//! Each MCTP packet is carried in one frame:
+//! flag(0x7E), revision(0x01), byte count, packet (escaped), FCS MSB, FCS LSB, flag(0x7E).
+
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const MCTP_SERIAL_FRAME_FLAG: u8 = 0x7E;
+pub const MCTP_SERIAL_ESCAPE_FLAG: u8 = 0x7D;
+pub const MCTP_SERIAL_REVISION: u8 = 0x01;
+
+/// The byte count is one byte.
+pub const MCTP_SERIAL_MAX_PACKET_SIZE: usize = 0xFF;
+/// Flags, revision, byte count and FCS, plus the worst case escaping.
+pub const MCTP_SERIAL_MAX_FRAME_SIZE: usize = 6 + MCTP_SERIAL_MAX_PACKET_SIZE * 2;
+
+const MCTP_SERIAL_FCS_INIT: u16 = 0xFFFF;
+
+/// FCS-16 of RFC1662 without the final complement, as computed by the
+/// Linux mctp-serial driver and libmctp.
+pub fn mctp_serial_fcs16(fcs: u16, data: &[u8]) -> u16 {
+    let mut fcs = fcs;
+    for d in data.iter() {
+        fcs ^= *d as u16;
+        for _ in 0..8 {
+            fcs = if fcs & 1 != 0 {
+                (fcs >> 1) ^ 0x8408
+            } else {
+                fcs >> 1
+            };
+        }
+    }
+    fcs
+}
+
+/// Frame one MCTP packet (starting with the MCTP transport header) into frame.
+pub fn mctp_serial_encode_frame(packet: &[u8], frame: &mut [u8]) -> SpdmResult<usize> {
+    if packet.is_empty() || packet.len() > MCTP_SERIAL_MAX_PACKET_SIZE {
+        return spdm_result_err!(EINVAL);
+    }
+
+    let header = [MCTP_SERIAL_REVISION, packet.len() as u8];
+    let fcs = mctp_serial_fcs16(MCTP_SERIAL_FCS_INIT, &header);
+    let fcs = mctp_serial_fcs16(fcs, packet);
+
+    let mut used = 0;
+    let mut push = |byte: u8| -> SpdmResult {
+        *frame.get_mut(used).ok_or(spdm_err!(ENOMEM))? = byte;
+        used += 1;
+        Ok(())
+    };
+    push(MCTP_SERIAL_FRAME_FLAG)?;
+    push(header[0])?;
+    push(header[1])?;
+    for byte in packet.iter() {
+        match *byte {
+            MCTP_SERIAL_FRAME_FLAG | MCTP_SERIAL_ESCAPE_FLAG => {
+                push(MCTP_SERIAL_ESCAPE_FLAG)?;
+                push(*byte ^ 0x20)?;
+            }
+            _ => push(*byte)?,
+        }
+    }
+    push((fcs >> 8) as u8)?;
+    push(fcs as u8)?;
+    push(MCTP_SERIAL_FRAME_FLAG)?;
+    Ok(used)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum MctpSerialRxState {
+    WaitStart,
+    Revision,
+    ByteCount,
+    Data,
+    DataEscaped,
+    FcsMsb,
+    FcsLsb,
+    WaitEnd,
+}
+
+/// Byte by byte frame decoder.
+///
+/// A bad revision, byte count, FCS or closing flag drops the frame and the
+/// decoder hunts for the next flag, so line noise costs at most the frames it hits.
+pub struct MctpSerialDecoder {
+    state: MctpSerialRxState,
+    packet: [u8; MCTP_SERIAL_MAX_PACKET_SIZE],
+    byte_count: usize,
+    used: usize,
+    fcs: u16,
+    error_count: usize,
+}
+
+impl Default for MctpSerialDecoder {
+    fn default() -> Self {
+        MctpSerialDecoder::new()
+    }
+}
+
+impl MctpSerialDecoder {
+    pub fn new() -> Self {
+        MctpSerialDecoder {
+            state: MctpSerialRxState::WaitStart,
+            packet: [0u8; MCTP_SERIAL_MAX_PACKET_SIZE],
+            byte_count: 0,
+            used: 0,
+            fcs: 0,
+            error_count: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = MctpSerialRxState::WaitStart;
+        self.byte_count = 0;
+        self.used = 0;
+    }
+
+    /// Number of frames dropped so far.
+    pub fn get_error_count(&self) -> usize {
+        self.error_count
+    }
+
+    /// The packet, valid after push_byte returns its size.
+    pub fn get_packet(&self) -> &[u8] {
+        &self.packet[..self.byte_count]
+    }
+
+    /// Feed one received byte, returns the packet size once a good frame ends.
+    pub fn push_byte(&mut self, byte: u8) -> Option<usize> {
+        match self.state {
+            MctpSerialRxState::WaitStart => {
+                if byte == MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialRxState::Revision;
+                }
+            }
+            MctpSerialRxState::Revision => match byte {
+                // back to back flags
+                MCTP_SERIAL_FRAME_FLAG => {}
+                MCTP_SERIAL_REVISION => {
+                    self.fcs = mctp_serial_fcs16(MCTP_SERIAL_FCS_INIT, &[byte]);
+                    self.state = MctpSerialRxState::ByteCount;
+                }
+                _ => self.drop_frame(byte),
+            },
+            // byte count and FCS are not escaped, they may hold the flag value.
+            MctpSerialRxState::ByteCount => {
+                if byte == 0 {
+                    self.drop_frame(byte);
+                } else {
+                    self.fcs = mctp_serial_fcs16(self.fcs, &[byte]);
+                    self.byte_count = byte as usize;
+                    self.used = 0;
+                    self.state = MctpSerialRxState::Data;
+                }
+            }
+            MctpSerialRxState::Data => match byte {
+                MCTP_SERIAL_FRAME_FLAG => self.drop_frame(byte),
+                MCTP_SERIAL_ESCAPE_FLAG => self.state = MctpSerialRxState::DataEscaped,
+                _ => self.push_data(byte),
+            },
+            MctpSerialRxState::DataEscaped => {
+                if byte == MCTP_SERIAL_FRAME_FLAG {
+                    self.drop_frame(byte);
+                } else {
+                    self.state = MctpSerialRxState::Data;
+                    self.push_data(byte ^ 0x20);
+                }
+            }
+            MctpSerialRxState::FcsMsb => {
+                self.fcs ^= (byte as u16) << 8;
+                self.state = MctpSerialRxState::FcsLsb;
+            }
+            MctpSerialRxState::FcsLsb => {
+                self.fcs ^= byte as u16;
+                if self.fcs == 0 {
+                    self.state = MctpSerialRxState::WaitEnd;
+                } else {
+                    self.drop_frame(0);
+                }
+            }
+            MctpSerialRxState::WaitEnd => {
+                if byte == MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialRxState::WaitStart;
+                    return Some(self.byte_count);
+                }
+                self.drop_frame(byte);
+            }
+        }
+        None
+    }
+
+    fn push_data(&mut self, byte: u8) {
+        self.packet[self.used] = byte;
+        self.used += 1;
+        if self.used == self.byte_count {
+            self.fcs = mctp_serial_fcs16(self.fcs, &self.packet[..self.used]);
+            self.state = MctpSerialRxState::FcsMsb;
+        }
+    }
+
+    /// A flag in the middle of a frame may open the next one.
+    fn drop_frame(&mut self, byte: u8) {
+        self.error_count += 1;
+        self.byte_count = 0;
+        self.used = 0;
+        self.state = if byte == MCTP_SERIAL_FRAME_FLAG {
+            MctpSerialRxState::Revision
+        } else {
+            MctpSerialRxState::WaitStart
+        };
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::serial_io::MctpSerialDeviceIo;
+
+#[cfg(feature = "std")]
+mod serial_io {
+    use super::*;
+    use spdmlib::common::SpdmDeviceIo;
+    use std::io::{ErrorKind, Read, Write};
+
+    const MCTP_SERIAL_READ_CHUNK_SIZE: usize = 64;
+
+    /// Carry MCTP packets over a byte stream, such as a UART.
+    ///
+    /// It works on packets, put MctpPacketDeviceIo on top of it to carry messages.
+    pub struct MctpSerialDeviceIo<S: Read + Write> {
+        stream: S,
+        decoder: MctpSerialDecoder,
+        read_buffer: [u8; MCTP_SERIAL_READ_CHUNK_SIZE],
+        read_offset: usize,
+        read_size: usize,
+    }
+
+    impl<S: Read + Write> MctpSerialDeviceIo<S> {
+        pub fn new(stream: S) -> Self {
+            MctpSerialDeviceIo {
+                stream,
+                decoder: MctpSerialDecoder::new(),
+                read_buffer: [0u8; MCTP_SERIAL_READ_CHUNK_SIZE],
+                read_offset: 0,
+                read_size: 0,
+            }
+        }
+
+        pub fn get_error_count(&self) -> usize {
+            self.decoder.get_error_count()
+        }
+
+        pub fn into_inner(self) -> S {
+            self.stream
+        }
+
+        fn read_byte(&mut self) -> Option<u8> {
+            while self.read_offset == self.read_size {
+                match self.stream.read(&mut self.read_buffer) {
+                    Ok(0) => return None,
+                    Ok(size) => {
+                        self.read_offset = 0;
+                        self.read_size = size;
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(_) => return None,
+                }
+            }
+            let byte = self.read_buffer[self.read_offset];
+            self.read_offset += 1;
+            Some(byte)
+        }
+    }
+
+    impl<S: Read + Write> SpdmDeviceIo for MctpSerialDeviceIo<S> {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            let mut frame = [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+            let used = mctp_serial_encode_frame(buffer, &mut frame)?;
+            self.stream
+                .write_all(&frame[..used])
+                .map_err(|_| spdm_err!(EIO))?;
+            self.stream.flush().map_err(|_| spdm_err!(EIO))
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+            loop {
+                let byte = self.read_byte().ok_or(0usize)?;
+                if let Some(used) = self.decoder.push_byte(byte) {
+                    if buffer.len() < used {
+                        return Err(0);
+                    }
+                    buffer[..used].copy_from_slice(self.decoder.get_packet());
+                    return Ok(used);
+                }
+            }
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            self.decoder.reset();
+            self.read_offset = 0;
+            self.read_size = 0;
+            self.stream.flush().map_err(|_| spdm_err!(EIO))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::packet::*;
+    use spdmlib::common::SpdmDeviceIo;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::io::{Read, Write};
+    use std::rc::Rc;
+
+    type Pipe = Rc<RefCell<VecDeque<u8>>>;
+
+    /// One end of an in-memory full duplex pipe.
+    struct PipeEnd {
+        rx: Pipe,
+        tx: Pipe,
+    }
+
+    fn pipe_pair() -> (PipeEnd, PipeEnd) {
+        let a = Pipe::default();
+        let b = Pipe::default();
+        (
+            PipeEnd {
+                rx: a.clone(),
+                tx: b.clone(),
+            },
+            PipeEnd { rx: b, tx: a },
+        )
+    }
+
+    impl Read for PipeEnd {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let mut rx = self.rx.borrow_mut();
+            let size = buf.len().min(rx.len());
+            for (d, s) in buf.iter_mut().zip(rx.drain(..size)) {
+                *d = s;
+            }
+            Ok(size)
+        }
+    }
+
+    impl Write for PipeEnd {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.tx.borrow_mut().extend(buf.iter());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_case0_mctp_serial_fcs16() {
+        assert_eq!(
+            mctp_serial_fcs16(MCTP_SERIAL_FCS_INIT, b"123456789"),
+            0x6F91
+        );
+    }
+
+    #[test]
+    fn test_case0_mctp_serial_encode_frame() {
+        let packet = [0x01, 0x08, 0x09, 0xC8, 0x05, 0x7E, 0x7D, 0x10];
+        let mut frame = [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+        let used = mctp_serial_encode_frame(&packet, &mut frame).unwrap();
+        assert_eq!(used, 6 + packet.len() + 2);
+        assert_eq!(&frame[..3], &[0x7E, 0x01, 0x08]);
+        assert_eq!(&frame[8..12], &[0x7D, 0x5E, 0x7D, 0x5D]);
+        assert_eq!(frame[used - 1], 0x7E);
+
+        let mut decoder = MctpSerialDecoder::new();
+        let mut size = None;
+        for byte in frame[..used].iter() {
+            size = decoder.push_byte(*byte);
+        }
+        assert_eq!(size, Some(packet.len()));
+        assert_eq!(decoder.get_packet(), &packet);
+
+        assert!(mctp_serial_encode_frame(&[], &mut frame).is_err());
+        assert!(mctp_serial_encode_frame(&[0u8; 256], &mut frame).is_err());
+        assert!(mctp_serial_encode_frame(&packet, &mut frame[..8]).is_err());
+    }
+
+    #[test]
+    fn test_case1_mctp_serial_resync() {
+        let packet = [0x01, 0x08, 0x09, 0xC8, 0x05, 0x10, 0x84, 0x00];
+        let mut frame = [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+        let used = mctp_serial_encode_frame(&packet, &mut frame).unwrap();
+
+        let mut stream = VecDeque::new();
+        // line noise
+        stream.extend([0x00u8, 0x55, 0x7D, 0xFF].iter());
+        // truncated frame
+        stream.extend(frame[..7].iter());
+        // corrupted FCS
+        let mut bad_frame = frame;
+        bad_frame[used - 2] ^= 0x01;
+        stream.extend(bad_frame[..used].iter());
+        // wrong revision
+        stream.extend([0x7E, 0x02, 0x01, 0x00, 0x00, 0x00, 0x7E].iter());
+        stream.extend(frame[..used].iter());
+
+        let mut decoder = MctpSerialDecoder::new();
+        let mut count = 0;
+        for byte in stream.iter() {
+            if let Some(size) = decoder.push_byte(*byte) {
+                assert_eq!(decoder.get_packet(), &packet[..size]);
+                count += 1;
+            }
+        }
+        assert_eq!(count, 1);
+        assert_eq!(decoder.get_error_count(), 3);
+    }
+
+    #[test]
+    fn test_case0_mctp_serial_device_io() {
+        let (requester_end, responder_end) = pipe_pair();
+        let mut requester_link = MctpSerialDeviceIo::new(requester_end);
+        let mut responder_link = MctpSerialDeviceIo::new(responder_end);
+
+        let mut message = [0u8; 200];
+        for (i, d) in message.iter_mut().enumerate() {
+            *d = (i as u8).wrapping_mul(0x3F);
+        }
+        message[0] = 0x05;
+
+        {
+            let mut requester = MctpPacketDeviceIo::new(&mut requester_link, 0x08, 0x09, true);
+            requester.send(&message).unwrap();
+        }
+        let mut responder = MctpPacketDeviceIo::new(&mut responder_link, 0x09, 0x08, false);
+        let mut buffer = [0u8; 256];
+        let used = responder.receive(&mut buffer).unwrap();
+        assert_eq!(&buffer[..used], &message[..]);
+
+        // empty pipe
+        assert!(responder.receive(&mut buffer).is_err());
+        assert_eq!(responder_link.get_error_count(), 0);
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! MCTP SMBus/I2C transport binding, see DSP0237.
+//!
+//! Each MCTP packet is carried in one SMBus block write:
+//! dest slave address, command code(0x0F), byte count, source slave address, MCTP packet, PEC.
+//! Slave addresses are 7-bit in the API and shifted on the wire.
+
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const MCTP_SMBUS_COMMAND_CODE: u8 = 0x0F;
+pub const MCTP_SMBUS_HEADER_SIZE: usize = 4;
+pub const MCTP_SMBUS_PEC_SIZE: usize = 1;
+/// The byte count covers the source slave address and the MCTP packet.
+pub const MCTP_SMBUS_MAX_BYTE_COUNT: usize = 0xFF;
+pub const MCTP_SMBUS_MAX_PACKET_SIZE: usize =
+    MCTP_SMBUS_HEADER_SIZE - 1 + MCTP_SMBUS_MAX_BYTE_COUNT + MCTP_SMBUS_PEC_SIZE;
+
+/// SMBus PEC, CRC-8 with polynomial x^8 + x^2 + x + 1.
+pub fn mctp_smbus_pec(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for d in data.iter() {
+        crc ^= *d;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct MctpSmbusHeader {
+    pub dest_addr: u8,
+    pub command_code: u8,
+    pub byte_count: u8,
+    pub source_addr: u8,
+}
+
+impl Codec for MctpSmbusHeader {
+    fn encode(&self, bytes: &mut Writer) {
+        (self.dest_addr << 1).encode(bytes);
+        self.command_code.encode(bytes);
+        self.byte_count.encode(bytes);
+        ((self.source_addr << 1) | 1).encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpSmbusHeader> {
+        let dest_addr = u8::read(r)?;
+        let command_code = u8::read(r)?;
+        let byte_count = u8::read(r)?;
+        let source_addr = u8::read(r)?;
+        if dest_addr & 1 != 0 || source_addr & 1 != 1 {
+            return None;
+        }
+        Some(MctpSmbusHeader {
+            dest_addr: dest_addr >> 1,
+            command_code,
+            byte_count,
+            source_addr: source_addr >> 1,
+        })
+    }
+}
+
+/// Wrap one MCTP packet (starting with the MCTP transport header) into an SMBus packet.
+pub fn mctp_smbus_encode_packet(
+    dest_addr: u8,
+    source_addr: u8,
+    mctp_packet: &[u8],
+    smbus_packet: &mut [u8],
+) -> SpdmResult<usize> {
+    let byte_count = mctp_packet.len() + 1;
+    if mctp_packet.is_empty() || byte_count > MCTP_SMBUS_MAX_BYTE_COUNT {
+        return spdm_result_err!(EINVAL);
+    }
+    if dest_addr > 0x7F || source_addr > 0x7F {
+        return spdm_result_err!(EINVAL);
+    }
+    let used = MCTP_SMBUS_HEADER_SIZE + mctp_packet.len();
+    if smbus_packet.len() < used + MCTP_SMBUS_PEC_SIZE {
+        return spdm_result_err!(ENOMEM);
+    }
+
+    let mut writer = Writer::init(smbus_packet);
+    MctpSmbusHeader {
+        dest_addr,
+        command_code: MCTP_SMBUS_COMMAND_CODE,
+        byte_count: byte_count as u8,
+        source_addr,
+    }
+    .encode(&mut writer);
+    smbus_packet[MCTP_SMBUS_HEADER_SIZE..used].copy_from_slice(mctp_packet);
+    smbus_packet[used] = mctp_smbus_pec(&smbus_packet[..used]);
+    Ok(used + MCTP_SMBUS_PEC_SIZE)
+}
+
+/// Check an SMBus packet and return its header and the MCTP packet.
+pub fn mctp_smbus_decode_packet(smbus_packet: &[u8]) -> SpdmResult<(MctpSmbusHeader, &[u8])> {
+    let mut reader = Reader::init(smbus_packet);
+    let header = MctpSmbusHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+    if header.command_code != MCTP_SMBUS_COMMAND_CODE {
+        return spdm_result_err!(EINVAL);
+    }
+    if header.byte_count < 2 {
+        return spdm_result_err!(EINVAL);
+    }
+    let used = MCTP_SMBUS_HEADER_SIZE - 1 + header.byte_count as usize;
+    if smbus_packet.len() != used + MCTP_SMBUS_PEC_SIZE {
+        return spdm_result_err!(EINVAL);
+    }
+    if mctp_smbus_pec(&smbus_packet[..used]) != smbus_packet[used] {
+        return spdm_result_err!(EFAULT);
+    }
+    Ok((header, &smbus_packet[MCTP_SMBUS_HEADER_SIZE..used]))
+}
+
+/// Carry MCTP packets between two SMBus endpoints.
+///
+/// The link sends and receives raw SMBus packets, the first byte being the
+/// destination slave address. It may be an i2c-dev device on Linux or the
+/// in-memory MctpSmbusBus. Put MctpPacketDeviceIo on top of it to carry messages.
+pub struct MctpSmbusDeviceIo<'a> {
+    link: &'a mut dyn SpdmDeviceIo,
+    local_addr: u8,
+    remote_addr: u8,
+}
+
+impl<'a> MctpSmbusDeviceIo<'a> {
+    pub fn new(link: &'a mut dyn SpdmDeviceIo, local_addr: u8, remote_addr: u8) -> Self {
+        MctpSmbusDeviceIo {
+            link,
+            local_addr,
+            remote_addr,
+        }
+    }
+}
+
+impl<'a> SpdmDeviceIo for MctpSmbusDeviceIo<'a> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        let mut smbus_packet = [0u8; MCTP_SMBUS_MAX_PACKET_SIZE];
+        let used =
+            mctp_smbus_encode_packet(self.remote_addr, self.local_addr, buffer, &mut smbus_packet)?;
+        self.link.send(&smbus_packet[..used])
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        let mut smbus_packet = [0u8; MCTP_SMBUS_MAX_PACKET_SIZE];
+        loop {
+            let used = self.link.receive(&mut smbus_packet)?;
+            // corrupted packets, or packets from other endpoints, are dropped.
+            let (header, mctp_packet) = match mctp_smbus_decode_packet(&smbus_packet[..used]) {
+                Ok(res) => res,
+                Err(_) => continue,
+            };
+            if header.dest_addr != self.local_addr || header.source_addr != self.remote_addr {
+                continue;
+            }
+            if buffer.len() < mctp_packet.len() {
+                return Err(0);
+            }
+            buffer[..mctp_packet.len()].copy_from_slice(mctp_packet);
+            return Ok(mctp_packet.len());
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.link.flush_all()
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::smbus_bus::{MctpSmbusBus, MctpSmbusBusEndpoint};
+
+#[cfg(feature = "std")]
+mod smbus_bus {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct MctpSmbusBusState {
+        endpoints: Vec<(u8, VecDeque<Vec<u8>>)>,
+    }
+
+    /// In-memory SMBus shared by several endpoints, for tests.
+    ///
+    /// A block write is delivered to the endpoint owning the destination
+    /// address; a write to an address nobody owns is NACKed.
+    #[derive(Clone, Default)]
+    pub struct MctpSmbusBus {
+        state: Rc<RefCell<MctpSmbusBusState>>,
+    }
+
+    impl MctpSmbusBus {
+        pub fn new() -> Self {
+            MctpSmbusBus::default()
+        }
+
+        /// Attach an endpoint owning the 7-bit slave address.
+        pub fn attach(&self, addr: u8) -> SpdmResult<MctpSmbusBusEndpoint> {
+            let mut state = self.state.borrow_mut();
+            if addr > 0x7F || state.endpoints.iter().any(|(a, _)| *a == addr) {
+                return spdm_result_err!(EINVAL);
+            }
+            state.endpoints.push((addr, VecDeque::new()));
+            Ok(MctpSmbusBusEndpoint {
+                bus: self.clone(),
+                addr,
+            })
+        }
+    }
+
+    pub struct MctpSmbusBusEndpoint {
+        bus: MctpSmbusBus,
+        addr: u8,
+    }
+
+    impl MctpSmbusBusEndpoint {
+        pub fn get_addr(&self) -> u8 {
+            self.addr
+        }
+    }
+
+    impl SpdmDeviceIo for MctpSmbusBusEndpoint {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            let dest_addr = buffer.first().ok_or(spdm_err!(EINVAL))? >> 1;
+            let mut state = self.bus.state.borrow_mut();
+            let (_, queue) = state
+                .endpoints
+                .iter_mut()
+                .find(|(addr, _)| *addr == dest_addr)
+                .ok_or(spdm_err!(EIO))?;
+            queue.push_back(buffer.to_vec());
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+            let mut state = self.bus.state.borrow_mut();
+            let addr = self.addr;
+            let (_, queue) = state
+                .endpoints
+                .iter_mut()
+                .find(|(a, _)| *a == addr)
+                .ok_or(0usize)?;
+            let packet = queue.pop_front().ok_or(0usize)?;
+            if buffer.len() < packet.len() {
+                return Err(0);
+            }
+            buffer[..packet.len()].copy_from_slice(&packet);
+            Ok(packet.len())
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_mctp_smbus_pec() {
+        assert_eq!(mctp_smbus_pec(b"123456789"), 0xF4);
+    }
+
+    #[test]
+    fn test_case0_mctp_smbus_packet() {
+        let mctp_packet = [0x01, 0x09, 0x08, 0xC8, 0x05, 0x10, 0x84];
+        let mut smbus_packet = [0u8; MCTP_SMBUS_MAX_PACKET_SIZE];
+        let used = mctp_smbus_encode_packet(0x20, 0x10, &mctp_packet, &mut smbus_packet).unwrap();
+        assert_eq!(used, 4 + mctp_packet.len() + 1);
+        assert_eq!(&smbus_packet[..4], &[0x40, 0x0F, 0x08, 0x21]);
+
+        let (header, packet) = mctp_smbus_decode_packet(&smbus_packet[..used]).unwrap();
+        assert_eq!(header.dest_addr, 0x20);
+        assert_eq!(header.source_addr, 0x10);
+        assert_eq!(packet, &mctp_packet);
+
+        // bad PEC
+        let mut bad_packet = smbus_packet;
+        bad_packet[used - 1] ^= 0x01;
+        assert!(mctp_smbus_decode_packet(&bad_packet[..used]).is_err());
+        // bad command code
+        let mut bad_packet = smbus_packet;
+        bad_packet[1] = 0x0E;
+        assert!(mctp_smbus_decode_packet(&bad_packet[..used]).is_err());
+        // byte count does not match
+        assert!(mctp_smbus_decode_packet(&smbus_packet[..used - 1]).is_err());
+        assert!(mctp_smbus_decode_packet(&smbus_packet[..used + 1]).is_err());
+
+        assert!(mctp_smbus_encode_packet(0x80, 0x10, &mctp_packet, &mut smbus_packet).is_err());
+        assert!(mctp_smbus_encode_packet(0x20, 0x10, &[0u8; 255], &mut smbus_packet).is_err());
+        assert!(mctp_smbus_encode_packet(0x20, 0x10, &mctp_packet, &mut [0u8; 8]).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_case0_mctp_smbus_bus() {
+        use crate::packet::MctpPacketDeviceIo;
+
+        let bus = MctpSmbusBus::new();
+        let mut requester_link = bus.attach(0x10).unwrap();
+        let mut responder_link = bus.attach(0x20).unwrap();
+        let mut other_link = bus.attach(0x30).unwrap();
+        assert!(bus.attach(0x20).is_err());
+
+        let mut message = [0u8; 150];
+        for (i, d) in message.iter_mut().enumerate() {
+            *d = i as u8;
+        }
+        message[0] = 0x05;
+
+        {
+            let mut smbus_io = MctpSmbusDeviceIo::new(&mut requester_link, 0x10, 0x20);
+            let mut requester = MctpPacketDeviceIo::new(&mut smbus_io, 0x08, 0x09, true);
+            requester.send(&message).unwrap();
+        }
+        {
+            // a packet from an endpoint that is not the peer is dropped.
+            let mut smbus_io = MctpSmbusDeviceIo::new(&mut other_link, 0x30, 0x20);
+            smbus_io.send(&[0x01, 0x09, 0x0A, 0xC8, 0x05]).unwrap();
+        }
+        let mut smbus_io = MctpSmbusDeviceIo::new(&mut responder_link, 0x20, 0x10);
+        let mut responder = MctpPacketDeviceIo::new(&mut smbus_io, 0x09, 0x08, false);
+        let mut buffer = [0u8; 256];
+        let used = responder.receive(&mut buffer).unwrap();
+        assert_eq!(&buffer[..used], &message[..]);
+        assert!(responder.receive(&mut buffer).is_err());
+
+        let mut buffer = [0u8; MCTP_SMBUS_MAX_PACKET_SIZE];
+        assert!(other_link.receive(&mut buffer).is_err());
+
+        // NACK
+        let mut smbus_io = MctpSmbusDeviceIo::new(&mut other_link, 0x30, 0x40);
+        assert!(smbus_io.send(&[0x01, 0x09, 0x0A, 0xC8, 0x05]).is_err());
+    }
+}
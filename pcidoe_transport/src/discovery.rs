@@ -0,0 +1,305 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::*;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const PCI_DOE_MAX_PROTOCOL_COUNT: usize = 8;
+pub const PCI_DOE_DISCOVERY_OBJECT_SIZE: usize = PCI_DOE_HEADER_SIZE + 4;
+
+/// A data object protocol supported by a DOE instance.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PciDoeProtocol {
+    pub vendor_id: PciDoeVendorId,
+    pub data_object_type: PciDoeDataObjectType,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PciDoeDiscoveryRequest {
+    pub index: u8,
+}
+
+impl Codec for PciDoeDiscoveryRequest {
+    fn encode(&self, bytes: &mut Writer) {
+        self.index.encode(bytes);
+        0u8.encode(bytes); // reserved
+        0u16.encode(bytes); // reserved
+    }
+
+    fn read(r: &mut Reader) -> Option<PciDoeDiscoveryRequest> {
+        let index = u8::read(r)?;
+        u8::read(r)?; // reserved
+        u16::read(r)?; // reserved
+        Some(PciDoeDiscoveryRequest { index })
+    }
+}
+
+/// next_index is 0 for the last protocol.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PciDoeDiscoveryResponse {
+    pub protocol: PciDoeProtocol,
+    pub next_index: u8,
+}
+
+impl Codec for PciDoeDiscoveryResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        self.protocol.vendor_id.encode(bytes);
+        self.protocol.data_object_type.encode(bytes);
+        self.next_index.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<PciDoeDiscoveryResponse> {
+        let vendor_id = PciDoeVendorId::read(r)?;
+        let data_object_type = PciDoeDataObjectType::read(r)?;
+        let next_index = u8::read(r)?;
+        Some(PciDoeDiscoveryResponse {
+            protocol: PciDoeProtocol {
+                vendor_id,
+                data_object_type,
+            },
+            next_index,
+        })
+    }
+}
+
+/// Read a DOE discovery object, the Length field must cover exactly the 1 dword payload.
+fn read_discovery_object(object: &[u8]) -> SpdmResult<Reader> {
+    let mut reader = Reader::init(object);
+    let header = PciDoeMessageHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+    if header.vendor_id != PciDoeVendorId::PciDoeVendorIdPciSig
+        || header.data_object_type != PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery
+    {
+        return spdm_result_err!(EINVAL);
+    }
+    if object.len() != PCI_DOE_DISCOVERY_OBJECT_SIZE
+        || header.payload_length as usize != PCI_DOE_DISCOVERY_OBJECT_SIZE - PCI_DOE_HEADER_SIZE
+    {
+        return spdm_result_err!(EINVAL);
+    }
+    Ok(reader)
+}
+
+fn write_discovery_header(writer: &mut Writer) {
+    PciDoeMessageHeader {
+        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
+        payload_length: (PCI_DOE_DISCOVERY_OBJECT_SIZE - PCI_DOE_HEADER_SIZE) as u32,
+    }
+    .encode(writer);
+}
+
+/// Answer DOE discovery requests with the list of supported protocols.
+#[derive(Debug, Copy, Clone)]
+pub struct PciDoeDiscoveryResponder {
+    protocol_count: usize,
+    protocols: [PciDoeProtocol; PCI_DOE_MAX_PROTOCOL_COUNT],
+}
+
+impl Default for PciDoeDiscoveryResponder {
+    /// DOE discovery, SPDM and secured SPDM.
+    fn default() -> Self {
+        PciDoeDiscoveryResponder::new(&[
+            PciDoeProtocol {
+                vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+                data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
+            },
+            PciDoeProtocol {
+                vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+                data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeSpdm,
+            },
+            PciDoeProtocol {
+                vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+                data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeSecuredSpdm,
+            },
+        ])
+    }
+}
+
+impl PciDoeDiscoveryResponder {
+    /// protocols must start with DOE discovery, index 0 always reports it.
+    pub fn new(protocols: &[PciDoeProtocol]) -> Self {
+        let protocol_count = protocols.len().min(PCI_DOE_MAX_PROTOCOL_COUNT);
+        let mut responder = PciDoeDiscoveryResponder {
+            protocol_count,
+            protocols: [PciDoeProtocol::default(); PCI_DOE_MAX_PROTOCOL_COUNT],
+        };
+        responder.protocols[..protocol_count].copy_from_slice(&protocols[..protocol_count]);
+        responder
+    }
+
+    /// Process a discovery request object and write the response object.
+    pub fn process_message(&self, request: &[u8], response: &mut [u8]) -> SpdmResult<usize> {
+        let mut reader = read_discovery_object(request)?;
+        let discovery_request = PciDoeDiscoveryRequest::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        let index = discovery_request.index as usize;
+        if index >= self.protocol_count {
+            return spdm_result_err!(EINVAL);
+        }
+        let next_index = if index + 1 == self.protocol_count {
+            0
+        } else {
+            index + 1
+        };
+
+        if response.len() < PCI_DOE_DISCOVERY_OBJECT_SIZE {
+            return spdm_result_err!(EINVAL);
+        }
+        let mut writer = Writer::init(response);
+        write_discovery_header(&mut writer);
+        PciDoeDiscoveryResponse {
+            protocol: self.protocols[index],
+            next_index: next_index as u8,
+        }
+        .encode(&mut writer);
+        Ok(writer.used())
+    }
+}
+
+/// Walk the DOE discovery list of a device.
+pub struct PciDoeDiscoveryRequester<'a> {
+    device_io: &'a mut dyn SpdmDeviceIo,
+}
+
+impl<'a> PciDoeDiscoveryRequester<'a> {
+    pub fn new(device_io: &'a mut dyn SpdmDeviceIo) -> Self {
+        PciDoeDiscoveryRequester { device_io }
+    }
+
+    pub fn send_receive_discovery(&mut self, index: u8) -> SpdmResult<PciDoeDiscoveryResponse> {
+        let mut request = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let mut writer = Writer::init(&mut request);
+        write_discovery_header(&mut writer);
+        PciDoeDiscoveryRequest { index }.encode(&mut writer);
+        self.device_io.send(&request)?;
+
+        let mut response = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let used = self
+            .device_io
+            .receive(&mut response)
+            .map_err(|_| spdm_err!(EIO))?;
+        let mut reader = read_discovery_object(&response[..used])?;
+        PciDoeDiscoveryResponse::read(&mut reader).ok_or(spdm_err!(EFAULT))
+    }
+
+    /// Fill protocols with the supported protocols and return the count.
+    pub fn discover(&mut self, protocols: &mut [PciDoeProtocol]) -> SpdmResult<usize> {
+        let mut index = 0u8;
+        let mut count = 0;
+        loop {
+            let response = self.send_receive_discovery(index)?;
+            *protocols.get_mut(count).ok_or(spdm_err!(ENOMEM))? = response.protocol;
+            count += 1;
+            if response.next_index == 0 {
+                return Ok(count);
+            }
+            // the index must move forward, or the walk never ends.
+            if response.next_index <= index {
+                return spdm_result_err!(EFAULT);
+            }
+            index = response.next_index;
+        }
+    }
+
+    /// Check that the device supports the given protocol, before SPDM starts.
+    pub fn is_supported(&mut self, protocol: PciDoeProtocol) -> SpdmResult<bool> {
+        let mut protocols = [PciDoeProtocol::default(); PCI_DOE_MAX_PROTOCOL_COUNT];
+        let count = self.discover(&mut protocols)?;
+        Ok(protocols[..count].contains(&protocol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Delivers every request to the discovery responder.
+    struct FakeDiscoveryIo {
+        responder: PciDoeDiscoveryResponder,
+        response: [u8; PCI_DOE_DISCOVERY_OBJECT_SIZE],
+        response_size: usize,
+    }
+
+    impl SpdmDeviceIo for FakeDiscoveryIo {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            self.response_size = self.responder.process_message(buffer, &mut self.response)?;
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+            buffer[..self.response_size].copy_from_slice(&self.response[..self.response_size]);
+            Ok(self.response_size)
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_case0_pci_doe_discovery_responder() {
+        let responder = PciDoeDiscoveryResponder::default();
+        let mut response = [0u8; 16];
+
+        let request = [
+            0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        ];
+        let used = responder.process_message(&request, &mut response).unwrap();
+        assert_eq!(
+            &response[..used],
+            &[0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x02]
+        );
+
+        let request = [
+            0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+        ];
+        let used = responder.process_message(&request, &mut response).unwrap();
+        assert_eq!(&response[8..used], &[0x01, 0x00, 0x02, 0x00]);
+
+        // index out of range
+        let request = [
+            0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+        ];
+        assert!(responder.process_message(&request, &mut response).is_err());
+        // Length does not match the object
+        let request = [
+            0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(responder.process_message(&request, &mut response).is_err());
+        let request = [0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00];
+        assert!(responder.process_message(&request, &mut response).is_err());
+        // not a discovery object
+        let request = [
+            0x01, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(responder.process_message(&request, &mut response).is_err());
+    }
+    #[test]
+    fn test_case0_pci_doe_discovery_requester() {
+        let mut device_io = FakeDiscoveryIo {
+            responder: PciDoeDiscoveryResponder::default(),
+            response: [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE],
+            response_size: 0,
+        };
+        let mut requester = PciDoeDiscoveryRequester::new(&mut device_io);
+
+        let mut protocols = [PciDoeProtocol::default(); PCI_DOE_MAX_PROTOCOL_COUNT];
+        assert_eq!(requester.discover(&mut protocols).unwrap(), 3);
+        assert_eq!(
+            protocols[2].data_object_type,
+            PciDoeDataObjectType::PciDoeDataObjectTypeSecuredSpdm
+        );
+        assert!(requester
+            .is_supported(PciDoeProtocol {
+                vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+                data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeSpdm,
+            })
+            .unwrap());
+
+        let mut protocols = [PciDoeProtocol::default(); 2];
+        assert!(requester.discover(&mut protocols).is_err());
+    }
+}
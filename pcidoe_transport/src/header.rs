@@ -26,6 +26,15 @@ enum_builder! {
     }
 }
 
+pub const PCI_DOE_HEADER_SIZE: usize = 8;
+/// The Length field is 18 bits, 0 means 2^18 dwords.
+pub const PCI_DOE_LENGTH_MASK: u32 = 0x3FFFF;
+pub const PCI_DOE_MAX_LENGTH_IN_DW: u32 = 0x40000;
+
+/// Secured messages over PCI DOE carry neither the sequence number nor random data.
+pub const PCI_DOE_SEQUENCE_NUMBER_COUNT: u8 = 0;
+pub const PCI_DOE_MAX_RANDOM_COUNT: u16 = 0;
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct PciDoeMessageHeader {
     pub vendor_id: PciDoeVendorId,
@@ -39,10 +48,10 @@ impl Codec for PciDoeMessageHeader {
         self.data_object_type.encode(bytes);
         0u8.encode(bytes);
         let mut length = (self.payload_length + 8) >> 2;
-        if length > 0x100000 {
+        if length > PCI_DOE_MAX_LENGTH_IN_DW {
             panic!();
         }
-        if length == 0x100000 {
+        if length == PCI_DOE_MAX_LENGTH_IN_DW {
             length = 0;
         }
         length.encode(bytes);
@@ -52,9 +61,9 @@ impl Codec for PciDoeMessageHeader {
         let vendor_id = PciDoeVendorId::read(r)?;
         let data_object_type = PciDoeDataObjectType::read(r)?;
         u8::read(r)?;
-        let mut length = u32::read(r)?;
+        let mut length = u32::read(r)? & PCI_DOE_LENGTH_MASK;
         if length == 0 {
-            length = 0x40000;
+            length = PCI_DOE_MAX_LENGTH_IN_DW;
         }
         if length < 2 {
             return None;
@@ -68,6 +77,19 @@ impl Codec for PciDoeMessageHeader {
     }
 }
 
+/// Size of a secured message without the dword padding.
+/// PCI DOE uses no sequence number, so the length follows the session ID.
+fn get_secured_message_size(payload: &[u8]) -> SpdmResult<usize> {
+    let mut reader = Reader::init(payload);
+    u32::read(&mut reader).ok_or(spdm_err!(EIO))?; // session_id
+    let length = u16::read(&mut reader).ok_or(spdm_err!(EIO))?;
+    let size = reader.used() + length as usize;
+    if size > payload.len() || payload.len() - size > 3 {
+        return spdm_result_err!(EINVAL);
+    }
+    Ok(size)
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct PciDoeTransportEncap {}
 
@@ -106,6 +128,7 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
     ) -> SpdmResult<(usize, bool)> {
         let mut reader = Reader::init(transport_buffer);
         let secured_message;
+        let payload_length;
         match PciDoeMessageHeader::read(&mut reader) {
             Some(pcidoe_header) => {
                 match pcidoe_header.vendor_id {
@@ -117,17 +140,26 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
                     PciDoeDataObjectType::PciDoeDataObjectTypeSecuredSpdm => secured_message = true,
                     _ => return spdm_result_err!(EINVAL),
                 }
+                payload_length = pcidoe_header.payload_length as usize;
             }
             None => return spdm_result_err!(EIO),
         }
         let header_size = reader.used();
-        let payload_size = transport_buffer.len() - header_size;
-        // TBD : check payload_size with Length field;
-        if spdm_buffer.len() < payload_size {
+        if transport_buffer.len() % 4 != 0 || transport_buffer.len() - header_size != payload_length
+        {
             return spdm_result_err!(EINVAL);
         }
         let payload = &transport_buffer[header_size..];
-        spdm_buffer[..payload_size].copy_from_slice(payload);
+        let payload_size = if secured_message {
+            get_secured_message_size(payload)?
+        } else {
+            // SPDM messages carry no length, the padding is left to the SPDM parser.
+            payload_length
+        };
+        if spdm_buffer.len() < payload_size {
+            return spdm_result_err!(EINVAL);
+        }
+        spdm_buffer[..payload_size].copy_from_slice(&payload[..payload_size]);
         Ok((payload_size, secured_message))
     }
 
@@ -135,8 +167,12 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        _is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
+        // the binding carries no application message type
+        if app_message_type.is_some() {
+            return spdm_result_err!(EINVAL);
+        }
         app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
         Ok(spdm_buffer.len())
     }
@@ -145,16 +181,16 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
-        Ok((app_buffer.len(), false))
+        Ok((app_buffer.len(), None))
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
-        0
+        PCI_DOE_SEQUENCE_NUMBER_COUNT
     }
     fn get_max_random_count(&mut self) -> u16 {
-        0
+        PCI_DOE_MAX_RANDOM_COUNT
     }
 }
 
@@ -258,4 +294,67 @@ mod tests_header {
         };
         value.encode(&mut writer);
     }
+    #[test]
+    fn test_case0_decap() {
+        let mut pcidoe_transport_encap = PciDoeTransportEncap {};
+        let mut transport_buffer = [0u8; 32];
+        let mut spdm_buffer = [0u8; 32];
+
+        // the SPDM payload is padded to dword
+        let used = pcidoe_transport_encap
+            .encap(
+                &[0x11, 0x84, 0x00, 0x00, 0x01],
+                &mut transport_buffer,
+                false,
+            )
+            .unwrap();
+        assert_eq!(used, 16);
+        let (size, secured_message) = pcidoe_transport_encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .unwrap();
+        assert_eq!(size, 8);
+        assert_eq!(
+            &spdm_buffer[..size],
+            &[0x11, 0x84, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]
+        );
+        assert!(!secured_message);
+
+        // the Length field does not match the object
+        assert!(pcidoe_transport_encap
+            .decap(&transport_buffer[..12], &mut spdm_buffer)
+            .is_err());
+        assert!(pcidoe_transport_encap
+            .decap(&transport_buffer[..15], &mut spdm_buffer)
+            .is_err());
+
+        // discovery is not an SPDM message
+        transport_buffer[2] = PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery.get_u8();
+        assert!(pcidoe_transport_encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .is_err());
+    }
+    #[test]
+    fn test_case1_decap() {
+        let mut pcidoe_transport_encap = PciDoeTransportEncap {};
+        let mut transport_buffer = [0u8; 32];
+        let mut spdm_buffer = [0u8; 32];
+
+        // session_id, length = 3, 3 bytes of data
+        let secured_message = [0xFE, 0xFF, 0xFF, 0xFF, 0x03, 0x00, 0xAA, 0xBB, 0xCC];
+        let used = pcidoe_transport_encap
+            .encap(&secured_message, &mut transport_buffer, true)
+            .unwrap();
+        assert_eq!(used, 20);
+        let (size, is_secured_message) = pcidoe_transport_encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .unwrap();
+        assert!(is_secured_message);
+        assert_eq!(&spdm_buffer[..size], &secured_message);
+
+        // the secured message length exceeds the object
+        transport_buffer[12] = 0x10;
+        assert!(pcidoe_transport_encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .is_err());
+    }
 }
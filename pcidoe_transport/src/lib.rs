@@ -7,3 +7,9 @@
 
 mod header;
 pub use header::*;
+
+mod discovery;
+pub use discovery::*;
+
+mod mailbox;
+pub use mailbox::*;
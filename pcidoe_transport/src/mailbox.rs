@@ -0,0 +1,310 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::discovery::PciDoeDiscoveryResponder;
+use crate::header::*;
+use codec::{Codec, Reader};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::config;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const PCI_DOE_CONTROL_ABORT: u32 = 0x0000_0001;
+pub const PCI_DOE_CONTROL_INTERRUPT_ENABLE: u32 = 0x0000_0002;
+pub const PCI_DOE_CONTROL_GO: u32 = 0x8000_0000;
+
+pub const PCI_DOE_STATUS_BUSY: u32 = 0x0000_0001;
+pub const PCI_DOE_STATUS_INTERRUPT_STATUS: u32 = 0x0000_0002;
+pub const PCI_DOE_STATUS_ERROR: u32 = 0x0000_0004;
+pub const PCI_DOE_STATUS_DATA_OBJECT_READY: u32 = 0x8000_0000;
+
+const PCI_DOE_MAILBOX_SIZE: usize = config::MAX_SPDM_TRANSPORT_SIZE;
+
+/// The function behind a DOE mailbox, it consumes a request data object
+/// and produces a response data object.
+pub trait PciDoeMailboxDevice {
+    fn process(&mut self, request: &[u8], response: &mut [u8]) -> SpdmResult<usize>;
+}
+
+impl PciDoeMailboxDevice for PciDoeDiscoveryResponder {
+    fn process(&mut self, request: &[u8], response: &mut [u8]) -> SpdmResult<usize> {
+        self.process_message(request, response)
+    }
+}
+
+/// Register level model of a DOE instance, see PCIe 6.0 section 6.30.
+///
+/// Software writes the request object a dword at a time into the write
+/// mailbox and sets GO. The device clears BUSY and sets Data Object Ready
+/// once the response is available in the read mailbox. Writing any value
+/// to the read mailbox advances to the next dword.
+pub struct PciDoeMailbox<'a> {
+    device: &'a mut dyn PciDoeMailboxDevice,
+    control: u32,
+    status: u32,
+    write_buffer: [u8; PCI_DOE_MAILBOX_SIZE],
+    write_size: usize,
+    read_buffer: [u8; PCI_DOE_MAILBOX_SIZE],
+    read_size: usize,
+    read_offset: usize,
+}
+
+impl<'a> PciDoeMailbox<'a> {
+    pub fn new(device: &'a mut dyn PciDoeMailboxDevice) -> Self {
+        PciDoeMailbox {
+            device,
+            control: 0,
+            status: 0,
+            write_buffer: [0u8; PCI_DOE_MAILBOX_SIZE],
+            write_size: 0,
+            read_buffer: [0u8; PCI_DOE_MAILBOX_SIZE],
+            read_size: 0,
+            read_offset: 0,
+        }
+    }
+
+    pub fn read_control(&self) -> u32 {
+        self.control & PCI_DOE_CONTROL_INTERRUPT_ENABLE
+    }
+
+    pub fn write_control(&mut self, value: u32) {
+        if value & PCI_DOE_CONTROL_ABORT != 0 {
+            self.abort();
+        }
+        self.control = value & PCI_DOE_CONTROL_INTERRUPT_ENABLE;
+        if value & PCI_DOE_CONTROL_GO != 0 {
+            if self.status & PCI_DOE_STATUS_BUSY != 0 {
+                self.status |= PCI_DOE_STATUS_ERROR;
+            } else if self.status & PCI_DOE_STATUS_ERROR == 0 {
+                self.status |= PCI_DOE_STATUS_BUSY;
+                self.control |= PCI_DOE_CONTROL_GO;
+            }
+        }
+    }
+
+    pub fn read_status(&self) -> u32 {
+        self.status
+    }
+
+    /// Interrupt Status is RW1C.
+    pub fn write_status(&mut self, value: u32) {
+        if value & PCI_DOE_STATUS_INTERRUPT_STATUS != 0 {
+            self.status &= !PCI_DOE_STATUS_INTERRUPT_STATUS;
+        }
+    }
+
+    pub fn write_mailbox(&mut self, value: u32) {
+        if self.status & (PCI_DOE_STATUS_BUSY | PCI_DOE_STATUS_ERROR) != 0 {
+            self.status |= PCI_DOE_STATUS_ERROR;
+            return;
+        }
+        if self.write_size + 4 > PCI_DOE_MAILBOX_SIZE {
+            self.status |= PCI_DOE_STATUS_ERROR;
+            return;
+        }
+        self.write_buffer[self.write_size..self.write_size + 4]
+            .copy_from_slice(&value.to_le_bytes());
+        self.write_size += 4;
+    }
+
+    /// Returns 0 when no data object is ready.
+    pub fn read_mailbox(&self) -> u32 {
+        if self.status & PCI_DOE_STATUS_DATA_OBJECT_READY == 0 {
+            return 0;
+        }
+        let mut dword = [0u8; 4];
+        dword.copy_from_slice(&self.read_buffer[self.read_offset..self.read_offset + 4]);
+        u32::from_le_bytes(dword)
+    }
+
+    /// Any write advances the read mailbox, Data Object Ready clears after the last dword.
+    pub fn advance_read_mailbox(&mut self) {
+        if self.status & PCI_DOE_STATUS_DATA_OBJECT_READY == 0 {
+            return;
+        }
+        self.read_offset += 4;
+        if self.read_offset >= self.read_size {
+            self.status &= !PCI_DOE_STATUS_DATA_OBJECT_READY;
+            self.read_size = 0;
+            self.read_offset = 0;
+        }
+    }
+
+    /// Let the device run, the request is processed once GO has been set.
+    pub fn poll(&mut self) {
+        if self.control & PCI_DOE_CONTROL_GO == 0 {
+            return;
+        }
+        self.control &= !PCI_DOE_CONTROL_GO;
+
+        let request_size = self.write_size;
+        self.write_size = 0;
+        let result = if Self::check_object(&self.write_buffer[..request_size]) {
+            self.device
+                .process(&self.write_buffer[..request_size], &mut self.read_buffer)
+        } else {
+            spdm_result_err!(EINVAL)
+        };
+
+        self.status &= !PCI_DOE_STATUS_BUSY;
+        match result {
+            Ok(used) if used % 4 == 0 && Self::check_object(&self.read_buffer[..used]) => {
+                self.read_size = used;
+                self.read_offset = 0;
+                self.status |= PCI_DOE_STATUS_DATA_OBJECT_READY;
+            }
+            _ => self.status |= PCI_DOE_STATUS_ERROR,
+        }
+        if self.control & PCI_DOE_CONTROL_INTERRUPT_ENABLE != 0 {
+            self.status |= PCI_DOE_STATUS_INTERRUPT_STATUS;
+        }
+    }
+
+    fn abort(&mut self) {
+        self.control &= !PCI_DOE_CONTROL_GO;
+        self.status &=
+            !(PCI_DOE_STATUS_BUSY | PCI_DOE_STATUS_ERROR | PCI_DOE_STATUS_DATA_OBJECT_READY);
+        self.write_size = 0;
+        self.read_size = 0;
+        self.read_offset = 0;
+    }
+
+    /// The Length field must describe exactly the dwords that were written.
+    fn check_object(object: &[u8]) -> bool {
+        let mut reader = Reader::init(object);
+        match PciDoeMessageHeader::read(&mut reader) {
+            Some(header) => header.payload_length as usize + PCI_DOE_HEADER_SIZE == object.len(),
+            None => false,
+        }
+    }
+}
+
+impl SpdmDeviceIo for PciDoeMailbox<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        if buffer.len() % 4 != 0 {
+            return spdm_result_err!(EINVAL);
+        }
+        if self.read_status() & PCI_DOE_STATUS_ERROR != 0 {
+            self.write_control(PCI_DOE_CONTROL_ABORT);
+        }
+        for dword in buffer.chunks(4) {
+            let mut value = [0u8; 4];
+            value.copy_from_slice(dword);
+            self.write_mailbox(u32::from_le_bytes(value));
+        }
+        self.write_control(self.read_control() | PCI_DOE_CONTROL_GO);
+        if self.read_status() & PCI_DOE_STATUS_ERROR != 0 {
+            return spdm_result_err!(EIO);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        self.poll();
+        let status = self.read_status();
+        if status & PCI_DOE_STATUS_ERROR != 0 || status & PCI_DOE_STATUS_DATA_OBJECT_READY == 0 {
+            return Err(0);
+        }
+
+        let mut used = 0;
+        while self.read_status() & PCI_DOE_STATUS_DATA_OBJECT_READY != 0 {
+            if used + 4 > buffer.len() {
+                self.write_control(PCI_DOE_CONTROL_ABORT);
+                return Err(used);
+            }
+            buffer[used..used + 4].copy_from_slice(&self.read_mailbox().to_le_bytes());
+            self.advance_read_mailbox();
+            used += 4;
+        }
+        Ok(used)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.write_control(PCI_DOE_CONTROL_ABORT);
+        if self.read_status() != 0 {
+            return Err(spdm_err!(EIO));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::*;
+
+    #[test]
+    fn test_case0_pci_doe_mailbox_registers() {
+        let mut device = PciDoeDiscoveryResponder::default();
+        let mut mailbox = PciDoeMailbox::new(&mut device);
+
+        mailbox.write_control(PCI_DOE_CONTROL_INTERRUPT_ENABLE);
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_mailbox(0x0000_0003);
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_control(PCI_DOE_CONTROL_INTERRUPT_ENABLE | PCI_DOE_CONTROL_GO);
+        assert_eq!(mailbox.read_status(), PCI_DOE_STATUS_BUSY);
+
+        // written while busy
+        mailbox.write_mailbox(0);
+        assert_ne!(mailbox.read_status() & PCI_DOE_STATUS_ERROR, 0);
+        mailbox.poll();
+        mailbox.write_control(PCI_DOE_CONTROL_INTERRUPT_ENABLE | PCI_DOE_CONTROL_ABORT);
+        mailbox.write_status(PCI_DOE_STATUS_INTERRUPT_STATUS);
+        assert_eq!(mailbox.read_status(), 0);
+
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_mailbox(0x0000_0003);
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_control(PCI_DOE_CONTROL_INTERRUPT_ENABLE | PCI_DOE_CONTROL_GO);
+        mailbox.poll();
+        assert_eq!(
+            mailbox.read_status(),
+            PCI_DOE_STATUS_DATA_OBJECT_READY | PCI_DOE_STATUS_INTERRUPT_STATUS
+        );
+        mailbox.write_status(PCI_DOE_STATUS_INTERRUPT_STATUS);
+        let mut response = [0u32; 3];
+        for dword in response.iter_mut() {
+            *dword = mailbox.read_mailbox();
+            mailbox.advance_read_mailbox();
+        }
+        assert_eq!(response, [0x0000_0001, 0x0000_0003, 0x0201_0001]);
+        assert_eq!(mailbox.read_status(), 0);
+    }
+
+    #[test]
+    fn test_case1_pci_doe_mailbox_length_mismatch() {
+        let mut device = PciDoeDiscoveryResponder::default();
+        let mut mailbox = PciDoeMailbox::new(&mut device);
+
+        // Length says 4 dwords, only 3 are written.
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_mailbox(0x0000_0004);
+        mailbox.write_mailbox(0x0000_0001);
+        mailbox.write_control(PCI_DOE_CONTROL_GO);
+        mailbox.poll();
+        assert_eq!(mailbox.read_status(), PCI_DOE_STATUS_ERROR);
+
+        // GO is ignored until abort.
+        mailbox.write_control(PCI_DOE_CONTROL_GO);
+        assert_eq!(mailbox.read_status(), PCI_DOE_STATUS_ERROR);
+        mailbox.write_control(PCI_DOE_CONTROL_ABORT);
+        assert_eq!(mailbox.read_status(), 0);
+    }
+
+    #[test]
+    fn test_case2_pci_doe_mailbox_device_io() {
+        let mut device = PciDoeDiscoveryResponder::default();
+        let mut mailbox = PciDoeMailbox::new(&mut device);
+
+        let mut requester = PciDoeDiscoveryRequester::new(&mut mailbox);
+        let mut protocols = [PciDoeProtocol::default(); PCI_DOE_MAX_PROTOCOL_COUNT];
+        assert_eq!(requester.discover(&mut protocols).unwrap(), 3);
+
+        assert!(mailbox.send(&[0x01, 0x00]).is_err());
+        let mut buffer = [0u8; 16];
+        assert!(mailbox.receive(&mut buffer).is_err());
+        assert!(mailbox.flush_all().is_ok());
+    }
+}
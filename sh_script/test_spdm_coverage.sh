@@ -11,7 +11,7 @@ export LLVM_PROFILE_FILE="rust-spdm-%p%m.profraw"
 
 cargo build -p spdm-responder-emu -p spdm-requester-emu
 
-cargo run -p spdm-responder-emu & 
+cargo run -p spdm-responder-emu -- --max_conn_count 1 &
 cargo run -p spdm-requester-emu
 
 grcov . -s . --binary-path ./target/debug/ -t html --branch --ignore-not-existing -o ./target/debug/test_spdm_coverage/
\ No newline at end of file
@@ -15,9 +15,12 @@ struct SpdmConfig {
     algo_config: SpdmAlgoConfig,
     cert_config: SpdmCertConfig,
     max_opaque_size: usize,
+    max_opaque_element_count: usize,
     measurement_config: SpdmMeasurementConfig,
     psk_config: SpdmPskConfig,
     max_session_count: usize,
+    event_config: SpdmEventConfig,
+    endpoint_info_config: SpdmEndpointInfoConfig,
     max_msg_buffer_size: usize,
     max_transport_size: usize,
 }
@@ -30,6 +33,9 @@ impl SpdmConfig {
         // Check if meet SPDM requirements.
         assert!(self.cert_config.max_cert_portion_len < self.max_transport_size);
         assert!(self.max_opaque_size < 1024);
+        assert!(self.event_config.max_event_count <= self.event_config.max_event_queue_size);
+        assert!(self.endpoint_info_config.max_endpoint_info_size < self.max_transport_size);
+        assert!(self.endpoint_info_config.max_mel_portion_len < self.max_transport_size);
 
         // TODO: add more sanity checks if needed.
     }
@@ -61,6 +67,21 @@ struct SpdmPskConfig {
     max_psk_hint_size: usize,
 }
 
+#[derive(Debug, PartialEq, Deserialize)]
+struct SpdmEventConfig {
+    max_event_group_count: usize,
+    max_event_type_count: usize,
+    max_event_count: usize,
+    max_event_detail_size: usize,
+    max_event_queue_size: usize,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct SpdmEndpointInfoConfig {
+    max_endpoint_info_size: usize,
+    max_mel_portion_len: usize,
+}
+
 macro_rules! TEMPLATE {
     () => {
 "// Copyright (c) 2021 Intel Corporation
@@ -91,6 +112,9 @@ pub const MAX_SPDM_CERT_CHAIN_DATA_SIZE: usize = {cert_chain_data_sz}; // 0x1000
 /// It should be smaller than 1024
 pub const MAX_SPDM_OPAQUE_SIZE: usize = {opaque_sz};
 
+/// This is used in SpdmGeneralOpaqueData, the DMTF general opaque data format.
+pub const MAX_SPDM_OPAQUE_ELEMENT_COUNT: usize = {opaque_element_cnt};
+
 /// This is used in SpdmDmtfMeasurementStructure <- SpdmMeasurementBlockStructure <- SpdmMeasurementsResponsePayload
 /// It should be MAX (MAX MEASUREMENT_MANIFEST_LEN, MAX supported DIGEST SIZE)
 pub const MAX_SPDM_MEASUREMENT_VALUE_LEN: usize = {meas_val_len};
@@ -110,6 +134,25 @@ pub const MAX_SPDM_PSK_HINT_SIZE: usize = {psk_hint_sz};
 /// This is used in SpdmContext
 pub const MAX_SPDM_SESSION_COUNT: usize = {session_cnt};
 
+/// This is used in SpdmSupportedEventTypesResponsePayload / SpdmSubscribeEventTypesRequestPayload
+pub const MAX_SPDM_EVENT_GROUP_COUNT: usize = {event_group_cnt};
+pub const MAX_SPDM_EVENT_TYPE_COUNT: usize = {event_type_cnt};
+
+/// This is used in SpdmSendEventRequestPayload
+pub const MAX_SPDM_EVENT_COUNT: usize = {event_cnt};
+pub const MAX_SPDM_EVENT_DETAIL_SIZE: usize = {event_detail_sz};
+
+/// This is used in SpdmEventQueue, the pending events of all sessions.
+pub const MAX_SPDM_EVENT_QUEUE_SIZE: usize = {event_queue_sz};
+
+/// This is used in SpdmEndpointInfoResponsePayload
+/// It should be smaller than MAX_SPDM_TRANSPORT_SIZE
+pub const MAX_SPDM_ENDPOINT_INFO_SIZE: usize = {ep_info_sz};
+
+/// This is used in SpdmMeasurementExtensionLogResponsePayload
+/// It should be smaller than MAX_SPDM_TRANSPORT_SIZE
+pub const MAX_SPDM_MEL_PORTION_LEN: usize = {mel_portion_len};
+
 /// This is used in SpdmRuntimeInfo. max cached size
 pub const MAX_SPDM_MESSAGE_BUFFER_SIZE: usize = {msg_buf_sz}; // 0x1200
 
@@ -149,11 +192,19 @@ fn main() {
         cert_portion_len = spdm_config.cert_config.max_cert_portion_len,
         cert_chain_data_sz = spdm_config.cert_config.max_cert_chain_data_size,
         opaque_sz = spdm_config.max_opaque_size,
+        opaque_element_cnt = spdm_config.max_opaque_element_count,
         meas_val_len = spdm_config.measurement_config.max_measurement_val_len,
         meas_block_cnt = spdm_config.measurement_config.max_measurement_block_count,
         psk_ctx_sz = spdm_config.psk_config.max_psk_context_size,
         psk_hint_sz = spdm_config.psk_config.max_psk_hint_size,
         session_cnt = spdm_config.max_session_count,
+        event_group_cnt = spdm_config.event_config.max_event_group_count,
+        event_type_cnt = spdm_config.event_config.max_event_type_count,
+        event_cnt = spdm_config.event_config.max_event_count,
+        event_detail_sz = spdm_config.event_config.max_event_detail_size,
+        event_queue_sz = spdm_config.event_config.max_event_queue_size,
+        ep_info_sz = spdm_config.endpoint_info_config.max_endpoint_info_size,
+        mel_portion_len = spdm_config.endpoint_info_config.max_mel_portion_len,
         msg_buf_sz = spdm_config.max_msg_buffer_size,
         trans_sz = spdm_config.max_transport_size
     )
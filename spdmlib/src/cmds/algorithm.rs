@@ -50,7 +50,7 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmNegotiateAlgorithmsRequestPayload> {
         let alg_struct_count = u8::read(r)?; // param1
@@ -149,7 +149,7 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmAlgorithmsResponsePayload> {
         let alg_struct_count = u8::read(r)?; // param1
@@ -233,8 +233,11 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
-        let spdm_sturct_data =
-            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_sturct_data = SpdmNegotiateAlgorithmsRequestPayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(
             spdm_sturct_data.measurement_specification,
             SpdmMeasurementSpecification::DMTF
@@ -280,8 +283,11 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
-        let spdm_sturct_data =
-            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_sturct_data = SpdmNegotiateAlgorithmsRequestPayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(
             spdm_sturct_data.measurement_specification,
             SpdmMeasurementSpecification::empty()
@@ -312,7 +318,10 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
         let spdm_negotiate_algorithms_request_payload =
-            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(&mut context, &mut reader);
+            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(
+                &context.get_decode_info(),
+                &mut reader,
+            );
         assert_eq!(spdm_negotiate_algorithms_request_payload.is_none(), true);
         assert_eq!(10, reader.left());
     }
@@ -342,7 +351,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(50, reader.left());
         let spdm_sturct_data =
-            SpdmAlgorithmsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmAlgorithmsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             spdm_sturct_data.measurement_specification_sel,
             SpdmMeasurementSpecification::DMTF
@@ -398,7 +408,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
         let spdm_algorithms_response_payload =
-            SpdmAlgorithmsResponsePayload::spdm_read(&mut context, &mut reader);
+            SpdmAlgorithmsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader);
         assert_eq!(spdm_algorithms_response_payload.is_none(), true);
     }
     #[test]
@@ -422,7 +432,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(50, reader.left());
         let spdm_sturct_data =
-            SpdmAlgorithmsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmAlgorithmsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             spdm_sturct_data.measurement_specification_sel,
             SpdmMeasurementSpecification::empty()
@@ -55,7 +55,7 @@ impl SpdmCodec for SpdmGetCapabilitiesRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmGetCapabilitiesRequestPayload> {
         u8::read(r)?; // param1
@@ -92,6 +92,11 @@ bitflags! {
         const KEY_UPD_CAP = 0b0100_0000_0000_0000;
         const HANDSHAKE_IN_THE_CLEAR_CAP = 0b1000_0000_0000_0000;
         const PUB_KEY_ID_CAP = 0b0000_0001_0000_0000_0000_0000;
+        const EP_INFO_CAP_NO_SIG = 0b0000_0000_0100_0000_0000_0000_0000_0000;
+        const EP_INFO_CAP_SIG = 0b0000_0000_1000_0000_0000_0000_0000_0000;
+        const EP_INFO_CAP_MASK = Self::EP_INFO_CAP_NO_SIG.bits | Self::EP_INFO_CAP_SIG.bits;
+        const MEL_CAP = 0b0000_0001_0000_0000_0000_0000_0000_0000;
+        const EVENT_CAP = 0b0000_0010_0000_0000_0000_0000_0000_0000;
     }
 }
 
@@ -125,7 +130,7 @@ impl SpdmCodec for SpdmCapabilitiesResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmCapabilitiesResponsePayload> {
         u8::read(r)?; // param1
@@ -248,7 +253,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_get_capabilities_request_payload =
-            SpdmGetCapabilitiesRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmGetCapabilitiesRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_get_capabilities_request_payload.ct_exponent, 100);
         assert_eq!(
             spdm_get_capabilities_request_payload.flags,
@@ -273,7 +279,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_get_capabilities_request_payload =
-            SpdmGetCapabilitiesRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmGetCapabilitiesRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_get_capabilities_request_payload.ct_exponent, 0);
         assert_eq!(
             spdm_get_capabilities_request_payload.flags,
@@ -294,7 +301,7 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
-        SpdmGetCapabilitiesRequestPayload::spdm_read(&mut context, &mut reader);
+        SpdmGetCapabilitiesRequestPayload::spdm_read(&context.get_decode_info(), &mut reader);
         assert_eq!(2, reader.left());
     }
     #[test]
@@ -314,7 +321,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_capabilities_response_payload =
-            SpdmCapabilitiesResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmCapabilitiesResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_capabilities_response_payload.ct_exponent, 100);
         assert_eq!(
             spdm_capabilities_response_payload.flags,
@@ -339,7 +347,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_capabilities_response_payload =
-            SpdmCapabilitiesResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmCapabilitiesResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_capabilities_response_payload.ct_exponent, 0);
         assert_eq!(
             spdm_capabilities_response_payload.flags,
@@ -361,7 +370,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_capabilities_response_payload =
-            SpdmCapabilitiesResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmCapabilitiesResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_capabilities_response_payload.ct_exponent, 0);
         assert_eq!(
             spdm_capabilities_response_payload.flags,
@@ -23,7 +23,7 @@ impl SpdmCodec for SpdmGetCertificateRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmGetCertificateRequestPayload> {
         let slot_id = u8::read(r)?; // param1
@@ -70,7 +70,7 @@ impl SpdmCodec for SpdmCertificateResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmCertificateResponsePayload> {
         let slot_id = u8::read(r)?; // param1
@@ -113,7 +113,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_get_certificate_request_payload =
-            SpdmGetCertificateRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmGetCertificateRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_get_certificate_request_payload.slot_id, 100);
         assert_eq!(spdm_get_certificate_request_payload.offset, 100);
         assert_eq!(spdm_get_certificate_request_payload.length, 100);
@@ -143,7 +144,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(520, reader.left());
         let spdm_get_certificate_request_payload =
-            SpdmCertificateResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmCertificateResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_get_certificate_request_payload.slot_id, 100);
         assert_eq!(spdm_get_certificate_request_payload.portion_length, 512);
         assert_eq!(spdm_get_certificate_request_payload.remainder_length, 100);
@@ -25,7 +25,7 @@ impl SpdmCodec for SpdmChallengeRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmChallengeRequestPayload> {
         let slot_id = u8::read(r)?;
@@ -74,22 +74,22 @@ impl SpdmCodec for SpdmChallengeAuthResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmChallengeAuthResponsePayload> {
         let param1 = u8::read(r)?;
         let slot_id = param1 & 0xF;
         let challenge_auth_attribute = SpdmChallengeAuthAttribute::from_bits(param1 & 0xF0)?;
         let slot_mask = u8::read(r)?; // param2
-        let cert_chain_hash = SpdmDigestStruct::spdm_read(context, r)?;
+        let cert_chain_hash = SpdmDigestStruct::spdm_read(decode_info, r)?;
         let nonce = SpdmNonceStruct::read(r)?;
-        let measurement_summary_hash = if context.runtime_info.need_measurement_summary_hash {
-            SpdmDigestStruct::spdm_read(context, r)?
+        let measurement_summary_hash = if decode_info.need_measurement_summary_hash {
+            SpdmDigestStruct::spdm_read(decode_info, r)?
         } else {
             SpdmDigestStruct::default()
         };
-        let opaque = SpdmOpaqueStruct::spdm_read(context, r)?;
-        let signature = SpdmSignatureStruct::spdm_read(context, r)?;
+        let opaque = SpdmOpaqueStruct::spdm_read(decode_info, r)?;
+        let signature = SpdmSignatureStruct::spdm_read(decode_info, r)?;
         Some(SpdmChallengeAuthResponsePayload {
             slot_id,
             slot_mask,
@@ -129,7 +129,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(34, reader.left());
         let spdm_challenge_request_payload =
-            SpdmChallengeRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmChallengeRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_challenge_request_payload.slot_id, 100);
         assert_eq!(
             spdm_challenge_request_payload.measurement_summary_hash_type,
@@ -181,7 +182,8 @@ mod tests {
 
         assert_eq!(800, reader.left());
         let spdm_read_data =
-            SpdmChallengeAuthResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmChallengeAuthResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(60, reader.left());
         assert_eq!(spdm_read_data.slot_id, 0x0f);
         assert_eq!(spdm_read_data.slot_mask, 100);
@@ -248,7 +250,8 @@ mod tests {
 
         assert_eq!(800, reader.left());
         let spdm_read_data =
-            SpdmChallengeAuthResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmChallengeAuthResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(124, reader.left());
         assert_eq!(spdm_read_data.measurement_summary_hash.data_size, 0);
         for i in 0..64 {
@@ -17,7 +17,7 @@ impl SpdmCodec for SpdmGetDigestsRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmGetDigestsRequestPayload> {
         u8::read(r)?; // param1
@@ -56,7 +56,7 @@ impl SpdmCodec for SpdmDigestsResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmDigestsResponsePayload> {
         u8::read(r)?; // param1
@@ -71,7 +71,7 @@ impl SpdmCodec for SpdmDigestsResponsePayload {
 
         let mut digests = [SpdmDigestStruct::default(); SPDM_MAX_SLOT_NUMBER];
         for digest in digests.iter_mut().take(slot_count as usize) {
-            *digest = SpdmDigestStruct::spdm_read(context, r)?;
+            *digest = SpdmDigestStruct::spdm_read(decode_info, r)?;
         }
         Some(SpdmDigestsResponsePayload {
             slot_mask,
@@ -114,7 +114,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(514, reader.left());
         let spdm_digests_response_payload =
-            SpdmDigestsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmDigestsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_digests_response_payload.slot_mask, 0b11111111);
         assert_eq!(spdm_digests_response_payload.slot_count, 8);
         for i in 0..8 {
@@ -143,7 +143,7 @@ mod tests {
         context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_512;
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmDigestsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        SpdmDigestsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
 
         let u8_slice = &mut [0u8; 10];
         let mut writer = Writer::init(u8_slice);
@@ -167,6 +167,6 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmGetDigestsRequestPayload::spdm_read(&mut context, &mut reader);
+        SpdmGetDigestsRequestPayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
 }
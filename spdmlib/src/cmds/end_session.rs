@@ -37,7 +37,7 @@ impl SpdmCodec for SpdmEndSessionRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmEndSessionRequestPayload> {
         let end_session_request_attributes = SpdmEndSessionRequestAttributes::read(r)?; // param1
@@ -59,7 +59,7 @@ impl SpdmCodec for SpdmEndSessionResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmEndSessionResponsePayload> {
         u8::read(r)?; // param1
@@ -104,7 +104,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(12, reader.left());
         let spdm_end_session_request_payload =
-            SpdmEndSessionRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmEndSessionRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             spdm_end_session_request_payload.end_session_request_attributes,
             SpdmEndSessionRequestAttributes::PRESERVE_NEGOTIATED_STATE
@@ -121,6 +122,6 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmEndSessionResponsePayload::spdm_read(&mut context, &mut reader);
+        SpdmEndSessionResponsePayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
 }
@@ -0,0 +1,299 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common;
+use crate::config;
+use crate::msgs::SpdmCodec;
+use crate::msgs::{SpdmNonceStruct, SpdmSignatureStruct};
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+
+pub const SPDM_SLOT_ID_MASK: u8 = 0x0F;
+
+enum_builder! {
+    @U8
+    EnumName: SpdmEndpointInfoSubCode;
+    EnumVal{
+        SpdmEndpointInfoSubCodeDeviceClassIdentifier => 0x1
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmEndpointInfoRequestAttributes: u8 {
+        const SIGNATURE_REQUESTED = 0b00000001;
+    }
+}
+
+impl Codec for SpdmEndpointInfoRequestAttributes {
+    fn encode(&self, bytes: &mut Writer) {
+        self.bits().encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmEndpointInfoRequestAttributes> {
+        let bits = u8::read(r)?;
+
+        SpdmEndpointInfoRequestAttributes::from_bits(bits)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmGetEndpointInfoRequestPayload {
+    pub sub_code: SpdmEndpointInfoSubCode,
+    pub request_attributes: SpdmEndpointInfoRequestAttributes,
+    pub slot_id: u8,
+    pub nonce: SpdmNonceStruct,
+}
+
+impl SpdmCodec for SpdmGetEndpointInfoRequestPayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        self.sub_code.encode(bytes); // param1
+        self.request_attributes.encode(bytes); // param2
+        (self.slot_id & SPDM_SLOT_ID_MASK).encode(bytes);
+        0u8.encode(bytes); // reserved
+        0u16.encode(bytes); // reserved
+        if self
+            .request_attributes
+            .contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED)
+        {
+            self.nonce.encode(bytes);
+        }
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmGetEndpointInfoRequestPayload> {
+        let sub_code = SpdmEndpointInfoSubCode::read(r)?; // param1
+        let request_attributes = SpdmEndpointInfoRequestAttributes::read(r)?; // param2
+        let slot_id = u8::read(r)? & SPDM_SLOT_ID_MASK;
+        u8::read(r)?; // reserved
+        u16::read(r)?; // reserved
+        let nonce = if request_attributes
+            .contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED)
+        {
+            SpdmNonceStruct::read(r)?
+        } else {
+            SpdmNonceStruct::default()
+        };
+
+        Some(SpdmGetEndpointInfoRequestPayload {
+            sub_code,
+            request_attributes,
+            slot_id,
+            nonce,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SpdmEndpointInfoResponsePayload {
+    pub slot_id: u8,
+    pub nonce: SpdmNonceStruct,
+    pub ep_info_len: u32,
+    pub ep_info: [u8; config::MAX_SPDM_ENDPOINT_INFO_SIZE],
+    pub signature: SpdmSignatureStruct,
+}
+
+impl Default for SpdmEndpointInfoResponsePayload {
+    fn default() -> SpdmEndpointInfoResponsePayload {
+        SpdmEndpointInfoResponsePayload {
+            slot_id: 0,
+            nonce: SpdmNonceStruct::default(),
+            ep_info_len: 0,
+            ep_info: [0u8; config::MAX_SPDM_ENDPOINT_INFO_SIZE],
+            signature: SpdmSignatureStruct::default(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for SpdmEndpointInfoResponsePayload {
+    fn as_ref(&self) -> &[u8] {
+        &self.ep_info[0..(self.ep_info_len as usize)]
+    }
+}
+
+impl SpdmCodec for SpdmEndpointInfoResponsePayload {
+    fn spdm_encode(&self, context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        (self.slot_id & SPDM_SLOT_ID_MASK).encode(bytes); // param2
+        if context.runtime_info.need_endpoint_info_signature {
+            self.nonce.encode(bytes);
+        }
+        self.ep_info_len.encode(bytes);
+        for d in self.ep_info.iter().take(self.ep_info_len as usize) {
+            d.encode(bytes);
+        }
+        if context.runtime_info.need_endpoint_info_signature {
+            self.signature.spdm_encode(context, bytes);
+        }
+    }
+
+    fn spdm_read(
+        decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmEndpointInfoResponsePayload> {
+        u8::read(r)?; // param1
+        let slot_id = u8::read(r)? & SPDM_SLOT_ID_MASK; // param2
+        let nonce = if decode_info.need_endpoint_info_signature {
+            SpdmNonceStruct::read(r)?
+        } else {
+            SpdmNonceStruct::default()
+        };
+        let ep_info_len = u32::read(r)?;
+        if ep_info_len as usize > config::MAX_SPDM_ENDPOINT_INFO_SIZE {
+            return None;
+        }
+        let mut ep_info = [0u8; config::MAX_SPDM_ENDPOINT_INFO_SIZE];
+        for d in ep_info.iter_mut().take(ep_info_len as usize) {
+            *d = u8::read(r)?;
+        }
+        let signature = if decode_info.need_endpoint_info_signature {
+            SpdmSignatureStruct::spdm_read(decode_info, r)?
+        } else {
+            SpdmSignatureStruct::default()
+        };
+
+        Some(SpdmEndpointInfoResponsePayload {
+            slot_id,
+            nonce,
+            ep_info_len,
+            ep_info,
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msgs::*;
+    use crate::testlib::*;
+
+    #[test]
+    fn test_case0_spdm_get_endpoint_info_request_payload() {
+        let u8_slice = &mut [0u8; 48];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmGetEndpointInfoRequestPayload {
+            sub_code: SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier,
+            request_attributes: SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+            slot_id: 0xf3,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+        };
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(38, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let get_endpoint_info =
+            SpdmGetEndpointInfoRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
+        assert_eq!(
+            get_endpoint_info.sub_code,
+            SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier
+        );
+        assert_eq!(get_endpoint_info.slot_id, 3);
+        for i in 0..SPDM_NONCE_SIZE {
+            assert_eq!(get_endpoint_info.nonce.data[i], 100u8);
+        }
+        assert_eq!(10, reader.left());
+    }
+    #[test]
+    fn test_case1_spdm_get_endpoint_info_request_payload() {
+        let u8_slice = &mut [0u8; 8];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmGetEndpointInfoRequestPayload {
+            sub_code: SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier,
+            request_attributes: SpdmEndpointInfoRequestAttributes::empty(),
+            slot_id: 0,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+        };
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(6, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let get_endpoint_info =
+            SpdmGetEndpointInfoRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
+        for i in 0..SPDM_NONCE_SIZE {
+            assert_eq!(get_endpoint_info.nonce.data[i], 0);
+        }
+        assert_eq!(2, reader.left());
+    }
+    #[test]
+    fn test_case0_spdm_endpoint_info_response_payload() {
+        let u8_slice = &mut [0u8; 256];
+        let mut writer = Writer::init(u8_slice);
+        let mut value = SpdmEndpointInfoResponsePayload {
+            slot_id: 1,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+            ep_info_len: 16,
+            signature: SpdmSignatureStruct {
+                data_size: 96,
+                data: [0xa5u8; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+            ..Default::default()
+        };
+        value.ep_info[..16].copy_from_slice(&[0x5au8; 16]);
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+        context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+        context.runtime_info.need_endpoint_info_signature = true;
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(2 + 32 + 4 + 16 + 96, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let endpoint_info =
+            SpdmEndpointInfoResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
+        assert_eq!(endpoint_info.slot_id, 1);
+        assert_eq!(endpoint_info.as_ref(), &[0x5au8; 16]);
+        assert_eq!(endpoint_info.signature.data_size, 96);
+        for i in 0..96 {
+            assert_eq!(endpoint_info.signature.data[i], 0xa5u8);
+        }
+
+        context.runtime_info.need_endpoint_info_signature = false;
+        let u8_slice = &mut [0u8; 256];
+        let mut writer = Writer::init(u8_slice);
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(2 + 4 + 16, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let endpoint_info =
+            SpdmEndpointInfoResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
+        assert_eq!(endpoint_info.as_ref(), &[0x5au8; 16]);
+        assert_eq!(endpoint_info.signature.data_size, 0);
+    }
+    #[test]
+    fn test_case1_spdm_endpoint_info_response_payload() {
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        0u8.encode(&mut writer);
+        0u8.encode(&mut writer);
+        (config::MAX_SPDM_ENDPOINT_INFO_SIZE as u32 + 1).encode(&mut writer);
+
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmEndpointInfoResponsePayload::spdm_read(
+            &common::SpdmDecodeInfo::default(),
+            &mut reader
+        )
+        .is_none());
+    }
+}
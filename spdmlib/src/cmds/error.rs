@@ -35,7 +35,7 @@ impl SpdmCodec for SpdmErrorResponseNoneExtData {
     fn spdm_encode(&self, _context: &mut common::SpdmContext, _bytes: &mut Writer) {}
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         _r: &mut Reader,
     ) -> Option<SpdmErrorResponseNoneExtData> {
         Some(SpdmErrorResponseNoneExtData {})
@@ -59,7 +59,7 @@ impl SpdmCodec for SpdmErrorResponseNotReadyExtData {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmErrorResponseNotReadyExtData> {
         let rdt_exponent = u8::read(r)?;
@@ -90,7 +90,7 @@ impl SpdmCodec for SpdmErrorResponseVendorExtData {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmErrorResponseVendorExtData> {
         let mut data_size = 0;
@@ -151,7 +151,7 @@ impl SpdmCodec for SpdmErrorResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmErrorResponsePayload> {
         let error_code = SpdmErrorCode::read(r)?; // param1
@@ -160,16 +160,16 @@ impl SpdmCodec for SpdmErrorResponsePayload {
         let extended_data = match error_code {
             SpdmErrorCode::SpdmErrorResponseNotReady => {
                 Some(SpdmErrorResponseExtData::SpdmErrorExtDataNotReady(
-                    SpdmErrorResponseNotReadyExtData::spdm_read(context, r)?,
+                    SpdmErrorResponseNotReadyExtData::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmErrorCode::SpdmErrorVendorDefined => {
                 Some(SpdmErrorResponseExtData::SpdmErrorExtDataVendorDefined(
-                    SpdmErrorResponseVendorExtData::spdm_read(context, r)?,
+                    SpdmErrorResponseVendorExtData::spdm_read(decode_info, r)?,
                 ))
             }
             _ => Some(SpdmErrorResponseExtData::SpdmErrorExtDataNone(
-                SpdmErrorResponseNoneExtData::spdm_read(context, r)?,
+                SpdmErrorResponseNoneExtData::spdm_read(decode_info, r)?,
             )),
         };
 
@@ -209,7 +209,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(8, reader.left());
         let spdm_error_response_not_ready_ext_data =
-            SpdmErrorResponseNotReadyExtData::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmErrorResponseNotReadyExtData::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_error_response_not_ready_ext_data.rdt_exponent, 0xaa);
         assert_eq!(spdm_error_response_not_ready_ext_data.request_code, 0xaa);
         assert_eq!(spdm_error_response_not_ready_ext_data.token, 0x55);
@@ -232,7 +233,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(32, reader.left());
         let response_vendor_ext_data =
-            SpdmErrorResponseVendorExtData::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmErrorResponseVendorExtData::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(response_vendor_ext_data.data_size, 32);
         for i in 0..32 {
             assert_eq!(response_vendor_ext_data.data[i], 100u8);
@@ -251,7 +253,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(32, reader.left());
         let response_vendor_ext_data =
-            SpdmErrorResponseVendorExtData::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmErrorResponseVendorExtData::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(response_vendor_ext_data.data_size, 32);
         for i in 0..32 {
             assert_eq!(response_vendor_ext_data.data[i], 0);
@@ -333,7 +336,7 @@ mod tests {
         value.spdm_encode(context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         let spdm_error_response_payload =
-            SpdmErrorResponsePayload::spdm_read(context, &mut reader).unwrap();
+            SpdmErrorResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         spdm_error_response_payload
     }
 }
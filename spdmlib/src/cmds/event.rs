@@ -0,0 +1,747 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common;
+use crate::config;
+use crate::msgs::SpdmCodec;
+use crate::msgs::{SpdmStandardId, SpdmSvhStruct};
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+
+pub const SPDM_DMTF_EVENT_GROUP_VERSION: u8 = 1;
+pub const SPDM_MEASUREMENT_INDEX_MASK_SIZE: usize = 32;
+
+enum_builder! {
+    @U16
+    EnumName: SpdmDmtfEventType;
+    EnumVal{
+        SpdmDmtfEventTypeEventLost => 0x1,
+        SpdmDmtfEventTypeMeasurementChanged => 0x2,
+        SpdmDmtfEventTypeMeasurementPreUpdate => 0x3,
+        SpdmDmtfEventTypeCertificateChanged => 0x4
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmDmtfEventTypeFlags: u16 {
+        const EVENT_LOST = 0b0000_0001;
+        const MEASUREMENT_CHANGED = 0b0000_0010;
+        const MEASUREMENT_PRE_UPDATE = 0b0000_0100;
+        const CERTIFICATE_CHANGED = 0b0000_1000;
+    }
+}
+
+impl SpdmDmtfEventTypeFlags {
+    pub fn from_event_type(event_type: SpdmDmtfEventType) -> SpdmDmtfEventTypeFlags {
+        match event_type {
+            SpdmDmtfEventType::Unknown(_) => SpdmDmtfEventTypeFlags::empty(),
+            _ => SpdmDmtfEventTypeFlags::from_bits_truncate(1 << (event_type.get_u16() - 1)),
+        }
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmEventGroupAttributes: u8 {
+        const SUBSCRIBE_ALL = 0b0000_0001;
+    }
+}
+
+impl Codec for SpdmEventGroupAttributes {
+    fn encode(&self, bytes: &mut Writer) {
+        self.bits().encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmEventGroupAttributes> {
+        let bits = u8::read(r)?;
+
+        SpdmEventGroupAttributes::from_bits(bits)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmEventGroupStruct {
+    pub event_group_id: SpdmSvhStruct,
+    pub event_group_ver: u8,
+    pub attributes: SpdmEventGroupAttributes,
+    pub event_type_count: u8,
+    pub event_types: [u16; config::MAX_SPDM_EVENT_TYPE_COUNT],
+}
+
+impl SpdmEventGroupStruct {
+    pub fn new_dmtf(
+        attributes: SpdmEventGroupAttributes,
+        event_types: SpdmDmtfEventTypeFlags,
+    ) -> SpdmEventGroupStruct {
+        let mut event_group = SpdmEventGroupStruct {
+            event_group_id: SpdmSvhStruct::dmtf(),
+            event_group_ver: SPDM_DMTF_EVENT_GROUP_VERSION,
+            attributes,
+            ..Default::default()
+        };
+        for i in 0..16u16 {
+            if event_types.bits() & (1 << i) != 0 {
+                event_group.event_types[event_group.event_type_count as usize] = i + 1;
+                event_group.event_type_count += 1;
+            }
+        }
+        event_group
+    }
+
+    /// Return the DMTF event types listed in this group,
+    /// or None if this is not the DMTF event group.
+    pub fn get_dmtf_event_types(&self) -> Option<SpdmDmtfEventTypeFlags> {
+        if self.event_group_id.id != SpdmStandardId::SpdmStandardIdDMTF {
+            return None;
+        }
+        if self
+            .attributes
+            .contains(SpdmEventGroupAttributes::SUBSCRIBE_ALL)
+        {
+            return Some(SpdmDmtfEventTypeFlags::all());
+        }
+        let mut event_types = SpdmDmtfEventTypeFlags::empty();
+        for event_type in self.event_types.iter().take(self.event_type_count as usize) {
+            event_types |=
+                SpdmDmtfEventTypeFlags::from_event_type(SpdmDmtfEventType::from(*event_type));
+        }
+        Some(event_types)
+    }
+}
+
+impl From<u16> for SpdmDmtfEventType {
+    fn from(event_type: u16) -> SpdmDmtfEventType {
+        let bytes = event_type.to_le_bytes();
+        SpdmDmtfEventType::read_bytes(&bytes).unwrap_or_default()
+    }
+}
+
+impl Codec for SpdmEventGroupStruct {
+    fn encode(&self, bytes: &mut Writer) {
+        self.event_group_id.encode(bytes);
+        self.event_group_ver.encode(bytes);
+        self.attributes.encode(bytes);
+        self.event_type_count.encode(bytes);
+        for event_type in self.event_types.iter().take(self.event_type_count as usize) {
+            event_type.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmEventGroupStruct> {
+        let event_group_id = SpdmSvhStruct::read(r)?;
+        let event_group_ver = u8::read(r)?;
+        let attributes = SpdmEventGroupAttributes::read(r)?;
+        let event_type_count = u8::read(r)?;
+        if event_type_count as usize > config::MAX_SPDM_EVENT_TYPE_COUNT {
+            return None;
+        }
+        let mut event_types = [0u16; config::MAX_SPDM_EVENT_TYPE_COUNT];
+        for event_type in event_types.iter_mut().take(event_type_count as usize) {
+            *event_type = u16::read(r)?;
+        }
+
+        Some(SpdmEventGroupStruct {
+            event_group_id,
+            event_group_ver,
+            attributes,
+            event_type_count,
+            event_types,
+        })
+    }
+}
+
+fn encode_event_group_list(
+    event_group_count: u8,
+    event_groups: &[SpdmEventGroupStruct],
+    bytes: &mut Writer,
+) {
+    let list_len_offset = bytes.used();
+    0u32.encode(bytes); // list length, filled below
+    for event_group in event_groups.iter().take(event_group_count as usize) {
+        event_group.encode(bytes);
+    }
+    let list_len = (bytes.used() - list_len_offset - 4) as u32;
+    bytes.mut_used_slice()[list_len_offset..(list_len_offset + 4)]
+        .copy_from_slice(&list_len.to_le_bytes());
+}
+
+fn read_event_group_list(
+    event_group_count: u8,
+    r: &mut Reader,
+) -> Option<[SpdmEventGroupStruct; config::MAX_SPDM_EVENT_GROUP_COUNT]> {
+    if event_group_count as usize > config::MAX_SPDM_EVENT_GROUP_COUNT {
+        return None;
+    }
+    let list_len = u32::read(r)?;
+    let mut list = r.sub(list_len as usize)?;
+    let mut event_groups = [SpdmEventGroupStruct::default(); config::MAX_SPDM_EVENT_GROUP_COUNT];
+    for event_group in event_groups.iter_mut().take(event_group_count as usize) {
+        *event_group = SpdmEventGroupStruct::read(&mut list)?;
+    }
+    if list.any_left() {
+        return None;
+    }
+    Some(event_groups)
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmGetSupportedEventTypesRequestPayload {}
+
+impl SpdmCodec for SpdmGetSupportedEventTypesRequestPayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmGetSupportedEventTypesRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmGetSupportedEventTypesRequestPayload {})
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSupportedEventTypesResponsePayload {
+    pub event_group_count: u8,
+    pub event_groups: [SpdmEventGroupStruct; config::MAX_SPDM_EVENT_GROUP_COUNT],
+}
+
+impl SpdmCodec for SpdmSupportedEventTypesResponsePayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        self.event_group_count.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+
+        encode_event_group_list(self.event_group_count, &self.event_groups, bytes);
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmSupportedEventTypesResponsePayload> {
+        let event_group_count = u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        let event_groups = read_event_group_list(event_group_count, r)?;
+
+        Some(SpdmSupportedEventTypesResponsePayload {
+            event_group_count,
+            event_groups,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSubscribeEventTypesRequestPayload {
+    pub event_group_count: u8,
+    pub event_groups: [SpdmEventGroupStruct; config::MAX_SPDM_EVENT_GROUP_COUNT],
+}
+
+impl SpdmCodec for SpdmSubscribeEventTypesRequestPayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        self.event_group_count.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+
+        encode_event_group_list(self.event_group_count, &self.event_groups, bytes);
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmSubscribeEventTypesRequestPayload> {
+        let event_group_count = u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        let event_groups = read_event_group_list(event_group_count, r)?;
+
+        Some(SpdmSubscribeEventTypesRequestPayload {
+            event_group_count,
+            event_groups,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSubscribeEventTypesAckResponsePayload {}
+
+impl SpdmCodec for SpdmSubscribeEventTypesAckResponsePayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmSubscribeEventTypesAckResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmSubscribeEventTypesAckResponsePayload {})
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SpdmEventLostStruct {
+    pub last_acked_event_instance_id: u32,
+    pub last_lost_event_instance_id: u32,
+}
+
+/// Bit N set means measurement index N is affected.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SpdmMeasurementIndexMask {
+    pub data: [u8; SPDM_MEASUREMENT_INDEX_MASK_SIZE],
+}
+
+impl SpdmMeasurementIndexMask {
+    pub fn set_index(&mut self, index: u8) {
+        self.data[(index / 8) as usize] |= 1 << (index % 8);
+    }
+
+    pub fn contains_index(&self, index: u8) -> bool {
+        self.data[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SpdmCertificateChangedStruct {
+    pub slot_id: u8,
+}
+
+/// Event details of the DMTF event group.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpdmDmtfEventDetail {
+    EventLost(SpdmEventLostStruct),
+    MeasurementChanged(SpdmMeasurementIndexMask),
+    MeasurementPreUpdate(SpdmMeasurementIndexMask),
+    CertificateChanged(SpdmCertificateChangedStruct),
+}
+
+impl SpdmDmtfEventDetail {
+    pub fn get_event_type(&self) -> SpdmDmtfEventType {
+        match self {
+            SpdmDmtfEventDetail::EventLost(_) => SpdmDmtfEventType::SpdmDmtfEventTypeEventLost,
+            SpdmDmtfEventDetail::MeasurementChanged(_) => {
+                SpdmDmtfEventType::SpdmDmtfEventTypeMeasurementChanged
+            }
+            SpdmDmtfEventDetail::MeasurementPreUpdate(_) => {
+                SpdmDmtfEventType::SpdmDmtfEventTypeMeasurementPreUpdate
+            }
+            SpdmDmtfEventDetail::CertificateChanged(_) => {
+                SpdmDmtfEventType::SpdmDmtfEventTypeCertificateChanged
+            }
+        }
+    }
+
+    fn encode_detail(&self, bytes: &mut Writer) {
+        match self {
+            SpdmDmtfEventDetail::EventLost(detail) => {
+                detail.last_acked_event_instance_id.encode(bytes);
+                detail.last_lost_event_instance_id.encode(bytes);
+            }
+            SpdmDmtfEventDetail::MeasurementChanged(detail)
+            | SpdmDmtfEventDetail::MeasurementPreUpdate(detail) => {
+                for d in detail.data.iter() {
+                    d.encode(bytes);
+                }
+            }
+            SpdmDmtfEventDetail::CertificateChanged(detail) => {
+                (detail.slot_id & 0xF).encode(bytes);
+            }
+        }
+    }
+
+    fn read_detail(event_type: SpdmDmtfEventType, r: &mut Reader) -> Option<SpdmDmtfEventDetail> {
+        match event_type {
+            SpdmDmtfEventType::SpdmDmtfEventTypeEventLost => {
+                let last_acked_event_instance_id = u32::read(r)?;
+                let last_lost_event_instance_id = u32::read(r)?;
+                Some(SpdmDmtfEventDetail::EventLost(SpdmEventLostStruct {
+                    last_acked_event_instance_id,
+                    last_lost_event_instance_id,
+                }))
+            }
+            SpdmDmtfEventType::SpdmDmtfEventTypeMeasurementChanged
+            | SpdmDmtfEventType::SpdmDmtfEventTypeMeasurementPreUpdate => {
+                let mut mask = SpdmMeasurementIndexMask::default();
+                for d in mask.data.iter_mut() {
+                    *d = u8::read(r)?;
+                }
+                if event_type == SpdmDmtfEventType::SpdmDmtfEventTypeMeasurementChanged {
+                    Some(SpdmDmtfEventDetail::MeasurementChanged(mask))
+                } else {
+                    Some(SpdmDmtfEventDetail::MeasurementPreUpdate(mask))
+                }
+            }
+            SpdmDmtfEventType::SpdmDmtfEventTypeCertificateChanged => {
+                let slot_id = u8::read(r)? & 0xF;
+                Some(SpdmDmtfEventDetail::CertificateChanged(
+                    SpdmCertificateChangedStruct { slot_id },
+                ))
+            }
+            SpdmDmtfEventType::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SpdmEventDataStruct {
+    pub event_instance_id: u32,
+    pub event_group_id: SpdmSvhStruct,
+    pub event_type_id: u16,
+    pub event_detail_size: u16,
+    pub event_detail: [u8; config::MAX_SPDM_EVENT_DETAIL_SIZE],
+}
+
+impl Default for SpdmEventDataStruct {
+    fn default() -> SpdmEventDataStruct {
+        SpdmEventDataStruct {
+            event_instance_id: 0,
+            event_group_id: SpdmSvhStruct::default(),
+            event_type_id: 0,
+            event_detail_size: 0,
+            event_detail: [0u8; config::MAX_SPDM_EVENT_DETAIL_SIZE],
+        }
+    }
+}
+
+impl SpdmEventDataStruct {
+    pub fn new_dmtf(event_instance_id: u32, detail: &SpdmDmtfEventDetail) -> SpdmEventDataStruct {
+        let mut event = SpdmEventDataStruct {
+            event_instance_id,
+            event_group_id: SpdmSvhStruct::dmtf(),
+            event_type_id: detail.get_event_type().get_u16(),
+            ..Default::default()
+        };
+        let mut writer = Writer::init(&mut event.event_detail);
+        detail.encode_detail(&mut writer);
+        event.event_detail_size = writer.used() as u16;
+        event
+    }
+
+    /// Decode the event detail, if this event belongs to the DMTF event group.
+    pub fn get_dmtf_event(&self) -> Option<SpdmDmtfEventDetail> {
+        if self.event_group_id.id != SpdmStandardId::SpdmStandardIdDMTF {
+            return None;
+        }
+        let mut reader = Reader::init(&self.event_detail[..(self.event_detail_size as usize)]);
+        let detail = SpdmDmtfEventDetail::read_detail(
+            SpdmDmtfEventType::from(self.event_type_id),
+            &mut reader,
+        )?;
+        if reader.any_left() {
+            return None;
+        }
+        Some(detail)
+    }
+}
+
+impl Codec for SpdmEventDataStruct {
+    fn encode(&self, bytes: &mut Writer) {
+        self.event_instance_id.encode(bytes);
+        0u32.encode(bytes); // reserved
+        self.event_group_id.encode(bytes);
+        self.event_type_id.encode(bytes);
+        self.event_detail_size.encode(bytes);
+        for d in self
+            .event_detail
+            .iter()
+            .take(self.event_detail_size as usize)
+        {
+            d.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmEventDataStruct> {
+        let event_instance_id = u32::read(r)?;
+        u32::read(r)?; // reserved
+        let event_group_id = SpdmSvhStruct::read(r)?;
+        let event_type_id = u16::read(r)?;
+        let event_detail_size = u16::read(r)?;
+        if event_detail_size as usize > config::MAX_SPDM_EVENT_DETAIL_SIZE {
+            return None;
+        }
+        let mut event_detail = [0u8; config::MAX_SPDM_EVENT_DETAIL_SIZE];
+        for d in event_detail.iter_mut().take(event_detail_size as usize) {
+            *d = u8::read(r)?;
+        }
+
+        Some(SpdmEventDataStruct {
+            event_instance_id,
+            event_group_id,
+            event_type_id,
+            event_detail_size,
+            event_detail,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSendEventRequestPayload {
+    pub event_count: u32,
+    pub events: [SpdmEventDataStruct; config::MAX_SPDM_EVENT_COUNT],
+}
+
+impl SpdmCodec for SpdmSendEventRequestPayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+
+        self.event_count.encode(bytes);
+        for event in self.events.iter().take(self.event_count as usize) {
+            event.encode(bytes);
+        }
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmSendEventRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        let event_count = u32::read(r)?;
+        if event_count as usize > config::MAX_SPDM_EVENT_COUNT {
+            return None;
+        }
+        let mut events = [SpdmEventDataStruct::default(); config::MAX_SPDM_EVENT_COUNT];
+        for event in events.iter_mut().take(event_count as usize) {
+            *event = SpdmEventDataStruct::read(r)?;
+        }
+
+        Some(SpdmSendEventRequestPayload {
+            event_count,
+            events,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmEventAckResponsePayload {}
+
+impl SpdmCodec for SpdmEventAckResponsePayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmEventAckResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmEventAckResponsePayload {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testlib::*;
+
+    #[test]
+    fn test_case0_spdm_event_group_struct() {
+        let u8_slice = &mut [0u8; 32];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED
+                | SpdmDmtfEventTypeFlags::CERTIFICATE_CHANGED,
+        );
+        value.encode(&mut writer);
+        assert_eq!(9, writer.used());
+
+        let mut reader = Reader::init(u8_slice);
+        let event_group = SpdmEventGroupStruct::read(&mut reader).unwrap();
+        assert_eq!(event_group.event_type_count, 2);
+        assert_eq!(event_group.event_types[0], 2);
+        assert_eq!(event_group.event_types[1], 4);
+        assert_eq!(
+            event_group.get_dmtf_event_types().unwrap(),
+            SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED
+                | SpdmDmtfEventTypeFlags::CERTIFICATE_CHANGED
+        );
+        assert_eq!(23, reader.left());
+    }
+    #[test]
+    fn test_case1_spdm_event_group_struct() {
+        let mut value = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::SUBSCRIBE_ALL,
+            SpdmDmtfEventTypeFlags::empty(),
+        );
+        assert_eq!(
+            value.get_dmtf_event_types().unwrap(),
+            SpdmDmtfEventTypeFlags::all()
+        );
+        value.event_group_id.id = SpdmStandardId::SpdmStandardIdPCISIG;
+        assert!(value.get_dmtf_event_types().is_none());
+
+        let u8_slice = &mut [0u8; 8];
+        u8_slice[4] = config::MAX_SPDM_EVENT_TYPE_COUNT as u8 + 1;
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmEventGroupStruct::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_spdm_supported_event_types_response_payload() {
+        let u8_slice = &mut [0u8; 64];
+        let mut writer = Writer::init(u8_slice);
+        let mut value = SpdmSupportedEventTypesResponsePayload {
+            event_group_count: 1,
+            ..Default::default()
+        };
+        value.event_groups[0] = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            SpdmDmtfEventTypeFlags::all(),
+        );
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(19, writer.used());
+        assert_eq!(u8_slice[2], 13);
+        let mut reader = Reader::init(u8_slice);
+        let payload = SpdmSupportedEventTypesResponsePayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(payload.event_group_count, 1);
+        assert_eq!(payload.event_groups[0].event_type_count, 4);
+        assert_eq!(
+            payload.event_groups[0].get_dmtf_event_types().unwrap(),
+            SpdmDmtfEventTypeFlags::all()
+        );
+        assert_eq!(45, reader.left());
+    }
+    #[test]
+    fn test_case1_spdm_supported_event_types_response_payload() {
+        let u8_slice = &mut [0u8; 64];
+        let mut writer = Writer::init(u8_slice);
+        let mut value = SpdmSupportedEventTypesResponsePayload {
+            event_group_count: 1,
+            ..Default::default()
+        };
+        value.event_groups[0] = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            SpdmDmtfEventTypeFlags::all(),
+        );
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        // list length covers more than the event group
+        u8_slice[2] += 1;
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmSupportedEventTypesResponsePayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader
+        )
+        .is_none());
+    }
+    #[test]
+    fn test_case0_spdm_subscribe_event_types_request_payload() {
+        let u8_slice = &mut [0u8; 64];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSubscribeEventTypesRequestPayload::default();
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(6, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let payload = SpdmSubscribeEventTypesRequestPayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(payload.event_group_count, 0);
+        assert_eq!(58, reader.left());
+    }
+    #[test]
+    fn test_case0_spdm_dmtf_event_detail() {
+        let mut mask = SpdmMeasurementIndexMask::default();
+        mask.set_index(1);
+        mask.set_index(254);
+        let details = [
+            SpdmDmtfEventDetail::EventLost(SpdmEventLostStruct {
+                last_acked_event_instance_id: 3,
+                last_lost_event_instance_id: 7,
+            }),
+            SpdmDmtfEventDetail::MeasurementChanged(mask),
+            SpdmDmtfEventDetail::MeasurementPreUpdate(mask),
+            SpdmDmtfEventDetail::CertificateChanged(SpdmCertificateChangedStruct { slot_id: 2 }),
+        ];
+        for (i, detail) in details.iter().enumerate() {
+            let event = SpdmEventDataStruct::new_dmtf(i as u32, detail);
+            assert_eq!(event.event_type_id, i as u16 + 1);
+            assert_eq!(event.get_dmtf_event().unwrap(), *detail);
+        }
+        assert!(mask.contains_index(254));
+        assert!(!mask.contains_index(2));
+
+        let mut event = SpdmEventDataStruct::new_dmtf(0, &details[3]);
+        event.event_detail_size = 0;
+        assert!(event.get_dmtf_event().is_none());
+    }
+    #[test]
+    fn test_case0_spdm_send_event_request_payload() {
+        let u8_slice = &mut [0u8; 128];
+        let mut writer = Writer::init(u8_slice);
+        let mut mask = SpdmMeasurementIndexMask::default();
+        mask.set_index(3);
+        let mut value = SpdmSendEventRequestPayload {
+            event_count: 2,
+            ..Default::default()
+        };
+        value.events[0] =
+            SpdmEventDataStruct::new_dmtf(10, &SpdmDmtfEventDetail::MeasurementChanged(mask));
+        value.events[1] = SpdmEventDataStruct::new_dmtf(
+            11,
+            &SpdmDmtfEventDetail::CertificateChanged(SpdmCertificateChangedStruct { slot_id: 1 }),
+        );
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(6 + 46 + 15, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let payload =
+            SpdmSendEventRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
+        assert_eq!(payload.event_count, 2);
+        assert_eq!(payload.events[0].event_instance_id, 10);
+        assert_eq!(
+            payload.events[0].get_dmtf_event().unwrap(),
+            SpdmDmtfEventDetail::MeasurementChanged(mask)
+        );
+        assert_eq!(payload.events[1].event_instance_id, 11);
+        assert_eq!(128 - 67, reader.left());
+    }
+    #[test]
+    fn test_case1_spdm_send_event_request_payload() {
+        let u8_slice = &mut [0u8; 16];
+        u8_slice[2] = config::MAX_SPDM_EVENT_COUNT as u8 + 1;
+
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmSendEventRequestPayload::spdm_read(
+            &common::SpdmDecodeInfo::default(),
+            &mut reader
+        )
+        .is_none());
+    }
+}
@@ -50,16 +50,16 @@ impl SpdmCodec for SpdmFinishRequestPayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmFinishRequestPayload> {
         let finish_request_attributes = SpdmFinishRequestAttributes::read(r)?; // param1
         let req_slot_id = u8::read(r)?; // param2
         let mut signature = SpdmSignatureStruct::default();
         if finish_request_attributes.contains(SpdmFinishRequestAttributes::SIGNATURE_INCLUDED) {
-            signature = SpdmSignatureStruct::spdm_read(context, r)?;
+            signature = SpdmSignatureStruct::spdm_read(decode_info, r)?;
         }
-        let verify_data = SpdmDigestStruct::spdm_read(context, r)?;
+        let verify_data = SpdmDigestStruct::spdm_read(decode_info, r)?;
 
         Some(SpdmFinishRequestPayload {
             finish_request_attributes,
@@ -93,24 +93,22 @@ impl SpdmCodec for SpdmFinishResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmFinishResponsePayload> {
         u8::read(r)?; // param1
         u8::read(r)?; // param2
 
-        let in_clear_text = context
-            .negotiate_info
+        let in_clear_text = decode_info
             .req_capabilities_sel
             .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && context
-                .negotiate_info
+            && decode_info
                 .rsp_capabilities_sel
                 .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
 
         let mut verify_data = SpdmDigestStruct::default();
         if in_clear_text {
-            verify_data = SpdmDigestStruct::spdm_read(context, r)?;
+            verify_data = SpdmDigestStruct::spdm_read(decode_info, r)?;
         }
 
         Some(SpdmFinishResponsePayload { verify_data })
@@ -150,7 +148,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(680, reader.left());
         let spdm_finish_request_payload =
-            SpdmFinishRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmFinishRequestPayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(
             spdm_finish_request_payload.finish_request_attributes,
             SpdmFinishRequestAttributes::SIGNATURE_INCLUDED
@@ -192,7 +190,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(680, reader.left());
         let spdm_finish_request_payload =
-            SpdmFinishRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmFinishRequestPayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(
             spdm_finish_request_payload.finish_request_attributes,
             SpdmFinishRequestAttributes::empty()
@@ -227,7 +225,8 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(68, reader.left());
-        let spdm_read = SpdmFinishResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_read =
+            SpdmFinishResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_read.verify_data.data_size, 64);
         for i in 0..64 {
             assert_eq!(spdm_read.verify_data.data[i], 100u8);
@@ -257,7 +256,8 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(68, reader.left());
-        let spdm_read = SpdmFinishResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_read =
+            SpdmFinishResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_read.verify_data.data_size, 0);
         for i in 0..64 {
             assert_eq!(spdm_read.verify_data.data[i], 0);
@@ -16,7 +16,7 @@ impl SpdmCodec for SpdmHeartbeatRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmHeartbeatRequestPayload> {
         u8::read(r)?; // param1
@@ -36,7 +36,7 @@ impl SpdmCodec for SpdmHeartbeatResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmHeartbeatResponsePayload> {
         u8::read(r)?; // param1
@@ -61,7 +61,7 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmHeartbeatResponsePayload::spdm_read(&mut context, &mut reader);
+        SpdmHeartbeatResponsePayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
     #[test]
     fn test_case0_spdm_heartbeat_request_payload() {
@@ -73,6 +73,6 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmHeartbeatRequestPayload::spdm_read(&mut context, &mut reader);
+        SpdmHeartbeatRequestPayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
 }
@@ -15,6 +15,7 @@ pub struct SpdmKeyExchangeRequestPayload {
     pub measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     pub slot_id: u8,
     pub req_session_id: u16,
+    pub session_policy: SpdmSessionPolicy,
     pub random: SpdmRandomStruct,
     pub exchange: SpdmDheExchangeStruct,
     pub opaque: SpdmOpaqueStruct,
@@ -25,7 +26,8 @@ impl SpdmCodec for SpdmKeyExchangeRequestPayload {
         self.measurement_summary_hash_type.encode(bytes); // param1
         self.slot_id.encode(bytes); // param2
         self.req_session_id.encode(bytes);
-        0u16.encode(bytes); // reserved
+        self.session_policy.encode(bytes);
+        0u8.encode(bytes); // reserved
 
         self.random.encode(bytes);
         self.exchange.spdm_encode(context, bytes);
@@ -33,22 +35,24 @@ impl SpdmCodec for SpdmKeyExchangeRequestPayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmKeyExchangeRequestPayload> {
         let measurement_summary_hash_type = SpdmMeasurementSummaryHashType::read(r)?; // param1
         let slot_id = u8::read(r)?; // param2
         let req_session_id = u16::read(r)?;
-        u16::read(r)?;
+        let session_policy = SpdmSessionPolicy::read(r)?;
+        u8::read(r)?; // reserved
 
         let random = SpdmRandomStruct::read(r)?;
-        let exchange = SpdmDheExchangeStruct::spdm_read(context, r)?;
-        let opaque = SpdmOpaqueStruct::spdm_read(context, r)?;
+        let exchange = SpdmDheExchangeStruct::spdm_read(decode_info, r)?;
+        let opaque = SpdmOpaqueStruct::spdm_read(decode_info, r)?;
 
         Some(SpdmKeyExchangeRequestPayload {
             measurement_summary_hash_type,
             slot_id,
             req_session_id,
+            session_policy,
             random,
             exchange,
             opaque,
@@ -56,6 +60,29 @@ impl SpdmCodec for SpdmKeyExchangeRequestPayload {
     }
 }
 
+bitflags! {
+    /// Session policy of KEY_EXCHANGE and PSK_EXCHANGE.
+    #[derive(Default)]
+    pub struct SpdmSessionPolicy: u8 {
+        /// Terminate the session when the responder updates its runtime
+        /// code or configuration.
+        const TERMINATION_POLICY_RUNTIME_UPDATE = 0b00000001;
+    }
+}
+
+impl Codec for SpdmSessionPolicy {
+    fn encode(&self, bytes: &mut Writer) {
+        self.bits().encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmSessionPolicy> {
+        let bits = u8::read(r)?;
+
+        // reserved bits are ignored
+        Some(SpdmSessionPolicy::from_bits_truncate(bits))
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct SpdmKeyExchangeMutAuthAttributes: u8 {
@@ -110,7 +137,7 @@ impl SpdmCodec for SpdmKeyExchangeResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmKeyExchangeResponsePayload> {
         let heartbeat_period = u8::read(r)?; // param1
@@ -120,15 +147,15 @@ impl SpdmCodec for SpdmKeyExchangeResponsePayload {
         let mut_auth_req = SpdmKeyExchangeMutAuthAttributes::read(r)?;
         let req_slot_id = u8::read(r)?;
         let random = SpdmRandomStruct::read(r)?;
-        let exchange = SpdmDheExchangeStruct::spdm_read(context, r)?;
-        let measurement_summary_hash = if context.runtime_info.need_measurement_summary_hash {
-            SpdmDigestStruct::spdm_read(context, r)?
+        let exchange = SpdmDheExchangeStruct::spdm_read(decode_info, r)?;
+        let measurement_summary_hash = if decode_info.need_measurement_summary_hash {
+            SpdmDigestStruct::spdm_read(decode_info, r)?
         } else {
             SpdmDigestStruct::default()
         };
-        let opaque = SpdmOpaqueStruct::spdm_read(context, r)?;
-        let signature = SpdmSignatureStruct::spdm_read(context, r)?;
-        let verify_data = SpdmDigestStruct::spdm_read(context, r)?;
+        let opaque = SpdmOpaqueStruct::spdm_read(decode_info, r)?;
+        let signature = SpdmSignatureStruct::spdm_read(decode_info, r)?;
+        let verify_data = SpdmDigestStruct::spdm_read(decode_info, r)?;
 
         Some(SpdmKeyExchangeResponsePayload {
             heartbeat_period,
@@ -175,6 +202,7 @@ mod tests {
                 SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
             slot_id: 100u8,
             req_session_id: 100u16,
+            session_policy: SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE,
             random: SpdmRandomStruct {
                 data: [100u8; SPDM_RANDOM_SIZE],
             },
@@ -197,13 +225,18 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(680, reader.left());
         let exchange_request_payload =
-            SpdmKeyExchangeRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmKeyExchangeRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(
             exchange_request_payload.measurement_summary_hash_type,
             SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone
         );
         assert_eq!(exchange_request_payload.slot_id, 100);
+        assert_eq!(
+            exchange_request_payload.session_policy,
+            SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE
+        );
         for i in 0..32 {
             assert_eq!(exchange_request_payload.random.data[i], 100);
         }
@@ -263,7 +296,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(1256, reader.left());
         let exchange_request_payload =
-            SpdmKeyExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmKeyExchangeResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(exchange_request_payload.heartbeat_period, 100);
         assert_eq!(exchange_request_payload.rsp_session_id, 100);
@@ -342,7 +376,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(1256, reader.left());
         let exchange_request_payload =
-            SpdmKeyExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmKeyExchangeResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(exchange_request_payload.heartbeat_period, 100);
         assert_eq!(exchange_request_payload.rsp_session_id, 100);
@@ -30,7 +30,7 @@ impl SpdmCodec for SpdmKeyUpdateRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmKeyUpdateRequestPayload> {
         let key_update_operation = SpdmKeyUpdateOperation::read(r)?; // param1
@@ -56,7 +56,7 @@ impl SpdmCodec for SpdmKeyUpdateResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmKeyUpdateResponsePayload> {
         let key_update_operation = SpdmKeyUpdateOperation::read(r)?; // param1
@@ -91,7 +91,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(2, reader.left());
         let key_request_payload =
-            SpdmKeyUpdateRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmKeyUpdateRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             key_request_payload.key_update_operation,
             SpdmKeyUpdateOperation::SpdmUpdateAllKeys
@@ -116,7 +117,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(2, reader.left());
         let key_response_payload =
-            SpdmKeyUpdateResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmKeyUpdateResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             key_response_payload.key_update_operation,
             SpdmKeyUpdateOperation::SpdmUpdateAllKeys
@@ -60,7 +60,7 @@ impl SpdmCodec for SpdmGetMeasurementsRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmGetMeasurementsRequestPayload> {
         let measurement_attributes = SpdmMeasurementeAttributes::read(r)?; // param1
@@ -110,16 +110,16 @@ impl SpdmCodec for SpdmMeasurementsResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmMeasurementsResponsePayload> {
         let number_of_measurement = u8::read(r)?; // param1
         let slot_id = u8::read(r)?; // param2
-        let measurement_record = SpdmMeasurementRecordStructure::spdm_read(context, r)?;
+        let measurement_record = SpdmMeasurementRecordStructure::spdm_read(decode_info, r)?;
         let nonce = SpdmNonceStruct::read(r)?;
-        let opaque = SpdmOpaqueStruct::spdm_read(context, r)?;
-        let signature = if context.runtime_info.need_measurement_signature {
-            SpdmSignatureStruct::spdm_read(context, r)?
+        let opaque = SpdmOpaqueStruct::spdm_read(decode_info, r)?;
+        let signature = if decode_info.need_measurement_signature {
+            SpdmSignatureStruct::spdm_read(decode_info, r)?
         } else {
             SpdmSignatureStruct::default()
         };
@@ -177,7 +177,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
         let get_measurements =
-            SpdmGetMeasurementsRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmGetMeasurementsRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             get_measurements.measurement_attributes,
             SpdmMeasurementeAttributes::INCLUDE_SIGNATURE
@@ -213,7 +214,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(48, reader.left());
         let get_measurements =
-            SpdmGetMeasurementsRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmGetMeasurementsRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(
             get_measurements.measurement_attributes,
             SpdmMeasurementeAttributes::empty()
@@ -274,7 +276,8 @@ mod tests {
 
         assert_eq!(1000, reader.left());
         let mut measurements_response =
-            SpdmMeasurementsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmMeasurementsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(measurements_response.number_of_measurement, 100);
         assert_eq!(measurements_response.slot_id, 100);
 
@@ -342,7 +345,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(1000, reader.left());
         measurements_response =
-            SpdmMeasurementsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmMeasurementsResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(measurements_response.signature.data_size, 0);
 
@@ -0,0 +1,167 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common;
+use crate::config;
+use crate::msgs::SpdmCodec;
+use codec::{Codec, Reader, Writer};
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmGetMeasurementExtensionLogRequestPayload {
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl SpdmCodec for SpdmGetMeasurementExtensionLogRequestPayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+        self.offset.encode(bytes);
+        self.length.encode(bytes);
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmGetMeasurementExtensionLogRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let offset = u32::read(r)?;
+        let length = u32::read(r)?;
+
+        Some(SpdmGetMeasurementExtensionLogRequestPayload { offset, length })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SpdmMeasurementExtensionLogResponsePayload {
+    pub portion_length: u32,
+    pub remainder_length: u32,
+    pub mel_portion: [u8; config::MAX_SPDM_MEL_PORTION_LEN],
+}
+
+impl Default for SpdmMeasurementExtensionLogResponsePayload {
+    fn default() -> SpdmMeasurementExtensionLogResponsePayload {
+        SpdmMeasurementExtensionLogResponsePayload {
+            portion_length: 0,
+            remainder_length: 0,
+            mel_portion: [0u8; config::MAX_SPDM_MEL_PORTION_LEN],
+        }
+    }
+}
+
+impl AsRef<[u8]> for SpdmMeasurementExtensionLogResponsePayload {
+    fn as_ref(&self) -> &[u8] {
+        &self.mel_portion[0..(self.portion_length as usize)]
+    }
+}
+
+impl SpdmCodec for SpdmMeasurementExtensionLogResponsePayload {
+    fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
+        0u8.encode(bytes); // param1
+        0u8.encode(bytes); // param2
+        self.portion_length.encode(bytes);
+        self.remainder_length.encode(bytes);
+        for d in self.mel_portion.iter().take(self.portion_length as usize) {
+            d.encode(bytes);
+        }
+    }
+
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmMeasurementExtensionLogResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let portion_length = u32::read(r)?;
+        let remainder_length = u32::read(r)?;
+        if portion_length as usize > config::MAX_SPDM_MEL_PORTION_LEN {
+            return None;
+        }
+        let mut mel_portion = [0u8; config::MAX_SPDM_MEL_PORTION_LEN];
+        for d in mel_portion.iter_mut().take(portion_length as usize) {
+            *d = u8::read(r)?;
+        }
+
+        Some(SpdmMeasurementExtensionLogResponsePayload {
+            portion_length,
+            remainder_length,
+            mel_portion,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testlib::*;
+
+    #[test]
+    fn test_case0_spdm_get_measurement_extension_log_request_payload() {
+        let u8_slice = &mut [0u8; 12];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmGetMeasurementExtensionLogRequestPayload {
+            offset: 0x200,
+            length: 0x100,
+        };
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        let mut reader = Reader::init(u8_slice);
+        let get_mel = SpdmGetMeasurementExtensionLogRequestPayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(get_mel.offset, 0x200);
+        assert_eq!(get_mel.length, 0x100);
+        assert_eq!(2, reader.left());
+    }
+    #[test]
+    fn test_case0_spdm_measurement_extension_log_response_payload() {
+        let u8_slice = &mut [0u8; 64];
+        let mut writer = Writer::init(u8_slice);
+        let mut value = SpdmMeasurementExtensionLogResponsePayload {
+            portion_length: 32,
+            remainder_length: 100,
+            ..Default::default()
+        };
+        value.mel_portion[..32].copy_from_slice(&[0x5au8; 32]);
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+
+        value.spdm_encode(&mut context, &mut writer);
+        assert_eq!(42, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let mel = SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+            &context.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(mel.remainder_length, 100);
+        assert_eq!(mel.as_ref(), &[0x5au8; 32]);
+        assert_eq!(22, reader.left());
+    }
+    #[test]
+    fn test_case1_spdm_measurement_extension_log_response_payload() {
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        0u8.encode(&mut writer);
+        0u8.encode(&mut writer);
+        (config::MAX_SPDM_MEL_PORTION_LEN as u32 + 1).encode(&mut writer);
+        0u32.encode(&mut writer);
+
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+            &common::SpdmDecodeInfo::default(),
+            &mut reader
+        )
+        .is_none());
+    }
+}
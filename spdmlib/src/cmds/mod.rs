@@ -21,3 +21,8 @@ pub mod key_exchange;
 pub mod key_update;
 pub mod psk_exchange;
 pub mod psk_finish;
+
+// SPDM 1.3
+pub mod endpoint_info;
+pub mod event;
+pub mod measurement_extension_log;
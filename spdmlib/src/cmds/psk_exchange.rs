@@ -6,13 +6,14 @@ use crate::common;
 use crate::msgs::SpdmCodec;
 use crate::msgs::{
     SpdmDigestStruct, SpdmMeasurementSummaryHashType, SpdmOpaqueStruct, SpdmPskContextStruct,
-    SpdmPskHintStruct,
+    SpdmPskHintStruct, SpdmSessionPolicy,
 };
 use codec::{Codec, Reader, Writer};
 
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SpdmPskExchangeRequestPayload {
     pub measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    pub session_policy: SpdmSessionPolicy,
     pub req_session_id: u16,
     pub psk_hint: SpdmPskHintStruct,
     pub psk_context: SpdmPskContextStruct,
@@ -22,7 +23,7 @@ pub struct SpdmPskExchangeRequestPayload {
 impl SpdmCodec for SpdmPskExchangeRequestPayload {
     fn spdm_encode(&self, _context: &mut common::SpdmContext, bytes: &mut Writer) {
         self.measurement_summary_hash_type.encode(bytes); // param1
-        0u8.encode(bytes); // param2
+        self.session_policy.encode(bytes); // param2
         self.req_session_id.encode(bytes);
 
         self.psk_hint.data_size.encode(bytes);
@@ -51,11 +52,11 @@ impl SpdmCodec for SpdmPskExchangeRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmPskExchangeRequestPayload> {
         let measurement_summary_hash_type = SpdmMeasurementSummaryHashType::read(r)?; // param1
-        u8::read(r)?; // param2
+        let session_policy = SpdmSessionPolicy::read(r)?; // param2
         let req_session_id = u16::read(r)?;
 
         let mut psk_hint = SpdmPskHintStruct::default();
@@ -82,6 +83,7 @@ impl SpdmCodec for SpdmPskExchangeRequestPayload {
 
         Some(SpdmPskExchangeRequestPayload {
             measurement_summary_hash_type,
+            session_policy,
             req_session_id,
             psk_hint,
             psk_context,
@@ -128,7 +130,7 @@ impl SpdmCodec for SpdmPskExchangeResponsePayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmPskExchangeResponsePayload> {
         let heartbeat_period = u8::read(r)?; // param1
@@ -143,8 +145,8 @@ impl SpdmCodec for SpdmPskExchangeResponsePayload {
         psk_context.data_size = u16::read(r)?;
         opaque.data_size = u16::read(r)?;
 
-        let measurement_summary_hash = if context.runtime_info.need_measurement_summary_hash {
-            SpdmDigestStruct::spdm_read(context, r)?
+        let measurement_summary_hash = if decode_info.need_measurement_summary_hash {
+            SpdmDigestStruct::spdm_read(decode_info, r)?
         } else {
             SpdmDigestStruct::default()
         };
@@ -159,7 +161,7 @@ impl SpdmCodec for SpdmPskExchangeResponsePayload {
         for d in opaque.data.iter_mut().take(opaque.data_size as usize) {
             *d = u8::read(r)?;
         }
-        let verify_data = SpdmDigestStruct::spdm_read(context, r)?;
+        let verify_data = SpdmDigestStruct::spdm_read(decode_info, r)?;
 
         Some(SpdmPskExchangeResponsePayload {
             heartbeat_period,
@@ -186,6 +188,7 @@ mod tests {
         let value = SpdmPskExchangeRequestPayload {
             measurement_summary_hash_type:
                 SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
+            session_policy: SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE,
             req_session_id: 100u16,
             psk_hint: SpdmPskHintStruct {
                 data_size: 32,
@@ -209,12 +212,17 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(180, reader.left());
         let psk_exchange_request =
-            SpdmPskExchangeRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskExchangeRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(
             psk_exchange_request.measurement_summary_hash_type,
             SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll
         );
+        assert_eq!(
+            psk_exchange_request.session_policy,
+            SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE
+        );
         assert_eq!(psk_exchange_request.psk_hint.data_size, 32);
         assert_eq!(psk_exchange_request.psk_context.data_size, 64);
         assert_eq!(psk_exchange_request.opaque.data_size, 64);
@@ -234,6 +242,7 @@ mod tests {
         let value = SpdmPskExchangeRequestPayload {
             measurement_summary_hash_type:
                 SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
+            session_policy: SpdmSessionPolicy::empty(),
             req_session_id: 100u16,
             psk_hint: SpdmPskHintStruct {
                 data_size: 0,
@@ -257,7 +266,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(180, reader.left());
         let psk_exchange_request =
-            SpdmPskExchangeRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskExchangeRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(
             psk_exchange_request.measurement_summary_hash_type,
@@ -310,7 +320,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(280, reader.left());
         let psk_exchange_response =
-            SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskExchangeResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(psk_exchange_response.heartbeat_period, 0xaau8);
         assert_eq!(psk_exchange_response.rsp_session_id, 0xaa55u16);
@@ -337,7 +348,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(420, reader.left());
         let psk_exchange_response =
-            SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskExchangeResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(psk_exchange_response.measurement_summary_hash.data_size, 0);
         for i in 0..64 {
@@ -380,7 +392,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(280, reader.left());
         let psk_exchange_response =
-            SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskExchangeResponsePayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(psk_exchange_response.heartbeat_period, 0xaau8);
         assert_eq!(psk_exchange_response.rsp_session_id, 0xaa55u16);
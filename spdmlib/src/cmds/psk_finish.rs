@@ -20,12 +20,12 @@ impl SpdmCodec for SpdmPskFinishRequestPayload {
     }
 
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmPskFinishRequestPayload> {
         u8::read(r)?; // param1
         u8::read(r)?; // param2
-        let verify_data = SpdmDigestStruct::spdm_read(context, r)?;
+        let verify_data = SpdmDigestStruct::spdm_read(decode_info, r)?;
 
         Some(SpdmPskFinishRequestPayload { verify_data })
     }
@@ -41,7 +41,7 @@ impl SpdmCodec for SpdmPskFinishResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmPskFinishResponsePayload> {
         u8::read(r)?; // param1
@@ -77,7 +77,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(80, reader.left());
         let psk_finish_request =
-            SpdmPskFinishRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmPskFinishRequestPayload::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
 
         assert_eq!(psk_finish_request.verify_data.data_size, 64);
         for i in 0..64 {
@@ -95,6 +96,6 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmPskFinishResponsePayload::spdm_read(&mut context, &mut reader);
+        SpdmPskFinishResponsePayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
 }
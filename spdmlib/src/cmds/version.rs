@@ -18,7 +18,7 @@ impl SpdmCodec for SpdmGetVersionRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmGetVersionRequestPayload> {
         u8::read(r)?; // param1
@@ -70,7 +70,7 @@ impl SpdmCodec for SpdmVersionResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmVersionResponsePayload> {
         u8::read(r)?; // param1
@@ -150,7 +150,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(8, reader.left());
         let version_response =
-            SpdmVersionResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmVersionResponsePayload::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
 
         assert_eq!(version_response.version_number_entry_count, 2u8);
         for i in 0..2 {
@@ -172,6 +172,6 @@ mod tests {
         let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmGetVersionRequestPayload::spdm_read(&mut context, &mut reader);
+        SpdmGetVersionRequestPayload::spdm_read(&context.get_decode_info(), &mut reader);
     }
 }
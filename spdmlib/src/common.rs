@@ -6,16 +6,18 @@ use crate::config;
 use crate::crypto;
 use crate::error::SpdmResult;
 use crate::msgs::*;
+use crate::secret::SpdmSecret;
 use crate::session::*;
-use codec::Writer;
+use codec::{Codec, Reader, Writer};
+use zeroize::Zeroize;
 
-pub const OPAQUE_DATA_SUPPORT_VERSION: [u8; 20] = [
-    0x46, 0x54, 0x4d, 0x44, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x01, 0x01, 0x01, 0x00,
-    0x11, 0x00, 0x00, 0x00,
-];
-pub const OPAQUE_DATA_VERSION_SELECTION: [u8; 16] = [
-    0x46, 0x54, 0x4d, 0x44, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01, 0x00, 0x00, 0x11,
-];
+/// Blob of suspend_session: magic, version, nonce and length, then the
+/// session state encrypted with AES-256-GCM and the tag. The header is the
+/// additional authenticated data.
+pub const SPDM_SESSION_BLOB_MAGIC: u32 = 0x4253_5053; // "SPSB"
+pub const SPDM_SESSION_BLOB_VERSION: u8 = 1;
+pub const SPDM_SESSION_BLOB_MAX_SIZE: usize = 512;
+const SPDM_SESSION_BLOB_AEAD_ALGO: SpdmAeadAlgo = SpdmAeadAlgo::AES_256_GCM;
 
 pub trait SpdmDeviceIo {
     fn send(&mut self, buffer: &[u8]) -> SpdmResult;
@@ -46,15 +48,23 @@ pub trait SpdmTransportEncap {
         spdm_buffer: &mut [u8],
     ) -> SpdmResult<(usize, bool)>;
 
+    /// Wrap a message into the application data of a secured message.
+    /// app_message_type is None for an SPDM message, otherwise the
+    /// transport defined message type, e.g. the MCTP message type.
     fn encap_app(
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize>;
 
-    fn decap_app(&mut self, app_buffer: &[u8], spdm_buffer: &mut [u8])
-        -> SpdmResult<(usize, bool)>;
+    /// Return the payload size and the application message type,
+    /// None for an SPDM message.
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)>;
 
     // for session
     fn get_sequence_number_count(&mut self) -> u8;
@@ -67,13 +77,30 @@ impl Debug for dyn SpdmTransportEncap {
     }
 }
 
+/// Monotonic clock used for the session heartbeat.
+pub trait SpdmTimeSource {
+    /// Return the time in milliseconds since an arbitrary start point.
+    fn get_time_ms(&mut self) -> u64;
+}
+
+impl Debug for dyn SpdmTimeSource {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Dyn SpdmTimeSource")
+    }
+}
+
 pub struct SpdmContext<'a> {
     pub device_io: &'a mut dyn SpdmDeviceIo,
     pub transport_encap: &'a mut dyn SpdmTransportEncap,
+    pub time_source: Option<&'a mut dyn SpdmTimeSource>,
 
     pub config_info: SpdmConfigInfo,
     pub negotiate_info: SpdmNegotiateInfo,
     pub runtime_info: SpdmRuntimeInfo,
+    /// The negotiated state for the next connection, kept across a reset
+    /// with CACHE_CAP. END_SESSION keeps it with PRESERVE_NEGOTIATED_STATE
+    /// and wipes it without.
+    pub cached_negotiated_state: Option<SpdmNegotiatedState>,
 
     pub provision_info: SpdmProvisionInfo,
     pub peer_info: SpdmPeerInfo,
@@ -91,12 +118,14 @@ impl<'a> SpdmContext<'a> {
         SpdmContext {
             device_io,
             transport_encap,
+            time_source: None,
             config_info,
             negotiate_info: SpdmNegotiateInfo::default(),
             runtime_info: SpdmRuntimeInfo::default(),
+            cached_negotiated_state: None,
             provision_info,
             peer_info: SpdmPeerInfo::default(),
-            session: [SpdmSession::new(); config::MAX_SPDM_SESSION_COUNT],
+            session: Default::default(),
         }
     }
 
@@ -110,10 +139,187 @@ impl<'a> SpdmContext<'a> {
         self.negotiate_info.dhe_sel.get_size()
     }
 
+    /// The negotiated state the message codecs need to read a message.
+    pub fn get_decode_info(&self) -> SpdmDecodeInfo {
+        SpdmDecodeInfo {
+            req_capabilities_sel: self.negotiate_info.req_capabilities_sel,
+            rsp_capabilities_sel: self.negotiate_info.rsp_capabilities_sel,
+            base_hash_sel: self.negotiate_info.base_hash_sel,
+            base_asym_sel: self.negotiate_info.base_asym_sel,
+            dhe_sel: self.negotiate_info.dhe_sel,
+            need_measurement_summary_hash: self.runtime_info.need_measurement_summary_hash,
+            need_measurement_signature: self.runtime_info.need_measurement_signature,
+            need_endpoint_info_signature: self.runtime_info.need_endpoint_info_signature,
+        }
+    }
+
+    pub fn register_time_source(&mut self, time_source: &'a mut dyn SpdmTimeSource) {
+        self.time_source = Some(time_source);
+    }
+
+    /// Return the current time, None if no time source is registered.
+    pub fn get_time_ms(&mut self) -> Option<u64> {
+        self.time_source
+            .as_mut()
+            .map(|time_source| time_source.get_time_ms())
+    }
+
+    /// The heartbeat period to advertise in KEY_EXCHANGE_RSP/PSK_EXCHANGE_RSP,
+    /// 0 unless both sides support HEARTBEAT.
+    pub fn get_heartbeat_period(&self) -> u8 {
+        if self
+            .negotiate_info
+            .req_capabilities_sel
+            .contains(SpdmRequestCapabilityFlags::HBEAT_CAP)
+            && self
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::HBEAT_CAP)
+        {
+            self.config_info.heartbeat_period
+        } else {
+            0
+        }
+    }
+
+    /// The random padding bound of new sessions, never above the transport maximum.
+    pub fn get_max_random_count(&mut self) -> u16 {
+        let max_random_count = self.transport_encap.get_max_random_count();
+        match self.config_info.max_random_count {
+            Some(count) => count.min(max_random_count),
+            None => max_random_count,
+        }
+    }
+
     pub fn reset_runtime_info(&mut self) {
         self.runtime_info = SpdmRuntimeInfo::default();
     }
 
+    /// The negotiated state to cache across a reset, available once
+    /// NEGOTIATE_ALGORITHMS completed and only if the responder sets CACHE_CAP.
+    pub fn get_negotiated_state(&self) -> SpdmResult<SpdmNegotiatedState> {
+        if self.negotiate_info.base_hash_sel.is_empty() {
+            return spdm_result_err!(ENOENT);
+        }
+        if !self
+            .negotiate_info
+            .rsp_capabilities_sel
+            .contains(SpdmResponseCapabilityFlags::CACHE_CAP)
+        {
+            return spdm_result_err!(EPERM);
+        }
+        Ok(SpdmNegotiatedState {
+            negotiate_info: self.negotiate_info,
+            message_a: self.runtime_info.message_a,
+        })
+    }
+
+    /// Take a state of get_negotiated_state instead of running GET_VERSION,
+    /// GET_CAPABILITIES and NEGOTIATE_ALGORITHMS. The other transcripts
+    /// are reset, and the state stays cached until an END_SESSION wipes it.
+    pub fn restore_negotiated_state(&mut self, state: &SpdmNegotiatedState) -> SpdmResult {
+        if state.negotiate_info.base_hash_sel.is_empty() {
+            return spdm_result_err!(EINVAL);
+        }
+        if !state
+            .negotiate_info
+            .rsp_capabilities_sel
+            .contains(SpdmResponseCapabilityFlags::CACHE_CAP)
+        {
+            return spdm_result_err!(EPERM);
+        }
+        self.reset_runtime_info();
+        self.negotiate_info = state.negotiate_info;
+        self.runtime_info.message_a = state.message_a;
+        self.cached_negotiated_state = Some(*state);
+        Ok(())
+    }
+
+    /// Honor the END_SESSION attributes: cache the negotiated state for the
+    /// next connection if it shall be preserved, wipe the cache otherwise.
+    pub fn end_session_negotiated_state(
+        &mut self,
+        end_session_attributes: SpdmEndSessionRequestAttributes,
+    ) {
+        self.cached_negotiated_state = if end_session_attributes
+            .contains(SpdmEndSessionRequestAttributes::PRESERVE_NEGOTIATED_STATE)
+        {
+            self.get_negotiated_state().ok()
+        } else {
+            None
+        };
+    }
+
+    fn get_secured_message_versions(&self) -> impl Iterator<Item = &SpdmSecuredMessageVersion> {
+        self.config_info
+            .secured_message_version
+            .iter()
+            .filter(|v| !matches!(v, SpdmSecuredMessageVersion::Unknown(_)))
+    }
+
+    /// Build the KEY_EXCHANGE/PSK_EXCHANGE opaque data:
+    /// the supported secured message versions, followed by the application elements.
+    pub fn encode_supported_version_opaque(&self) -> SpdmResult<SpdmOpaqueStruct> {
+        let mut versions =
+            [SpdmSecuredMessageVersion::default(); SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT];
+        let mut count = 0;
+        for version in self.get_secured_message_versions() {
+            versions[count] = *version;
+            count += 1;
+        }
+        let element = SpdmOpaqueElement::new_supported_version(&versions[..count])
+            .ok_or(spdm_err!(EINVAL))?;
+        self.encode_opaque_with_app_elements(&element)
+    }
+
+    /// Build the KEY_EXCHANGE_RSP/PSK_EXCHANGE_RSP opaque data:
+    /// the selected secured message version, followed by the application elements.
+    pub fn encode_version_selection_opaque(
+        &self,
+        version: SpdmSecuredMessageVersion,
+    ) -> SpdmResult<SpdmOpaqueStruct> {
+        let element = SpdmOpaqueElement::new_version_selection(version);
+        self.encode_opaque_with_app_elements(&element)
+    }
+
+    fn encode_opaque_with_app_elements(
+        &self,
+        element: &SpdmOpaqueElement,
+    ) -> SpdmResult<SpdmOpaqueStruct> {
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        opaque_data.add_element(element).ok_or(spdm_err!(ENOMEM))?;
+        for app_element in self.config_info.opaque_elements.iter() {
+            opaque_data
+                .add_element(app_element)
+                .ok_or(spdm_err!(ENOMEM))?;
+        }
+        opaque_data.to_opaque().ok_or(spdm_err!(ENOMEM))
+    }
+
+    /// Pick the first local secured message version that the requester supports.
+    pub fn select_secured_message_version(
+        &self,
+        opaque_data: &SpdmGeneralOpaqueData,
+    ) -> Option<SpdmSecuredMessageVersion> {
+        let (peer_versions, peer_count) = opaque_data.get_supported_version()?;
+        self.get_secured_message_versions()
+            .find(|v| peer_versions[..peer_count].contains(v))
+            .copied()
+    }
+
+    /// Check that the responder selected one of the local secured message versions.
+    pub fn check_version_selection(
+        &self,
+        opaque_data: &SpdmGeneralOpaqueData,
+    ) -> Option<SpdmSecuredMessageVersion> {
+        let version = opaque_data.get_version_selection()?;
+        if self.get_secured_message_versions().any(|v| *v == version) {
+            Some(version)
+        } else {
+            None
+        }
+    }
+
     pub fn get_immutable_session_via_id(&self, session_id: u32) -> Option<&SpdmSession> {
         for session in self.session.iter() {
             if session.get_session_id() == session_id {
@@ -136,6 +342,136 @@ impl<'a> SpdmContext<'a> {
         self.get_session_via_id(0)
     }
 
+    /// Record the current time as the last message time of the session,
+    /// for the heartbeat. Nothing is recorded without a time source.
+    pub fn record_session_activity(&mut self, session_id: u32) {
+        if let Some(now) = self.get_time_ms() {
+            if let Some(session) = self.get_session_via_id(session_id) {
+                session.heartbeat_info.last_message_time = now;
+            }
+        }
+    }
+
+    /// Restart the rekey policy of the session once its keys are created or updated.
+    pub fn reset_session_rekey_info(&mut self, session_id: u32) {
+        let now = self.get_time_ms();
+        if let Some(session) = self.get_session_via_id(session_id) {
+            session.reset_rekey_info(now);
+        }
+    }
+
+    /// Keying material of an established session for the application, e.g.
+    /// PCIe IDE or storage encryption keys, see SpdmSession::export_keying_material.
+    pub fn export_keying_material(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: usize,
+    ) -> SpdmResult<SpdmSecret<SpdmDigestStruct>> {
+        self.get_immutable_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?
+            .export_keying_material(label, context, length)
+    }
+
+    /// Suspend an established session, e.g. to hand it over from the firmware
+    /// to the OS, into blob with the negotiated connection state. The blob is
+    /// encrypted and authenticated with blob_key, a 32 byte AES-256-GCM key of
+    /// the caller. The session is torn down, so that its sequence numbers are
+    /// never reused here.
+    pub fn suspend_session(
+        &mut self,
+        session_id: u32,
+        blob_key: &[u8],
+        blob: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut state = [0u8; SPDM_SESSION_BLOB_MAX_SIZE];
+        let result = self
+            .encode_resume_state(session_id, &mut state)
+            .and_then(|size| seal_session_blob(blob_key, &state[..size], blob));
+        state.zeroize();
+        let used = result?;
+
+        self.get_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?
+            .teardown(session_id)?;
+        Ok(used)
+    }
+
+    /// Resume a session of suspend_session with the same blob_key, and take
+    /// the negotiated connection state of the blob.
+    pub fn resume_session(&mut self, blob_key: &[u8], blob: &[u8]) -> SpdmResult<u32> {
+        let mut state = [0u8; SPDM_SESSION_BLOB_MAX_SIZE];
+        let result = open_session_blob(blob_key, blob, &mut state)
+            .and_then(|size| self.read_resume_state(&state[..size]));
+        state.zeroize();
+        let session_id = result?;
+
+        self.record_session_activity(session_id);
+        self.reset_session_rekey_info(session_id);
+        Ok(session_id)
+    }
+
+    fn encode_resume_state(&self, session_id: u32, state: &mut [u8]) -> SpdmResult<usize> {
+        let session = self
+            .get_immutable_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?;
+        let mut writer = Writer::init(state);
+        let negotiate_info = &self.negotiate_info;
+        encode_resume_field(&mut writer, &negotiate_info.spdm_version_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.req_capabilities_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.rsp_capabilities_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.req_ct_exponent_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.rsp_ct_exponent_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.measurement_specification_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.measurement_hash_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.base_hash_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.base_asym_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.dhe_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.aead_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.req_asym_sel)?;
+        encode_resume_field(&mut writer, &negotiate_info.key_schedule_sel)?;
+        session.encode_resume_state(&mut writer)?;
+        Ok(writer.used())
+    }
+
+    fn read_resume_state(&mut self, state: &[u8]) -> SpdmResult<u32> {
+        let mut reader = Reader::init(state);
+        let negotiate_info = SpdmNegotiateInfo {
+            spdm_version_sel: SpdmVersion::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            req_capabilities_sel: SpdmRequestCapabilityFlags::read(&mut reader)
+                .ok_or(spdm_err!(EINVAL))?,
+            rsp_capabilities_sel: SpdmResponseCapabilityFlags::read(&mut reader)
+                .ok_or(spdm_err!(EINVAL))?,
+            req_ct_exponent_sel: u8::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            rsp_ct_exponent_sel: u8::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            measurement_specification_sel: SpdmMeasurementSpecification::read(&mut reader)
+                .ok_or(spdm_err!(EINVAL))?,
+            measurement_hash_sel: SpdmMeasurementHashAlgo::read(&mut reader)
+                .ok_or(spdm_err!(EINVAL))?,
+            base_hash_sel: SpdmBaseHashAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            base_asym_sel: SpdmBaseAsymAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            dhe_sel: SpdmDheAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            aead_sel: SpdmAeadAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            req_asym_sel: SpdmReqAsymAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+            key_schedule_sel: SpdmKeyScheduleAlgo::read(&mut reader).ok_or(spdm_err!(EINVAL))?,
+        };
+
+        let mut session = SpdmSession::new();
+        session.read_resume_state(&mut reader)?;
+        if reader.any_left() {
+            return spdm_result_err!(EINVAL);
+        }
+        let session_id = session.get_session_id();
+        if self.get_immutable_session_via_id(session_id).is_some() {
+            return spdm_result_err!(EEXIST);
+        }
+        let free_session = self.get_next_avaiable_session().ok_or(spdm_err!(ENOMEM))?;
+        *free_session = session;
+        self.negotiate_info = negotiate_info;
+        Ok(session_id)
+    }
+
     pub fn calc_req_transcript_data(
         &self,
         use_psk: bool,
@@ -345,6 +681,66 @@ impl<'a> SpdmContext<'a> {
         .ok_or_else(|| spdm_err!(EFAULT))
     }
 
+    pub fn verify_endpoint_info_signature(
+        &mut self,
+        signature: &SpdmSignatureStruct,
+    ) -> SpdmResult {
+        let mut message = ManagedBuffer::default();
+        message
+            .append_message(self.runtime_info.message_a.as_ref())
+            .ok_or_else(|| spdm_err!(ENOMEM))?;
+        message
+            .append_message(self.runtime_info.message_e.as_ref())
+            .ok_or_else(|| spdm_err!(ENOMEM))?;
+        // we dont need create message hash for verify
+        // we just print message hash for debug purpose
+        let message_hash =
+            crypto::hash::hash_all(self.negotiate_info.base_hash_sel, message.as_ref())
+                .ok_or_else(|| spdm_err!(EFAULT))?;
+        debug!("message_hash - {:02x?}", message_hash.as_ref());
+
+        // GET_ENDPOINT_INFO may come before GET_CERTIFICATE, so there may be
+        // no peer certificate chain to verify against
+        let cert_chain_offset = 4usize + self.negotiate_info.base_hash_sel.get_size() as usize;
+        let cert_chain_size = self.peer_info.peer_cert_chain.cert_chain.data_size as usize;
+        if cert_chain_size < cert_chain_offset {
+            return spdm_result_err!(EINVAL);
+        }
+        let cert_chain_data =
+            &self.peer_info.peer_cert_chain.cert_chain.data[cert_chain_offset..cert_chain_size];
+
+        crypto::asym_verify::verify(
+            self.negotiate_info.base_hash_sel,
+            self.negotiate_info.base_asym_sel,
+            cert_chain_data,
+            message.as_ref(),
+            signature,
+        )
+    }
+
+    pub fn generate_endpoint_info_signature(&mut self) -> SpdmResult<SpdmSignatureStruct> {
+        let mut message = ManagedBuffer::default();
+        message
+            .append_message(self.runtime_info.message_a.as_ref())
+            .ok_or_else(|| spdm_err!(ENOMEM))?;
+        message
+            .append_message(self.runtime_info.message_e.as_ref())
+            .ok_or_else(|| spdm_err!(ENOMEM))?;
+        // we dont need create message hash for verify
+        // we just print message hash for debug purpose
+        let message_hash =
+            crypto::hash::hash_all(self.negotiate_info.base_hash_sel, message.as_ref())
+                .ok_or_else(|| spdm_err!(EFAULT))?;
+        debug!("message_hash - {:02x?}", message_hash.as_ref());
+
+        crypto::asym_sign::sign(
+            self.negotiate_info.base_hash_sel,
+            self.negotiate_info.base_asym_sel,
+            message.as_ref(),
+        )
+        .ok_or_else(|| spdm_err!(EFAULT))
+    }
+
     pub fn verify_key_exchange_rsp_signature(
         &mut self,
         message_k: &ManagedBuffer,
@@ -391,6 +787,21 @@ impl<'a> SpdmContext<'a> {
         .ok_or_else(|| spdm_err!(EFAULT))
     }
 
+    /// The provisioned PSK, or the built in test PSK.
+    pub fn get_psk(&self) -> SpdmSecret<SpdmDheFinalKeyStruct> {
+        SpdmSecret::new(match self.provision_info.psk {
+            Some(psk) => psk,
+            None => {
+                let mut psk = SpdmDheFinalKeyStruct {
+                    data_size: b"TestPskData\0".len() as u16,
+                    ..Default::default()
+                };
+                psk.data[0..(psk.data_size as usize)].copy_from_slice(b"TestPskData\0");
+                psk
+            }
+        })
+    }
+
     pub fn encap(&mut self, send_buffer: &[u8], transport_buffer: &mut [u8]) -> SpdmResult<usize> {
         self.transport_encap
             .encap(send_buffer, transport_buffer, false)
@@ -402,12 +813,12 @@ impl<'a> SpdmContext<'a> {
         send_buffer: &[u8],
         transport_buffer: &mut [u8],
         is_requester: bool,
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
         let mut app_buffer = [0u8; config::MAX_SPDM_MESSAGE_BUFFER_SIZE];
-        let used = self
-            .transport_encap
-            .encap_app(send_buffer, &mut app_buffer, is_app_message)?;
+        let used =
+            self.transport_encap
+                .encap_app(send_buffer, &mut app_buffer, app_message_type)?;
 
         let spdm_session = self
             .get_session_via_id(session_id)
@@ -446,6 +857,24 @@ impl<'a> SpdmContext<'a> {
         transport_buffer: &[u8],
         receive_buffer: &mut [u8],
     ) -> SpdmResult<usize> {
+        let (used, app_message_type) =
+            self.decode_secured_app_message(session_id, transport_buffer, receive_buffer)?;
+
+        if app_message_type.is_some() {
+            return spdm_result_err!(EFAULT);
+        }
+
+        Ok(used)
+    }
+
+    /// Decode a secured message which may carry an SPDM or an application
+    /// message, see SpdmTransportEncap::decap_app.
+    pub fn decode_secured_app_message(
+        &mut self,
+        session_id: u32,
+        transport_buffer: &[u8],
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
         let mut encoded_receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let (used, secured_message) = self
             .transport_encap
@@ -466,15 +895,12 @@ impl<'a> SpdmContext<'a> {
             false,
         )?;
 
-        let used = self
-            .transport_encap
-            .decap_app(&app_buffer[0..decode_size], receive_buffer)?;
-
-        Ok(used.0)
+        self.transport_encap
+            .decap_app(&app_buffer[0..decode_size], receive_buffer)
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct SpdmConfigInfo {
     pub spdm_version: [SpdmVersion; config::MAX_SPDM_VERSION_COUNT],
     pub req_capabilities: SpdmRequestCapabilityFlags,
@@ -489,9 +915,55 @@ pub struct SpdmConfigInfo {
     pub aead_algo: SpdmAeadAlgo,
     pub req_asym_algo: SpdmReqAsymAlgo,
     pub key_schedule_algo: SpdmKeyScheduleAlgo,
+    pub supported_event_types: SpdmDmtfEventTypeFlags,
+    /// Secured message versions (DSP0277) in priority order, 1.1 and 1.0 by default.
+    /// Unknown entries are ignored.
+    pub secured_message_version:
+        [SpdmSecuredMessageVersion; SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT],
+    /// Application opaque data elements sent in KEY_EXCHANGE/PSK_EXCHANGE and their responses.
+    pub opaque_elements: SpdmGeneralOpaqueData,
+    /// Heartbeat period in seconds advertised by the responder, 0 to disable.
+    pub heartbeat_period: u8,
+    /// Rekey policy of the sessions the requester starts.
+    pub rekey_policy: SpdmRekeyPolicy,
+    /// Session policy the requester sends in KEY_EXCHANGE/PSK_EXCHANGE.
+    pub session_policy: SpdmSessionPolicy,
+    /// Upper bound of the random padding added to secured messages,
+    /// None to use the transport default.
+    pub max_random_count: Option<u16>,
 }
 
-#[derive(Debug, Default)]
+impl Default for SpdmConfigInfo {
+    fn default() -> Self {
+        SpdmConfigInfo {
+            spdm_version: Default::default(),
+            req_capabilities: Default::default(),
+            rsp_capabilities: Default::default(),
+            req_ct_exponent: 0,
+            rsp_ct_exponent: 0,
+            measurement_specification: Default::default(),
+            measurement_hash_algo: Default::default(),
+            base_hash_algo: Default::default(),
+            base_asym_algo: Default::default(),
+            dhe_algo: Default::default(),
+            aead_algo: Default::default(),
+            req_asym_algo: Default::default(),
+            key_schedule_algo: Default::default(),
+            supported_event_types: Default::default(),
+            secured_message_version: [
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+            ],
+            opaque_elements: Default::default(),
+            heartbeat_period: 0,
+            rekey_policy: Default::default(),
+            session_policy: Default::default(),
+            max_random_count: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
 pub struct SpdmNegotiateInfo {
     pub spdm_version_sel: SpdmVersion,
     pub req_capabilities_sel: SpdmRequestCapabilityFlags,
@@ -508,6 +980,30 @@ pub struct SpdmNegotiateInfo {
     pub key_schedule_sel: SpdmKeyScheduleAlgo,
 }
 
+/// Negotiated state of a connection, i.e. the result of GET_VERSION,
+/// GET_CAPABILITIES and NEGOTIATE_ALGORITHMS and their transcript message_a.
+/// A responder with CACHE_CAP keeps it across a reset, so the requester can
+/// go on with GET_DIGESTS, CHALLENGE or KEY_EXCHANGE directly.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmNegotiatedState {
+    pub negotiate_info: SpdmNegotiateInfo,
+    pub message_a: ManagedBuffer,
+}
+
+/// The negotiated state the message codecs read, see `SpdmContext::get_decode_info`.
+/// It allows to decode a message without a connection, e.g. in a dissector.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmDecodeInfo {
+    pub req_capabilities_sel: SpdmRequestCapabilityFlags,
+    pub rsp_capabilities_sel: SpdmResponseCapabilityFlags,
+    pub base_hash_sel: SpdmBaseHashAlgo,
+    pub base_asym_sel: SpdmBaseAsymAlgo,
+    pub dhe_sel: SpdmDheAlgo,
+    pub need_measurement_summary_hash: bool,
+    pub need_measurement_signature: bool,
+    pub need_endpoint_info_signature: bool,
+}
+
 // TBD ManagedSmallBuffer
 #[derive(Debug, Copy, Clone)]
 pub struct ManagedBuffer(usize, [u8; config::MAX_SPDM_MESSAGE_BUFFER_SIZE]);
@@ -541,10 +1037,81 @@ impl Default for ManagedBuffer {
 pub struct SpdmRuntimeInfo {
     pub need_measurement_summary_hash: bool,
     pub need_measurement_signature: bool,
+    pub need_endpoint_info_signature: bool,
     pub message_a: ManagedBuffer,
     pub message_b: ManagedBuffer,
     pub message_c: ManagedBuffer,
     pub message_m: ManagedBuffer,
+    pub message_e: ManagedBuffer,
+}
+
+fn seal_session_blob(blob_key: &[u8], state: &[u8], blob: &mut [u8]) -> SpdmResult<usize> {
+    let aead_algo = SPDM_SESSION_BLOB_AEAD_ALGO;
+    let iv_size = aead_algo.get_iv_size() as usize;
+    let tag_size = aead_algo.get_tag_size() as usize;
+    if blob_key.len() != aead_algo.get_key_size() as usize {
+        return spdm_result_err!(EINVAL);
+    }
+    let header_size = 4 + 1 + iv_size + 2;
+    if blob.len() < header_size + state.len() + tag_size {
+        return spdm_result_err!(ENOMEM);
+    }
+
+    let mut nonce = [0u8; SPDM_MAX_AEAD_IV_SIZE];
+    crypto::rand::get_random(&mut nonce[..iv_size])?;
+    let mut writer = Writer::init(blob);
+    SPDM_SESSION_BLOB_MAGIC.encode(&mut writer);
+    SPDM_SESSION_BLOB_VERSION.encode(&mut writer);
+    writer.extend_from_slice(&nonce[..iv_size]);
+    (state.len() as u16).encode(&mut writer);
+
+    let (header, rest) = blob.split_at_mut(header_size);
+    let (cipher_text, rest) = rest.split_at_mut(state.len());
+    crypto::aead::encrypt(
+        aead_algo,
+        blob_key,
+        &nonce[..iv_size],
+        header,
+        state,
+        &mut rest[..tag_size],
+        cipher_text,
+    )?;
+    Ok(header_size + state.len() + tag_size)
+}
+
+fn open_session_blob(blob_key: &[u8], blob: &[u8], state: &mut [u8]) -> SpdmResult<usize> {
+    let aead_algo = SPDM_SESSION_BLOB_AEAD_ALGO;
+    let iv_size = aead_algo.get_iv_size() as usize;
+    let tag_size = aead_algo.get_tag_size() as usize;
+    if blob_key.len() != aead_algo.get_key_size() as usize {
+        return spdm_result_err!(EINVAL);
+    }
+
+    let mut reader = Reader::init(blob);
+    if u32::read(&mut reader) != Some(SPDM_SESSION_BLOB_MAGIC)
+        || u8::read(&mut reader) != Some(SPDM_SESSION_BLOB_VERSION)
+    {
+        return spdm_result_err!(EINVAL);
+    }
+    let mut nonce = [0u8; SPDM_MAX_AEAD_IV_SIZE];
+    nonce[..iv_size].copy_from_slice(reader.take(iv_size).ok_or(spdm_err!(EINVAL))?);
+    let state_size = u16::read(&mut reader).ok_or(spdm_err!(EINVAL))? as usize;
+    let header_size = reader.used();
+    if blob.len() != header_size + state_size + tag_size || state_size > state.len() {
+        return spdm_result_err!(EINVAL);
+    }
+
+    let cipher_text = &blob[header_size..(header_size + state_size)];
+    let tag = &blob[(header_size + state_size)..];
+    crypto::aead::decrypt(
+        aead_algo,
+        blob_key,
+        &nonce[..iv_size],
+        &blob[..header_size],
+        cipher_text,
+        tag,
+        &mut state[..state_size],
+    )
 }
 
 #[derive(Default)]
@@ -554,9 +1121,17 @@ pub struct SpdmProvisionInfo {
     // TBD: union peer. But it is still option.
     pub peer_cert_chain_data: Option<SpdmCertChainData>,
     pub peer_cert_chain_root_hash: Option<SpdmDigestStruct>,
+    /// Pre-shared key for PSK_EXCHANGE, the built in test PSK if None.
+    pub psk: Option<SpdmDheFinalKeyStruct>,
+    /// Hint sent by the requester, checked by the responder, to identify the PSK.
+    pub psk_hint: Option<SpdmPskHintStruct>,
 }
 
 #[derive(Default)]
 pub struct SpdmPeerInfo {
     pub peer_cert_chain: SpdmCertChain,
+    /// Slot mask and digests of the last DIGESTS response.
+    pub peer_digests: SpdmDigestsResponsePayload,
+    /// Measurement blocks of the last MEASUREMENTS response.
+    pub peer_measurement_record: SpdmMeasurementRecordStructure,
 }
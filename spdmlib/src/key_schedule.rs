@@ -58,7 +58,7 @@ impl SpdmKeySchedule {
         )?;
         let salt_1 = crypto::hkdf::hkdf_expand(hash_algo, key, bin_str0, hash_algo.get_size())?;
 
-        debug!("salt_1 - {:02x?}", salt_1.as_ref());
+        debug_secret!("salt_1 - {:02x?}", salt_1.as_ref());
 
         crypto::hmac::hmac(
             hash_algo,
@@ -200,18 +200,33 @@ impl SpdmKeySchedule {
         &self,
         hash_algo: SpdmBaseHashAlgo,
         key: &[u8],
+        th2: &[u8],
     ) -> Option<SpdmDigestStruct> {
         let buffer = &mut [0; MAX_SPDM_MESSAGE_BUFFER_SIZE];
         let bin_str8 = self.binconcat(
             hash_algo.get_size(),
             SPDM_VERSION_VALUE,
             BIN_STR8_LABEL,
-            None,
+            Some(th2),
             buffer,
         )?;
         crypto::hkdf::hkdf_expand(hash_algo, key, bin_str8, hash_algo.get_size())
     }
 
+    /// Keys for the application, bound to label and context.
+    pub fn derive_exported_key(
+        &self,
+        hash_algo: SpdmBaseHashAlgo,
+        key: &[u8],
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> Option<SpdmDigestStruct> {
+        let buffer = &mut [0; MAX_SPDM_MESSAGE_BUFFER_SIZE];
+        let bin_str = self.binconcat(length, SPDM_VERSION_VALUE, label, Some(context), buffer)?;
+        crypto::hkdf::hkdf_expand(hash_algo, key, bin_str, length)
+    }
+
     pub fn derive_update_secret(
         &self,
         hash_algo: SpdmBaseHashAlgo,
@@ -21,6 +21,8 @@ pub mod config;
 pub mod cmds;
 pub mod common;
 pub mod msgs;
+#[macro_use]
+pub mod secret;
 pub mod requester;
 pub mod responder;
 
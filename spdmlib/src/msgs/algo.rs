@@ -330,6 +330,55 @@ impl Codec for SpdmExtAlgStruct {
     }
 }
 
+pub const SPDM_MAX_VENDOR_ID_LEN: usize = 4;
+
+/// Standards body or vendor defined header (SVH), used to identify
+/// the owner of event groups and other vendor extensible data.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SpdmSvhStruct {
+    pub id: SpdmStandardId,
+    pub vendor_id_len: u8,
+    pub vendor_id: [u8; SPDM_MAX_VENDOR_ID_LEN],
+}
+
+impl SpdmSvhStruct {
+    pub fn dmtf() -> Self {
+        SpdmSvhStruct {
+            id: SpdmStandardId::SpdmStandardIdDMTF,
+            vendor_id_len: 0,
+            vendor_id: [0u8; SPDM_MAX_VENDOR_ID_LEN],
+        }
+    }
+}
+
+impl Codec for SpdmSvhStruct {
+    fn encode(&self, bytes: &mut Writer) {
+        self.id.encode(bytes);
+        self.vendor_id_len.encode(bytes);
+        for d in self.vendor_id.iter().take(self.vendor_id_len as usize) {
+            d.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmSvhStruct> {
+        let id = SpdmStandardId::read(r)?;
+        let vendor_id_len = u8::read(r)?;
+        if vendor_id_len as usize > SPDM_MAX_VENDOR_ID_LEN {
+            return None;
+        }
+        let mut vendor_id = [0u8; SPDM_MAX_VENDOR_ID_LEN];
+        for d in vendor_id.iter_mut().take(vendor_id_len as usize) {
+            *d = u8::read(r)?;
+        }
+
+        Some(SpdmSvhStruct {
+            id,
+            vendor_id_len,
+            vendor_id,
+        })
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct SpdmDheAlgo: u16 {
@@ -1091,6 +1140,27 @@ mod tests {
         assert_eq!(0, reader.left());
     }
     #[test]
+    fn test_case0_spdm_svh_struct() {
+        let u8_slice = &mut [0u8; 8];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSvhStruct {
+            id: SpdmStandardId::SpdmStandardIdPCISIG,
+            vendor_id_len: 2,
+            vendor_id: [0x86, 0x80, 0, 0],
+        };
+        value.encode(&mut writer);
+        assert_eq!(4, writer.used());
+        let mut reader = Reader::init(u8_slice);
+        let spdm_svh_struct = SpdmSvhStruct::read(&mut reader).unwrap();
+        assert_eq!(spdm_svh_struct, value);
+        assert_eq!(4, reader.left());
+
+        let u8_slice = &mut [0u8; 8];
+        u8_slice[1] = SPDM_MAX_VENDOR_ID_LEN as u8 + 1;
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmSvhStruct::read(&mut reader).is_none());
+    }
+    #[test]
     fn test_case0_spdm_dhe_algo() {
         let u8_slice = &mut [0u8; 4];
         let mut writer = Writer::init(u8_slice);
@@ -1278,7 +1348,7 @@ mod tests {
 
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        SpdmDigestStruct::spdm_read(&mut context, &mut reader).unwrap();
+        SpdmDigestStruct::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         let spdm_digest_struct = SpdmDigestStruct::from(bytes_mut);
         assert_eq!(spdm_digest_struct.data_size, 0);
     }
@@ -10,7 +10,9 @@ enum_builder! {
     EnumName: SpdmVersion;
     EnumVal{
         SpdmVersion10 => 0x10,
-        SpdmVersion11 => 0x11
+        SpdmVersion11 => 0x11,
+        SpdmVersion12 => 0x12,
+        SpdmVersion13 => 0x13
     }
 }
 
@@ -38,6 +40,12 @@ enum_builder! {
 //        SpdmResponseEncapsulatedRequest => 0x6A,
 //        SpdmResponseEncapsulatedResponseAck => 0x6B,
         SpdmResponseEndSessionAck => 0x6C,
+        // 1.3 response
+        SpdmResponseSupportedEventTypes => 0x62,
+        SpdmResponseSubscribeEventTypesAck => 0x70,
+        SpdmResponseEventAck => 0x71,
+        SpdmResponseEndpointInfo => 0x07,
+        SpdmResponseMeasurementExtensionLog => 0x6F,
 
         // 1.0 rerquest
         SpdmRequestGetDigests => 0x81,
@@ -58,7 +66,13 @@ enum_builder! {
         SpdmRequestKeyUpdate => 0xE9,
 //        SpdmRequestGetEncapsulatedRequest => 0xEA,
 //        SpdmRequestDeliverEncapsulatedResponse => 0xEB,
-        SpdmRequestEndSession => 0xEC
+        SpdmRequestEndSession => 0xEC,
+        // 1.3 request
+        SpdmRequestGetSupportedEventTypes => 0xE2,
+        SpdmRequestSubscribeEventTypes => 0xF0,
+        SpdmRequestSendEvent => 0xF1,
+        SpdmRequestGetEndpointInfo => 0x87,
+        SpdmRequestGetMeasurementExtensionLog => 0xEF
     }
 }
 
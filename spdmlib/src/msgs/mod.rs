@@ -24,11 +24,14 @@ pub use challenge::*;
 pub use cmd_digest::*;
 pub use cmd_key_exchange::*;
 pub use end_session::*;
+pub use endpoint_info::*;
 pub use error::*;
+pub use event::*;
 pub use finish::*;
 pub use heartbeat::*;
 pub use key_update::*;
 pub use measurement::*;
+pub use measurement_extension_log::*;
 pub use psk_exchange::*;
 pub use psk_finish::*;
 pub use version::*;
@@ -89,13 +92,28 @@ pub enum SpdmMessagePayload {
     SpdmEndSessionRequest(SpdmEndSessionRequestPayload),
     SpdmEndSessionResponse(SpdmEndSessionResponsePayload),
 
+    SpdmGetSupportedEventTypesRequest(SpdmGetSupportedEventTypesRequestPayload),
+    SpdmSupportedEventTypesResponse(SpdmSupportedEventTypesResponsePayload),
+
+    SpdmSubscribeEventTypesRequest(SpdmSubscribeEventTypesRequestPayload),
+    SpdmSubscribeEventTypesAckResponse(SpdmSubscribeEventTypesAckResponsePayload),
+
+    SpdmSendEventRequest(SpdmSendEventRequestPayload),
+    SpdmEventAckResponse(SpdmEventAckResponsePayload),
+
+    SpdmGetEndpointInfoRequest(SpdmGetEndpointInfoRequestPayload),
+    SpdmEndpointInfoResponse(SpdmEndpointInfoResponsePayload),
+
+    SpdmGetMeasurementExtensionLogRequest(SpdmGetMeasurementExtensionLogRequestPayload),
+    SpdmMeasurementExtensionLogResponse(SpdmMeasurementExtensionLogResponsePayload),
+
     // Add new SPDM command here.
     SpdmErrorResponse(SpdmErrorResponsePayload),
 }
 
 impl SpdmMessage {
     pub fn read_with_detailed_error(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmMessage> {
         let header = SpdmMessageHeader::read(r)?;
@@ -103,162 +121,217 @@ impl SpdmMessage {
         let payload = match header.request_response_code {
             SpdmResponseResponseCode::SpdmResponseVersion => {
                 Some(SpdmMessagePayload::SpdmVersionResponse(
-                    SpdmVersionResponsePayload::spdm_read(context, r)?,
+                    SpdmVersionResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestGetVersion => {
                 Some(SpdmMessagePayload::SpdmGetVersionRequest(
-                    SpdmGetVersionRequestPayload::spdm_read(context, r)?,
+                    SpdmGetVersionRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseCapabilities => {
                 Some(SpdmMessagePayload::SpdmCapabilitiesResponse(
-                    SpdmCapabilitiesResponsePayload::spdm_read(context, r)?,
+                    SpdmCapabilitiesResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestGetCapabilities => {
                 Some(SpdmMessagePayload::SpdmGetCapabilitiesRequest(
-                    SpdmGetCapabilitiesRequestPayload::spdm_read(context, r)?,
+                    SpdmGetCapabilitiesRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseAlgorithms => {
                 Some(SpdmMessagePayload::SpdmAlgorithmsResponse(
-                    SpdmAlgorithmsResponsePayload::spdm_read(context, r)?,
+                    SpdmAlgorithmsResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestNegotiateAlgorithms => {
                 Some(SpdmMessagePayload::SpdmNegotiateAlgorithmsRequest(
-                    SpdmNegotiateAlgorithmsRequestPayload::spdm_read(context, r)?,
+                    SpdmNegotiateAlgorithmsRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseDigests => {
                 Some(SpdmMessagePayload::SpdmDigestsResponse(
-                    SpdmDigestsResponsePayload::spdm_read(context, r)?,
+                    SpdmDigestsResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestGetDigests => {
                 Some(SpdmMessagePayload::SpdmGetDigestsRequest(
-                    SpdmGetDigestsRequestPayload::spdm_read(context, r)?,
+                    SpdmGetDigestsRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseCertificate => {
                 Some(SpdmMessagePayload::SpdmCertificateResponse(
-                    SpdmCertificateResponsePayload::spdm_read(context, r)?,
+                    SpdmCertificateResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestGetCertificate => {
                 Some(SpdmMessagePayload::SpdmGetCertificateRequest(
-                    SpdmGetCertificateRequestPayload::spdm_read(context, r)?,
+                    SpdmGetCertificateRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseChallengeAuth => {
                 Some(SpdmMessagePayload::SpdmChallengeAuthResponse(
-                    SpdmChallengeAuthResponsePayload::spdm_read(context, r)?,
+                    SpdmChallengeAuthResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestChallenge => {
                 Some(SpdmMessagePayload::SpdmChallengeRequest(
-                    SpdmChallengeRequestPayload::spdm_read(context, r)?,
+                    SpdmChallengeRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseMeasurements => {
                 Some(SpdmMessagePayload::SpdmMeasurementsResponse(
-                    SpdmMeasurementsResponsePayload::spdm_read(context, r)?,
+                    SpdmMeasurementsResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestGetMeasurements => {
                 Some(SpdmMessagePayload::SpdmGetMeasurementsRequest(
-                    SpdmGetMeasurementsRequestPayload::spdm_read(context, r)?,
+                    SpdmGetMeasurementsRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseKeyExchangeRsp => {
                 Some(SpdmMessagePayload::SpdmKeyExchangeResponse(
-                    SpdmKeyExchangeResponsePayload::spdm_read(context, r)?,
+                    SpdmKeyExchangeResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestKeyExchange => {
                 Some(SpdmMessagePayload::SpdmKeyExchangeRequest(
-                    SpdmKeyExchangeRequestPayload::spdm_read(context, r)?,
+                    SpdmKeyExchangeRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseFinishRsp => {
                 Some(SpdmMessagePayload::SpdmFinishResponse(
-                    SpdmFinishResponsePayload::spdm_read(context, r)?,
+                    SpdmFinishResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestFinish => {
                 Some(SpdmMessagePayload::SpdmFinishRequest(
-                    SpdmFinishRequestPayload::spdm_read(context, r)?,
+                    SpdmFinishRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponsePskExchangeRsp => {
                 Some(SpdmMessagePayload::SpdmPskExchangeResponse(
-                    SpdmPskExchangeResponsePayload::spdm_read(context, r)?,
+                    SpdmPskExchangeResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestPskExchange => {
                 Some(SpdmMessagePayload::SpdmPskExchangeRequest(
-                    SpdmPskExchangeRequestPayload::spdm_read(context, r)?,
+                    SpdmPskExchangeRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponsePskFinishRsp => {
                 Some(SpdmMessagePayload::SpdmPskFinishResponse(
-                    SpdmPskFinishResponsePayload::spdm_read(context, r)?,
+                    SpdmPskFinishResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestPskFinish => {
                 Some(SpdmMessagePayload::SpdmPskFinishRequest(
-                    SpdmPskFinishRequestPayload::spdm_read(context, r)?,
+                    SpdmPskFinishRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseHeartbeatAck => {
                 Some(SpdmMessagePayload::SpdmHeartbeatResponse(
-                    SpdmHeartbeatResponsePayload::spdm_read(context, r)?,
+                    SpdmHeartbeatResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestHeartbeat => {
                 Some(SpdmMessagePayload::SpdmHeartbeatRequest(
-                    SpdmHeartbeatRequestPayload::spdm_read(context, r)?,
+                    SpdmHeartbeatRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseKeyUpdateAck => {
                 Some(SpdmMessagePayload::SpdmKeyUpdateResponse(
-                    SpdmKeyUpdateResponsePayload::spdm_read(context, r)?,
+                    SpdmKeyUpdateResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestKeyUpdate => {
                 Some(SpdmMessagePayload::SpdmKeyUpdateRequest(
-                    SpdmKeyUpdateRequestPayload::spdm_read(context, r)?,
+                    SpdmKeyUpdateRequestPayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             SpdmResponseResponseCode::SpdmResponseEndSessionAck => {
                 Some(SpdmMessagePayload::SpdmEndSessionResponse(
-                    SpdmEndSessionResponsePayload::spdm_read(context, r)?,
+                    SpdmEndSessionResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
             SpdmResponseResponseCode::SpdmRequestEndSession => {
                 Some(SpdmMessagePayload::SpdmEndSessionRequest(
-                    SpdmEndSessionRequestPayload::spdm_read(context, r)?,
+                    SpdmEndSessionRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+
+            SpdmResponseResponseCode::SpdmResponseSupportedEventTypes => {
+                Some(SpdmMessagePayload::SpdmSupportedEventTypesResponse(
+                    SpdmSupportedEventTypesResponsePayload::spdm_read(decode_info, r)?,
+                ))
+            }
+            SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes => {
+                Some(SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(
+                    SpdmGetSupportedEventTypesRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+
+            SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck => {
+                Some(SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(
+                    SpdmSubscribeEventTypesAckResponsePayload::spdm_read(decode_info, r)?,
+                ))
+            }
+            SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes => {
+                Some(SpdmMessagePayload::SpdmSubscribeEventTypesRequest(
+                    SpdmSubscribeEventTypesRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+
+            SpdmResponseResponseCode::SpdmResponseEventAck => {
+                Some(SpdmMessagePayload::SpdmEventAckResponse(
+                    SpdmEventAckResponsePayload::spdm_read(decode_info, r)?,
+                ))
+            }
+            SpdmResponseResponseCode::SpdmRequestSendEvent => {
+                Some(SpdmMessagePayload::SpdmSendEventRequest(
+                    SpdmSendEventRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+
+            SpdmResponseResponseCode::SpdmRequestGetEndpointInfo => {
+                Some(SpdmMessagePayload::SpdmGetEndpointInfoRequest(
+                    SpdmGetEndpointInfoRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+            SpdmResponseResponseCode::SpdmResponseEndpointInfo => {
+                Some(SpdmMessagePayload::SpdmEndpointInfoResponse(
+                    SpdmEndpointInfoResponsePayload::spdm_read(decode_info, r)?,
+                ))
+            }
+
+            SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog => {
+                Some(SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(
+                    SpdmGetMeasurementExtensionLogRequestPayload::spdm_read(decode_info, r)?,
+                ))
+            }
+            SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog => {
+                Some(SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(
+                    SpdmMeasurementExtensionLogResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
 
             // Add new SPDM command here.
             SpdmResponseResponseCode::SpdmResponseError => {
                 Some(SpdmMessagePayload::SpdmErrorResponse(
-                    SpdmErrorResponsePayload::spdm_read(context, r)?,
+                    SpdmErrorResponsePayload::spdm_read(decode_info, r)?,
                 ))
             }
 
@@ -371,6 +444,41 @@ impl SpdmCodec for SpdmMessage {
                 payload.spdm_encode(context, bytes);
             }
 
+            SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+            SpdmMessagePayload::SpdmSupportedEventTypesResponse(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+
+            SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+            SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+
+            SpdmMessagePayload::SpdmSendEventRequest(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+            SpdmMessagePayload::SpdmEventAckResponse(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+
+            SpdmMessagePayload::SpdmGetEndpointInfoRequest(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+            SpdmMessagePayload::SpdmEndpointInfoResponse(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+
+            SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+            SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(payload) => {
+                payload.spdm_encode(context, bytes);
+            }
+
             // Add new SPDM command here.
             SpdmMessagePayload::SpdmErrorResponse(payload) => {
                 payload.spdm_encode(context, bytes);
@@ -378,8 +486,8 @@ impl SpdmCodec for SpdmMessage {
         }
     }
 
-    fn spdm_read(context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmMessage> {
-        SpdmMessage::read_with_detailed_error(context, r)
+    fn spdm_read(decode_info: &common::SpdmDecodeInfo, r: &mut Reader) -> Option<SpdmMessage> {
+        SpdmMessage::read_with_detailed_error(decode_info, r)
     }
 }
 
@@ -877,6 +985,7 @@ mod tests {
                     SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
                 slot_id: 100u8,
                 req_session_id: 100u16,
+                session_policy: SpdmSessionPolicy::empty(),
                 random: SpdmRandomStruct {
                     data: [100u8; SPDM_RANDOM_SIZE],
                 },
@@ -1013,6 +1122,7 @@ mod tests {
                 measurement_summary_hash_type:
                     SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
                 req_session_id: 100u16,
+                session_policy: SpdmSessionPolicy::empty(),
                 psk_hint: SpdmPskHintStruct {
                     data_size: 32,
                     data: [100u8; MAX_SPDM_PSK_HINT_SIZE],
@@ -1379,7 +1489,7 @@ mod tests {
         let mut writer = Writer::init(u8_slice);
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
-        let spdm_message = SpdmMessage::spdm_read(&mut context, &mut reader);
+        let spdm_message = SpdmMessage::spdm_read(&context.get_decode_info(), &mut reader);
         assert_eq!(spdm_message.is_none(), true);
     }
 
@@ -1431,4 +1541,114 @@ mod tests {
         let context = new_context(my_spdm_device_io, pcidoe_transport_encap);
         new_spdm_message(value, context);
     }
+    #[test]
+    fn test_case29_spdm_message() {
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+
+        let mut payload = SpdmSendEventRequestPayload {
+            event_count: 1,
+            ..Default::default()
+        };
+        payload.events[0] = SpdmEventDataStruct::new_dmtf(
+            5,
+            &SpdmDmtfEventDetail::CertificateChanged(SpdmCertificateChangedStruct { slot_id: 3 }),
+        );
+        let value = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestSendEvent,
+            },
+            payload: SpdmMessagePayload::SpdmSendEventRequest(payload),
+        };
+        let context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+        let spdm_message = new_spdm_message(value, context);
+        assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion13);
+        if let SpdmMessagePayload::SpdmSendEventRequest(payload) = &spdm_message.payload {
+            assert_eq!(payload.event_count, 1);
+            assert_eq!(payload.events[0].event_instance_id, 5);
+            assert_eq!(
+                payload.events[0].get_dmtf_event().unwrap(),
+                SpdmDmtfEventDetail::CertificateChanged(SpdmCertificateChangedStruct {
+                    slot_id: 3
+                })
+            );
+        } else {
+            panic!();
+        }
+    }
+    #[test]
+    fn test_case30_spdm_message() {
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+
+        let mut payload = SpdmMeasurementExtensionLogResponsePayload {
+            portion_length: 8,
+            remainder_length: 24,
+            ..Default::default()
+        };
+        payload.mel_portion[..8].copy_from_slice(&[0xaau8; 8]);
+        let value = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code:
+                    SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog,
+            },
+            payload: SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(payload),
+        };
+        let context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+        let spdm_message = new_spdm_message(value, context);
+        assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion13);
+        if let SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(payload) =
+            &spdm_message.payload
+        {
+            assert_eq!(payload.remainder_length, 24);
+            assert_eq!(payload.as_ref(), &[0xaau8; 8]);
+        } else {
+            panic!();
+        }
+    }
+    #[test]
+    fn test_case0_spdm_decode_info() {
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let my_spdm_device_io = &mut MySpdmDeviceIo;
+        let mut context = new_context(my_spdm_device_io, pcidoe_transport_encap);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+        let value = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion11,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestPskFinish,
+            },
+            payload: SpdmMessagePayload::SpdmPskFinishRequest(SpdmPskFinishRequestPayload {
+                verify_data: SpdmDigestStruct {
+                    data_size: SHA384_DIGEST_SIZE as u16,
+                    data: [0x5au8; SPDM_MAX_HASH_SIZE],
+                },
+            }),
+        };
+        let u8_slice = &mut [0u8; 64];
+        let mut writer = Writer::init(u8_slice);
+        value.spdm_encode(&mut context, &mut writer);
+        let used = writer.used();
+
+        let decode_info = context.get_decode_info();
+        let mut reader = Reader::init(&u8_slice[..used]);
+        let spdm_message = SpdmMessage::spdm_read(&decode_info, &mut reader).unwrap();
+        assert!(!reader.any_left());
+        if let SpdmMessagePayload::SpdmPskFinishRequest(payload) = &spdm_message.payload {
+            assert_eq!(payload.verify_data.data_size, SHA384_DIGEST_SIZE as u16);
+            assert_eq!(payload.verify_data.data[47], 0x5a);
+        } else {
+            panic!();
+        }
+
+        let decode_info = common::SpdmDecodeInfo {
+            base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ..decode_info
+        };
+        let mut reader = Reader::init(&u8_slice[..used]);
+        SpdmMessage::spdm_read(&decode_info, &mut reader).unwrap();
+        assert_eq!(reader.left(), SHA384_DIGEST_SIZE - SHA256_DIGEST_SIZE);
+    }
 }
@@ -5,6 +5,8 @@
 use crate::common;
 use crate::config;
 use crate::msgs::SpdmCodec;
+use crate::msgs::{SpdmStandardId, SpdmSvhStruct};
+use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
 
 //pub const SPDM_MAX_OPAQUE_SIZE : usize = 1024;
@@ -30,7 +32,10 @@ impl SpdmCodec for SpdmOpaqueStruct {
             d.encode(bytes);
         }
     }
-    fn spdm_read(_context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmOpaqueStruct> {
+    fn spdm_read(
+        _decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmOpaqueStruct> {
         let data_size = u16::read(r)?;
         let mut data = [0u8; config::MAX_SPDM_OPAQUE_SIZE];
         for d in data.iter_mut().take(data_size as usize) {
@@ -39,6 +44,308 @@ impl SpdmCodec for SpdmOpaqueStruct {
         Some(SpdmOpaqueStruct { data_size, data })
     }
 }
+/// "DMTF" in little endian
+pub const SPDM_GENERAL_OPAQUE_DATA_SPEC_ID: u32 = 0x444D_5446;
+pub const SPDM_GENERAL_OPAQUE_DATA_VERSION: u8 = 1;
+
+/// Version of the secured message opaque data element defined in DSP0277
+pub const SPDM_SECURED_MESSAGE_DATA_VERSION: u8 = 1;
+pub const SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT: usize = 2;
+
+enum_builder! {
+    @U8
+    EnumName: SpdmSecuredMessageDataId;
+    EnumVal{
+        SpdmSecuredMessageDataIdVersionSelection => 0x0,
+        SpdmSecuredMessageDataIdSupportedVersion => 0x1
+    }
+}
+
+enum_builder! {
+    @U16
+    EnumName: SpdmSecuredMessageVersion;
+    EnumVal{
+        SpdmSecuredMessageVersion10 => 0x1000,
+        SpdmSecuredMessageVersion11 => 0x1100
+    }
+}
+
+impl From<u16> for SpdmSecuredMessageVersion {
+    /// The update and alpha version numbers are ignored.
+    fn from(version: u16) -> SpdmSecuredMessageVersion {
+        let bytes = (version & 0xFF00).to_le_bytes();
+        SpdmSecuredMessageVersion::read_bytes(&bytes).unwrap_or_default()
+    }
+}
+
+/// One element of the DMTF general opaque data.
+/// The element is padded to 4 bytes on the wire.
+#[derive(Debug, Copy, Clone)]
+pub struct SpdmOpaqueElement {
+    pub id: SpdmSvhStruct,
+    pub data_size: u16,
+    pub data: [u8; config::MAX_SPDM_OPAQUE_SIZE],
+}
+
+impl Default for SpdmOpaqueElement {
+    fn default() -> SpdmOpaqueElement {
+        SpdmOpaqueElement {
+            id: SpdmSvhStruct::default(),
+            data_size: 0,
+            data: [0u8; config::MAX_SPDM_OPAQUE_SIZE],
+        }
+    }
+}
+
+impl AsRef<[u8]> for SpdmOpaqueElement {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[0..(self.data_size as usize)]
+    }
+}
+
+impl SpdmOpaqueElement {
+    pub fn new(id: SpdmSvhStruct, data: &[u8]) -> Option<SpdmOpaqueElement> {
+        if data.len() > config::MAX_SPDM_OPAQUE_SIZE {
+            return None;
+        }
+        let mut element = SpdmOpaqueElement {
+            id,
+            data_size: data.len() as u16,
+            ..Default::default()
+        };
+        element.data[..data.len()].copy_from_slice(data);
+        Some(element)
+    }
+
+    pub fn new_supported_version(
+        versions: &[SpdmSecuredMessageVersion],
+    ) -> Option<SpdmOpaqueElement> {
+        let mut data = [0u8; config::MAX_SPDM_OPAQUE_SIZE];
+        let mut writer = Writer::init(&mut data);
+        SPDM_SECURED_MESSAGE_DATA_VERSION.encode(&mut writer);
+        SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdSupportedVersion.encode(&mut writer);
+        (versions.len() as u8).encode(&mut writer);
+        for version in versions.iter() {
+            version.encode(&mut writer);
+        }
+        let used = writer.used();
+        SpdmOpaqueElement::new(SpdmSvhStruct::dmtf(), &data[..used])
+    }
+
+    pub fn new_version_selection(version: SpdmSecuredMessageVersion) -> SpdmOpaqueElement {
+        let mut data = [0u8; 4];
+        let mut writer = Writer::init(&mut data);
+        SPDM_SECURED_MESSAGE_DATA_VERSION.encode(&mut writer);
+        SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdVersionSelection.encode(&mut writer);
+        version.encode(&mut writer);
+        SpdmOpaqueElement::new(SpdmSvhStruct::dmtf(), &data).unwrap()
+    }
+
+    fn get_secured_message_data(&self, data_id: SpdmSecuredMessageDataId) -> Option<Reader> {
+        if self.id.id != SpdmStandardId::SpdmStandardIdDMTF {
+            return None;
+        }
+        let mut r = Reader::init(self.as_ref());
+        if u8::read(&mut r)? != SPDM_SECURED_MESSAGE_DATA_VERSION
+            || SpdmSecuredMessageDataId::read(&mut r)? != data_id
+        {
+            return None;
+        }
+        Some(r)
+    }
+
+    /// Return the versions listed in a SupportedVersion element.
+    /// Unknown versions are skipped.
+    pub fn get_supported_version(
+        &self,
+    ) -> Option<(
+        [SpdmSecuredMessageVersion; SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT],
+        usize,
+    )> {
+        let mut r = self.get_secured_message_data(
+            SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdSupportedVersion,
+        )?;
+        let version_count = u8::read(&mut r)?;
+        let mut versions =
+            [SpdmSecuredMessageVersion::default(); SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT];
+        let mut count = 0;
+        for _ in 0..version_count {
+            let version = SpdmSecuredMessageVersion::from(u16::read(&mut r)?);
+            if let SpdmSecuredMessageVersion::Unknown(_) = version {
+                continue;
+            }
+            if count < SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT && !versions.contains(&version) {
+                versions[count] = version;
+                count += 1;
+            }
+        }
+        Some((versions, count))
+    }
+
+    /// Return the version chosen in a VersionSelection element.
+    pub fn get_version_selection(&self) -> Option<SpdmSecuredMessageVersion> {
+        let mut r = self.get_secured_message_data(
+            SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdVersionSelection,
+        )?;
+        Some(SpdmSecuredMessageVersion::from(u16::read(&mut r)?))
+    }
+
+    fn is_secured_message_data(&self) -> bool {
+        self.get_secured_message_data(
+            SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdSupportedVersion,
+        )
+        .is_some()
+            || self
+                .get_secured_message_data(
+                    SpdmSecuredMessageDataId::SpdmSecuredMessageDataIdVersionSelection,
+                )
+                .is_some()
+    }
+}
+
+impl Codec for SpdmOpaqueElement {
+    fn encode(&self, bytes: &mut Writer) {
+        let start = bytes.used();
+        self.id.encode(bytes);
+        self.data_size.encode(bytes);
+        for d in self.data.iter().take(self.data_size as usize) {
+            d.encode(bytes);
+        }
+        let padding_size = (4 - (bytes.used() - start) % 4) % 4;
+        for _ in 0..padding_size {
+            0u8.encode(bytes); // padding
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmOpaqueElement> {
+        let start = r.used();
+        let id = SpdmSvhStruct::read(r)?;
+        let data_size = u16::read(r)?;
+        if data_size as usize > config::MAX_SPDM_OPAQUE_SIZE {
+            return None;
+        }
+        let mut data = [0u8; config::MAX_SPDM_OPAQUE_SIZE];
+        for d in data.iter_mut().take(data_size as usize) {
+            *d = u8::read(r)?;
+        }
+        let padding_size = (4 - (r.used() - start) % 4) % 4;
+        for _ in 0..padding_size {
+            u8::read(r)?; // padding
+        }
+
+        Some(SpdmOpaqueElement {
+            id,
+            data_size,
+            data,
+        })
+    }
+}
+
+/// DMTF general opaque data format, carried in SpdmOpaqueStruct.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmGeneralOpaqueData {
+    pub element_count: u8,
+    pub elements: [SpdmOpaqueElement; config::MAX_SPDM_OPAQUE_ELEMENT_COUNT],
+}
+
+impl SpdmGeneralOpaqueData {
+    pub fn add_element(&mut self, element: &SpdmOpaqueElement) -> Option<()> {
+        let slot = self.elements.get_mut(self.element_count as usize)?;
+        *slot = *element;
+        self.element_count += 1;
+        Some(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SpdmOpaqueElement> {
+        self.elements.iter().take(self.element_count as usize)
+    }
+
+    /// Return the versions of the first SupportedVersion element.
+    pub fn get_supported_version(
+        &self,
+    ) -> Option<(
+        [SpdmSecuredMessageVersion; SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT],
+        usize,
+    )> {
+        self.iter().find_map(|e| e.get_supported_version())
+    }
+
+    /// Return the version of the first VersionSelection element.
+    pub fn get_version_selection(&self) -> Option<SpdmSecuredMessageVersion> {
+        self.iter().find_map(|e| e.get_version_selection())
+    }
+
+    /// Return a copy with the secured message version elements removed,
+    /// leaving the elements that belong to the application.
+    pub fn get_app_elements(&self) -> SpdmGeneralOpaqueData {
+        let mut app_elements = SpdmGeneralOpaqueData::default();
+        for element in self.iter().filter(|e| !e.is_secured_message_data()) {
+            app_elements.add_element(element).unwrap();
+        }
+        app_elements
+    }
+
+    pub fn from_opaque(opaque: &SpdmOpaqueStruct) -> Option<SpdmGeneralOpaqueData> {
+        let mut r = Reader::init(&opaque.data[..(opaque.data_size as usize)]);
+        let general_opaque_data = SpdmGeneralOpaqueData::read(&mut r)?;
+        if r.any_left() {
+            return None;
+        }
+        Some(general_opaque_data)
+    }
+
+    pub fn to_opaque(&self) -> Option<SpdmOpaqueStruct> {
+        // encode into a bigger buffer first, so an overflow can be detected.
+        let mut data = [0u8; config::MAX_SPDM_OPAQUE_SIZE + 1];
+        let mut writer = Writer::init(&mut data);
+        self.encode(&mut writer);
+        let used = writer.used();
+        if used > config::MAX_SPDM_OPAQUE_SIZE {
+            return None;
+        }
+        let mut opaque = SpdmOpaqueStruct {
+            data_size: used as u16,
+            ..Default::default()
+        };
+        opaque.data[..used].copy_from_slice(&data[..used]);
+        Some(opaque)
+    }
+}
+
+impl Codec for SpdmGeneralOpaqueData {
+    fn encode(&self, bytes: &mut Writer) {
+        SPDM_GENERAL_OPAQUE_DATA_SPEC_ID.encode(bytes);
+        SPDM_GENERAL_OPAQUE_DATA_VERSION.encode(bytes);
+        self.element_count.encode(bytes);
+        0u16.encode(bytes); // reserved
+        for element in self.iter() {
+            element.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmGeneralOpaqueData> {
+        if u32::read(r)? != SPDM_GENERAL_OPAQUE_DATA_SPEC_ID
+            || u8::read(r)? != SPDM_GENERAL_OPAQUE_DATA_VERSION
+        {
+            return None;
+        }
+        let element_count = u8::read(r)?;
+        u16::read(r)?; // reserved
+        if element_count as usize > config::MAX_SPDM_OPAQUE_ELEMENT_COUNT {
+            return None;
+        }
+        let mut elements = [SpdmOpaqueElement::default(); config::MAX_SPDM_OPAQUE_ELEMENT_COUNT];
+        for element in elements.iter_mut().take(element_count as usize) {
+            *element = SpdmOpaqueElement::read(r)?;
+        }
+
+        Some(SpdmGeneralOpaqueData {
+            element_count,
+            elements,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,11 +368,121 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(68, reader.left());
-        let spdm_opaque_struct = SpdmOpaqueStruct::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_opaque_struct =
+            SpdmOpaqueStruct::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_opaque_struct.data_size, 64);
         for i in 0..64 {
             assert_eq!(spdm_opaque_struct.data[i], 100);
         }
         assert_eq!(2, reader.left());
     }
+    #[test]
+    fn test_case0_spdm_general_opaque_data() {
+        // the encoding is compatible with the fixed v1.1 opaque data used before
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        opaque_data
+            .add_element(
+                &SpdmOpaqueElement::new_supported_version(&[
+                    SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                ])
+                .unwrap(),
+            )
+            .unwrap();
+        let opaque = opaque_data.to_opaque().unwrap();
+        assert_eq!(
+            &opaque.data[..(opaque.data_size as usize)],
+            &[
+                0x46, 0x54, 0x4d, 0x44, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x01, 0x01,
+                0x01, 0x00, 0x11, 0x00, 0x00, 0x00,
+            ]
+        );
+
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        opaque_data
+            .add_element(&SpdmOpaqueElement::new_version_selection(
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+            ))
+            .unwrap();
+        let opaque = opaque_data.to_opaque().unwrap();
+        assert_eq!(
+            &opaque.data[..(opaque.data_size as usize)],
+            &[
+                0x46, 0x54, 0x4d, 0x44, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01, 0x00,
+                0x00, 0x11,
+            ]
+        );
+    }
+    #[test]
+    fn test_case1_spdm_general_opaque_data() {
+        let vendor = SpdmSvhStruct {
+            id: SpdmStandardId::SpdmStandardIdPCISIG,
+            vendor_id_len: 2,
+            vendor_id: [0x86, 0x80, 0, 0],
+        };
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        opaque_data
+            .add_element(
+                &SpdmOpaqueElement::new_supported_version(&[
+                    SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                    SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+                ])
+                .unwrap(),
+            )
+            .unwrap();
+        opaque_data
+            .add_element(&SpdmOpaqueElement::new(vendor, &[1, 2, 3]).unwrap())
+            .unwrap();
+
+        let opaque = opaque_data.to_opaque().unwrap();
+        let opaque_data = SpdmGeneralOpaqueData::from_opaque(&opaque).unwrap();
+        assert_eq!(opaque_data.element_count, 2);
+        let (versions, count) = opaque_data.get_supported_version().unwrap();
+        assert_eq!(
+            &versions[..count],
+            &[
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+            ]
+        );
+        assert!(opaque_data.get_version_selection().is_none());
+
+        let app_elements = opaque_data.get_app_elements();
+        assert_eq!(app_elements.element_count, 1);
+        assert_eq!(app_elements.elements[0].id, vendor);
+        assert_eq!(app_elements.elements[0].as_ref(), &[1, 2, 3]);
+    }
+    #[test]
+    fn test_case2_spdm_general_opaque_data() {
+        // the update version is ignored, unknown versions are dropped
+        assert_eq!(
+            SpdmSecuredMessageVersion::from(0x1105),
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11
+        );
+        let element = SpdmOpaqueElement::new(
+            SpdmSvhStruct::dmtf(),
+            &[0x01, 0x01, 0x02, 0x00, 0x20, 0x00, 0x10],
+        )
+        .unwrap();
+        let (_, count) = element.get_supported_version().unwrap();
+        assert_eq!(count, 1);
+
+        // wrong spec id
+        let mut opaque = SpdmGeneralOpaqueData::default().to_opaque().unwrap();
+        opaque.data[0] = 0;
+        assert!(SpdmGeneralOpaqueData::from_opaque(&opaque).is_none());
+
+        // too many elements
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        let element = SpdmOpaqueElement::new(SpdmSvhStruct::dmtf(), &[0u8; 8]).unwrap();
+        for _ in 0..config::MAX_SPDM_OPAQUE_ELEMENT_COUNT {
+            opaque_data.add_element(&element).unwrap();
+        }
+        assert!(opaque_data.add_element(&element).is_none());
+
+        // too large for SpdmOpaqueStruct
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        let element = SpdmOpaqueElement::new(SpdmSvhStruct::dmtf(), &[0u8; 60]).unwrap();
+        opaque_data.add_element(&element).unwrap();
+        assert!(opaque_data.to_opaque().is_none());
+    }
 }
@@ -16,7 +16,7 @@ pub trait SpdmCodec: Debug + Sized {
 
     /// Decode yourself by fiddling with the `Reader`.
     /// Return Some if it worked, None if not.
-    fn spdm_read(_context: &mut common::SpdmContext, _: &mut Reader) -> Option<Self>;
+    fn spdm_read(_decode_info: &common::SpdmDecodeInfo, _: &mut Reader) -> Option<Self>;
 
     // /// Convenience function to get the results of `encode()`.
     // /// TBD: Encode may fail if the caller encodes too many data that exceeds the max size of preallocated slice.
@@ -29,9 +29,9 @@ pub trait SpdmCodec: Debug + Sized {
 
     /// Read one of these from the front of `bytes` and
     /// return it.
-    fn spdm_read_bytes(context: &mut common::SpdmContext, bytes: &[u8]) -> Option<Self> {
+    fn spdm_read_bytes(decode_info: &common::SpdmDecodeInfo, bytes: &[u8]) -> Option<Self> {
         let mut rd = Reader::init(bytes);
-        Self::spdm_read(context, &mut rd)
+        Self::spdm_read(decode_info, &mut rd)
     }
 }
 
@@ -42,8 +42,8 @@ impl SpdmCodec for SpdmDigestStruct {
             d.encode(bytes);
         }
     }
-    fn spdm_read(context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmDigestStruct> {
-        let data_size = context.get_hash_size();
+    fn spdm_read(decode_info: &common::SpdmDecodeInfo, r: &mut Reader) -> Option<SpdmDigestStruct> {
+        let data_size = decode_info.base_hash_sel.get_size();
         let mut data = [0u8; SPDM_MAX_HASH_SIZE];
         for d in data.iter_mut().take(data_size as usize) {
             *d = u8::read(r)?;
@@ -59,8 +59,11 @@ impl SpdmCodec for SpdmSignatureStruct {
             d.encode(bytes);
         }
     }
-    fn spdm_read(context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmSignatureStruct> {
-        let data_size = context.get_asym_key_size();
+    fn spdm_read(
+        decode_info: &common::SpdmDecodeInfo,
+        r: &mut Reader,
+    ) -> Option<SpdmSignatureStruct> {
+        let data_size = decode_info.base_asym_sel.get_size();
         let mut data = [0u8; SPDM_MAX_ASYM_KEY_SIZE];
         for d in data.iter_mut().take(data_size as usize) {
             *d = u8::read(r)?;
@@ -85,10 +88,10 @@ impl SpdmCodec for SpdmCertChain {
             d.encode(bytes);
         }
     }
-    fn spdm_read(context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmCertChain> {
+    fn spdm_read(decode_info: &common::SpdmDecodeInfo, r: &mut Reader) -> Option<SpdmCertChain> {
         let length = u16::read(r)?;
         u16::read(r)?;
-        let root_hash = SpdmDigestStruct::spdm_read(context, r)?;
+        let root_hash = SpdmDigestStruct::spdm_read(decode_info, r)?;
         let data_size = length - 4 - root_hash.data_size as u16;
         let mut cert_chain = SpdmCertChainData {
             data_size,
@@ -123,7 +126,7 @@ impl SpdmCodec for SpdmMeasurementRecordStructure {
         }
     }
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmMeasurementRecordStructure> {
         let number_of_blocks = u8::read(r)?;
@@ -132,7 +135,7 @@ impl SpdmCodec for SpdmMeasurementRecordStructure {
         let mut record =
             [SpdmMeasurementBlockStructure::default(); config::MAX_SPDM_MEASUREMENT_BLOCK_COUNT];
         for d in record.iter_mut().take(number_of_blocks as usize) {
-            *d = SpdmMeasurementBlockStructure::spdm_read(context, r)?;
+            *d = SpdmMeasurementBlockStructure::spdm_read(decode_info, r)?;
         }
 
         let mut calc_length = 0u32;
@@ -160,10 +163,10 @@ impl SpdmCodec for SpdmDheExchangeStruct {
         }
     }
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmDheExchangeStruct> {
-        let data_size = context.get_dhe_key_size();
+        let data_size = decode_info.dhe_sel.get_size();
         let mut data = [0u8; SPDM_MAX_DHE_KEY_SIZE];
         for d in data.iter_mut().take(data_size as usize) {
             *d = u8::read(r)?;
@@ -187,7 +190,7 @@ impl SpdmCodec for SpdmDmtfMeasurementStructure {
         }
     }
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        _decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmDmtfMeasurementStructure> {
         let final_value = u8::read(r)?;
@@ -231,13 +234,13 @@ impl SpdmCodec for SpdmMeasurementBlockStructure {
         self.measurement.spdm_encode(context, bytes);
     }
     fn spdm_read(
-        context: &mut common::SpdmContext,
+        decode_info: &common::SpdmDecodeInfo,
         r: &mut Reader,
     ) -> Option<SpdmMeasurementBlockStructure> {
         let index = u8::read(r)?;
         let measurement_specification = SpdmMeasurementSpecification::read(r)?;
         let measurement_size = u16::read(r)?;
-        let measurement = SpdmDmtfMeasurementStructure::spdm_read(context, r)?;
+        let measurement = SpdmDmtfMeasurementStructure::spdm_read(decode_info, r)?;
         Some(SpdmMeasurementBlockStructure {
             index,
             measurement_specification,
@@ -267,7 +270,8 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(68, reader.left());
-        let spdm_digest_struct = SpdmDigestStruct::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_digest_struct =
+            SpdmDigestStruct::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_digest_struct.data_size, 64);
         for i in 0..64 {
             assert_eq!(spdm_digest_struct.data[i], 100u8);
@@ -292,7 +296,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(512, reader.left());
         let spdm_signature_struct =
-            SpdmSignatureStruct::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmSignatureStruct::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_signature_struct.data_size, 512);
         for i in 0..512 {
             assert_eq!(spdm_signature_struct.data[i], 100);
@@ -321,7 +325,8 @@ mod tests {
         value.spdm_encode(&mut context, &mut writer);
         let mut reader = Reader::init(u8_slice);
         assert_eq!(4192, reader.left());
-        let spdm_cert_chain = SpdmCertChain::spdm_read(&mut context, &mut reader).unwrap();
+        let spdm_cert_chain =
+            SpdmCertChain::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_cert_chain.root_hash.data_size, 64);
         for i in 0..64 {
             assert_eq!(spdm_cert_chain.root_hash.data[i], 100);
@@ -359,7 +364,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(512, reader.left());
         let measurement_record =
-            SpdmMeasurementRecordStructure::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmMeasurementRecordStructure::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(measurement_record.number_of_blocks, 5);
         for i in 0..5 {
             assert_eq!(measurement_record.record[i].index, 100);
@@ -418,7 +424,7 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(512, reader.left());
         let spdm_dhe_exchange_struct =
-            SpdmDheExchangeStruct::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmDheExchangeStruct::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_dhe_exchange_struct.data_size, 512);
         for i in 0..512 {
             assert_eq!(spdm_dhe_exchange_struct.data[i], 100);
@@ -456,7 +462,8 @@ mod tests {
             let mut reader = Reader::init(u8_slice);
             assert_eq!(68, reader.left());
             let spdm_dmtf_measurement_structure =
-                SpdmDmtfMeasurementStructure::spdm_read(&mut context, &mut reader).unwrap();
+                SpdmDmtfMeasurementStructure::spdm_read(&context.get_decode_info(), &mut reader)
+                    .unwrap();
             assert_eq!(spdm_dmtf_measurement_structure.r#type, r#type[i]);
             if i < 2 {
                 assert_eq!(
@@ -495,7 +502,8 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         assert_eq!(80, reader.left());
         let spdm_block_structure =
-            SpdmMeasurementBlockStructure::spdm_read(&mut context, &mut reader).unwrap();
+            SpdmMeasurementBlockStructure::spdm_read(&context.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_block_structure.index, 100);
         assert_eq!(
             spdm_block_structure.measurement_specification,
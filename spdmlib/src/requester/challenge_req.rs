@@ -74,8 +74,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseChallengeAuth => {
-                    let challenge_auth =
-                        SpdmChallengeAuthResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let challenge_auth = SpdmChallengeAuthResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(challenge_auth) = challenge_auth {
                         debug!("!!! challenge_auth : {:02x?}\n", challenge_auth);
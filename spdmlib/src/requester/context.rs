@@ -6,9 +6,12 @@ use crate::common::{self, SpdmDeviceIo, SpdmTransportEncap};
 use crate::config;
 use crate::error::SpdmResult;
 use crate::msgs::*;
+use crate::requester::SpdmEventCallback;
+use crate::secret::SpdmSecret;
 
 pub struct RequesterContext<'a> {
     pub common: common::SpdmContext<'a>,
+    pub event_callback: Option<SpdmEventCallback>,
 }
 
 impl<'a> RequesterContext<'a> {
@@ -25,6 +28,7 @@ impl<'a> RequesterContext<'a> {
                 config_info,
                 provision_info,
             ),
+            event_callback: None,
         }
     }
 
@@ -40,6 +44,15 @@ impl<'a> RequesterContext<'a> {
         self.send_receive_spdm_algorithm()
     }
 
+    /// Skip GET_VERSION, GET_CAPABILITIES and NEGOTIATE_ALGORITHMS with the
+    /// state cached by a former connection to a responder with CACHE_CAP.
+    pub fn init_connection_with_cache(
+        &mut self,
+        negotiated_state: &common::SpdmNegotiatedState,
+    ) -> SpdmResult {
+        self.common.restore_negotiated_state(negotiated_state)
+    }
+
     pub fn start_session(
         &mut self,
         use_psk: bool,
@@ -74,9 +87,21 @@ impl<'a> RequesterContext<'a> {
         }
     }
 
+    /// End the session without preserving the negotiated state,
+    /// see send_receive_spdm_end_session.
     pub fn end_session(&mut self, session_id: u32) -> SpdmResult {
-        let _result = self.send_receive_spdm_end_session(session_id);
-        Ok(())
+        self.send_receive_spdm_end_session(session_id, SpdmEndSessionRequestAttributes::empty())
+    }
+
+    pub fn export_keying_material(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: usize,
+    ) -> SpdmResult<SpdmSecret<SpdmDigestStruct>> {
+        self.common
+            .export_keying_material(session_id, label, context, length)
     }
 
     pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
@@ -89,17 +114,21 @@ impl<'a> RequesterContext<'a> {
         &mut self,
         session_id: u32,
         send_buffer: &[u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult {
+        self.update_keys_if_due(session_id, send_buffer, app_message_type)?;
+
         let mut transport_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.common.encode_secured_message(
             session_id,
             send_buffer,
             &mut transport_buffer,
             true,
-            is_app_message,
+            app_message_type,
         )?;
-        self.common.device_io.send(&transport_buffer[..used])
+        self.common.device_io.send(&transport_buffer[..used])?;
+        self.common.record_session_activity(session_id);
+        Ok(())
     }
 
     pub fn receive_message(&mut self, receive_buffer: &mut [u8]) -> SpdmResult<usize> {
@@ -133,12 +162,54 @@ impl<'a> RequesterContext<'a> {
         self.common
             .decode_secured_message(session_id, &transport_buffer[..used], receive_buffer)
     }
+
+    /// Receive a secured message which may carry an application message.
+    /// Return the payload size and the application message type, None for SPDM.
+    pub fn receive_secured_app_message(
+        &mut self,
+        session_id: u32,
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        info!("receive_secured_app_message!\n");
+
+        let mut transport_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+
+        let used = self
+            .common
+            .device_io
+            .receive(&mut transport_buffer)
+            .map_err(|_| spdm_err!(EIO))?;
+
+        self.common.decode_secured_app_message(
+            session_id,
+            &transport_buffer[..used],
+            receive_buffer,
+        )
+    }
+
+    /// Send an application message of app_message_type, e.g. PLDM over MCTP,
+    /// in the session and copy the decrypted reply into response.
+    /// Return the reply size.
+    pub fn send_receive_app_message(
+        &mut self,
+        session_id: u32,
+        app_message_type: u8,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> SpdmResult<usize> {
+        self.send_secured_message(session_id, request, Some(app_message_type))?;
+
+        let (used, reply_type) = self.receive_secured_app_message(session_id, response)?;
+        if reply_type != Some(app_message_type) {
+            return spdm_result_err!(EFAULT);
+        }
+        Ok(used)
+    }
 }
 
 #[cfg(test)]
 mod tests_requester {
     use super::*;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
     use codec::Writer;
@@ -202,6 +273,129 @@ mod tests_requester {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_case0_suspend_resume_session() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (mut req_config_info, req_provision_info) = create_info();
+        let rekey_policy = crate::session::SpdmRekeyPolicy {
+            max_message_count: 1000,
+            max_byte_count: 0x10_0000,
+            max_key_lifetime_ms: 60_000,
+        };
+        req_config_info.rekey_policy = rekey_policy;
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+        assert!(requester.send_receive_spdm_digest().is_ok());
+        assert!(requester.send_receive_spdm_certificate(0).is_ok());
+        let session_id = requester
+            .start_session(
+                false,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+        let aead_sel = requester.common.negotiate_info.aead_sel;
+        let rsp_capabilities_sel = requester.common.negotiate_info.rsp_capabilities_sel;
+
+        let blob_key = [0x5au8; 32];
+        let mut blob = [0u8; common::SPDM_SESSION_BLOB_MAX_SIZE];
+        assert!(requester
+            .common
+            .suspend_session(session_id, &blob_key[..16], &mut blob)
+            .is_err());
+        let used = requester
+            .common
+            .suspend_session(session_id, &blob_key, &mut blob)
+            .unwrap();
+        assert!(requester
+            .common
+            .get_immutable_session_via_id(session_id)
+            .is_none());
+        drop(requester);
+
+        let (req_config_info, req_provision_info) = create_info();
+        let pcidoe_transport_encap3 = &mut PciDoeTransportEncap {};
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap3,
+            req_config_info,
+            req_provision_info,
+        );
+
+        let mut tampered = blob;
+        tampered[used - 1] ^= 1;
+        assert!(requester
+            .common
+            .resume_session(&blob_key, &tampered[..used])
+            .is_err());
+        assert!(requester
+            .common
+            .resume_session(&[0xa5u8; 32], &blob[..used])
+            .is_err());
+        assert!(requester
+            .common
+            .resume_session(&blob_key, &blob[..(used - 1)])
+            .is_err());
+
+        assert_eq!(
+            requester
+                .common
+                .resume_session(&blob_key, &blob[..used])
+                .unwrap(),
+            session_id
+        );
+        assert_eq!(requester.common.negotiate_info.aead_sel, aead_sel);
+        assert_eq!(
+            requester.common.negotiate_info.rsp_capabilities_sel,
+            rsp_capabilities_sel
+        );
+        let policy = requester
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .rekey_info
+            .policy;
+        assert_eq!(policy.max_message_count, rekey_policy.max_message_count);
+        assert_eq!(policy.max_byte_count, rekey_policy.max_byte_count);
+        assert_eq!(policy.max_key_lifetime_ms, rekey_policy.max_key_lifetime_ms);
+        assert!(requester
+            .common
+            .resume_session(&blob_key, &blob[..used])
+            .is_err());
+
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+        assert!(requester
+            .send_receive_spdm_key_update(session_id, SpdmKeyUpdateOperation::SpdmUpdateAllKeys)
+            .is_ok());
+        assert!(requester
+            .send_receive_spdm_end_session(session_id, SpdmEndSessionRequestAttributes::empty())
+            .is_ok());
+    }
+
     #[test]
     fn test_case0_receive_secured_message() {
         let (rsp_config_info, rsp_provision_info) = create_info();
@@ -223,7 +417,7 @@ mod tests_requester {
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(session_id).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -247,7 +441,7 @@ mod tests_requester {
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(session_id).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -273,7 +467,7 @@ mod tests_requester {
         let used = writer.used();
 
         let status = requester
-            .send_secured_message(session_id, &send_buffer[..used], false)
+            .send_secured_message(session_id, &send_buffer[..used], None)
             .is_ok();
         assert!(status);
 
@@ -284,4 +478,131 @@ mod tests_requester {
             .is_ok();
         assert!(status);
     }
+
+    #[test]
+    fn test_case0_init_connection_with_cache() {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+        rsp_config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::CACHE_CAP;
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        assert!(responder.common.get_negotiated_state().is_err());
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+        let negotiated_state = requester.common.get_negotiated_state().unwrap();
+        drop(requester);
+
+        // the responder caches its own state after NEGOTIATE_ALGORITHMS
+        let rsp_negotiated_state = responder.common.cached_negotiated_state.unwrap();
+        assert_eq!(
+            rsp_negotiated_state.message_a.as_ref(),
+            negotiated_state.message_a.as_ref()
+        );
+
+        // both sides reset, the requester goes on with GET_DIGESTS
+        assert!(responder.reset_connection().is_ok());
+        assert_eq!(
+            responder.common.runtime_info.message_a.as_ref(),
+            negotiated_state.message_a.as_ref()
+        );
+
+        let (req_config_info, req_provision_info) = create_info();
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        let mut uncached_state = negotiated_state;
+        uncached_state.negotiate_info.rsp_capabilities_sel -=
+            SpdmResponseCapabilityFlags::CACHE_CAP;
+        assert!(requester
+            .init_connection_with_cache(&uncached_state)
+            .is_err());
+        assert!(requester
+            .init_connection_with_cache(&common::SpdmNegotiatedState::default())
+            .is_err());
+
+        assert!(requester
+            .init_connection_with_cache(&negotiated_state)
+            .is_ok());
+        assert!(requester.send_receive_spdm_digest().is_ok());
+        assert!(requester.send_receive_spdm_certificate(0).is_ok());
+        assert!(requester
+            .send_receive_spdm_challenge(
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone
+            )
+            .is_ok());
+        let session_id = requester
+            .start_session(
+                false,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+        assert!(requester
+            .send_receive_spdm_end_session(
+                session_id,
+                SpdmEndSessionRequestAttributes::PRESERVE_NEGOTIATED_STATE
+            )
+            .is_ok());
+        let cached_state = requester.common.cached_negotiated_state.unwrap();
+        assert_eq!(
+            cached_state.message_a.as_ref(),
+            negotiated_state.message_a.as_ref()
+        );
+        drop(requester);
+        assert!(responder.common.cached_negotiated_state.is_some());
+
+        let (req_config_info, req_provision_info) = create_info();
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+        assert!(requester.init_connection_with_cache(&cached_state).is_ok());
+        let session_id = requester
+            .start_session(
+                true,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester.end_session(session_id).is_ok());
+        assert!(requester.common.cached_negotiated_state.is_none());
+        drop(requester);
+
+        // END_SESSION without PRESERVE_NEGOTIATED_STATE wipes the responder cache
+        assert!(responder.common.cached_negotiated_state.is_none());
+        assert!(responder.reset_connection().is_ok());
+        assert!(responder.common.negotiate_info.base_hash_sel.is_empty());
+    }
 }
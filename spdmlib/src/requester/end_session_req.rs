@@ -6,18 +6,47 @@ use crate::error::SpdmResult;
 use crate::requester::*;
 
 impl<'a> RequesterContext<'a> {
-    pub fn send_receive_spdm_end_session(&mut self, session_id: u32) -> SpdmResult {
+    /// End the session. Its slot is torn down even if the responder does
+    /// not acknowledge it.
+    pub fn send_receive_spdm_end_session(
+        &mut self,
+        session_id: u32,
+        end_session_attributes: SpdmEndSessionRequestAttributes,
+    ) -> SpdmResult {
         info!("send spdm end_session\n");
+        let result = self.exchange_spdm_end_session(session_id, end_session_attributes);
+        if result.is_err() {
+            self.common.cached_negotiated_state = None;
+            if let Some(session) = self.common.get_session_via_id(session_id) {
+                let _ = session.teardown(session_id);
+            }
+        }
+        result
+    }
+
+    fn exchange_spdm_end_session(
+        &mut self,
+        session_id: u32,
+        end_session_attributes: SpdmEndSessionRequestAttributes,
+    ) -> SpdmResult {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
-        let used = self.encode_spdm_end_session(&mut send_buffer);
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+        let used = self.encode_spdm_end_session(&mut send_buffer, end_session_attributes);
+        self.send_secured_message(session_id, &send_buffer[..used], None)?;
 
         let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.receive_secured_message(session_id, &mut receive_buffer)?;
-        self.handle_spdm_end_session_response(session_id, &receive_buffer[..used])
+        self.handle_spdm_end_session_response(
+            session_id,
+            end_session_attributes,
+            &receive_buffer[..used],
+        )
     }
 
-    pub fn encode_spdm_end_session(&mut self, buf: &mut [u8]) -> usize {
+    pub fn encode_spdm_end_session(
+        &mut self,
+        buf: &mut [u8],
+        end_session_attributes: SpdmEndSessionRequestAttributes,
+    ) -> usize {
         let mut writer = Writer::init(buf);
 
         let request = SpdmMessage {
@@ -26,7 +55,7 @@ impl<'a> RequesterContext<'a> {
                 request_response_code: SpdmResponseResponseCode::SpdmRequestEndSession,
             },
             payload: SpdmMessagePayload::SpdmEndSessionRequest(SpdmEndSessionRequestPayload {
-                end_session_request_attributes: SpdmEndSessionRequestAttributes::empty(),
+                end_session_request_attributes: end_session_attributes,
             }),
         };
         request.spdm_encode(&mut self.common, &mut writer);
@@ -36,19 +65,27 @@ impl<'a> RequesterContext<'a> {
     pub fn handle_spdm_end_session_response(
         &mut self,
         session_id: u32,
+        end_session_attributes: SpdmEndSessionRequestAttributes,
         receive_buffer: &[u8],
     ) -> SpdmResult {
         let mut reader = Reader::init(receive_buffer);
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseEndSessionAck => {
-                    let end_session_rsp =
-                        SpdmEndSessionResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let end_session_rsp = SpdmEndSessionResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     if let Some(end_session_rsp) = end_session_rsp {
                         debug!("!!! end_session rsp : {:02x?}\n", end_session_rsp);
 
-                        let session = self.common.get_session_via_id(session_id).unwrap();
+                        let session = self
+                            .common
+                            .get_session_via_id(session_id)
+                            .ok_or(spdm_err!(EINVAL))?;
                         session.teardown(session_id)?;
+                        self.common
+                            .end_session_negotiated_state(end_session_attributes);
 
                         Ok(())
                     } else {
@@ -56,6 +93,10 @@ impl<'a> RequesterContext<'a> {
                         spdm_result_err!(EFAULT)
                     }
                 }
+                SpdmResponseResponseCode::SpdmResponseError => {
+                    error!("!!! end_session : not acknowledged !!!\n");
+                    spdm_result_err!(EIO)
+                }
                 _ => spdm_result_err!(EINVAL),
             },
             None => spdm_result_err!(EIO),
@@ -66,7 +107,6 @@ impl<'a> RequesterContext<'a> {
 #[cfg(test)]
 mod tests_requester {
     use super::*;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
 
@@ -91,7 +131,7 @@ mod tests_requester {
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(session_id).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -115,7 +155,7 @@ mod tests_requester {
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(session_id).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -129,4 +169,61 @@ mod tests_requester {
         let status = requester.end_session(session_id).is_ok();
         assert!(status);
     }
+
+    #[test]
+    fn test_case1_send_receive_spdm_end_session() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        // the responder has no such session and does not answer
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        let session_id = (0xffu32 << 16) + 0xffu32;
+        requester.common.session = Default::default();
+        requester.common.session[0].setup(session_id).unwrap();
+        requester.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.common.session[0]
+            .set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
+        requester.common.cached_negotiated_state =
+            Some(crate::common::SpdmNegotiatedState::default());
+
+        assert!(requester
+            .send_receive_spdm_end_session(
+                session_id,
+                SpdmEndSessionRequestAttributes::PRESERVE_NEGOTIATED_STATE
+            )
+            .is_err());
+        assert!(requester
+            .common
+            .get_immutable_session_via_id(session_id)
+            .is_none());
+        assert!(requester.common.cached_negotiated_state.is_none());
+    }
 }
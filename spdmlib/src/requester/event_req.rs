@@ -0,0 +1,345 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::SpdmResult;
+use crate::requester::*;
+
+/// Called for every event received in a SEND_EVENT message, before EVENT_ACK is sent.
+pub type SpdmEventCallback = fn(session_id: u32, event: &SpdmEventDataStruct);
+
+impl<'a> RequesterContext<'a> {
+    pub fn register_event_callback(&mut self, event_callback: SpdmEventCallback) {
+        self.event_callback = Some(event_callback);
+    }
+
+    pub fn send_receive_spdm_get_supported_event_types(
+        &mut self,
+        session_id: u32,
+    ) -> SpdmResult<SpdmSupportedEventTypesResponsePayload> {
+        info!("send spdm get_supported_event_types\n");
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.encode_spdm_get_supported_event_types(&mut send_buffer);
+        self.send_secured_message(session_id, &send_buffer[..used], None)?;
+
+        let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer)?;
+        self.handle_spdm_supported_event_types_response(&receive_buffer[..used])
+    }
+
+    pub fn encode_spdm_get_supported_event_types(&mut self, buf: &mut [u8]) -> usize {
+        let mut writer = Writer::init(buf);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(
+                SpdmGetSupportedEventTypesRequestPayload {},
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer);
+        writer.used()
+    }
+
+    pub fn handle_spdm_supported_event_types_response(
+        &mut self,
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmSupportedEventTypesResponsePayload> {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => match message_header.request_response_code {
+                SpdmResponseResponseCode::SpdmResponseSupportedEventTypes => {
+                    let supported_event_types = SpdmSupportedEventTypesResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
+                    if let Some(supported_event_types) = supported_event_types {
+                        debug!(
+                            "!!! supported_event_types : {:02x?}\n",
+                            supported_event_types
+                        );
+                        Ok(supported_event_types)
+                    } else {
+                        error!("!!! supported_event_types : fail !!!\n");
+                        spdm_result_err!(EFAULT)
+                    }
+                }
+                _ => spdm_result_err!(EINVAL),
+            },
+            None => spdm_result_err!(EIO),
+        }
+    }
+
+    /// Subscribe to the given DMTF event types.
+    /// An empty set clears all subscriptions of the session.
+    pub fn send_receive_spdm_subscribe_event_types(
+        &mut self,
+        session_id: u32,
+        event_types: SpdmDmtfEventTypeFlags,
+    ) -> SpdmResult {
+        info!("send spdm subscribe_event_types\n");
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.encode_spdm_subscribe_event_types(event_types, &mut send_buffer);
+        self.send_secured_message(session_id, &send_buffer[..used], None)?;
+
+        let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer)?;
+        self.handle_spdm_subscribe_event_types_response(&receive_buffer[..used])
+    }
+
+    pub fn encode_spdm_subscribe_event_types(
+        &mut self,
+        event_types: SpdmDmtfEventTypeFlags,
+        buf: &mut [u8],
+    ) -> usize {
+        let mut writer = Writer::init(buf);
+        let mut payload = SpdmSubscribeEventTypesRequestPayload::default();
+        if !event_types.is_empty() {
+            payload.event_group_count = 1;
+            payload.event_groups[0] =
+                SpdmEventGroupStruct::new_dmtf(SpdmEventGroupAttributes::empty(), event_types);
+        }
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload),
+        };
+        request.spdm_encode(&mut self.common, &mut writer);
+        writer.used()
+    }
+
+    pub fn handle_spdm_subscribe_event_types_response(
+        &mut self,
+        receive_buffer: &[u8],
+    ) -> SpdmResult {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => match message_header.request_response_code {
+                SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck => {
+                    let subscribe_event_types_ack =
+                        SpdmSubscribeEventTypesAckResponsePayload::spdm_read(
+                            &self.common.get_decode_info(),
+                            &mut reader,
+                        );
+                    if let Some(subscribe_event_types_ack) = subscribe_event_types_ack {
+                        debug!(
+                            "!!! subscribe_event_types_ack : {:02x?}\n",
+                            subscribe_event_types_ack
+                        );
+                        Ok(())
+                    } else {
+                        error!("!!! subscribe_event_types_ack : fail !!!\n");
+                        spdm_result_err!(EFAULT)
+                    }
+                }
+                _ => spdm_result_err!(EINVAL),
+            },
+            None => spdm_result_err!(EIO),
+        }
+    }
+
+    /// Wait for one SEND_EVENT from the responder, pass each event to the
+    /// registered callback and acknowledge them with EVENT_ACK.
+    pub fn receive_spdm_event(&mut self, session_id: u32) -> SpdmResult {
+        let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer)?;
+        self.handle_spdm_send_event(session_id, &receive_buffer[..used])?;
+
+        info!("send spdm event_ack\n");
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = self.encode_spdm_event_ack(&mut send_buffer);
+        self.send_secured_message(session_id, &send_buffer[..used], None)
+    }
+
+    pub fn handle_spdm_send_event(&mut self, session_id: u32, receive_buffer: &[u8]) -> SpdmResult {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => match message_header.request_response_code {
+                SpdmResponseResponseCode::SpdmRequestSendEvent => {
+                    let send_event = SpdmSendEventRequestPayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
+                    if let Some(send_event) = send_event {
+                        debug!("!!! send_event : {:02x?}\n", send_event);
+                        if let Some(event_callback) = self.event_callback {
+                            for event in send_event
+                                .events
+                                .iter()
+                                .take(send_event.event_count as usize)
+                            {
+                                event_callback(session_id, event);
+                            }
+                        }
+                        Ok(())
+                    } else {
+                        error!("!!! send_event : fail !!!\n");
+                        spdm_result_err!(EFAULT)
+                    }
+                }
+                _ => spdm_result_err!(EINVAL),
+            },
+            None => spdm_result_err!(EIO),
+        }
+    }
+
+    pub fn encode_spdm_event_ack(&mut self, buf: &mut [u8]) -> usize {
+        let mut writer = Writer::init(buf);
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmResponseEventAck,
+            },
+            payload: SpdmMessagePayload::SpdmEventAckResponse(SpdmEventAckResponsePayload {}),
+        };
+        response.spdm_encode(&mut self.common, &mut writer);
+        writer.used()
+    }
+}
+
+#[cfg(test)]
+mod tests_requester {
+    use super::*;
+    use crate::session::{SpdmSession, SpdmSessionState};
+    use crate::testlib::*;
+    use crate::{crypto, responder};
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    static RECEIVED_EVENT_INSTANCE_ID: AtomicU32 = AtomicU32::new(0);
+
+    fn event_callback(_session_id: u32, event: &SpdmEventDataStruct) {
+        if let Some(SpdmDmtfEventDetail::MeasurementChanged(mask)) = event.get_dmtf_event() {
+            if mask.contains_index(1) {
+                RECEIVED_EVENT_INSTANCE_ID.store(event.event_instance_id, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn setup_session(session: &mut [SpdmSession; 4], session_id: u32) {
+        *session = Default::default();
+        session[0].setup(session_id).unwrap();
+        session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+    }
+
+    #[test]
+    fn test_case0_send_receive_spdm_event() {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+        rsp_config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EVENT_CAP;
+        rsp_config_info.supported_event_types = SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED;
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let session_id = (0x11u32 << 16) + 0x11u32;
+        responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        setup_session(&mut responder.common.session, session_id);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        setup_session(&mut requester.common.session, session_id);
+        requester.register_event_callback(event_callback);
+
+        let supported_event_types = requester
+            .send_receive_spdm_get_supported_event_types(session_id)
+            .unwrap();
+        assert_eq!(supported_event_types.event_group_count, 1);
+        assert_eq!(
+            supported_event_types.event_groups[0]
+                .get_dmtf_event_types()
+                .unwrap(),
+            SpdmDmtfEventTypeFlags::EVENT_LOST | SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED
+        );
+
+        let status = requester
+            .send_receive_spdm_subscribe_event_types(
+                session_id,
+                SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED,
+            )
+            .is_ok();
+        assert!(status);
+
+        let status = requester
+            .send_receive_spdm_subscribe_event_types(
+                session_id,
+                SpdmDmtfEventTypeFlags::CERTIFICATE_CHANGED,
+            )
+            .is_err();
+        assert!(status);
+    }
+
+    #[test]
+    fn test_case1_send_receive_spdm_event() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let session_id = (0x11u32 << 16) + 0x11u32;
+        responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        setup_session(&mut responder.common.session, session_id);
+        responder.common.session[0]
+            .event_info
+            .subscribed_event_types = SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED;
+
+        let mut mask = SpdmMeasurementIndexMask::default();
+        mask.set_index(1);
+        responder.queue_event(&SpdmDmtfEventDetail::MeasurementChanged(mask));
+        assert!(responder.send_pending_events().is_ok());
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        setup_session(&mut requester.common.session, session_id);
+        requester.register_event_callback(event_callback);
+
+        let status = requester.receive_spdm_event(session_id).is_ok();
+        assert!(status);
+        assert_eq!(RECEIVED_EVENT_INSTANCE_ID.load(Ordering::SeqCst), 1);
+    }
+}
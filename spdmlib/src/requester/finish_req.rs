@@ -13,7 +13,7 @@ impl<'a> RequesterContext<'a> {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let (send_used, base_hash_size, message_f) =
             self.encode_spdm_finish(session_id, &mut send_buffer)?;
-        self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
+        self.send_secured_message(session_id, &send_buffer[..send_used], None)?;
 
         let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let receive_used = self.receive_secured_message(session_id, &mut receive_buffer)?;
@@ -101,8 +101,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseFinishRsp => {
-                    let finish_rsp =
-                        SpdmFinishResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let finish_rsp = SpdmFinishResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let receive_used = reader.used();
                     if let Some(finish_rsp) = finish_rsp {
                         debug!("!!! finish rsp : {:02x?}\n", finish_rsp);
@@ -186,7 +188,6 @@ impl<'a> RequesterContext<'a> {
 #[cfg(test)]
 mod tests_requester {
     use super::*;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
 
@@ -225,7 +226,7 @@ mod tests_requester {
 
         responder.common.reset_runtime_info();
 
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(4294901758).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -262,7 +263,7 @@ mod tests_requester {
 
         requester.common.reset_runtime_info();
 
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(4294901758).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
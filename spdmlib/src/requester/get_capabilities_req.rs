@@ -43,8 +43,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseCapabilities => {
-                    let capabilities =
-                        SpdmCapabilitiesResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let capabilities = SpdmCapabilitiesResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(capabilities) = capabilities {
                         debug!("!!! capabilities : {:02x?}\n", capabilities);
@@ -63,8 +63,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseCertificate => {
-                    let certificate =
-                        SpdmCertificateResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let certificate = SpdmCertificateResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(certificate) = certificate {
                         debug!("!!! certificate : {:02x?}\n", certificate);
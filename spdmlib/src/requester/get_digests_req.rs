@@ -39,11 +39,14 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseDigests => {
-                    let digests =
-                        SpdmDigestsResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let digests = SpdmDigestsResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(digests) = digests {
                         debug!("!!! digests : {:02x?}\n", digests);
+                        self.common.peer_info.peer_digests = digests;
 
                         let message_b = &mut self.common.runtime_info.message_b;
                         message_b
@@ -106,5 +109,6 @@ mod tests_requester {
 
         let status = requester.send_receive_spdm_digest().is_ok();
         assert!(status);
+        assert_eq!(requester.common.peer_info.peer_digests.slot_mask, 0x01);
     }
 }
@@ -0,0 +1,275 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::crypto;
+use crate::error::SpdmResult;
+use crate::requester::*;
+
+impl<'a> RequesterContext<'a> {
+    /// Get the device class identifier endpoint info.
+    /// If request_attributes asks for a signature, it is verified against the peer certificate chain.
+    pub fn send_receive_spdm_endpoint_info(
+        &mut self,
+        session_id: Option<u32>,
+        request_attributes: SpdmEndpointInfoRequestAttributes,
+        slot_id: u8,
+    ) -> SpdmResult<SpdmEndpointInfoResponsePayload> {
+        info!("send spdm get_endpoint_info\n");
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let send_used =
+            self.encode_spdm_endpoint_info(request_attributes, slot_id, &mut send_buffer)?;
+        let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = match session_id {
+            None => {
+                self.send_message(&send_buffer[..send_used])?;
+                self.receive_message(&mut receive_buffer)?
+            }
+            Some(session_id) => {
+                self.send_secured_message(session_id, &send_buffer[..send_used], None)?;
+                self.receive_secured_message(session_id, &mut receive_buffer)?
+            }
+        };
+        self.handle_spdm_endpoint_info_response(
+            request_attributes,
+            &send_buffer[..send_used],
+            &receive_buffer[..used],
+        )
+    }
+
+    pub fn encode_spdm_endpoint_info(
+        &mut self,
+        request_attributes: SpdmEndpointInfoRequestAttributes,
+        slot_id: u8,
+        buf: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut writer = Writer::init(buf);
+        let mut nonce = [0u8; SPDM_NONCE_SIZE];
+        crypto::rand::get_random(&mut nonce)?;
+
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestGetEndpointInfo,
+            },
+            payload: SpdmMessagePayload::SpdmGetEndpointInfoRequest(
+                SpdmGetEndpointInfoRequestPayload {
+                    sub_code: SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier,
+                    request_attributes,
+                    slot_id,
+                    nonce: SpdmNonceStruct { data: nonce },
+                },
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer);
+        Ok(writer.used())
+    }
+
+    pub fn handle_spdm_endpoint_info_response(
+        &mut self,
+        request_attributes: SpdmEndpointInfoRequestAttributes,
+        send_buffer: &[u8],
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmEndpointInfoResponsePayload> {
+        let need_signature =
+            request_attributes.contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED);
+        self.common.runtime_info.need_endpoint_info_signature = need_signature;
+
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => match message_header.request_response_code {
+                SpdmResponseResponseCode::SpdmResponseEndpointInfo => {
+                    let endpoint_info = SpdmEndpointInfoResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
+                    let used = reader.used();
+                    if let Some(endpoint_info) = endpoint_info {
+                        debug!("!!! endpoint_info : {:02x?}\n", endpoint_info.as_ref());
+
+                        let message_e = &mut self.common.runtime_info.message_e;
+                        message_e
+                            .append_message(send_buffer)
+                            .map_or_else(|| spdm_result_err!(ENOMEM), |_| Ok(()))?;
+                        if need_signature {
+                            let base_asym_size =
+                                self.common.negotiate_info.base_asym_sel.get_size() as usize;
+                            message_e
+                                .append_message(&receive_buffer[..(used - base_asym_size)])
+                                .map_or_else(|| spdm_result_err!(ENOMEM), |_| Ok(()))?;
+
+                            let result = self
+                                .common
+                                .verify_endpoint_info_signature(&endpoint_info.signature);
+                            self.common.runtime_info.message_e.reset_message();
+                            if result.is_err() {
+                                error!("verify_endpoint_info_signature fail");
+                                return spdm_result_err!(EFAULT);
+                            } else {
+                                info!("verify_endpoint_info_signature pass");
+                            }
+                        } else {
+                            message_e
+                                .append_message(&receive_buffer[..used])
+                                .map_or_else(|| spdm_result_err!(ENOMEM), |_| Ok(()))?;
+                        }
+                        Ok(endpoint_info)
+                    } else {
+                        error!("!!! endpoint_info : fail !!!\n");
+                        spdm_result_err!(EFAULT)
+                    }
+                }
+                _ => spdm_result_err!(EINVAL),
+            },
+            None => spdm_result_err!(EIO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_requester {
+    use super::*;
+    use crate::testlib::*;
+    use crate::{crypto, responder};
+
+    #[test]
+    fn test_case0_send_receive_spdm_endpoint_info() {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        rsp_config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EP_INFO_CAP_SIG;
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        responder.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+        assert!(requester.send_receive_spdm_digest().is_ok());
+        assert!(requester.send_receive_spdm_certificate(0).is_ok());
+
+        let endpoint_info = requester
+            .send_receive_spdm_endpoint_info(
+                None,
+                SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+                0,
+            )
+            .unwrap();
+        assert_eq!(endpoint_info.as_ref(), FAKE_ENDPOINT_INFO);
+
+        let endpoint_info = requester
+            .send_receive_spdm_endpoint_info(None, SpdmEndpointInfoRequestAttributes::empty(), 0)
+            .unwrap();
+        assert_eq!(endpoint_info.as_ref(), FAKE_ENDPOINT_INFO);
+        assert_eq!(endpoint_info.signature.data_size, 0);
+
+        // the unsigned exchange above is covered by the next signature
+        assert!(requester
+            .send_receive_spdm_endpoint_info(
+                None,
+                SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+                0,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_case1_send_receive_spdm_endpoint_info() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        responder.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+
+        // EP_INFO_CAP is not set
+        assert!(requester
+            .send_receive_spdm_endpoint_info(None, SpdmEndpointInfoRequestAttributes::empty(), 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_case2_send_receive_spdm_endpoint_info() {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        rsp_config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EP_INFO_CAP_SIG;
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        responder.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+
+        // no GET_CERTIFICATE before, so no certificate chain to verify the signature
+        assert!(requester
+            .send_receive_spdm_endpoint_info(
+                None,
+                SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+                0,
+            )
+            .is_err());
+    }
+}
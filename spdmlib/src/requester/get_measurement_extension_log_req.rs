@@ -0,0 +1,171 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::SpdmResult;
+use crate::requester::*;
+
+impl<'a> RequesterContext<'a> {
+    /// Read the whole measurement extension log into mel, one portion at a time,
+    /// and return its length.
+    pub fn send_receive_spdm_measurement_extension_log(
+        &mut self,
+        session_id: Option<u32>,
+        mel: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut offset = 0usize;
+        loop {
+            let portion = self.send_receive_spdm_measurement_extension_log_portion(
+                session_id,
+                offset as u32,
+                config::MAX_SPDM_MEL_PORTION_LEN as u32,
+            )?;
+            let portion_length = portion.portion_length as usize;
+            if portion_length == 0 {
+                return spdm_result_err!(EFAULT);
+            }
+            if offset + portion_length > mel.len() {
+                return spdm_result_err!(ENOMEM);
+            }
+            mel[offset..(offset + portion_length)].copy_from_slice(portion.as_ref());
+            offset += portion_length;
+            if portion.remainder_length == 0 {
+                return Ok(offset);
+            }
+        }
+    }
+
+    pub fn send_receive_spdm_measurement_extension_log_portion(
+        &mut self,
+        session_id: Option<u32>,
+        offset: u32,
+        length: u32,
+    ) -> SpdmResult<SpdmMeasurementExtensionLogResponsePayload> {
+        info!("send spdm get_measurement_extension_log\n");
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let send_used =
+            self.encode_spdm_measurement_extension_log(offset, length, &mut send_buffer);
+        let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = match session_id {
+            None => {
+                self.send_message(&send_buffer[..send_used])?;
+                self.receive_message(&mut receive_buffer)?
+            }
+            Some(session_id) => {
+                self.send_secured_message(session_id, &send_buffer[..send_used], None)?;
+                self.receive_secured_message(session_id, &mut receive_buffer)?
+            }
+        };
+        self.handle_spdm_measurement_extension_log_response(length, &receive_buffer[..used])
+    }
+
+    pub fn encode_spdm_measurement_extension_log(
+        &mut self,
+        offset: u32,
+        length: u32,
+        buf: &mut [u8],
+    ) -> usize {
+        let mut writer = Writer::init(buf);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code:
+                    SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog,
+            },
+            payload: SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(
+                SpdmGetMeasurementExtensionLogRequestPayload { offset, length },
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer);
+        writer.used()
+    }
+
+    pub fn handle_spdm_measurement_extension_log_response(
+        &mut self,
+        length: u32,
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmMeasurementExtensionLogResponsePayload> {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => match message_header.request_response_code {
+                SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog => {
+                    let mel = SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
+                    if let Some(mel) = mel {
+                        debug!(
+                            "!!! measurement_extension_log : {:02x?}\n",
+                            mel.portion_length
+                        );
+                        if mel.portion_length > length {
+                            error!("!!! measurement_extension_log : portion too long !!!\n");
+                            return spdm_result_err!(EFAULT);
+                        }
+                        Ok(mel)
+                    } else {
+                        error!("!!! measurement_extension_log : fail !!!\n");
+                        spdm_result_err!(EFAULT)
+                    }
+                }
+                _ => spdm_result_err!(EINVAL),
+            },
+            None => spdm_result_err!(EIO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_requester {
+    use super::*;
+    use crate::testlib::*;
+    use crate::{crypto, responder};
+
+    #[test]
+    fn test_case0_send_receive_spdm_measurement_extension_log() {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        rsp_config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::MEL_CAP;
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        responder.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        assert!(requester.init_connection().is_ok());
+
+        let mut mel = [0u8; FAKE_MEL_SIZE];
+        let used = requester
+            .send_receive_spdm_measurement_extension_log(None, &mut mel)
+            .unwrap();
+        assert_eq!(used, FAKE_MEL_SIZE);
+        for (i, d) in mel.iter().enumerate() {
+            assert_eq!(*d, i as u8);
+        }
+
+        let mut mel = [0u8; FAKE_MEL_SIZE - 1];
+        assert!(requester
+            .send_receive_spdm_measurement_extension_log(None, &mut mel)
+            .is_err());
+    }
+}
@@ -80,8 +80,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseMeasurements => {
-                    let measurements =
-                        SpdmMeasurementsResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let measurements = SpdmMeasurementsResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(measurements) = measurements {
                         debug!("!!! measurements : {:02x?}\n", measurements);
@@ -123,6 +125,9 @@ impl<'a> RequesterContext<'a> {
                                 .map_or_else(|| spdm_result_err!(ENOMEM), |_| Ok(()))?;
                         }
 
+                        self.common.peer_info.peer_measurement_record =
+                            measurements.measurement_record;
+
                         match measurement_operation {
                             SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber => {
                                 Ok(measurements.number_of_measurement)
@@ -282,11 +287,22 @@ mod tests_requester {
             .send_receive_spdm_measurement(measurement_operation, 0)
             .is_ok();
         assert!(status);
+        assert!(
+            requester
+                .common
+                .peer_info
+                .peer_measurement_record
+                .number_of_blocks
+                > 1
+        );
 
         let measurement_operation = SpdmMeasurementOperation::Unknown(5);
         let status = requester
             .send_receive_spdm_measurement(measurement_operation, 0)
             .is_ok();
         assert!(status);
+        let measurement_record = &requester.common.peer_info.peer_measurement_record;
+        assert_eq!(measurement_record.number_of_blocks, 1);
+        assert_eq!(measurement_record.record[0].index, 5);
     }
 }
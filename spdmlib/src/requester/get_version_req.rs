@@ -38,8 +38,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseVersion => {
-                    let version =
-                        SpdmVersionResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let version = SpdmVersionResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(version) = version {
                         debug!("!!! version : {:02x?}\n", version);
@@ -4,13 +4,14 @@
 
 use crate::error::SpdmResult;
 use crate::requester::*;
+use crate::session::SpdmSessionState;
 
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_heartbeat(&mut self, session_id: u32) -> SpdmResult {
         info!("send spdm heartbeat\n");
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.encode_spdm_heartbeat(&mut send_buffer);
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+        self.send_secured_message(session_id, &send_buffer[..used], None)?;
 
         // Receive
         let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
@@ -18,6 +19,42 @@ impl<'a> RequesterContext<'a> {
         self.handle_spdm_heartbeat_response(&receive_buffer[..used])
     }
 
+    /// Return the time a HEARTBEAT is due in the session, None if the session
+    /// is not established or no heartbeat was negotiated.
+    pub fn get_heartbeat_deadline(&mut self, session_id: u32) -> Option<u64> {
+        let session = self.common.get_session_via_id(session_id)?;
+        let heartbeat_info = session.heartbeat_info;
+        if session.get_session_state() != SpdmSessionState::SpdmSessionEstablished
+            || heartbeat_info.heartbeat_period == 0
+        {
+            return None;
+        }
+        Some(heartbeat_info.last_message_time + 1000 * heartbeat_info.heartbeat_period as u64)
+    }
+
+    /// Send HEARTBEAT in every session with nothing sent for its heartbeat
+    /// period. The caller drives it, e.g. from its event loop, at least
+    /// once per heartbeat period. Nothing is sent without a time source.
+    pub fn poll(&mut self) -> SpdmResult {
+        let now = match self.common.get_time_ms() {
+            Some(now) => now,
+            None => return Ok(()),
+        };
+        for index in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session_id = self.common.session[index].get_session_id();
+            if session_id == 0 {
+                continue;
+            }
+            match self.get_heartbeat_deadline(session_id) {
+                Some(deadline) if now >= deadline => {
+                    self.send_receive_spdm_heartbeat(session_id)?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     pub fn encode_spdm_heartbeat(&mut self, buf: &mut [u8]) -> usize {
         let mut writer = Writer::init(buf);
         let request = SpdmMessage {
@@ -36,8 +73,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseHeartbeatAck => {
-                    let heartbeat_rsp =
-                        SpdmHeartbeatResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let heartbeat_rsp = SpdmHeartbeatResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     if let Some(heartbeat_rsp) = heartbeat_rsp {
                         debug!("!!! heartbeat rsp : {:02x?}\n", heartbeat_rsp);
                         Ok(())
@@ -56,7 +95,6 @@ impl<'a> RequesterContext<'a> {
 #[cfg(test)]
 mod tests_requester {
     use super::*;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
 
@@ -81,7 +119,7 @@ mod tests_requester {
         let rsp_session_id = 0x11u16;
         let session_id = (0x11u32 << 16) + rsp_session_id as u32;
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(session_id).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -105,7 +143,7 @@ mod tests_requester {
         let rsp_session_id = 0x11u16;
         let session_id = (0x11u32 << 16) + rsp_session_id as u32;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(session_id).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -119,4 +157,72 @@ mod tests_requester {
         let status = requester.send_receive_spdm_heartbeat(session_id).is_ok();
         assert!(status);
     }
+
+    #[test]
+    fn test_case0_poll() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let session_id = (0x11u32 << 16) + 0x11u32;
+        responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        responder.common.session = Default::default();
+        responder.common.session[0].setup(session_id).unwrap();
+        responder.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+        let now = std::cell::Cell::new(0u64);
+        let mut time_source = FakeSpdmTimeSource { now: &now };
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+        requester.common.register_time_source(&mut time_source);
+
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        requester.common.session = Default::default();
+        requester.common.session[0].setup(session_id).unwrap();
+        requester.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        assert_eq!(requester.get_heartbeat_deadline(session_id), None);
+
+        requester.common.session[0].heartbeat_info.heartbeat_period = 2;
+        requester.common.record_session_activity(session_id);
+        assert_eq!(requester.get_heartbeat_deadline(session_id), Some(2000));
+
+        now.set(1999);
+        assert!(requester.poll().is_ok());
+        assert_eq!(requester.get_heartbeat_deadline(session_id), Some(2000));
+
+        now.set(2000);
+        assert!(requester.poll().is_ok());
+        assert_eq!(requester.get_heartbeat_deadline(session_id), Some(4000));
+    }
 }
@@ -11,6 +11,7 @@ use crate::requester::*;
 use crate::common::ManagedBuffer;
 
 use crate::crypto;
+use crate::secret::SpdmSecret;
 
 const INITIAL_SESSION_ID: u16 = 0xFFFE;
 
@@ -59,12 +60,7 @@ impl<'a> RequesterContext<'a> {
                 .ok_or(spdm_err!(EFAULT))?;
 
         debug!("!!! exchange data : {:02x?}\n", exchange);
-        let mut opaque = SpdmOpaqueStruct {
-            data_size: crate::common::OPAQUE_DATA_SUPPORT_VERSION.len() as u16,
-            ..Default::default()
-        };
-        opaque.data[..(opaque.data_size as usize)]
-            .copy_from_slice(crate::common::OPAQUE_DATA_SUPPORT_VERSION.as_ref());
+        let opaque = self.common.encode_supported_version_opaque()?;
         let request = SpdmMessage {
             header: SpdmMessageHeader {
                 version: SpdmVersion::SpdmVersion11,
@@ -74,6 +70,7 @@ impl<'a> RequesterContext<'a> {
                 slot_id,
                 measurement_summary_hash_type,
                 req_session_id,
+                session_policy: self.common.config_info.session_policy,
                 random: SpdmRandomStruct { data: random },
                 exchange,
                 opaque,
@@ -104,21 +101,34 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseKeyExchangeRsp => {
-                    let key_exchange_rsp =
-                        SpdmKeyExchangeResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let key_exchange_rsp = SpdmKeyExchangeResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let receive_used = reader.used();
                     if let Some(key_exchange_rsp) = key_exchange_rsp {
                         debug!("!!! key_exchange rsp : {:02x?}\n", key_exchange_rsp);
+
+                        let peer_opaque_data =
+                            SpdmGeneralOpaqueData::from_opaque(&key_exchange_rsp.opaque)
+                                .ok_or(spdm_err!(EFAULT))?;
+                        let secured_message_version = self
+                            .common
+                            .check_version_selection(&peer_opaque_data)
+                            .ok_or(spdm_err!(EFAULT))?;
+                        let peer_opaque_data = peer_opaque_data.get_app_elements();
                         debug!(
                             "!!! exchange data (peer) : {:02x?}\n",
                             &key_exchange_rsp.exchange
                         );
 
-                        let final_key = key_exchange_context
-                            .compute_final_key(&key_exchange_rsp.exchange)
-                            .ok_or(spdm_err!(EFAULT))?;
+                        let final_key = SpdmSecret::new(
+                            key_exchange_context
+                                .compute_final_key(&key_exchange_rsp.exchange)
+                                .ok_or(spdm_err!(EFAULT))?,
+                        );
 
-                        debug!("!!! final_key : {:02x?}\n", final_key.as_ref());
+                        debug_secret!("!!! final_key : {:02x?}\n", final_key.as_ref());
 
                         // verify signature
                         let base_asym_size =
@@ -163,7 +173,9 @@ impl<'a> RequesterContext<'a> {
                         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
                         let sequence_number_count =
                             self.common.transport_encap.get_sequence_number_count();
-                        let max_random_count = self.common.transport_encap.get_max_random_count();
+                        let max_random_count = self.common.get_max_random_count();
+                        let rekey_policy = self.common.config_info.rekey_policy;
+                        let session_policy = self.common.config_info.session_policy;
 
                         let session_id = ((INITIAL_SESSION_ID as u32) << 16)
                             + key_exchange_rsp.rsp_session_id as u32;
@@ -174,6 +186,11 @@ impl<'a> RequesterContext<'a> {
 
                         session.setup(session_id).unwrap();
                         session.set_use_psk(false);
+                        session.session_policy = session_policy;
+                        session.heartbeat_info.heartbeat_period = key_exchange_rsp.heartbeat_period;
+                        session.rekey_info.policy = rekey_policy;
+                        session.set_secured_message_version(secured_message_version);
+                        session.set_peer_opaque_data(&peer_opaque_data);
 
                         session.set_crypto_param(
                             base_hash_algo,
@@ -214,6 +231,8 @@ impl<'a> RequesterContext<'a> {
                         session.set_session_state(
                             crate::session::SpdmSessionState::SpdmSessionHandshaking,
                         );
+                        self.common.record_session_activity(session_id);
+                        self.common.reset_session_rekey_info(session_id);
 
                         Ok(session_id)
                     } else {
@@ -237,7 +256,8 @@ mod tests_requester {
     #[test]
     fn test_case0_send_receive_spdm_key_exchange() {
         let (rsp_config_info, rsp_provision_info) = create_info();
-        let (req_config_info, req_provision_info) = create_info();
+        let (mut req_config_info, req_provision_info) = create_info();
+        req_config_info.session_policy = SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE;
 
         let shared_buffer = SharedBuffer::new();
         let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
@@ -300,9 +320,123 @@ mod tests_requester {
 
         let measurement_summary_hash_type =
             SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll;
-        let status = requester
+        let session_id = requester
             .send_receive_spdm_key_exchange(0, measurement_summary_hash_type)
-            .is_ok();
-        assert!(status);
+            .unwrap();
+        let session = requester.common.get_session_via_id(session_id).unwrap();
+        assert_eq!(
+            session.session_policy,
+            SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE
+        );
+    }
+
+    fn key_exchange_with_secured_message_version(
+        req_version: [SpdmSecuredMessageVersion; SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT],
+        rsp_version: [SpdmSecuredMessageVersion; SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT],
+        rsp_opaque_elements: SpdmGeneralOpaqueData,
+    ) -> SpdmResult<(SpdmSecuredMessageVersion, SpdmGeneralOpaqueData)> {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        let (mut req_config_info, req_provision_info) = create_info();
+        rsp_config_info.secured_message_version = rsp_version;
+        rsp_config_info.opaque_elements = rsp_opaque_elements;
+        req_config_info.secured_message_version = req_version;
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        requester.init_connection()?;
+        requester.send_receive_spdm_digest()?;
+        requester.send_receive_spdm_certificate(0)?;
+
+        let session_id = requester.send_receive_spdm_key_exchange(
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )?;
+        let session = requester.common.get_session_via_id(session_id).unwrap();
+        Ok((
+            session.get_secured_message_version(),
+            *session.get_peer_opaque_data(),
+        ))
+    }
+
+    #[test]
+    fn test_case1_send_receive_spdm_key_exchange() {
+        let vendor_element = SpdmOpaqueElement::new(
+            SpdmSvhStruct {
+                id: SpdmStandardId::SpdmStandardIdPCISIG,
+                vendor_id_len: 2,
+                vendor_id: [0x86, 0x80, 0, 0],
+            },
+            &[0xAA, 0x55],
+        )
+        .unwrap();
+        let mut rsp_opaque_elements = SpdmGeneralOpaqueData::default();
+        rsp_opaque_elements.add_element(&vendor_element).unwrap();
+
+        // the highest common version is selected, app elements are passed through
+        let (version, peer_opaque_data) = key_exchange_with_secured_message_version(
+            [
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+            ],
+            [
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+                SpdmSecuredMessageVersion::default(),
+            ],
+            rsp_opaque_elements,
+        )
+        .unwrap();
+        assert_eq!(
+            version,
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10
+        );
+        assert_eq!(peer_opaque_data.element_count, 1);
+        assert_eq!(peer_opaque_data.elements[0].as_ref(), &[0xAA, 0x55]);
+
+        // a default config offers 1.1 and 1.0
+        let (version, _) = key_exchange_with_secured_message_version(
+            crate::common::SpdmConfigInfo::default().secured_message_version,
+            crate::common::SpdmConfigInfo::default().secured_message_version,
+            SpdmGeneralOpaqueData::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            version,
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11
+        );
+
+        // no common version
+        assert!(key_exchange_with_secured_message_version(
+            [
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                SpdmSecuredMessageVersion::default(),
+            ],
+            [
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+                SpdmSecuredMessageVersion::default(),
+            ],
+            SpdmGeneralOpaqueData::default(),
+        )
+        .is_err());
     }
 }
@@ -15,23 +15,38 @@ impl<'a> RequesterContext<'a> {
         info!("send spdm key_update\n");
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.encode_spdm_key_update_op(key_update_operation, tag, &mut send_buffer);
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+        self.send_secured_message(session_id, &send_buffer[..used], None)?;
 
-        // update key
-        let session = self.common.get_session_via_id(session_id).unwrap();
+        // the request key is used for the next request, and for UpdateAllKeys
+        // the response key already protects KEY_UPDATE_ACK
+        let session = self
+            .common
+            .get_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?;
+        session.key_update_info.last_request = Some((key_update_operation, tag));
         let update_requester = key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateSingleKey
             || key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
         let update_responder = key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
         session.create_data_secret_update(update_requester, update_responder)?;
+
         let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
-        let used = self.receive_secured_message(session_id, &mut receive_buffer)?;
+        let result = self
+            .receive_secured_message(session_id, &mut receive_buffer)
+            .and_then(|used| {
+                self.handle_spdm_key_update_op_response(
+                    key_update_operation,
+                    tag,
+                    &receive_buffer[..used],
+                )
+            });
 
-        self.handle_spdm_key_update_op_response(
-            session_id,
-            update_requester,
-            update_responder,
-            &receive_buffer[..used],
-        )
+        // keep the new keys once acknowledged, otherwise restore the old ones
+        let session = self
+            .common
+            .get_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?;
+        session.activate_data_secret_update(update_requester, update_responder, result.is_ok())?;
+        result
     }
 
     pub fn encode_spdm_key_update_op(
@@ -57,34 +72,30 @@ impl<'a> RequesterContext<'a> {
 
     pub fn handle_spdm_key_update_op_response(
         &mut self,
-        session_id: u32,
-        update_requester: bool,
-        update_responder: bool,
+        key_update_operation: SpdmKeyUpdateOperation,
+        tag: u8,
         receive_buffer: &[u8],
     ) -> SpdmResult {
         let mut reader = Reader::init(receive_buffer);
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseKeyUpdateAck => {
-                    let key_update_rsp =
-                        SpdmKeyUpdateResponsePayload::spdm_read(&mut self.common, &mut reader);
-                    let session = self.common.get_session_via_id(session_id).unwrap();
-                    if let Some(key_update_rsp) = key_update_rsp {
-                        debug!("!!! key_update rsp : {:02x?}\n", key_update_rsp);
-                        session.activate_data_secret_update(
-                            update_requester,
-                            update_responder,
-                            true,
-                        )?;
-                        Ok(())
-                    } else {
-                        error!("!!! key_update : fail !!!\n");
-                        session.activate_data_secret_update(
-                            update_requester,
-                            update_responder,
-                            false,
-                        )?;
-                        spdm_result_err!(EFAULT)
+                    let key_update_rsp = SpdmKeyUpdateResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
+                    match key_update_rsp {
+                        Some(key_update_rsp)
+                            if key_update_rsp.key_update_operation == key_update_operation
+                                && key_update_rsp.tag == tag =>
+                        {
+                            debug!("!!! key_update rsp : {:02x?}\n", key_update_rsp);
+                            Ok(())
+                        }
+                        _ => {
+                            error!("!!! key_update : fail !!!\n");
+                            spdm_result_err!(EFAULT)
+                        }
                     }
                 }
                 _ => spdm_result_err!(EINVAL),
@@ -103,19 +114,68 @@ impl<'a> RequesterContext<'a> {
         {
             return spdm_result_err!(EINVAL);
         }
-        self.send_receive_spdm_key_update_op(session_id, key_update_operation, 1)?;
+        let tag = self.get_next_key_update_tag(session_id)?;
+        self.send_receive_spdm_key_update_op(session_id, key_update_operation, tag)?;
+        let tag = self.get_next_key_update_tag(session_id)?;
         self.send_receive_spdm_key_update_op(
             session_id,
             SpdmKeyUpdateOperation::SpdmVerifyNewKey,
-            2,
-        )
+            tag,
+        )?;
+        self.common.reset_session_rekey_info(session_id);
+        Ok(())
+    }
+
+    // A new tag for each request, so that the responder tells a new request
+    // from a retried one.
+    fn get_next_key_update_tag(&mut self, session_id: u32) -> SpdmResult<u8> {
+        let session = self
+            .common
+            .get_session_via_id(session_id)
+            .ok_or(spdm_err!(EINVAL))?;
+        Ok(match session.key_update_info.last_request {
+            Some((_, tag)) => tag.wrapping_add(1),
+            None => 1,
+        })
+    }
+
+    /// Update all keys of the session before send_buffer is sent, if the
+    /// rekey policy of the session asks for it. KEY_UPDATE is sent as is.
+    pub(crate) fn update_keys_if_due(
+        &mut self,
+        session_id: u32,
+        send_buffer: &[u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult {
+        if app_message_type.is_none() {
+            let mut reader = Reader::init(send_buffer);
+            if let Some(message_header) = SpdmMessageHeader::read(&mut reader) {
+                if message_header.request_response_code
+                    == SpdmResponseResponseCode::SpdmRequestKeyUpdate
+                {
+                    return Ok(());
+                }
+            }
+        }
+        let now = self.common.get_time_ms();
+        let rekey_due = self
+            .common
+            .get_session_via_id(session_id)
+            .map_or(false, |session| session.is_rekey_due(now));
+        if rekey_due {
+            info!("rekey session {:#x}\n", session_id);
+            self.send_receive_spdm_key_update(
+                session_id,
+                SpdmKeyUpdateOperation::SpdmUpdateAllKeys,
+            )?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests_requester {
     use super::*;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
 
@@ -140,7 +200,7 @@ mod tests_requester {
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        responder.common.session = [SpdmSession::new(); 4];
+        responder.common.session = Default::default();
         responder.common.session[0].setup(session_id).unwrap();
         responder.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -164,7 +224,7 @@ mod tests_requester {
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        requester.common.session = [SpdmSession::new(); 4];
+        requester.common.session = Default::default();
         requester.common.session[0].setup(session_id).unwrap();
         requester.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -187,4 +247,159 @@ mod tests_requester {
             .is_err();
         assert!(status);
     }
+    #[test]
+    fn test_case1_send_receive_spdm_key_update() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        // the responder has no session and answers with an ERROR
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        let session_id = (0xffu32 << 16) + 0xFFFEu32;
+        requester.common.session = Default::default();
+        requester.common.session[0].setup(session_id).unwrap();
+        requester.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.common.session[0]
+            .set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
+        let (request_key, response_key) = requester.common.session[0].export_keys();
+
+        let status = requester
+            .send_receive_spdm_key_update(session_id, SpdmKeyUpdateOperation::SpdmUpdateAllKeys)
+            .is_err();
+        assert!(status);
+
+        // the keys are rolled back
+        let (rollback_request_key, rollback_response_key) =
+            requester.common.session[0].export_keys();
+        assert_eq!(
+            rollback_request_key.encryption_key.data,
+            request_key.encryption_key.data
+        );
+        assert_eq!(
+            rollback_response_key.encryption_key.data,
+            response_key.encryption_key.data
+        );
+        assert!(
+            !requester.common.session[0]
+                .key_update_info
+                .pending_requester
+        );
+        assert!(
+            !requester.common.session[0]
+                .key_update_info
+                .pending_responder
+        );
+
+        // an acknowledgement of another request
+        let ack = [0x11u8, 0x69, 0x02, 0x01];
+        let status = requester
+            .handle_spdm_key_update_op_response(SpdmKeyUpdateOperation::SpdmUpdateAllKeys, 1, &ack)
+            .is_ok();
+        assert!(status);
+        let status = requester
+            .handle_spdm_key_update_op_response(SpdmKeyUpdateOperation::SpdmUpdateAllKeys, 2, &ack)
+            .is_err();
+        assert!(status);
+        let status = requester
+            .handle_spdm_key_update_op_response(
+                SpdmKeyUpdateOperation::SpdmUpdateSingleKey,
+                1,
+                &ack,
+            )
+            .is_err();
+        assert!(status);
+    }
+
+    #[test]
+    fn test_case0_update_keys_if_due() {
+        let (rsp_config_info, rsp_provision_info) = create_info();
+        let (req_config_info, req_provision_info) = create_info();
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+
+        let session_id = (0xffu32 << 16) + 0xFFFEu32;
+        responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        responder.common.session = Default::default();
+        responder.common.session[0].setup(session_id).unwrap();
+        responder.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        responder.common.session[0]
+            .set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        requester.common.session = Default::default();
+        requester.common.session[0].setup(session_id).unwrap();
+        requester.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.common.session[0]
+            .set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
+        requester.common.session[0]
+            .rekey_info
+            .policy
+            .max_message_count = 2;
+
+        // the HEARTBEAT request and response
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+        assert_eq!(requester.common.session[0].rekey_info.message_count, 2);
+
+        // the keys are updated before the next request
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+        assert_eq!(requester.common.session[0].rekey_info.message_count, 2);
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    }
 }
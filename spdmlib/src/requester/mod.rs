@@ -6,10 +6,13 @@ mod context;
 
 mod challenge_req;
 mod end_session_req;
+mod event_req;
 mod finish_req;
 mod get_capabilities_req;
 mod get_certificate_req;
 mod get_digests_req;
+mod get_endpoint_info_req;
+mod get_measurement_extension_log_req;
 mod get_measurements_req;
 mod get_version_req;
 mod heartbeat_req;
@@ -20,6 +23,7 @@ mod psk_exchange_req;
 mod psk_finish_req;
 
 pub use context::RequesterContext;
+pub use event_req::SpdmEventCallback;
 
 use crate::config;
 use crate::msgs::*;
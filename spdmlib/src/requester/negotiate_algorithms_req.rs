@@ -75,8 +75,10 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponseAlgorithms => {
-                    let algorithms =
-                        SpdmAlgorithmsResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let algorithms = SpdmAlgorithmsResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let used = reader.used();
                     if let Some(algorithms) = algorithms {
                         debug!("!!! algorithms : {:02x?}\n", algorithms);
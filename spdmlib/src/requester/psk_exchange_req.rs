@@ -47,12 +47,7 @@ impl<'a> RequesterContext<'a> {
         let mut psk_context = [0u8; MAX_SPDM_PSK_CONTEXT_SIZE];
         crypto::rand::get_random(&mut psk_context)?;
 
-        let mut opaque = SpdmOpaqueStruct {
-            data_size: crate::common::OPAQUE_DATA_SUPPORT_VERSION.len() as u16,
-            ..Default::default()
-        };
-        opaque.data[..(opaque.data_size as usize)]
-            .copy_from_slice(crate::common::OPAQUE_DATA_SUPPORT_VERSION.as_ref());
+        let opaque = self.common.encode_supported_version_opaque()?;
         let request = SpdmMessage {
             header: SpdmMessageHeader {
                 version: SpdmVersion::SpdmVersion11,
@@ -60,8 +55,9 @@ impl<'a> RequesterContext<'a> {
             },
             payload: SpdmMessagePayload::SpdmPskExchangeRequest(SpdmPskExchangeRequestPayload {
                 measurement_summary_hash_type,
+                session_policy: self.common.config_info.session_policy,
                 req_session_id,
-                psk_hint: SpdmPskHintStruct::default(),
+                psk_hint: self.common.provision_info.psk_hint.unwrap_or_default(),
                 psk_context: SpdmPskContextStruct {
                     data_size: self.common.negotiate_info.base_hash_sel.get_size(),
                     data: psk_context,
@@ -93,12 +89,23 @@ impl<'a> RequesterContext<'a> {
         match SpdmMessageHeader::read(&mut reader) {
             Some(message_header) => match message_header.request_response_code {
                 SpdmResponseResponseCode::SpdmResponsePskExchangeRsp => {
-                    let psk_exchange_rsp =
-                        SpdmPskExchangeResponsePayload::spdm_read(&mut self.common, &mut reader);
+                    let psk_exchange_rsp = SpdmPskExchangeResponsePayload::spdm_read(
+                        &self.common.get_decode_info(),
+                        &mut reader,
+                    );
                     let receive_used = reader.used();
                     if let Some(psk_exchange_rsp) = psk_exchange_rsp {
                         debug!("!!! psk_exchange rsp : {:02x?}\n", psk_exchange_rsp);
 
+                        let peer_opaque_data =
+                            SpdmGeneralOpaqueData::from_opaque(&psk_exchange_rsp.opaque)
+                                .ok_or(spdm_err!(EFAULT))?;
+                        let secured_message_version = self
+                            .common
+                            .check_version_selection(&peer_opaque_data)
+                            .ok_or(spdm_err!(EFAULT))?;
+                        let peer_opaque_data = peer_opaque_data.get_app_elements();
+
                         let base_hash_size =
                             self.common.negotiate_info.base_hash_sel.get_size() as usize;
 
@@ -122,10 +129,13 @@ impl<'a> RequesterContext<'a> {
                         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
                         let sequence_number_count =
                             self.common.transport_encap.get_sequence_number_count();
-                        let max_random_count = self.common.transport_encap.get_max_random_count();
+                        let max_random_count = self.common.get_max_random_count();
+                        let rekey_policy = self.common.config_info.rekey_policy;
+                        let session_policy = self.common.config_info.session_policy;
 
                         let session_id = ((INITIAL_SESSION_ID as u32) << 16)
                             + psk_exchange_rsp.rsp_session_id as u32;
+                        let psk_key = self.common.get_psk();
                         let session = self
                             .common
                             .get_next_avaiable_session()
@@ -133,12 +143,11 @@ impl<'a> RequesterContext<'a> {
 
                         session.setup(session_id).unwrap();
                         session.set_use_psk(true);
-                        let mut psk_key = SpdmDheFinalKeyStruct {
-                            data_size: b"TestPskData\0".len() as u16,
-                            ..Default::default()
-                        };
-                        psk_key.data[0..(psk_key.data_size as usize)]
-                            .copy_from_slice(b"TestPskData\0");
+                        session.session_policy = session_policy;
+                        session.heartbeat_info.heartbeat_period = psk_exchange_rsp.heartbeat_period;
+                        session.rekey_info.policy = rekey_policy;
+                        session.set_secured_message_version(secured_message_version);
+                        session.set_peer_opaque_data(&peer_opaque_data);
                         session.set_crypto_param(
                             base_hash_algo,
                             dhe_algo,
@@ -178,6 +187,8 @@ impl<'a> RequesterContext<'a> {
                         session.set_session_state(
                             crate::session::SpdmSessionState::SpdmSessionHandshaking,
                         );
+                        self.common.record_session_activity(session_id);
+                        self.common.reset_session_rekey_info(session_id);
 
                         Ok(session_id)
                     } else {
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use crate::crypto;
+use crate::error::SpdmResult;
 use crate::responder::*;
 
 impl<'a> ResponderContext<'a> {
@@ -17,8 +18,10 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let negotiate_algorithms =
-            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let negotiate_algorithms = SpdmNegotiateAlgorithmsRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
         if let Some(negotiate_algorithms) = negotiate_algorithms {
             debug!("!!! negotiate_algorithms : {:02x?}\n", negotiate_algorithms);
             self.common.negotiate_info.measurement_specification_sel =
@@ -88,37 +91,8 @@ impl<'a> ResponderContext<'a> {
             .key_schedule_sel
             .prioritize(self.common.config_info.key_schedule_algo);
 
-        //
-        // update cert chain - append root cert hash
-        //
-        if self.common.provision_info.my_cert_chain.is_none()
-            && self.common.provision_info.my_cert_chain_data.is_some()
-        {
-            let cert_chain = self.common.provision_info.my_cert_chain_data.unwrap();
-            let (root_cert_begin, root_cert_end) =
-                crypto::cert_operation::get_cert_from_cert_chain(
-                    &cert_chain.data[..(cert_chain.data_size as usize)],
-                    0,
-                )
-                .unwrap();
-            let root_cert = &cert_chain.data[root_cert_begin..root_cert_end];
-            if let Some(root_hash) =
-                crypto::hash::hash_all(self.common.negotiate_info.base_hash_sel, root_cert)
-            {
-                let data_size = 4 + root_hash.data_size + cert_chain.data_size;
-                let mut data = [0u8; config::MAX_SPDM_CERT_CHAIN_DATA_SIZE];
-                data[0] = (data_size & 0xFF) as u8;
-                data[1] = (data_size >> 8) as u8;
-                data[4..(4 + root_hash.data_size as usize)]
-                    .copy_from_slice(&root_hash.data[..(root_hash.data_size as usize)]);
-                data[(4 + root_hash.data_size as usize)..(data_size as usize)]
-                    .copy_from_slice(&cert_chain.data[..(cert_chain.data_size as usize)]);
-                self.common.provision_info.my_cert_chain =
-                    Some(SpdmCertChainData { data_size, data });
-                debug!("my_cert_chain - {:02x?}\n", &data[..(data_size as usize)]);
-            } else {
-                return;
-            }
+        if self.update_my_cert_chain().is_err() {
+            return;
         }
 
         info!("send spdm algorithm\n");
@@ -174,6 +148,44 @@ impl<'a> ResponderContext<'a> {
             .runtime_info
             .message_a
             .append_message(writer.used_slice());
+
+        // the responder keeps its own negotiated state for a reset
+        self.common.cached_negotiated_state = self.common.get_negotiated_state().ok();
+    }
+
+    /// Build my_cert_chain of the negotiated hash algorithm, i.e. the cert
+    /// chain data with the length and the root cert hash in front of it.
+    pub(crate) fn update_my_cert_chain(&mut self) -> SpdmResult {
+        if self.common.provision_info.my_cert_chain.is_none()
+            && self.common.provision_info.my_cert_chain_data.is_some()
+        {
+            let cert_chain = self.common.provision_info.my_cert_chain_data.unwrap();
+            let (root_cert_begin, root_cert_end) =
+                crypto::cert_operation::get_cert_from_cert_chain(
+                    &cert_chain.data[..(cert_chain.data_size as usize)],
+                    0,
+                )
+                .unwrap();
+            let root_cert = &cert_chain.data[root_cert_begin..root_cert_end];
+            if let Some(root_hash) =
+                crypto::hash::hash_all(self.common.negotiate_info.base_hash_sel, root_cert)
+            {
+                let data_size = 4 + root_hash.data_size + cert_chain.data_size;
+                let mut data = [0u8; config::MAX_SPDM_CERT_CHAIN_DATA_SIZE];
+                data[0] = (data_size & 0xFF) as u8;
+                data[1] = (data_size >> 8) as u8;
+                data[4..(4 + root_hash.data_size as usize)]
+                    .copy_from_slice(&root_hash.data[..(root_hash.data_size as usize)]);
+                data[(4 + root_hash.data_size as usize)..(data_size as usize)]
+                    .copy_from_slice(&cert_chain.data[..(cert_chain.data_size as usize)]);
+                self.common.provision_info.my_cert_chain =
+                    Some(SpdmCertChainData { data_size, data });
+                debug!("my_cert_chain - {:02x?}\n", &data[..(data_size as usize)]);
+            } else {
+                return spdm_result_err!(EFAULT);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -248,9 +260,11 @@ mod tests_responder {
 
         let u8_slice = &u8_slice[2..];
         let mut reader = Reader::init(u8_slice);
-        let spdm_sturct_data =
-            SpdmNegotiateAlgorithmsRequestPayload::spdm_read(&mut context.common, &mut reader)
-                .unwrap();
+        let spdm_sturct_data = SpdmNegotiateAlgorithmsRequestPayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(
             spdm_sturct_data.measurement_specification,
             SpdmMeasurementSpecification::DMTF
@@ -280,7 +294,7 @@ mod tests_responder {
         let u8_slice = &u8_slice[46..];
         let mut reader = Reader::init(u8_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
 
         assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion11);
         assert_eq!(
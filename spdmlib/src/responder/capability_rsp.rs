@@ -16,8 +16,10 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let get_capabilities =
-            SpdmGetCapabilitiesRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let get_capabilities = SpdmGetCapabilitiesRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
         if let Some(get_capabilities) = get_capabilities {
             debug!("!!! get_capabilities : {:02x?}\n", get_capabilities);
             self.common.negotiate_info.req_ct_exponent_sel = get_capabilities.ct_exponent;
@@ -130,8 +132,11 @@ mod tests_responder {
         );
         let capabilities_slice = &u8_slice[2..];
         let mut reader = Reader::init(capabilities_slice);
-        let capabilities_request =
-            SpdmGetCapabilitiesRequestPayload::spdm_read(&mut context.common, &mut reader).unwrap();
+        let capabilities_request = SpdmGetCapabilitiesRequestPayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(capabilities_request.ct_exponent, 100);
         assert_eq!(
             capabilities_request.flags,
@@ -140,7 +145,7 @@ mod tests_responder {
         let spdm_message_slice = &u8_slice[12..];
         let mut reader = Reader::init(spdm_message_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion11);
         assert_eq!(
             spdm_message.header.request_response_code,
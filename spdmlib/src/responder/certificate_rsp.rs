@@ -16,8 +16,10 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let get_certificate =
-            SpdmGetCertificateRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let get_certificate = SpdmGetCertificateRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
         if let Some(get_certificate) = get_certificate {
             debug!("!!! get_certificate : {:02x?}\n", get_certificate);
         } else {
@@ -148,8 +150,11 @@ mod tests_responder {
 
         let spdm_struct_slice = &u8_slice[2..];
         let mut reader = Reader::init(spdm_struct_slice);
-        let spdm_get_certificate_request_payload =
-            SpdmGetCertificateRequestPayload::spdm_read(&mut context.common, &mut reader).unwrap();
+        let spdm_get_certificate_request_payload = SpdmGetCertificateRequestPayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(spdm_get_certificate_request_payload.slot_id, 100);
         assert_eq!(spdm_get_certificate_request_payload.offset, 100);
         assert_eq!(spdm_get_certificate_request_payload.length, 600);
@@ -157,7 +162,7 @@ mod tests_responder {
         let spdm_message_slice = &u8_slice[8..];
         let mut reader = Reader::init(spdm_message_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion11);
         assert_eq!(
             spdm_message.header.request_response_code,
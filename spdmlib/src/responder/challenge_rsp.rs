@@ -17,7 +17,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let challenge = SpdmChallengeRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let challenge =
+            SpdmChallengeRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(challenge) = challenge {
             debug!("!!! challenge : {:02x?}\n", challenge);
 
@@ -181,7 +182,8 @@ mod tests_responder {
         let spdm_struct_slice = &u8_slice[2..];
         let mut reader = Reader::init(spdm_struct_slice);
         let spdm_challenge_request_payload =
-            SpdmChallengeRequestPayload::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmChallengeRequestPayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap();
         assert_eq!(spdm_challenge_request_payload.slot_id, 100);
         assert_eq!(
             spdm_challenge_request_payload.measurement_summary_hash_type,
@@ -194,7 +196,7 @@ mod tests_responder {
         let spdm_message_slice = &u8_slice[36..];
         let mut reader = Reader::init(spdm_message_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
         assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion11);
         assert_eq!(
             spdm_message.header.request_response_code,
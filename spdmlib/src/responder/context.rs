@@ -6,19 +6,53 @@ use crate::common::{self, SpdmDeviceIo, SpdmTransportEncap};
 use crate::config;
 use crate::error::SpdmResult;
 use crate::msgs::*;
+use crate::responder::SpdmEventQueue;
+use crate::secret::SpdmSecret;
 use codec::{Codec, Reader};
 
+/// Supplies the endpoint info and measurement extension log (MEL)
+/// returned by GET_ENDPOINT_INFO and GET_MEASUREMENT_EXTENSION_LOG.
+pub trait SpdmEndpointInfoProvider {
+    /// Copy the endpoint info of sub_code into ep_info and return its length.
+    fn get_endpoint_info(
+        &mut self,
+        sub_code: SpdmEndpointInfoSubCode,
+        ep_info: &mut [u8],
+    ) -> SpdmResult<usize>;
+
+    /// Return the total length of the MEL.
+    fn get_mel_size(&mut self) -> u32;
+
+    /// Fill mel_portion with the MEL bytes starting at offset.
+    /// The responder never asks for bytes beyond get_mel_size().
+    fn read_mel(&mut self, offset: u32, mel_portion: &mut [u8]) -> SpdmResult;
+}
+
+/// Handles application messages, such as PLDM or NVMe-MI, carried in a
+/// secured session. Handlers are registered per application message type,
+/// which is defined by the transport, e.g. the MCTP message type.
+pub trait SpdmAppMessageHandler {
+    /// Process the plaintext request and write the plaintext response.
+    /// Return the response size, 0 if nothing is sent back.
+    fn dispatch(
+        &mut self,
+        session_id: u32,
+        app_message_type: u8,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> SpdmResult<usize>;
+}
+
+pub const MAX_SPDM_APP_MESSAGE_HANDLER_COUNT: usize = 4;
+
 pub struct ResponderContext<'a> {
     pub common: common::SpdmContext<'a>,
+    pub event_queue: SpdmEventQueue,
+    pub endpoint_info_provider: Option<&'a mut dyn SpdmEndpointInfoProvider>,
+    pub app_message_handlers:
+        [Option<(u8, &'a mut dyn SpdmAppMessageHandler)>; MAX_SPDM_APP_MESSAGE_HANDLER_COUNT],
 }
 
-pub const M_SECURE_SESSION_RESPONSE: &[u8; 5] = &[
-    0x00u8, 0x00u8, //PLDM_MESSAGE_TYPE_CONTROL_DISCOVERY
-    0x02u8, //PLDM_CONTROL_DISCOVERY_COMMAND_GET_TID
-    0x00u8, //PLDM_BASE_CODE_SUCCESS
-    0x01u8, //TID
-];
-
 impl<'a> ResponderContext<'a> {
     pub fn new(
         device_io: &'a mut dyn SpdmDeviceIo,
@@ -33,9 +67,104 @@ impl<'a> ResponderContext<'a> {
                 config_info,
                 provision_info,
             ),
+            event_queue: SpdmEventQueue::new(),
+            endpoint_info_provider: None,
+            app_message_handlers: Default::default(),
+        }
+    }
+
+    pub fn register_endpoint_info_provider(
+        &mut self,
+        endpoint_info_provider: &'a mut dyn SpdmEndpointInfoProvider,
+    ) {
+        self.endpoint_info_provider = Some(endpoint_info_provider);
+    }
+
+    /// Register handler for app_message_type, replacing any handler
+    /// registered before for the same type.
+    pub fn register_app_message_handler(
+        &mut self,
+        app_message_type: u8,
+        handler: &'a mut dyn SpdmAppMessageHandler,
+    ) -> SpdmResult {
+        let index = self
+            .app_message_handlers
+            .iter()
+            .position(|entry| matches!(entry, Some((t, _)) if *t == app_message_type))
+            .or_else(|| self.app_message_handlers.iter().position(|e| e.is_none()))
+            .ok_or(spdm_err!(ENOMEM))?;
+        self.app_message_handlers[index] = Some((app_message_type, handler));
+        Ok(())
+    }
+
+    pub fn export_keying_material(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: usize,
+    ) -> SpdmResult<SpdmSecret<SpdmDigestStruct>> {
+        self.common
+            .export_keying_material(session_id, label, context, length)
+    }
+
+    /// Take the negotiated state cached across a reset, so that the requester
+    /// does not need to repeat GET_VERSION, GET_CAPABILITIES and
+    /// NEGOTIATE_ALGORITHMS. CACHE_CAP is required.
+    pub fn restore_negotiated_state(
+        &mut self,
+        negotiated_state: &common::SpdmNegotiatedState,
+    ) -> SpdmResult {
+        self.common.restore_negotiated_state(negotiated_state)?;
+        self.update_my_cert_chain()
+    }
+
+    /// Handle a reset of the responder. The sessions and transcripts are
+    /// dropped, and the negotiated state it cached itself, if any, is
+    /// restored so that the requester may skip GET_VERSION.
+    pub fn reset_connection(&mut self) -> SpdmResult {
+        for i in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session_id = self.common.session[i].get_session_id();
+            if session_id != 0 {
+                self.teardown_session(session_id);
+            }
+        }
+        self.common.reset_runtime_info();
+        self.common.negotiate_info = common::SpdmNegotiateInfo::default();
+        match self.common.cached_negotiated_state {
+            Some(negotiated_state) => self.restore_negotiated_state(&negotiated_state),
+            None => Ok(()),
         }
     }
 
+    /// Tell the responder that its runtime code or configuration was updated.
+    /// The sessions whose policy asks for it are torn down, the others are
+    /// kept. Return how many were torn down.
+    pub fn notify_runtime_update(&mut self) -> usize {
+        let mut count = 0;
+        for i in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session_id = self.common.session[i].get_session_id();
+            if session_id != 0
+                && self.common.session[i]
+                    .session_policy
+                    .contains(SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE)
+            {
+                info!("session {:#x} terminated on runtime update\n", session_id);
+                self.teardown_session(session_id);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Tear down a session and drop the events queued for it.
+    pub(crate) fn teardown_session(&mut self, session_id: u32) {
+        if let Some(session) = self.common.get_session_via_id(session_id) {
+            let _ = session.teardown(session_id);
+        }
+        self.drop_session_events(session_id);
+    }
+
     pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
         let mut transport_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.common.encap(send_buffer, &mut transport_buffer)?;
@@ -46,7 +175,7 @@ impl<'a> ResponderContext<'a> {
         &mut self,
         session_id: u32,
         send_buffer: &[u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult {
         let mut transport_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let used = self.common.encode_secured_message(
@@ -54,12 +183,14 @@ impl<'a> ResponderContext<'a> {
             send_buffer,
             &mut transport_buffer,
             false,
-            is_app_message,
+            app_message_type,
         )?;
         self.common.device_io.send(&transport_buffer[..used])
     }
 
     pub fn process_message(&mut self) -> Result<bool, (usize, [u8; 1024])> {
+        self.expire_idle_sessions();
+
         let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         match self.receive_message(&mut receive_buffer[..]) {
             Ok((used, secured_message)) => {
@@ -83,6 +214,7 @@ impl<'a> ResponderContext<'a> {
                         return Err((used, receive_buffer));
                     }
                     let decode_size = decode_size.unwrap();
+                    self.common.record_session_activity(session_id);
 
                     let mut spdm_buffer = [0u8; config::MAX_SPDM_MESSAGE_BUFFER_SIZE];
                     let decap_result = self
@@ -91,16 +223,16 @@ impl<'a> ResponderContext<'a> {
                         .decap_app(&app_buffer[0..decode_size], &mut spdm_buffer);
                     match decap_result {
                         Err(_) => Err((used, receive_buffer)),
-                        Ok((decode_size, is_app_message)) => {
-                            if !is_app_message {
-                                Ok(self.dispatch_secured_message(
-                                    session_id,
-                                    &spdm_buffer[0..decode_size],
-                                ))
-                            } else {
-                                Ok(self.dispatch_secured_app_message(session_id))
-                            }
+                        Ok((decode_size, None)) => {
+                            Ok(self
+                                .dispatch_secured_message(session_id, &spdm_buffer[0..decode_size]))
                         }
+                        Ok((decode_size, Some(app_message_type))) => Ok(self
+                            .dispatch_secured_app_message(
+                                session_id,
+                                app_message_type,
+                                &spdm_buffer[0..decode_size],
+                            )),
                     }
                 } else {
                     Ok(self.dispatch_message(&receive_buffer[0..used]))
@@ -173,6 +305,33 @@ impl<'a> ResponderContext<'a> {
                     self.handle_spdm_vendor_defined_request(session_id, bytes);
                     true
                 }
+
+                SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes => {
+                    self.handle_spdm_get_supported_event_types(session_id, bytes);
+                    true
+                }
+
+                SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes => {
+                    self.handle_spdm_subscribe_event_types(session_id, bytes);
+                    true
+                }
+
+                SpdmResponseResponseCode::SpdmResponseEventAck => {
+                    self.handle_spdm_event_ack(session_id, bytes);
+                    true
+                }
+
+                SpdmResponseResponseCode::SpdmRequestGetEndpointInfo => {
+                    self.handle_spdm_endpoint_info(Some(session_id), bytes);
+                    true
+                }
+
+                SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog => {
+                    self.handle_spdm_measurement_extension_log(Some(session_id), bytes);
+                    true
+                }
+
+                SpdmResponseResponseCode::SpdmRequestSendEvent => false,
                 SpdmResponseResponseCode::SpdmResponseDigests => false,
                 SpdmResponseResponseCode::SpdmResponseCertificate => false,
                 SpdmResponseResponseCode::SpdmResponseChallengeAuth => false,
@@ -187,6 +346,10 @@ impl<'a> ResponderContext<'a> {
                 SpdmResponseResponseCode::SpdmResponseHeartbeatAck => false,
                 SpdmResponseResponseCode::SpdmResponseKeyUpdateAck => false,
                 SpdmResponseResponseCode::SpdmResponseEndSessionAck => false,
+                SpdmResponseResponseCode::SpdmResponseSupportedEventTypes => false,
+                SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck => false,
+                SpdmResponseResponseCode::SpdmResponseEndpointInfo => false,
+                SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog => false,
                 SpdmResponseResponseCode::SpdmResponseError => false,
                 SpdmResponseResponseCode::Unknown(_) => false,
             },
@@ -194,10 +357,37 @@ impl<'a> ResponderContext<'a> {
         }
     }
 
-    fn dispatch_secured_app_message(&mut self, session_id: u32) -> bool {
-        debug!("Send app secured message!(PLDM)\n");
-        let _ = self.send_secured_message(session_id, M_SECURE_SESSION_RESPONSE, true);
-        true
+    fn dispatch_secured_app_message(
+        &mut self,
+        session_id: u32,
+        app_message_type: u8,
+        bytes: &[u8],
+    ) -> bool {
+        let handler = self
+            .app_message_handlers
+            .iter_mut()
+            .find_map(|entry| match entry {
+                Some((t, handler)) if *t == app_message_type => Some(handler),
+                _ => None,
+            });
+        let handler = match handler {
+            Some(handler) => handler,
+            None => {
+                debug!("no handler for app message type {:x}\n", app_message_type);
+                return false;
+            }
+        };
+
+        let mut response = [0u8; config::MAX_SPDM_MESSAGE_BUFFER_SIZE];
+        match handler.dispatch(session_id, app_message_type, bytes, &mut response) {
+            Ok(0) => true,
+            Ok(used) => {
+                debug!("send app message type {:x}\n", app_message_type);
+                self.send_secured_message(session_id, &response[..used], Some(app_message_type))
+                    .is_ok()
+            }
+            Err(_) => false,
+        }
     }
     pub fn dispatch_message(&mut self, bytes: &[u8]) -> bool {
         let mut reader = Reader::init(bytes);
@@ -231,6 +421,14 @@ impl<'a> ResponderContext<'a> {
                     self.handle_spdm_measurement(None, bytes);
                     true
                 }
+                SpdmResponseResponseCode::SpdmRequestGetEndpointInfo => {
+                    self.handle_spdm_endpoint_info(None, bytes);
+                    true
+                }
+                SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog => {
+                    self.handle_spdm_measurement_extension_log(None, bytes);
+                    true
+                }
 
                 SpdmResponseResponseCode::SpdmRequestKeyExchange => {
                     self.handle_spdm_key_exchange(bytes);
@@ -266,6 +464,14 @@ impl<'a> ResponderContext<'a> {
                 SpdmResponseResponseCode::SpdmResponseHeartbeatAck => false,
                 SpdmResponseResponseCode::SpdmResponseKeyUpdateAck => false,
                 SpdmResponseResponseCode::SpdmResponseEndSessionAck => false,
+                SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes => false,
+                SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes => false,
+                SpdmResponseResponseCode::SpdmRequestSendEvent => false,
+                SpdmResponseResponseCode::SpdmResponseSupportedEventTypes => false,
+                SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck => false,
+                SpdmResponseResponseCode::SpdmResponseEventAck => false,
+                SpdmResponseResponseCode::SpdmResponseEndpointInfo => false,
+                SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog => false,
                 SpdmResponseResponseCode::SpdmResponseError => false,
                 SpdmResponseResponseCode::Unknown(_) => false,
             },
@@ -278,7 +484,6 @@ impl<'a> ResponderContext<'a> {
 mod tests_responder {
     use super::*;
     use crate::msgs::SpdmMessageHeader;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
     use codec::Writer;
@@ -301,7 +506,7 @@ mod tests_responder {
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -327,7 +532,7 @@ mod tests_responder {
         value.spdm_encode(&mut context.common, &mut writer);
         let used = writer.used();
         let status = context
-            .send_secured_message(session_id, &send_buffer[0..used], false)
+            .send_secured_message(session_id, &send_buffer[0..used], None)
             .is_ok();
         assert!(status);
     }
@@ -359,7 +564,7 @@ mod tests_responder {
         value.spdm_encode(&mut context.common, &mut writer);
         let used = writer.used();
         let status = context
-            .send_secured_message(session_id, &send_buffer[0..used], false)
+            .send_secured_message(session_id, &send_buffer[0..used], None)
             .is_err();
         assert!(status);
     }
@@ -419,7 +624,7 @@ mod tests_responder {
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -434,6 +639,33 @@ mod tests_responder {
         assert!(status);
     }
     #[test]
+    fn test_case0_notify_runtime_update() {
+        let (config_info, provision_info) = create_info();
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+
+        let session_id = (0xffu32 << 16) + 0xfffe;
+        let session_id2 = (0xfeu32 << 16) + 0xfffd;
+        context.common.session = Default::default();
+        context.common.session[0].setup(session_id).unwrap();
+        context.common.session[0].session_policy =
+            SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE;
+        context.common.session[1].setup(session_id2).unwrap();
+
+        assert_eq!(context.notify_runtime_update(), 1);
+        assert!(context.common.get_session_via_id(session_id).is_none());
+        assert!(context.common.get_session_via_id(session_id2).is_some());
+        assert_eq!(context.notify_runtime_update(), 0);
+    }
+    #[test]
     fn test_case0_dispatch_secured_message() {
         let (config_info, provision_info) = create_info();
         let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
@@ -456,7 +688,7 @@ mod tests_responder {
 
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -471,7 +703,7 @@ mod tests_responder {
         context.common.session[0]
             .set_session_state(crate::session::SpdmSessionState::SpdmSessionHandshaking);
 
-        for i in 0..5 {
+        for i in 0..10 {
             let bytes = &mut [0u8; 4];
             let mut writer = Writer::init(bytes);
             let value = SpdmMessageHeader {
@@ -482,7 +714,7 @@ mod tests_responder {
             let status_secured = context.dispatch_secured_message(session_id, bytes);
             assert!(status_secured);
         }
-        for i in 0..24 {
+        for i in 0..29 {
             let bytes = &mut [0u8; 4];
             let mut writer = Writer::init(bytes);
             let value = SpdmMessageHeader {
@@ -493,7 +725,7 @@ mod tests_responder {
             let status_secured = context.dispatch_secured_message(session_id, bytes);
             assert!(!status_secured);
         }
-        for i in 0..9 {
+        for i in 0..11 {
             let bytes = &mut [0u8; 4];
             let mut writer = Writer::init(bytes);
             let value = SpdmMessageHeader {
@@ -504,7 +736,7 @@ mod tests_responder {
             let status = context.dispatch_message(bytes);
             assert!(status);
         }
-        for i in 0..21 {
+        for i in 0..29 {
             let bytes = &mut [0u8; 4];
             let mut writer = Writer::init(bytes);
             let value = SpdmMessageHeader {
@@ -542,6 +774,11 @@ mod tests_responder {
             SpdmResponseResponseCode::SpdmResponseEndSessionAck,
             SpdmResponseResponseCode::SpdmResponseError,
             SpdmResponseResponseCode::SpdmRequestPskExchange,
+            SpdmResponseResponseCode::SpdmRequestSendEvent,
+            SpdmResponseResponseCode::SpdmResponseSupportedEventTypes,
+            SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck,
+            SpdmResponseResponseCode::SpdmResponseEndpointInfo,
+            SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog,
             SpdmResponseResponseCode::Unknown(0),
         ];
         let response_true = [
@@ -550,6 +787,11 @@ mod tests_responder {
             SpdmResponseResponseCode::SpdmRequestPskFinish,
             SpdmResponseResponseCode::SpdmRequestHeartbeat,
             SpdmResponseResponseCode::SpdmRequestKeyUpdate,
+            SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes,
+            SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes,
+            SpdmResponseResponseCode::SpdmResponseEventAck,
+            SpdmResponseResponseCode::SpdmRequestGetEndpointInfo,
+            SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog,
             SpdmResponseResponseCode::SpdmRequestEndSession,
         ];
         if status {
@@ -567,6 +809,8 @@ mod tests_responder {
             SpdmResponseResponseCode::SpdmRequestGetCertificate,
             SpdmResponseResponseCode::SpdmRequestChallenge,
             SpdmResponseResponseCode::SpdmRequestGetMeasurements,
+            SpdmResponseResponseCode::SpdmRequestGetEndpointInfo,
+            SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog,
             SpdmResponseResponseCode::SpdmRequestKeyExchange,
             SpdmResponseResponseCode::SpdmRequestPskExchange,
         ];
@@ -590,6 +834,14 @@ mod tests_responder {
             SpdmResponseResponseCode::SpdmResponseHeartbeatAck,
             SpdmResponseResponseCode::SpdmResponseKeyUpdateAck,
             SpdmResponseResponseCode::SpdmResponseEndSessionAck,
+            SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes,
+            SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes,
+            SpdmResponseResponseCode::SpdmRequestSendEvent,
+            SpdmResponseResponseCode::SpdmResponseSupportedEventTypes,
+            SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck,
+            SpdmResponseResponseCode::SpdmResponseEventAck,
+            SpdmResponseResponseCode::SpdmResponseEndpointInfo,
+            SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog,
             SpdmResponseResponseCode::SpdmResponseError,
             SpdmResponseResponseCode::Unknown(0),
         ];
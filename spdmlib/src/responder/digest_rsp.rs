@@ -17,7 +17,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let get_digests = SpdmGetDigestsRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let get_digests =
+            SpdmGetDigestsRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(get_digests) = get_digests {
             debug!("!!! get_digests : {:02x?}\n", get_digests);
         } else {
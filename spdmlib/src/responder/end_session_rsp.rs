@@ -5,14 +5,14 @@
 use crate::responder::*;
 
 impl<'a> ResponderContext<'a> {
+    /// Answer END_SESSION in the session and tear it down, also if the
+    /// request is invalid.
     pub fn handle_spdm_end_session(&mut self, session_id: u32, bytes: &[u8]) {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
-        if self.write_spdm_end_session_response(bytes, &mut writer) {
-            let _ = self.send_secured_message(session_id, writer.used_slice(), false);
-        } else {
-            let _ = self.send_message(writer.used_slice());
-        }
+        self.write_spdm_end_session_response(bytes, &mut writer);
+        let _ = self.send_secured_message(session_id, writer.used_slice(), None);
+        self.teardown_session(session_id);
     }
 
     pub fn write_spdm_end_session_response(&mut self, bytes: &[u8], writer: &mut Writer) -> bool {
@@ -20,11 +20,15 @@ impl<'a> ResponderContext<'a> {
         SpdmMessageHeader::read(&mut reader);
 
         let end_session_req =
-            SpdmEndSessionRequestPayload::spdm_read(&mut self.common, &mut reader);
+            SpdmEndSessionRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(end_session_req) = end_session_req {
             debug!("!!! end_session req : {:02x?}\n", end_session_req);
+            self.common
+                .end_session_negotiated_state(end_session_req.end_session_request_attributes);
         } else {
             error!("!!! end_session req : fail !!!\n");
+            self.common.cached_negotiated_state = None;
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
             return false;
         }
 
@@ -46,7 +50,6 @@ impl<'a> ResponderContext<'a> {
 mod tests_responder {
     use super::*;
     use crate::msgs::SpdmMessageHeader;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
     use codec::{Codec, Writer};
@@ -83,7 +86,7 @@ mod tests_responder {
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -98,5 +101,8 @@ mod tests_responder {
         bytes.copy_from_slice(&spdm_message_header[0..]);
         bytes[2..].copy_from_slice(&session_request[0..1022]);
         context.handle_spdm_end_session(session_id, bytes);
+        assert!(context.common.get_session_via_id(session_id).is_none());
+        // the state is only preserved with CACHE_CAP
+        assert!(context.common.cached_negotiated_state.is_none());
     }
 }
@@ -0,0 +1,272 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::crypto;
+use crate::responder::*;
+
+impl<'a> ResponderContext<'a> {
+    pub fn handle_spdm_endpoint_info(&mut self, session_id: Option<u32>, bytes: &[u8]) {
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_endpoint_info_response(bytes, &mut writer);
+        match session_id {
+            None => {
+                let _ = self.send_message(writer.used_slice());
+            }
+            Some(session_id) => {
+                let _ = self.send_secured_message(session_id, writer.used_slice(), None);
+            }
+        }
+    }
+
+    pub fn write_spdm_endpoint_info_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+        let mut reader = Reader::init(bytes);
+        SpdmMessageHeader::read(&mut reader);
+
+        let get_endpoint_info = SpdmGetEndpointInfoRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
+        if let Some(get_endpoint_info) = get_endpoint_info {
+            debug!("!!! get_endpoint_info : {:02x?}\n", get_endpoint_info);
+        } else {
+            error!("!!! get_endpoint_info : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let get_endpoint_info = get_endpoint_info.unwrap();
+
+        let rsp_capabilities = self.common.config_info.rsp_capabilities;
+        if !rsp_capabilities.intersects(SpdmResponseCapabilityFlags::EP_INFO_CAP_MASK)
+            || self.endpoint_info_provider.is_none()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return;
+        }
+
+        let need_signature = get_endpoint_info
+            .request_attributes
+            .contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED);
+        if need_signature
+            && !rsp_capabilities.contains(SpdmResponseCapabilityFlags::EP_INFO_CAP_SIG)
+        {
+            error!("!!! get_endpoint_info : signature not supported !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        let mut response_payload = SpdmEndpointInfoResponsePayload {
+            slot_id: get_endpoint_info.slot_id,
+            ..Default::default()
+        };
+        let ep_info_len = self
+            .endpoint_info_provider
+            .as_mut()
+            .unwrap()
+            .get_endpoint_info(get_endpoint_info.sub_code, &mut response_payload.ep_info);
+        match ep_info_len {
+            Ok(ep_info_len) if ep_info_len <= config::MAX_SPDM_ENDPOINT_INFO_SIZE => {
+                response_payload.ep_info_len = ep_info_len as u32
+            }
+            _ => {
+                error!("!!! get_endpoint_info : no endpoint info !!!\n");
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                return;
+            }
+        }
+
+        let base_asym_size = self.common.negotiate_info.base_asym_sel.get_size() as usize;
+        if need_signature {
+            if crypto::rand::get_random(&mut response_payload.nonce.data).is_err() {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+                return;
+            }
+            response_payload.signature.data_size = base_asym_size as u16;
+        }
+
+        self.common.runtime_info.need_endpoint_info_signature = need_signature;
+        if self
+            .common
+            .runtime_info
+            .message_e
+            .append_message(&bytes[..reader.used()])
+            .is_none()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        info!("send spdm endpoint_info\n");
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmResponseEndpointInfo,
+            },
+            payload: SpdmMessagePayload::SpdmEndpointInfoResponse(response_payload),
+        };
+        // the response is built aside, so an ERROR can still be returned if signing fails.
+        let mut response_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut response_writer = Writer::init(&mut response_buffer);
+        response.spdm_encode(&mut self.common, &mut response_writer);
+        let used = response_writer.used();
+
+        if need_signature {
+            self.common
+                .runtime_info
+                .message_e
+                .append_message(&response_writer.used_slice()[..(used - base_asym_size)]);
+
+            let signature = self.common.generate_endpoint_info_signature();
+            self.common.runtime_info.message_e.reset_message();
+            if signature.is_err() {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+                return;
+            }
+            let signature = signature.unwrap();
+            // patch the message before send
+            response_writer.mut_used_slice()[(used - base_asym_size)..used]
+                .copy_from_slice(signature.as_ref());
+        } else {
+            self.common
+                .runtime_info
+                .message_e
+                .append_message(response_writer.used_slice());
+        }
+        writer.extend_from_slice(response_writer.used_slice());
+    }
+}
+
+#[cfg(test)]
+mod tests_responder {
+    use super::*;
+    use crate::testlib::*;
+    use crate::{crypto, responder};
+    use codec::{Codec, Writer};
+
+    #[test]
+    fn test_case0_handle_spdm_endpoint_info() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EP_INFO_CAP_SIG;
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        context.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+
+        context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+        let bytes = &mut [0u8; 64];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestGetEndpointInfo,
+        };
+        value.encode(&mut writer);
+        let value = SpdmGetEndpointInfoRequestPayload {
+            sub_code: SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier,
+            request_attributes: SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+            slot_id: 0,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let used = writer.used();
+
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_endpoint_info_response(&bytes[..used], &mut writer);
+        let used = writer.used();
+
+        let mut reader = Reader::init(&response_buffer[..used]);
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseEndpointInfo
+        );
+        let payload = SpdmEndpointInfoResponsePayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(payload.as_ref(), FAKE_ENDPOINT_INFO);
+        assert_eq!(payload.signature.data_size, 96);
+        assert_eq!(context.common.runtime_info.message_e.as_ref().len(), 0);
+    }
+
+    #[test]
+    fn test_case1_handle_spdm_endpoint_info() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EP_INFO_CAP_NO_SIG;
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+
+        let bytes = &mut [0u8; 64];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestGetEndpointInfo,
+        };
+        value.encode(&mut writer);
+        let value = SpdmGetEndpointInfoRequestPayload {
+            sub_code: SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier,
+            request_attributes: SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED,
+            slot_id: 0,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let used = writer.used();
+
+        // no provider registered
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_endpoint_info_response(&bytes[..used], &mut writer);
+        let mut reader = Reader::init(writer.used_slice());
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+        assert_eq!(
+            SpdmErrorResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap()
+                .error_code,
+            SpdmErrorCode::SpdmErrorUnsupportedRequest
+        );
+
+        // signature requested but only EP_INFO_CAP_NO_SIG
+        context.register_endpoint_info_provider(&mut endpoint_info_provider);
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_endpoint_info_response(&bytes[..used], &mut writer);
+        let mut reader = Reader::init(writer.used_slice());
+        SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            SpdmErrorResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap()
+                .error_code,
+            SpdmErrorCode::SpdmErrorInvalidRequest
+        );
+    }
+}
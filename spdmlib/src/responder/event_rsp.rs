@@ -0,0 +1,587 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::SpdmResult;
+use crate::responder::*;
+use crate::session::SpdmSessionState;
+
+#[derive(Debug, Copy, Clone, Default)]
+struct SpdmQueuedEvent {
+    session_id: u32,
+    in_flight: bool,
+    event: SpdmEventDataStruct,
+}
+
+/// Events waiting to be delivered to the subscribed sessions.
+/// An event stays in the queue until the requester acknowledges it with EVENT_ACK.
+pub struct SpdmEventQueue {
+    next_event_instance_id: u32,
+    events: [Option<SpdmQueuedEvent>; config::MAX_SPDM_EVENT_QUEUE_SIZE],
+}
+
+impl Default for SpdmEventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpdmEventQueue {
+    pub fn new() -> Self {
+        SpdmEventQueue {
+            next_event_instance_id: 1,
+            events: [None; config::MAX_SPDM_EVENT_QUEUE_SIZE],
+        }
+    }
+
+    fn allocate_event_instance_id(&mut self) -> u32 {
+        let event_instance_id = self.next_event_instance_id;
+        self.next_event_instance_id = self.next_event_instance_id.wrapping_add(1).max(1);
+        event_instance_id
+    }
+
+    pub fn pending_count(&self, session_id: u32) -> usize {
+        self.events
+            .iter()
+            .flatten()
+            .filter(|e| e.session_id == session_id)
+            .count()
+    }
+}
+
+impl<'a> ResponderContext<'a> {
+    pub fn handle_spdm_get_supported_event_types(&mut self, session_id: u32, bytes: &[u8]) {
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_supported_event_types_response(bytes, &mut writer);
+        let _ = self.send_secured_message(session_id, writer.used_slice(), None);
+    }
+
+    pub fn write_spdm_supported_event_types_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+        let mut reader = Reader::init(bytes);
+        SpdmMessageHeader::read(&mut reader);
+
+        let get_supported_event_types = SpdmGetSupportedEventTypesRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
+        if let Some(get_supported_event_types) = get_supported_event_types {
+            debug!(
+                "!!! get_supported_event_types : {:02x?}\n",
+                get_supported_event_types
+            );
+        } else {
+            error!("!!! get_supported_event_types : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        if !self
+            .common
+            .config_info
+            .rsp_capabilities
+            .contains(SpdmResponseCapabilityFlags::EVENT_CAP)
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return;
+        }
+
+        info!("send spdm supported_event_types\n");
+
+        let mut response_payload = SpdmSupportedEventTypesResponsePayload {
+            event_group_count: 1,
+            ..Default::default()
+        };
+        response_payload.event_groups[0] = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            self.common.config_info.supported_event_types | SpdmDmtfEventTypeFlags::EVENT_LOST,
+        );
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmResponseSupportedEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmSupportedEventTypesResponse(response_payload),
+        };
+        response.spdm_encode(&mut self.common, writer);
+    }
+
+    pub fn handle_spdm_subscribe_event_types(&mut self, session_id: u32, bytes: &[u8]) {
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_subscribe_event_types_response(session_id, bytes, &mut writer);
+        let _ = self.send_secured_message(session_id, writer.used_slice(), None);
+    }
+
+    pub fn write_spdm_subscribe_event_types_response(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) {
+        let mut reader = Reader::init(bytes);
+        SpdmMessageHeader::read(&mut reader);
+
+        let subscribe_event_types = SpdmSubscribeEventTypesRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
+        if let Some(subscribe_event_types) = subscribe_event_types {
+            debug!(
+                "!!! subscribe_event_types : {:02x?}\n",
+                subscribe_event_types
+            );
+        } else {
+            error!("!!! subscribe_event_types : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let subscribe_event_types = subscribe_event_types.unwrap();
+
+        if !self
+            .common
+            .config_info
+            .rsp_capabilities
+            .contains(SpdmResponseCapabilityFlags::EVENT_CAP)
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return;
+        }
+
+        let supported_event_types =
+            self.common.config_info.supported_event_types | SpdmDmtfEventTypeFlags::EVENT_LOST;
+        let mut subscribed_event_types = SpdmDmtfEventTypeFlags::empty();
+        for event_group in subscribe_event_types
+            .event_groups
+            .iter()
+            .take(subscribe_event_types.event_group_count as usize)
+        {
+            match event_group.get_dmtf_event_types() {
+                Some(event_types) if supported_event_types.contains(event_types) => {
+                    subscribed_event_types |= event_types
+                }
+                Some(event_types)
+                    if event_group
+                        .attributes
+                        .contains(SpdmEventGroupAttributes::SUBSCRIBE_ALL) =>
+                {
+                    subscribed_event_types |= event_types & supported_event_types
+                }
+                _ => {
+                    error!("!!! subscribe_event_types : unsupported event group !!!\n");
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                    return;
+                }
+            }
+        }
+
+        let session = self.common.get_session_via_id(session_id);
+        if session.is_none() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidSession, 0, writer);
+            return;
+        }
+        session.unwrap().event_info.subscribed_event_types = subscribed_event_types;
+        if subscribed_event_types.is_empty() {
+            self.drop_session_events(session_id);
+        }
+
+        info!("send spdm subscribe_event_types_ack\n");
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck,
+            },
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(
+                SpdmSubscribeEventTypesAckResponsePayload {},
+            ),
+        };
+        response.spdm_encode(&mut self.common, writer);
+    }
+
+    /// Queue an event for every established session subscribed to its type.
+    /// If the queue is full, the event is recorded as lost for that session
+    /// and an EventLost event is sent with the next SEND_EVENT.
+    pub fn queue_event(&mut self, detail: &SpdmDmtfEventDetail) {
+        let event_type = SpdmDmtfEventTypeFlags::from_event_type(detail.get_event_type());
+        for i in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session = &self.common.session[i];
+            if session.get_session_state() != SpdmSessionState::SpdmSessionEstablished
+                || !session
+                    .event_info
+                    .subscribed_event_types
+                    .contains(event_type)
+            {
+                continue;
+            }
+            let session_id = session.get_session_id();
+            let event_instance_id = self.event_queue.allocate_event_instance_id();
+            let event = SpdmEventDataStruct::new_dmtf(event_instance_id, detail);
+
+            match self.event_queue.events.iter_mut().find(|e| e.is_none()) {
+                Some(slot) => {
+                    *slot = Some(SpdmQueuedEvent {
+                        session_id,
+                        in_flight: false,
+                        event,
+                    })
+                }
+                None => {
+                    info!("event queue full, event {} lost\n", event_instance_id);
+                    self.common.session[i]
+                        .event_info
+                        .last_lost_event_instance_id = event_instance_id;
+                }
+            }
+        }
+    }
+
+    /// Send all queued events, one SEND_EVENT per session.
+    pub fn send_pending_events(&mut self) -> SpdmResult {
+        for i in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session = &self.common.session[i];
+            if session.get_session_state() != SpdmSessionState::SpdmSessionEstablished {
+                continue;
+            }
+            let session_id = session.get_session_id();
+            let event_info = session.event_info;
+
+            let mut payload = SpdmSendEventRequestPayload::default();
+            if event_info.last_lost_event_instance_id != 0
+                && event_info
+                    .subscribed_event_types
+                    .contains(SpdmDmtfEventTypeFlags::EVENT_LOST)
+            {
+                let event_instance_id = self.event_queue.allocate_event_instance_id();
+                payload.events[0] = SpdmEventDataStruct::new_dmtf(
+                    event_instance_id,
+                    &SpdmDmtfEventDetail::EventLost(SpdmEventLostStruct {
+                        last_acked_event_instance_id: event_info.last_acked_event_instance_id,
+                        last_lost_event_instance_id: event_info.last_lost_event_instance_id,
+                    }),
+                );
+                payload.event_count = 1;
+            }
+            self.common.session[i]
+                .event_info
+                .last_lost_event_instance_id = 0;
+
+            for queued_event in self.event_queue.events.iter_mut().flatten() {
+                if payload.event_count as usize == config::MAX_SPDM_EVENT_COUNT {
+                    break;
+                }
+                if queued_event.session_id != session_id || queued_event.in_flight {
+                    continue;
+                }
+                queued_event.in_flight = true;
+                payload.events[payload.event_count as usize] = queued_event.event;
+                payload.event_count += 1;
+            }
+
+            if payload.event_count == 0 {
+                continue;
+            }
+
+            info!("send spdm send_event\n");
+            let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+            let mut writer = Writer::init(&mut send_buffer);
+            let request = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: SpdmVersion::SpdmVersion13,
+                    request_response_code: SpdmResponseResponseCode::SpdmRequestSendEvent,
+                },
+                payload: SpdmMessagePayload::SpdmSendEventRequest(payload),
+            };
+            request.spdm_encode(&mut self.common, &mut writer);
+            self.send_secured_message(session_id, writer.used_slice(), None)?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_spdm_event_ack(&mut self, session_id: u32, bytes: &[u8]) {
+        let mut reader = Reader::init(bytes);
+        SpdmMessageHeader::read(&mut reader);
+
+        let event_ack =
+            SpdmEventAckResponsePayload::spdm_read(&self.common.get_decode_info(), &mut reader);
+        if let Some(event_ack) = event_ack {
+            debug!("!!! event_ack : {:02x?}\n", event_ack);
+        } else {
+            error!("!!! event_ack : fail !!!\n");
+            return;
+        }
+
+        let mut last_acked_event_instance_id = None;
+        for slot in self.event_queue.events.iter_mut().filter(
+            |e| matches!(e, Some(queued_event) if queued_event.session_id == session_id && queued_event.in_flight),
+        ) {
+            last_acked_event_instance_id = slot.map(|e| e.event.event_instance_id);
+            *slot = None;
+        }
+
+        if let Some(last_acked_event_instance_id) = last_acked_event_instance_id {
+            if let Some(session) = self.common.get_session_via_id(session_id) {
+                session.event_info.last_acked_event_instance_id = last_acked_event_instance_id;
+            }
+        }
+    }
+
+    /// Drop the events queued for a session, once it unsubscribed or is torn down.
+    pub(crate) fn drop_session_events(&mut self, session_id: u32) {
+        for slot in
+            self.event_queue.events.iter_mut().filter(
+                |e| matches!(e, Some(queued_event) if queued_event.session_id == session_id),
+            )
+        {
+            *slot = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_responder {
+    use super::*;
+    use crate::msgs::SpdmMessageHeader;
+    use crate::testlib::*;
+    use crate::{crypto, responder};
+    use codec::{Codec, Writer};
+
+    fn setup_session(context: &mut responder::ResponderContext, session_id: u32) {
+        context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        context.common.session = Default::default();
+        context.common.session[0].setup(session_id).unwrap();
+        context.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        context.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+    }
+
+    #[test]
+    fn test_case0_handle_spdm_subscribe_event_types() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::EVENT_CAP;
+        config_info.supported_event_types = SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED;
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        let session_id = (0xffu32 << 16) + 0xffu32;
+        setup_session(&mut context, session_id);
+
+        let bytes = &mut [0u8; 64];
+        let mut writer = Writer::init(bytes);
+        let mut payload = SpdmSubscribeEventTypesRequestPayload {
+            event_group_count: 1,
+            ..Default::default()
+        };
+        payload.event_groups[0] = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED,
+        );
+        let value = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestSubscribeEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload),
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+
+        let response = &mut [0u8; 64];
+        let mut writer = Writer::init(response);
+        context.write_spdm_subscribe_event_types_response(session_id, bytes, &mut writer);
+        let mut reader = Reader::init(response);
+        let header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseSubscribeEventTypesAck
+        );
+        assert_eq!(
+            context.common.session[0].event_info.subscribed_event_types,
+            SpdmDmtfEventTypeFlags::MEASUREMENT_CHANGED
+        );
+
+        // certificate change is not supported
+        payload.event_groups[0] = SpdmEventGroupStruct::new_dmtf(
+            SpdmEventGroupAttributes::empty(),
+            SpdmDmtfEventTypeFlags::CERTIFICATE_CHANGED,
+        );
+        let bytes = &mut [0u8; 64];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessage {
+            header: value.header,
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload),
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let response = &mut [0u8; 64];
+        let mut writer = Writer::init(response);
+        context.write_spdm_subscribe_event_types_response(session_id, bytes, &mut writer);
+        let mut reader = Reader::init(response);
+        let header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+    }
+
+    #[test]
+    fn test_case0_handle_spdm_get_supported_event_types() {
+        let (config_info, provision_info) = create_info();
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+
+        let bytes = &mut [0u8; 4];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestGetSupportedEventTypes,
+        };
+        value.encode(&mut writer);
+
+        // EVENT_CAP is not set
+        let response = &mut [0u8; 64];
+        let mut writer = Writer::init(response);
+        context.write_spdm_supported_event_types_response(bytes, &mut writer);
+        let mut reader = Reader::init(response);
+        let header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+    }
+
+    #[test]
+    fn test_case0_queue_event() {
+        let (config_info, provision_info) = create_info();
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        let session_id = (0xffu32 << 16) + 0xffu32;
+        setup_session(&mut context, session_id);
+
+        let detail = SpdmDmtfEventDetail::MeasurementChanged(SpdmMeasurementIndexMask::default());
+        // not subscribed
+        context.queue_event(&detail);
+        assert_eq!(context.event_queue.pending_count(session_id), 0);
+
+        context.common.session[0].event_info.subscribed_event_types = SpdmDmtfEventTypeFlags::all();
+        for _ in 0..(config::MAX_SPDM_EVENT_QUEUE_SIZE + 1) {
+            context.queue_event(&detail);
+        }
+        assert_eq!(
+            context.event_queue.pending_count(session_id),
+            config::MAX_SPDM_EVENT_QUEUE_SIZE
+        );
+        assert_ne!(
+            context.common.session[0]
+                .event_info
+                .last_lost_event_instance_id,
+            0
+        );
+
+        assert!(context.send_pending_events().is_ok());
+        assert_eq!(
+            context.common.session[0]
+                .event_info
+                .last_lost_event_instance_id,
+            0
+        );
+
+        let bytes = &mut [0u8; 4];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmResponseResponseCode::SpdmResponseEventAck,
+        };
+        value.encode(&mut writer);
+        context.handle_spdm_event_ack(session_id, bytes);
+        assert_eq!(
+            context.event_queue.pending_count(session_id),
+            config::MAX_SPDM_EVENT_QUEUE_SIZE - (config::MAX_SPDM_EVENT_COUNT - 1)
+        );
+        assert_eq!(
+            context.common.session[0]
+                .event_info
+                .last_acked_event_instance_id,
+            (config::MAX_SPDM_EVENT_COUNT - 1) as u32
+        );
+    }
+
+    #[test]
+    fn test_case1_queue_event() {
+        let (config_info, provision_info) = create_info();
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let now = std::cell::Cell::new(0u64);
+        let mut time_source = FakeSpdmTimeSource { now: &now };
+        crypto::asym_sign::register(ASYM_SIGN_IMPL);
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        context.common.register_time_source(&mut time_source);
+        let session_id = (0xffu32 << 16) + 0xffu32;
+        let detail = SpdmDmtfEventDetail::MeasurementChanged(SpdmMeasurementIndexMask::default());
+
+        // END_SESSION drops the events of the session
+        setup_session(&mut context, session_id);
+        context.common.session[0].event_info.subscribed_event_types = SpdmDmtfEventTypeFlags::all();
+        context.queue_event(&detail);
+        context.queue_event(&detail);
+        assert_eq!(context.event_queue.pending_count(session_id), 2);
+
+        let bytes = &mut [0u8; 8];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion11,
+                request_response_code: SpdmResponseResponseCode::SpdmRequestEndSession,
+            },
+            payload: SpdmMessagePayload::SpdmEndSessionRequest(SpdmEndSessionRequestPayload {
+                end_session_request_attributes: SpdmEndSessionRequestAttributes::empty(),
+            }),
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        context.handle_spdm_end_session(session_id, bytes);
+        assert!(context.common.get_session_via_id(session_id).is_none());
+        assert_eq!(context.event_queue.pending_count(session_id), 0);
+
+        // so does the heartbeat expiry
+        setup_session(&mut context, session_id);
+        context.common.session[0].event_info.subscribed_event_types = SpdmDmtfEventTypeFlags::all();
+        context.common.session[0].heartbeat_info.heartbeat_period = 1;
+        context.common.record_session_activity(session_id);
+        context.queue_event(&detail);
+        assert_eq!(context.event_queue.pending_count(session_id), 1);
+        now.set(2001);
+        assert_eq!(context.expire_idle_sessions(), 1);
+        assert_eq!(context.event_queue.pending_count(session_id), 0);
+    }
+}
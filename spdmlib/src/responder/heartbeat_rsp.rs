@@ -9,7 +9,7 @@ impl<'a> ResponderContext<'a> {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
         if self.write_spdm_heartbeat_response(bytes, &mut writer) {
-            let _ = self.send_secured_message(session_id, writer.used_slice(), false);
+            let _ = self.send_secured_message(session_id, writer.used_slice(), None);
         }
     }
 
@@ -17,7 +17,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let heartbeat_req = SpdmHeartbeatRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let heartbeat_req =
+            SpdmHeartbeatRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(heartbeat_req) = heartbeat_req {
             debug!("!!! heartbeat req : {:02x?}\n", heartbeat_req);
         } else {
@@ -37,14 +38,38 @@ impl<'a> ResponderContext<'a> {
         response.spdm_encode(&mut self.common, writer);
         true
     }
+
+    /// Tear down the sessions which received no message within twice their
+    /// heartbeat period, and return how many were torn down.
+    /// Sessions never expire without a registered time source.
+    pub fn expire_idle_sessions(&mut self) -> usize {
+        let now = match self.common.get_time_ms() {
+            Some(now) => now,
+            None => return 0,
+        };
+        let mut count = 0;
+        for i in 0..config::MAX_SPDM_SESSION_COUNT {
+            let session_id = self.common.session[i].get_session_id();
+            let heartbeat_info = self.common.session[i].heartbeat_info;
+            if session_id == 0 || heartbeat_info.heartbeat_period == 0 {
+                continue;
+            }
+            let timeout = 2 * 1000 * heartbeat_info.heartbeat_period as u64;
+            if now.saturating_sub(heartbeat_info.last_message_time) > timeout {
+                info!("session {:#x} heartbeat timeout\n", session_id);
+                self.teardown_session(session_id);
+                count += 1;
+            }
+        }
+        count
+    }
 }
 #[cfg(test)]
 mod tests_responder {
     use super::*;
     use crate::msgs::SpdmMessageHeader;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
-    use crate::{crypto, responder};
+    use crate::{common, crypto, responder};
     use codec::{Codec, Writer};
 
     #[test]
@@ -64,7 +89,7 @@ mod tests_responder {
         let rsp_session_id = 0xffu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -85,4 +110,50 @@ mod tests_responder {
 
         context.handle_spdm_heartbeat(session_id, bytes);
     }
+
+    #[test]
+    fn test_case0_expire_idle_sessions() {
+        let (config_info, provision_info) = create_info();
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let now = std::cell::Cell::new(0u64);
+        let mut time_source = FakeSpdmTimeSource { now: &now };
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            common::SpdmConfigInfo {
+                heartbeat_period: 1,
+                ..config_info
+            },
+            provision_info,
+        );
+
+        // advertised only when both sides support HEARTBEAT
+        assert_eq!(context.common.get_heartbeat_period(), 0);
+        context.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::HBEAT_CAP;
+        context.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::HBEAT_CAP;
+        assert_eq!(context.common.get_heartbeat_period(), 1);
+
+        let session_id = 0xfffefffeu32;
+        context.common.session = Default::default();
+        context.common.session[0].setup(session_id).unwrap();
+        context.common.session[0].heartbeat_info.heartbeat_period = 1;
+        context.common.session[1].setup(session_id - 1).unwrap();
+
+        // no time source, no expiry
+        now.set(10000);
+        assert_eq!(context.expire_idle_sessions(), 0);
+
+        context.common.register_time_source(&mut time_source);
+        now.set(500);
+        context.common.record_session_activity(session_id);
+        now.set(2500);
+        assert_eq!(context.expire_idle_sessions(), 0);
+        now.set(2501);
+        assert_eq!(context.expire_idle_sessions(), 1);
+        assert!(context.common.get_session_via_id(session_id).is_none());
+        // the session without heartbeat stays
+        assert!(context.common.get_session_via_id(session_id - 1).is_some());
+    }
 }
@@ -7,6 +7,7 @@ use crate::responder::*;
 use crate::common::ManagedBuffer;
 
 use crate::crypto;
+use crate::secret::SpdmSecret;
 
 impl<'a> ResponderContext<'a> {
     pub fn handle_spdm_key_exchange(&mut self, bytes: &[u8]) {
@@ -21,7 +22,7 @@ impl<'a> ResponderContext<'a> {
         SpdmMessageHeader::read(&mut reader);
 
         let key_exchange_req =
-            SpdmKeyExchangeRequestPayload::spdm_read(&mut self.common, &mut reader);
+            SpdmKeyExchangeRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(key_exchange_req) = key_exchange_req {
             debug!("!!! key_exchange req : {:02x?}\n", key_exchange_req);
 
@@ -40,6 +41,19 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
+        let peer_opaque_data =
+            SpdmGeneralOpaqueData::from_opaque(&key_exchange_req.unwrap().opaque);
+        let secured_message_version = peer_opaque_data
+            .as_ref()
+            .and_then(|opaque_data| self.common.select_secured_message_version(opaque_data));
+        if secured_message_version.is_none() {
+            error!("!!! key_exchange req : no common secured message version !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let secured_message_version = secured_message_version.unwrap();
+        let peer_opaque_data = peer_opaque_data.unwrap().get_app_elements();
+
         info!("send spdm key_exchange rsp\n");
 
         let (exchange, key_exchange_context) =
@@ -57,27 +71,30 @@ impl<'a> ResponderContext<'a> {
         if final_key.is_none() {
             return;
         }
-        let final_key = final_key.unwrap();
-        debug!("!!! final_key : {:02x?}\n", final_key.as_ref());
+        let final_key = SpdmSecret::new(final_key.unwrap());
+        debug_secret!("!!! final_key : {:02x?}\n", final_key.as_ref());
 
         let mut random = [0u8; SPDM_RANDOM_SIZE];
         let _ = crypto::rand::get_random(&mut random);
 
         let rsp_session_id = 0xFFFE;
 
-        let mut opaque = SpdmOpaqueStruct {
-            data_size: crate::common::OPAQUE_DATA_VERSION_SELECTION.len() as u16,
-            ..Default::default()
-        };
-        opaque.data[..(opaque.data_size as usize)]
-            .copy_from_slice(crate::common::OPAQUE_DATA_VERSION_SELECTION.as_ref());
+        let opaque = self
+            .common
+            .encode_version_selection_opaque(secured_message_version);
+        if opaque.is_err() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
+        }
+        let opaque = opaque.unwrap();
+        let heartbeat_period = self.common.get_heartbeat_period();
         let response = SpdmMessage {
             header: SpdmMessageHeader {
                 version: SpdmVersion::SpdmVersion11,
                 request_response_code: SpdmResponseResponseCode::SpdmResponseKeyExchangeRsp,
             },
             payload: SpdmMessagePayload::SpdmKeyExchangeResponse(SpdmKeyExchangeResponsePayload {
-                heartbeat_period: 0x0,
+                heartbeat_period,
                 rsp_session_id,
                 mut_auth_req: SpdmKeyExchangeMutAuthAttributes::empty(),
                 req_slot_id: 0x0,
@@ -147,7 +164,7 @@ impl<'a> ResponderContext<'a> {
         let aead_algo = self.common.negotiate_info.aead_sel;
         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
         let sequence_number_count = self.common.transport_encap.get_sequence_number_count();
-        let max_random_count = self.common.transport_encap.get_max_random_count();
+        let max_random_count = self.common.get_max_random_count();
 
         let session = self.common.get_next_avaiable_session();
         if session.is_none() {
@@ -161,6 +178,10 @@ impl<'a> ResponderContext<'a> {
             ((key_exchange_req.unwrap().req_session_id as u32) << 16) + rsp_session_id as u32;
         session.setup(session_id).unwrap();
         session.set_use_psk(false);
+        session.session_policy = key_exchange_req.unwrap().session_policy;
+        session.heartbeat_info.heartbeat_period = heartbeat_period;
+        session.set_secured_message_version(secured_message_version);
+        session.set_peer_opaque_data(&peer_opaque_data);
         session.set_crypto_param(hash_algo, dhe_algo, aead_algo, key_schedule_algo);
         session.set_transport_param(sequence_number_count, max_random_count);
         session.set_dhe_secret(&final_key);
@@ -198,6 +219,7 @@ impl<'a> ResponderContext<'a> {
 
         let session = self.common.get_session_via_id(session_id).unwrap();
         session.set_session_state(crate::session::SpdmSessionState::SpdmSessionHandshaking);
+        self.common.record_session_activity(session_id);
     }
 }
 
@@ -256,14 +278,12 @@ mod tests_responder {
                 SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeTcb,
             slot_id: 100u8,
             req_session_id: 0xffu16,
+            session_policy: SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE,
             random: SpdmRandomStruct {
                 data: [100u8; SPDM_RANDOM_SIZE],
             },
             exchange: SpdmDheExchangeStruct::from(public_key),
-            opaque: SpdmOpaqueStruct {
-                data_size: 64u16,
-                data: [100u8; crate::config::MAX_SPDM_OPAQUE_SIZE],
-            },
+            opaque: context.common.encode_supported_version_opaque().unwrap(),
         };
         value.spdm_encode(&mut context.common, &mut writer);
 
@@ -9,7 +9,7 @@ impl<'a> ResponderContext<'a> {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
         if self.write_spdm_key_update_response(session_id, bytes, &mut writer) {
-            let _ = self.send_secured_message(session_id, writer.used_slice(), false);
+            let _ = self.send_secured_message(session_id, writer.used_slice(), None);
         } else {
             let _ = self.send_message(writer.used_slice());
         }
@@ -24,7 +24,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let key_update_req = SpdmKeyUpdateRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let key_update_req =
+            SpdmKeyUpdateRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(key_update_req) = key_update_req {
             debug!("!!! key_update req : {:02x?}\n", key_update_req);
         } else {
@@ -33,24 +34,55 @@ impl<'a> ResponderContext<'a> {
             return false;
         }
         let key_update_req = key_update_req.unwrap();
+        let key_update_operation = key_update_req.key_update_operation;
 
-        let session = self.common.get_session_via_id(session_id).unwrap();
-        match key_update_req.key_update_operation {
-            SpdmKeyUpdateOperation::SpdmUpdateSingleKey => {
-                let _ = session.create_data_secret_update(true, false);
-            }
-            SpdmKeyUpdateOperation::SpdmUpdateAllKeys => {
-                let _ = session.create_data_secret_update(true, true);
-                let _ = session.activate_data_secret_update(true, true, true);
-            }
-            SpdmKeyUpdateOperation::SpdmVerifyNewKey => {
-                let _ = session.activate_data_secret_update(true, false, true);
+        let session = match self.common.get_session_via_id(session_id) {
+            Some(session) => session,
+            None => {
+                error!("!!! key_update req : no session !!!\n");
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidSession, 0, writer);
+                return false;
             }
-            _ => {
-                error!("!!! key_update req : fail !!!\n");
-                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+        };
+
+        // a retried request is acknowledged again without another update
+        let request = (key_update_operation, key_update_req.tag);
+        if session.key_update_info.last_request == Some(request) {
+            info!("!!! key_update req : retried !!!\n");
+        } else {
+            let pending = session.key_update_info.pending_requester;
+            let result = match key_update_operation {
+                // the old request key is kept until VERIFY_NEW_KEY, and for
+                // UpdateAllKeys the old response key, which is restored with it
+                SpdmKeyUpdateOperation::SpdmUpdateSingleKey if !pending => {
+                    session.create_data_secret_update(true, false)
+                }
+                SpdmKeyUpdateOperation::SpdmUpdateAllKeys if !pending => {
+                    session.create_data_secret_update(true, true)
+                }
+                SpdmKeyUpdateOperation::SpdmVerifyNewKey if pending => {
+                    let update_responder = session.key_update_info.pending_responder;
+                    session.activate_data_secret_update(true, update_responder, true)
+                }
+                SpdmKeyUpdateOperation::SpdmUpdateSingleKey
+                | SpdmKeyUpdateOperation::SpdmUpdateAllKeys
+                | SpdmKeyUpdateOperation::SpdmVerifyNewKey => {
+                    error!("!!! key_update req : unexpected !!!\n");
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorUnexpectedRequest, 0, writer);
+                    return false;
+                }
+                _ => {
+                    error!("!!! key_update req : fail !!!\n");
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                    return false;
+                }
+            };
+            if result.is_err() {
+                error!("!!! key_update req : update fail !!!\n");
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
                 return false;
             }
+            session.key_update_info.last_request = Some(request);
         }
 
         info!("send spdm key_update rsp\n");
@@ -61,7 +93,7 @@ impl<'a> ResponderContext<'a> {
                 request_response_code: SpdmResponseResponseCode::SpdmResponseKeyUpdateAck,
             },
             payload: SpdmMessagePayload::SpdmKeyUpdateResponse(SpdmKeyUpdateResponsePayload {
-                key_update_operation: key_update_req.key_update_operation,
+                key_update_operation,
                 tag: key_update_req.tag,
             }),
         };
@@ -74,7 +106,6 @@ impl<'a> ResponderContext<'a> {
 mod tests_responder {
     use super::*;
     use crate::msgs::SpdmMessageHeader;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
     use codec::{Codec, Writer};
@@ -97,7 +128,7 @@ mod tests_responder {
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -148,7 +179,7 @@ mod tests_responder {
         let rsp_session_id = 0xFFFEu16;
         let session_id = (0xffu32 << 16) + rsp_session_id as u32;
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(session_id).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -181,4 +212,101 @@ mod tests_responder {
 
         context.handle_spdm_key_update(session_id, bytes);
     }
+
+    // Return the response code and param1 of the response to KEY_UPDATE.
+    fn key_update(
+        context: &mut responder::ResponderContext,
+        session_id: u32,
+        key_update_operation: u8,
+        tag: u8,
+    ) -> (u8, u8) {
+        let request = [0x11u8, 0xe9, key_update_operation, tag];
+        let response = &mut [0u8; 1024];
+        let mut writer = Writer::init(response);
+        context.write_spdm_key_update_response(session_id, &request, &mut writer);
+        (response[1], response[2])
+    }
+
+    #[test]
+    fn test_case0_key_update_state() {
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let (config_info, provision_info) = create_info();
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+
+        let session_id = (0xffu32 << 16) + 0xFFFEu32;
+        context.common.session = Default::default();
+        context.common.session[0].setup(session_id).unwrap();
+        context.common.session[0].set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        context.common.session[0]
+            .set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
+        let (request_key, response_key) = context.common.session[0].export_keys();
+
+        // UpdateKey updates the request key, the old one is kept
+        assert_eq!(key_update(&mut context, session_id, 1, 1), (0x69, 1));
+        let (updated_request_key, updated_response_key) = context.common.session[0].export_keys();
+        assert_ne!(
+            updated_request_key.encryption_key.data,
+            request_key.encryption_key.data
+        );
+        assert_eq!(
+            updated_response_key.encryption_key.data,
+            response_key.encryption_key.data
+        );
+        assert!(context.common.session[0].key_update_info.pending_requester);
+
+        // a retried request does not update the key again
+        assert_eq!(key_update(&mut context, session_id, 1, 1), (0x69, 1));
+        let (retried_request_key, _) = context.common.session[0].export_keys();
+        assert_eq!(
+            retried_request_key.encryption_key.data,
+            updated_request_key.encryption_key.data
+        );
+
+        // no other update before VerifyNewKey
+        assert_eq!(
+            key_update(&mut context, session_id, 2, 2),
+            (0x7f, SpdmErrorCode::SpdmErrorUnexpectedRequest.get_u8())
+        );
+        assert_eq!(key_update(&mut context, session_id, 3, 2), (0x69, 3));
+        assert!(!context.common.session[0].key_update_info.pending_requester);
+
+        // VerifyNewKey without an update, or a retried one
+        assert_eq!(
+            key_update(&mut context, session_id, 3, 3),
+            (0x7f, SpdmErrorCode::SpdmErrorUnexpectedRequest.get_u8())
+        );
+        assert_eq!(key_update(&mut context, session_id, 3, 2), (0x69, 3));
+
+        // UpdateAllKeys updates both keys
+        assert_eq!(key_update(&mut context, session_id, 2, 4), (0x69, 2));
+        let (_, all_response_key) = context.common.session[0].export_keys();
+        assert_ne!(
+            all_response_key.encryption_key.data,
+            response_key.encryption_key.data
+        );
+        assert!(context.common.session[0].key_update_info.pending_responder);
+        assert_eq!(key_update(&mut context, session_id, 3, 5), (0x69, 3));
+        assert!(!context.common.session[0].key_update_info.pending_responder);
+
+        assert_eq!(
+            key_update(&mut context, session_id, 0, 6),
+            (0x7f, SpdmErrorCode::SpdmErrorInvalidRequest.get_u8())
+        );
+        assert_eq!(
+            key_update(&mut context, session_id + 1, 1, 7),
+            (0x7f, SpdmErrorCode::SpdmErrorInvalidSession.get_u8())
+        );
+    }
 }
@@ -0,0 +1,225 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::responder::*;
+
+impl<'a> ResponderContext<'a> {
+    pub fn handle_spdm_measurement_extension_log(&mut self, session_id: Option<u32>, bytes: &[u8]) {
+        let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_measurement_extension_log_response(bytes, &mut writer);
+        match session_id {
+            None => {
+                let _ = self.send_message(writer.used_slice());
+            }
+            Some(session_id) => {
+                let _ = self.send_secured_message(session_id, writer.used_slice(), None);
+            }
+        }
+    }
+
+    pub fn write_spdm_measurement_extension_log_response(
+        &mut self,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) {
+        let mut reader = Reader::init(bytes);
+        SpdmMessageHeader::read(&mut reader);
+
+        let get_mel = SpdmGetMeasurementExtensionLogRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
+        if let Some(get_mel) = get_mel {
+            debug!("!!! get_measurement_extension_log : {:02x?}\n", get_mel);
+        } else {
+            error!("!!! get_measurement_extension_log : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let get_mel = get_mel.unwrap();
+
+        if !self
+            .common
+            .config_info
+            .rsp_capabilities
+            .contains(SpdmResponseCapabilityFlags::MEL_CAP)
+            || self.endpoint_info_provider.is_none()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return;
+        }
+        let endpoint_info_provider = self.endpoint_info_provider.as_mut().unwrap();
+
+        let mel_size = endpoint_info_provider.get_mel_size();
+        if get_mel.offset >= mel_size || get_mel.length == 0 {
+            error!("!!! get_measurement_extension_log : invalid offset/length !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let portion_length = get_mel
+            .length
+            .min(mel_size - get_mel.offset)
+            .min(config::MAX_SPDM_MEL_PORTION_LEN as u32);
+
+        let mut response_payload = SpdmMeasurementExtensionLogResponsePayload {
+            portion_length,
+            remainder_length: mel_size - get_mel.offset - portion_length,
+            ..Default::default()
+        };
+        if endpoint_info_provider
+            .read_mel(
+                get_mel.offset,
+                &mut response_payload.mel_portion[..(portion_length as usize)],
+            )
+            .is_err()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
+        }
+
+        info!("send spdm measurement_extension_log\n");
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion13,
+                request_response_code:
+                    SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog,
+            },
+            payload: SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(response_payload),
+        };
+        response.spdm_encode(&mut self.common, writer);
+    }
+}
+
+#[cfg(test)]
+mod tests_responder {
+    use super::*;
+    use crate::responder;
+    use crate::testlib::*;
+    use codec::{Codec, Writer};
+
+    fn get_mel_response(
+        context: &mut ResponderContext,
+        offset: u32,
+        length: u32,
+        response_buffer: &mut [u8],
+    ) -> usize {
+        let bytes = &mut [0u8; 16];
+        let mut writer = Writer::init(bytes);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestGetMeasurementExtensionLog,
+        };
+        value.encode(&mut writer);
+        let value = SpdmGetMeasurementExtensionLogRequestPayload { offset, length };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let used = writer.used();
+
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_measurement_extension_log_response(&bytes[..used], &mut writer);
+        writer.used()
+    }
+
+    #[test]
+    fn test_case0_handle_spdm_measurement_extension_log() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::MEL_CAP;
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        context.register_endpoint_info_provider(&mut endpoint_info_provider);
+
+        // the portion is capped by MAX_SPDM_MEL_PORTION_LEN
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = get_mel_response(&mut context, 0, 0xFFFF_FFFF, response_buffer);
+        let mut reader = Reader::init(&response_buffer[..used]);
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseMeasurementExtensionLog
+        );
+        let payload = SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(
+            payload.portion_length as usize,
+            config::MAX_SPDM_MEL_PORTION_LEN
+        );
+        assert_eq!(
+            payload.remainder_length as usize,
+            FAKE_MEL_SIZE - config::MAX_SPDM_MEL_PORTION_LEN
+        );
+        for (i, d) in payload.as_ref().iter().enumerate() {
+            assert_eq!(*d, i as u8);
+        }
+
+        // the last portion is capped by the MEL size
+        let offset = FAKE_MEL_SIZE as u32 - 10;
+        let used = get_mel_response(&mut context, offset, 100, response_buffer);
+        let mut reader = Reader::init(&response_buffer[..used]);
+        SpdmMessageHeader::read(&mut reader).unwrap();
+        let payload = SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
+        assert_eq!(payload.portion_length, 10);
+        assert_eq!(payload.remainder_length, 0);
+        assert_eq!(payload.mel_portion[0], offset as u8);
+    }
+
+    #[test]
+    fn test_case1_handle_spdm_measurement_extension_log() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.rsp_capabilities |= SpdmResponseCapabilityFlags::MEL_CAP;
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let mut endpoint_info_provider = FakeSpdmEndpointInfoProvider::new();
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+
+        // no provider registered
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let used = get_mel_response(&mut context, 0, 16, response_buffer);
+        let mut reader = Reader::init(&response_buffer[..used]);
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+        assert_eq!(
+            SpdmErrorResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap()
+                .error_code,
+            SpdmErrorCode::SpdmErrorUnsupportedRequest
+        );
+
+        // offset beyond the MEL
+        context.register_endpoint_info_provider(&mut endpoint_info_provider);
+        let used = get_mel_response(&mut context, FAKE_MEL_SIZE as u32, 16, response_buffer);
+        let mut reader = Reader::init(&response_buffer[..used]);
+        SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            SpdmErrorResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap()
+                .error_code,
+            SpdmErrorCode::SpdmErrorInvalidRequest
+        );
+    }
+}
@@ -15,7 +15,7 @@ impl<'a> ResponderContext<'a> {
                 let _ = self.send_message(writer.used_slice());
             }
             Some(session_id) => {
-                let _ = self.send_secured_message(session_id, writer.used_slice(), false);
+                let _ = self.send_secured_message(session_id, writer.used_slice(), None);
             }
         }
     }
@@ -24,8 +24,10 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let get_measurements =
-            SpdmGetMeasurementsRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let get_measurements = SpdmGetMeasurementsRequestPayload::spdm_read(
+            &self.common.get_decode_info(),
+            &mut reader,
+        );
         if let Some(get_measurements) = get_measurements {
             debug!("!!! get_measurements : {:02x?}\n", get_measurements);
         } else {
@@ -300,8 +302,11 @@ mod tests_responder {
 
         let spdm_struct_slice = &u8_slice[2..];
         let mut reader = Reader::init(spdm_struct_slice);
-        let get_measurements =
-            SpdmGetMeasurementsRequestPayload::spdm_read(&mut context.common, &mut reader).unwrap();
+        let get_measurements = SpdmGetMeasurementsRequestPayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(
             get_measurements.measurement_attributes,
             SpdmMeasurementeAttributes::empty()
@@ -314,7 +319,7 @@ mod tests_responder {
         let spdm_message_slice = &u8_slice[4..];
         let mut reader = Reader::init(spdm_message_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
         assert_eq!(
             spdm_message.header.request_response_code,
             SpdmResponseResponseCode::SpdmResponseMeasurements
@@ -432,8 +437,11 @@ mod tests_responder {
 
         let spdm_struct_slice = &u8_slice[2..];
         let mut reader = Reader::init(spdm_struct_slice);
-        let get_measurements =
-            SpdmGetMeasurementsRequestPayload::spdm_read(&mut context.common, &mut reader).unwrap();
+        let get_measurements = SpdmGetMeasurementsRequestPayload::spdm_read(
+            &context.common.get_decode_info(),
+            &mut reader,
+        )
+        .unwrap();
         assert_eq!(
             get_measurements.measurement_attributes,
             SpdmMeasurementeAttributes::empty()
@@ -446,7 +454,7 @@ mod tests_responder {
         let spdm_message_slice = &u8_slice[4..];
         let mut reader = Reader::init(spdm_message_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
         assert_eq!(
             spdm_message.header.request_response_code,
             SpdmResponseResponseCode::SpdmResponseMeasurements
@@ -10,10 +10,13 @@ mod certificate_rsp;
 mod challenge_rsp;
 mod digest_rsp;
 mod end_session_rsp;
+mod endpoint_info_rsp;
+mod event_rsp;
 mod finish_rsp;
 mod heartbeat_rsp;
 mod key_exchange_rsp;
 mod key_update_rsp;
+mod measurement_extension_log_rsp;
 mod measurement_rsp;
 mod psk_exchange_rsp;
 mod psk_finish_rsp;
@@ -22,7 +25,11 @@ mod version_rsp;
 mod error_rsp;
 mod vendor_rsp;
 
-pub use context::ResponderContext;
+pub use context::{
+    ResponderContext, SpdmAppMessageHandler, SpdmEndpointInfoProvider,
+    MAX_SPDM_APP_MESSAGE_HANDLER_COUNT,
+};
+pub use event_rsp::SpdmEventQueue;
 
 use crate::config;
 use crate::msgs::*;
@@ -22,7 +22,7 @@ impl<'a> ResponderContext<'a> {
         SpdmMessageHeader::read(&mut reader);
 
         let psk_exchange_req =
-            SpdmPskExchangeRequestPayload::spdm_read(&mut self.common, &mut reader);
+            SpdmPskExchangeRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(psk_exchange_req) = psk_exchange_req {
             debug!("!!! psk_exchange req : {:02x?}\n", psk_exchange_req);
 
@@ -35,12 +35,32 @@ impl<'a> ResponderContext<'a> {
             } else {
                 self.common.runtime_info.need_measurement_summary_hash = false;
             }
+            if let Some(psk_hint) = self.common.provision_info.psk_hint {
+                if psk_hint.as_ref() != psk_exchange_req.psk_hint.as_ref() {
+                    error!("!!! psk_exchange req : unknown psk hint !!!\n");
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                    return;
+                }
+            }
         } else {
             error!("!!! psk_exchange req : fail !!!\n");
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
             return;
         }
 
+        let peer_opaque_data =
+            SpdmGeneralOpaqueData::from_opaque(&psk_exchange_req.unwrap().opaque);
+        let secured_message_version = peer_opaque_data
+            .as_ref()
+            .and_then(|opaque_data| self.common.select_secured_message_version(opaque_data));
+        if secured_message_version.is_none() {
+            error!("!!! psk_exchange req : no common secured message version !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+        let secured_message_version = secured_message_version.unwrap();
+        let peer_opaque_data = peer_opaque_data.unwrap().get_app_elements();
+
         info!("send spdm psk_exchange rsp\n");
 
         let mut psk_context = [0u8; MAX_SPDM_PSK_CONTEXT_SIZE];
@@ -48,24 +68,27 @@ impl<'a> ResponderContext<'a> {
 
         let rsp_session_id = 0xFFFD;
 
-        let mut opaque = SpdmOpaqueStruct {
-            data_size: crate::common::OPAQUE_DATA_VERSION_SELECTION.len() as u16,
-            ..Default::default()
-        };
-        opaque.data[..(opaque.data_size as usize)]
-            .copy_from_slice(crate::common::OPAQUE_DATA_VERSION_SELECTION.as_ref());
+        let opaque = self
+            .common
+            .encode_version_selection_opaque(secured_message_version);
+        if opaque.is_err() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
+        }
+        let opaque = opaque.unwrap();
 
         if self.common.negotiate_info.base_hash_sel.is_empty() {
             return;
         }
 
+        let heartbeat_period = self.common.get_heartbeat_period();
         let response = SpdmMessage {
             header: SpdmMessageHeader {
                 version: SpdmVersion::SpdmVersion11,
                 request_response_code: SpdmResponseResponseCode::SpdmResponsePskExchangeRsp,
             },
             payload: SpdmMessagePayload::SpdmPskExchangeResponse(SpdmPskExchangeResponsePayload {
-                heartbeat_period: 0x0,
+                heartbeat_period,
                 rsp_session_id,
                 measurement_summary_hash: SpdmDigestStruct {
                     data_size: self.common.negotiate_info.base_hash_sel.get_size(),
@@ -116,8 +139,9 @@ impl<'a> ResponderContext<'a> {
         let aead_algo = self.common.negotiate_info.aead_sel;
         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
         let sequence_number_count = self.common.transport_encap.get_sequence_number_count();
-        let max_random_count = self.common.transport_encap.get_max_random_count();
+        let max_random_count = self.common.get_max_random_count();
 
+        let psk_key = self.common.get_psk();
         let session = self.common.get_next_avaiable_session();
         if session.is_none() {
             error!("!!! too many sessions : fail !!!\n");
@@ -130,11 +154,10 @@ impl<'a> ResponderContext<'a> {
             ((psk_exchange_req.unwrap().req_session_id as u32) << 16) + rsp_session_id as u32;
         session.setup(session_id).unwrap();
         session.set_use_psk(true);
-        let mut psk_key = SpdmDheFinalKeyStruct {
-            data_size: b"TestPskData\0".len() as u16,
-            ..Default::default()
-        };
-        psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskData\0");
+        session.session_policy = psk_exchange_req.unwrap().session_policy;
+        session.heartbeat_info.heartbeat_period = heartbeat_period;
+        session.set_secured_message_version(secured_message_version);
+        session.set_peer_opaque_data(&peer_opaque_data);
         session.set_crypto_param(hash_algo, dhe_algo, aead_algo, key_schedule_algo);
         session.set_transport_param(sequence_number_count, max_random_count);
         session.set_dhe_secret(&psk_key); // TBD
@@ -168,13 +191,14 @@ impl<'a> ResponderContext<'a> {
 
         let session = self.common.get_session_via_id(session_id).unwrap();
         session.set_session_state(crate::session::SpdmSessionState::SpdmSessionHandshaking);
+        self.common.record_session_activity(session_id);
     }
 }
 
 #[cfg(test)]
 mod tests_responder {
     use super::*;
-    use crate::config::{MAX_SPDM_OPAQUE_SIZE, MAX_SPDM_PSK_HINT_SIZE};
+    use crate::config::MAX_SPDM_PSK_HINT_SIZE;
     use crate::msgs::SpdmMessageHeader;
     use crate::testlib::*;
     use crate::{crypto, responder};
@@ -217,6 +241,7 @@ mod tests_responder {
             measurement_summary_hash_type:
                 SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
             req_session_id: 100u16,
+            session_policy: SpdmSessionPolicy::empty(),
             psk_hint: SpdmPskHintStruct {
                 data_size: 32,
                 data: [100u8; MAX_SPDM_PSK_HINT_SIZE],
@@ -225,10 +250,7 @@ mod tests_responder {
                 data_size: 64,
                 data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
             },
-            opaque: SpdmOpaqueStruct {
-                data_size: 64,
-                data: [100u8; MAX_SPDM_OPAQUE_SIZE],
-            },
+            opaque: context.common.encode_supported_version_opaque().unwrap(),
         };
         value.spdm_encode(&mut context.common, &mut writer);
 
@@ -237,4 +259,135 @@ mod tests_responder {
         bytes[2..].copy_from_slice(&challenge[0..1022]);
         context.handle_spdm_psk_exchange(bytes);
     }
+    #[test]
+    fn test_case1_handle_spdm_psk_exchange() {
+        let (mut config_info, provision_info) = create_info();
+        config_info.secured_message_version = [
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+            SpdmSecuredMessageVersion::default(),
+        ];
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+
+        // the requester only supports secured message version 1.1
+        let mut opaque_data = SpdmGeneralOpaqueData::default();
+        opaque_data
+            .add_element(
+                &SpdmOpaqueElement::new_supported_version(&[
+                    SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                ])
+                .unwrap(),
+            )
+            .unwrap();
+
+        let request = &mut [0u8; 1024];
+        let mut writer = Writer::init(request);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion11,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestPskExchange,
+        };
+        value.encode(&mut writer);
+        let value = SpdmPskExchangeRequestPayload {
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            req_session_id: 100u16,
+            session_policy: SpdmSessionPolicy::empty(),
+            psk_hint: SpdmPskHintStruct {
+                data_size: 32,
+                data: [100u8; MAX_SPDM_PSK_HINT_SIZE],
+            },
+            psk_context: SpdmPskContextStruct {
+                data_size: 64,
+                data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+            },
+            opaque: opaque_data.to_opaque().unwrap(),
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let used = writer.used();
+
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_psk_exchange_response(&request[..used], &mut writer);
+        let mut reader = Reader::init(writer.used_slice());
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+        assert_eq!(
+            SpdmErrorResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+                .unwrap()
+                .error_code,
+            SpdmErrorCode::SpdmErrorInvalidRequest
+        );
+    }
+    #[test]
+    fn test_case2_handle_spdm_psk_exchange() {
+        let (config_info, mut provision_info) = create_info();
+        let mut psk_hint = SpdmPskHintStruct {
+            data_size: 4,
+            ..Default::default()
+        };
+        psk_hint.data[..4].copy_from_slice(b"hint");
+        provision_info.psk_hint = Some(psk_hint);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        let shared_buffer = SharedBuffer::new();
+        let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+        let mut context = responder::ResponderContext::new(
+            &mut socket_io_transport,
+            pcidoe_transport_encap,
+            config_info,
+            provision_info,
+        );
+        context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+
+        // the requester asks for a different PSK
+        let request = &mut [0u8; 1024];
+        let mut writer = Writer::init(request);
+        let value = SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion11,
+            request_response_code: SpdmResponseResponseCode::SpdmRequestPskExchange,
+        };
+        value.encode(&mut writer);
+        let value = SpdmPskExchangeRequestPayload {
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            req_session_id: 100u16,
+            session_policy: SpdmSessionPolicy::empty(),
+            psk_hint: SpdmPskHintStruct {
+                data_size: 32,
+                data: [100u8; MAX_SPDM_PSK_HINT_SIZE],
+            },
+            psk_context: SpdmPskContextStruct {
+                data_size: 64,
+                data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+            },
+            opaque: context.common.encode_supported_version_opaque().unwrap(),
+        };
+        value.spdm_encode(&mut context.common, &mut writer);
+        let used = writer.used();
+
+        let response_buffer = &mut [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+        let mut writer = Writer::init(response_buffer);
+        context.write_spdm_psk_exchange_response(&request[..used], &mut writer);
+        let mut reader = Reader::init(writer.used_slice());
+        let spdm_message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            spdm_message_header.request_response_code,
+            SpdmResponseResponseCode::SpdmResponseError
+        );
+        assert!(context.common.get_session_via_id(0x0064_FFFD).is_none());
+    }
 }
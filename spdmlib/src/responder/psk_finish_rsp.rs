@@ -11,7 +11,7 @@ impl<'a> ResponderContext<'a> {
         let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
         if self.write_spdm_psk_finish_response(session_id, bytes, &mut writer) {
-            let _ = self.send_secured_message(session_id, writer.used_slice(), false);
+            let _ = self.send_secured_message(session_id, writer.used_slice(), None);
             // change state after message is sent.
             let session = self.common.get_session_via_id(session_id).unwrap();
             session.set_session_state(crate::session::SpdmSessionState::SpdmSessionEstablished);
@@ -30,7 +30,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let psk_finish_req = SpdmPskFinishRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let psk_finish_req =
+            SpdmPskFinishRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(psk_finish_req) = psk_finish_req {
             debug!("!!! psk_finish req : {:02x?}\n", psk_finish_req);
         } else {
@@ -123,7 +124,6 @@ impl<'a> ResponderContext<'a> {
 mod tests_responder {
     use super::*;
     use crate::msgs::SpdmMessageHeader;
-    use crate::session::SpdmSession;
     use crate::testlib::*;
     use crate::{crypto, responder};
     use codec::{Codec, Writer};
@@ -147,7 +147,7 @@ mod tests_responder {
         context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
         context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
         context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
-        context.common.session = [SpdmSession::new(); 4];
+        context.common.session = Default::default();
         context.common.session[0].setup(4294901758).unwrap();
         context.common.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -11,6 +11,6 @@ pub const TEST_VENDOR_RESPONSE_DATA: &[u8; 19] = &[
 ];
 impl<'a> ResponderContext<'a> {
     pub fn handle_spdm_vendor_defined_request(&mut self, session_id: u32, _bytes: &[u8]) {
-        let _ = self.send_secured_message(session_id, TEST_VENDOR_RESPONSE_DATA, false);
+        let _ = self.send_secured_message(session_id, TEST_VENDOR_RESPONSE_DATA, None);
     }
 }
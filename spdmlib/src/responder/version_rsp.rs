@@ -16,7 +16,8 @@ impl<'a> ResponderContext<'a> {
         let mut reader = Reader::init(bytes);
         SpdmMessageHeader::read(&mut reader);
 
-        let get_version = SpdmGetVersionRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let get_version =
+            SpdmGetVersionRequestPayload::spdm_read(&self.common.get_decode_info(), &mut reader);
         if let Some(get_version) = get_version {
             debug!("!!! get_version : {:02x?}\n", get_version);
         } else {
@@ -25,8 +26,9 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
-        // clear cache data
+        // clear cache data, a new negotiation supersedes the cached state
         self.common.reset_runtime_info();
+        self.common.cached_negotiated_state = None;
 
         if self
             .common
@@ -120,7 +122,7 @@ mod tests_responder {
         let u8_slice = &u8_slice[4..];
         let mut reader = Reader::init(u8_slice);
         let spdm_message: SpdmMessage =
-            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+            SpdmMessage::spdm_read(&context.common.get_decode_info(), &mut reader).unwrap();
 
         assert_eq!(spdm_message.header.version, SpdmVersion::SpdmVersion10);
         assert_eq!(
@@ -0,0 +1,115 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Key material of the sessions.
+//!
+//! A secret is wiped when it is dropped or reset, and it is not `Copy`, so
+//! every duplicate is an explicit `clone()`. With the `no-secret-log`
+//! feature (on by default) secrets are never logged.
+
+use crate::msgs::{SpdmAeadIvStruct, SpdmAeadKeyStruct, SpdmDheFinalKeyStruct, SpdmDigestStruct};
+use core::fmt::{Debug, Formatter, Result};
+use core::ops::{Deref, DerefMut};
+use zeroize::Zeroize;
+
+/// `debug!` for key material, compiled out with the `no-secret-log` feature.
+macro_rules! debug_secret {
+    ($($arg:tt)*) => {
+        if !cfg!(feature = "no-secret-log") {
+            debug!($($arg)*);
+        }
+    };
+}
+
+#[derive(Clone, Default)]
+pub struct SpdmSecret<T: Zeroize>(T);
+
+impl<T: Zeroize> SpdmSecret<T> {
+    pub fn new(value: T) -> Self {
+        SpdmSecret(value)
+    }
+
+    /// Wipe the secret now, it stays usable as an all-zero value.
+    pub fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> From<T> for SpdmSecret<T> {
+    fn from(value: T) -> Self {
+        SpdmSecret::new(value)
+    }
+}
+
+impl<T: Zeroize> Deref for SpdmSecret<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for SpdmSecret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for SpdmSecret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize + Debug> Debug for SpdmSecret<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if cfg!(feature = "no-secret-log") {
+            write!(f, "SpdmSecret(..)")
+        } else {
+            f.debug_tuple("SpdmSecret").field(&self.0).finish()
+        }
+    }
+}
+
+macro_rules! impl_zeroize {
+    ($($name:ident),*) => {
+        $(
+            impl Zeroize for $name {
+                fn zeroize(&mut self) {
+                    self.data_size.zeroize();
+                    self.data.zeroize();
+                }
+            }
+        )*
+    };
+}
+
+impl_zeroize!(
+    SpdmDigestStruct,
+    SpdmDheFinalKeyStruct,
+    SpdmAeadKeyStruct,
+    SpdmAeadIvStruct
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_spdm_secret() {
+        let mut secret = SpdmSecret::new(SpdmDigestStruct::from(&[0x5au8; 48][..]));
+        assert_eq!(secret.as_ref(), &[0x5au8; 48][..]);
+        let copy = secret.clone();
+        secret.zeroize();
+        assert_eq!(secret.data_size, 0);
+        assert!(secret.data.iter().all(|b| *b == 0));
+        assert_eq!(copy.data_size, 48);
+
+        let debug = format!("{:?}", copy);
+        if cfg!(feature = "no-secret-log") {
+            assert_eq!(debug, "SpdmSecret(..)");
+        } else {
+            assert!(debug.contains("90"));
+        }
+    }
+}
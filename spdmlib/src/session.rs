@@ -8,6 +8,7 @@ use crate::msgs::*;
 use crate::config;
 use crate::error::SpdmResult;
 use crate::key_schedule::SpdmKeySchedule;
+use crate::secret::SpdmSecret;
 
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
@@ -28,6 +29,14 @@ enum_builder! {
     }
 }
 
+/// Sequence numbers left before the limit of the transport when the keys
+/// are due for an update, enough for the KEY_UPDATE exchanges themselves.
+pub const SPDM_REKEY_SEQUENCE_NUMBER_MARGIN: u64 = 16;
+
+/// Draws of the random padding length before the random source is deemed
+/// broken. A working one is rejected at most half of the time.
+const SPDM_RANDOM_COUNT_MAX_ATTEMPTS: usize = 64;
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SpdmSessionCryptoParam {
     pub base_hash_algo: SpdmBaseHashAlgo,
@@ -36,35 +45,35 @@ pub struct SpdmSessionCryptoParam {
     pub key_schedule_algo: SpdmKeyScheduleAlgo,
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SpdmSessionMasterSecret {
-    pub dhe_secret: SpdmDheFinalKeyStruct,
-    pub handshake_secret: SpdmDigestStruct,
-    pub master_secret: SpdmDigestStruct,
+    pub dhe_secret: SpdmSecret<SpdmDheFinalKeyStruct>,
+    pub handshake_secret: SpdmSecret<SpdmDigestStruct>,
+    pub master_secret: SpdmSecret<SpdmDigestStruct>,
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SpdmSessionSecretParam {
-    pub encryption_key: SpdmAeadKeyStruct,
-    pub salt: SpdmAeadIvStruct,
+    pub encryption_key: SpdmSecret<SpdmAeadKeyStruct>,
+    pub salt: SpdmSecret<SpdmAeadIvStruct>,
     pub sequence_number: u64,
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SpdmSessionHandshakeSecret {
-    pub request_handshake_secret: SpdmDigestStruct,
-    pub response_handshake_secret: SpdmDigestStruct,
-    pub export_master_secret: SpdmDigestStruct,
-    pub request_finished_key: SpdmDigestStruct,
-    pub response_finished_key: SpdmDigestStruct,
+    pub request_handshake_secret: SpdmSecret<SpdmDigestStruct>,
+    pub response_handshake_secret: SpdmSecret<SpdmDigestStruct>,
+    pub export_master_secret: SpdmSecret<SpdmDigestStruct>,
+    pub request_finished_key: SpdmSecret<SpdmDigestStruct>,
+    pub response_finished_key: SpdmSecret<SpdmDigestStruct>,
     pub request_direction: SpdmSessionSecretParam,
     pub response_direction: SpdmSessionSecretParam,
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SpdmSessionAppliationSecret {
-    pub request_data_secret: SpdmDigestStruct,
-    pub response_data_secret: SpdmDigestStruct,
+    pub request_data_secret: SpdmSecret<SpdmDigestStruct>,
+    pub response_data_secret: SpdmSecret<SpdmDigestStruct>,
     pub request_direction: SpdmSessionSecretParam,
     pub response_direction: SpdmSessionSecretParam,
 }
@@ -81,7 +90,58 @@ pub struct SpdmSessionRuntimeInfo {
     pub message_f: ManagedBuffer,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSessionEventInfo {
+    pub subscribed_event_types: SpdmDmtfEventTypeFlags,
+    pub last_acked_event_instance_id: u32,
+    pub last_lost_event_instance_id: u32,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSessionHeartbeatInfo {
+    /// Heartbeat period in seconds of KEY_EXCHANGE_RSP/PSK_EXCHANGE_RSP, 0 for none.
+    pub heartbeat_period: u8,
+    /// Time in milliseconds of the last message of the session.
+    pub last_message_time: u64,
+}
+
+/// When the requester updates the keys of an established session, in
+/// addition to before the transport sequence number wraps. 0 disables a limit.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmRekeyPolicy {
+    /// Messages sent and received with the same keys.
+    pub max_message_count: u64,
+    /// Application data bytes sent and received with the same keys.
+    pub max_byte_count: u64,
+    /// Milliseconds the same keys are used, needs a time source.
+    pub max_key_lifetime_ms: u64,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSessionRekeyInfo {
+    pub policy: SpdmRekeyPolicy,
+    pub message_count: u64,
+    pub byte_count: u64,
+    /// Time in milliseconds the keys were last updated.
+    pub key_start_time: u64,
+}
+
+/// State of KEY_UPDATE. The keys before an update stay in the backup until
+/// activate_data_secret_update either drops them or restores them.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmSessionKeyUpdateInfo {
+    /// Operation and tag of the last KEY_UPDATE sent by the requester or
+    /// handled by the responder, to detect a retried request.
+    pub last_request: Option<(SpdmKeyUpdateOperation, u8)>,
+    /// The request direction key is updated, the old one is in the backup.
+    pub pending_requester: bool,
+    /// The response direction key is updated, the old one is in the backup.
+    pub pending_responder: bool,
+}
+
+/// The keys are wiped when the session is torn down or dropped. The session
+/// is not Copy, so that no stray copy of them is left behind.
+#[derive(Debug, Clone)]
 pub struct SpdmSession {
     session_id: u32,
     use_psk: bool,
@@ -92,7 +152,14 @@ pub struct SpdmSession {
     application_secret: SpdmSessionAppliationSecret,
     application_secret_backup: SpdmSessionAppliationSecret,
     transport_param: SpdmSessionTransportParam,
+    secured_message_version: SpdmSecuredMessageVersion,
+    peer_opaque_data: SpdmGeneralOpaqueData,
     pub runtime_info: SpdmSessionRuntimeInfo,
+    pub event_info: SpdmSessionEventInfo,
+    pub session_policy: SpdmSessionPolicy,
+    pub heartbeat_info: SpdmSessionHeartbeatInfo,
+    pub rekey_info: SpdmSessionRekeyInfo,
+    pub key_update_info: SpdmSessionKeyUpdateInfo,
     key_schedule: SpdmKeySchedule,
 }
 
@@ -114,7 +181,14 @@ impl SpdmSession {
             application_secret: SpdmSessionAppliationSecret::default(),
             application_secret_backup: SpdmSessionAppliationSecret::default(),
             transport_param: SpdmSessionTransportParam::default(),
+            secured_message_version: SpdmSecuredMessageVersion::default(),
+            peer_opaque_data: SpdmGeneralOpaqueData::default(),
             runtime_info: SpdmSessionRuntimeInfo::default(),
+            event_info: SpdmSessionEventInfo::default(),
+            session_policy: SpdmSessionPolicy::default(),
+            heartbeat_info: SpdmSessionHeartbeatInfo::default(),
+            rekey_info: SpdmSessionRekeyInfo::default(),
+            key_update_info: SpdmSessionKeyUpdateInfo::default(),
             key_schedule: SpdmKeySchedule::new(),
         }
     }
@@ -127,6 +201,14 @@ impl SpdmSession {
         self.master_secret = SpdmSessionMasterSecret::default();
         self.handshake_secret = SpdmSessionHandshakeSecret::default();
         self.application_secret = SpdmSessionAppliationSecret::default();
+        self.application_secret_backup = SpdmSessionAppliationSecret::default();
+        self.secured_message_version = SpdmSecuredMessageVersion::default();
+        self.peer_opaque_data = SpdmGeneralOpaqueData::default();
+        self.event_info = SpdmSessionEventInfo::default();
+        self.session_policy = SpdmSessionPolicy::default();
+        self.heartbeat_info = SpdmSessionHeartbeatInfo::default();
+        self.rekey_info = SpdmSessionRekeyInfo::default();
+        self.key_update_info = SpdmSessionKeyUpdateInfo::default();
     }
 
     pub fn get_session_id(&self) -> u32 {
@@ -157,29 +239,29 @@ impl SpdmSession {
     }
 
     pub fn set_dhe_secret(&mut self, dhe_secret: &SpdmDheFinalKeyStruct) {
-        self.master_secret.dhe_secret = *dhe_secret;
+        *self.master_secret.dhe_secret = *dhe_secret;
         let key = &self.master_secret.dhe_secret.as_ref();
 
         // generate master_secret.handshake_secret and master_secret.master_secret
-        let handshake_secret = self
+        *self.master_secret.handshake_secret = self
             .key_schedule
             .derive_handshake_secret(self.crypto_param.base_hash_algo, key)
             .unwrap();
 
-        let key = handshake_secret.as_ref();
-        let master_secret = self
+        let key = self.master_secret.handshake_secret.as_ref();
+        *self.master_secret.master_secret = self
             .key_schedule
             .derive_master_secret(self.crypto_param.base_hash_algo, key)
             .unwrap();
 
-        self.master_secret.handshake_secret = handshake_secret;
-        self.master_secret.master_secret = master_secret;
-
-        debug!(
+        debug_secret!(
             "!!! handshake_secret !!!: {:02x?}\n",
-            handshake_secret.as_ref()
+            self.master_secret.handshake_secret.as_ref()
+        );
+        debug_secret!(
+            "!!! master_secret !!!: {:02x?}\n",
+            self.master_secret.master_secret.as_ref()
         );
-        debug!("!!! master_secret !!!: {:02x?}\n", master_secret.as_ref());
     }
 
     pub fn set_crypto_param(
@@ -204,13 +286,35 @@ impl SpdmSession {
         self.session_state = session_state;
     }
 
+    pub fn get_session_state(&self) -> SpdmSessionState {
+        self.session_state
+    }
+
+    pub fn set_secured_message_version(&mut self, version: SpdmSecuredMessageVersion) {
+        self.secured_message_version = version;
+    }
+
+    pub fn get_secured_message_version(&self) -> SpdmSecuredMessageVersion {
+        self.secured_message_version
+    }
+
+    /// The opaque data elements received from the peer in KEY_EXCHANGE/PSK_EXCHANGE,
+    /// without the secured message version element.
+    pub fn set_peer_opaque_data(&mut self, opaque_data: &SpdmGeneralOpaqueData) {
+        self.peer_opaque_data = *opaque_data;
+    }
+
+    pub fn get_peer_opaque_data(&self) -> &SpdmGeneralOpaqueData {
+        &self.peer_opaque_data
+    }
+
     pub fn generate_handshake_secret(&mut self, th1: &SpdmDigestStruct) -> SpdmResult {
         // generate key
         info!("!!! generate_handshake_secret !!!:\n");
         let hash_algo = self.crypto_param.base_hash_algo;
         let aead_algo = self.crypto_param.aead_algo;
 
-        self.handshake_secret.request_handshake_secret = self
+        *self.handshake_secret.request_handshake_secret = self
             .key_schedule
             .derive_request_handshake_secret(
                 hash_algo,
@@ -218,11 +322,11 @@ impl SpdmSession {
                 th1.as_ref(),
             )
             .unwrap();
-        debug!(
+        debug_secret!(
             "!!! request_handshake_secret !!!: {:02x?}\n",
             self.handshake_secret.request_handshake_secret.as_ref()
         );
-        self.handshake_secret.response_handshake_secret = self
+        *self.handshake_secret.response_handshake_secret = self
             .key_schedule
             .derive_response_handshake_secret(
                 hash_algo,
@@ -230,29 +334,29 @@ impl SpdmSession {
                 th1.as_ref(),
             )
             .unwrap();
-        debug!(
+        debug_secret!(
             "!!! response_handshake_secret !!!: {:02x?}\n",
             self.handshake_secret.response_handshake_secret.as_ref()
         );
-        self.handshake_secret.request_finished_key = self
+        *self.handshake_secret.request_finished_key = self
             .key_schedule
             .derive_finished_key(
                 hash_algo,
                 self.handshake_secret.request_handshake_secret.as_ref(),
             )
             .unwrap();
-        debug!(
+        debug_secret!(
             "!!! request_finished_key !!!: {:02x?}\n",
             self.handshake_secret.request_finished_key.as_ref()
         );
-        self.handshake_secret.response_finished_key = self
+        *self.handshake_secret.response_finished_key = self
             .key_schedule
             .derive_finished_key(
                 hash_algo,
                 self.handshake_secret.response_handshake_secret.as_ref(),
             )
             .unwrap();
-        debug!(
+        debug_secret!(
             "!!! response_finished_key !!!: {:02x?}\n",
             self.handshake_secret.response_finished_key.as_ref()
         );
@@ -266,16 +370,16 @@ impl SpdmSession {
             )
             .unwrap();
 
-        self.handshake_secret.request_direction.encryption_key = res.0;
-        self.handshake_secret.request_direction.salt = res.1;
-        debug!(
+        *self.handshake_secret.request_direction.encryption_key = res.0;
+        *self.handshake_secret.request_direction.salt = res.1;
+        debug_secret!(
             "!!! request_direction.encryption_key !!!: {:02x?}\n",
             self.handshake_secret
                 .request_direction
                 .encryption_key
                 .as_ref()
         );
-        debug!(
+        debug_secret!(
             "!!! request_direction.salt !!!: {:02x?}\n",
             self.handshake_secret.request_direction.salt.as_ref()
         );
@@ -288,28 +392,20 @@ impl SpdmSession {
                 self.handshake_secret.response_handshake_secret.as_ref(),
             )
             .unwrap();
-        self.handshake_secret.response_direction.encryption_key = res.0;
-        self.handshake_secret.response_direction.salt = res.1;
-        debug!(
+        *self.handshake_secret.response_direction.encryption_key = res.0;
+        *self.handshake_secret.response_direction.salt = res.1;
+        debug_secret!(
             "!!! response_direction.encryption_key !!!: {:02x?}\n",
             self.handshake_secret
                 .response_direction
                 .encryption_key
                 .as_ref()
         );
-        debug!(
+        debug_secret!(
             "!!! response_direction.salt !!!: {:02x?}\n",
             self.handshake_secret.response_direction.salt.as_ref()
         );
 
-        self.handshake_secret.export_master_secret = self
-            .key_schedule
-            .derive_export_master_secret(
-                hash_algo,
-                self.handshake_secret.export_master_secret.as_ref(),
-            )
-            .unwrap();
-
         Ok(())
     }
 
@@ -319,7 +415,7 @@ impl SpdmSession {
         let hash_algo = self.crypto_param.base_hash_algo;
         let aead_algo = self.crypto_param.aead_algo;
 
-        self.application_secret.request_data_secret = self
+        *self.application_secret.request_data_secret = self
             .key_schedule
             .derive_request_data_secret(
                 hash_algo,
@@ -327,7 +423,7 @@ impl SpdmSession {
                 th2.as_ref(),
             )
             .unwrap();
-        self.application_secret.response_data_secret = self
+        *self.application_secret.response_data_secret = self
             .key_schedule
             .derive_response_data_secret(
                 hash_algo,
@@ -335,15 +431,24 @@ impl SpdmSession {
                 th2.as_ref(),
             )
             .unwrap();
-        debug!(
+        debug_secret!(
             "!!! request_data_secret !!!: {:02x?}\n",
             self.application_secret.request_data_secret.as_ref()
         );
-        debug!(
+        debug_secret!(
             "!!! response_data_secret !!!: {:02x?}\n",
             self.application_secret.response_data_secret.as_ref()
         );
 
+        *self.handshake_secret.export_master_secret = self
+            .key_schedule
+            .derive_export_master_secret(
+                hash_algo,
+                self.master_secret.master_secret.as_ref(),
+                th2.as_ref(),
+            )
+            .unwrap();
+
         let res = self
             .key_schedule
             .derive_aead_key_iv(
@@ -352,16 +457,16 @@ impl SpdmSession {
                 self.application_secret.request_data_secret.as_ref(),
             )
             .unwrap();
-        self.application_secret.request_direction.encryption_key = res.0;
-        self.application_secret.request_direction.salt = res.1;
-        debug!(
+        *self.application_secret.request_direction.encryption_key = res.0;
+        *self.application_secret.request_direction.salt = res.1;
+        debug_secret!(
             "!!! request_direction.encryption_key !!!: {:02x?}\n",
             self.application_secret
                 .request_direction
                 .encryption_key
                 .as_ref()
         );
-        debug!(
+        debug_secret!(
             "!!! request_direction.salt !!!: {:02x?}\n",
             self.application_secret.request_direction.salt.as_ref()
         );
@@ -374,16 +479,16 @@ impl SpdmSession {
                 self.application_secret.response_data_secret.as_ref(),
             )
             .unwrap();
-        self.application_secret.response_direction.encryption_key = res.0;
-        self.application_secret.response_direction.salt = res.1;
-        debug!(
+        *self.application_secret.response_direction.encryption_key = res.0;
+        *self.application_secret.response_direction.salt = res.1;
+        debug_secret!(
             "!!! response_direction.encryption_key !!!: {:02x?}\n",
             self.application_secret
                 .response_direction
                 .encryption_key
                 .as_ref()
         );
-        debug!(
+        debug_secret!(
             "!!! response_direction.salt !!!: {:02x?}\n",
             self.application_secret.response_direction.salt.as_ref()
         );
@@ -405,18 +510,18 @@ impl SpdmSession {
 
         if update_requester {
             self.application_secret_backup.request_data_secret =
-                self.application_secret.request_data_secret;
+                self.application_secret.request_data_secret.clone();
             self.application_secret_backup.request_direction =
-                self.application_secret.request_direction;
+                self.application_secret.request_direction.clone();
 
-            self.application_secret.request_data_secret = self
+            *self.application_secret.request_data_secret = self
                 .key_schedule
                 .derive_update_secret(
                     hash_algo,
                     self.application_secret.request_data_secret.as_ref(),
                 )
                 .unwrap();
-            debug!(
+            debug_secret!(
                 "!!! request_data_secret !!!: {:02x?}\n",
                 self.application_secret.request_data_secret.as_ref()
             );
@@ -429,36 +534,37 @@ impl SpdmSession {
                     self.application_secret.request_data_secret.as_ref(),
                 )
                 .unwrap();
-            self.application_secret.request_direction.encryption_key = res.0;
-            self.application_secret.request_direction.salt = res.1;
-            debug!(
+            *self.application_secret.request_direction.encryption_key = res.0;
+            *self.application_secret.request_direction.salt = res.1;
+            debug_secret!(
                 "!!! request_direction.encryption_key !!!: {:02x?}\n",
                 self.application_secret
                     .request_direction
                     .encryption_key
                     .as_ref()
             );
-            debug!(
+            debug_secret!(
                 "!!! request_direction.salt !!!: {:02x?}\n",
                 self.application_secret.request_direction.salt.as_ref()
             );
             self.application_secret.request_direction.sequence_number = 0;
+            self.key_update_info.pending_requester = true;
         }
 
         if update_responder {
             self.application_secret_backup.response_data_secret =
-                self.application_secret.response_data_secret;
+                self.application_secret.response_data_secret.clone();
             self.application_secret_backup.response_direction =
-                self.application_secret.response_direction;
+                self.application_secret.response_direction.clone();
 
-            self.application_secret.response_data_secret = self
+            *self.application_secret.response_data_secret = self
                 .key_schedule
                 .derive_update_secret(
                     hash_algo,
                     self.application_secret.response_data_secret.as_ref(),
                 )
                 .unwrap();
-            debug!(
+            debug_secret!(
                 "!!! response_data_secret !!!: {:02x?}\n",
                 self.application_secret.response_data_secret.as_ref()
             );
@@ -471,23 +577,26 @@ impl SpdmSession {
                     self.application_secret.response_data_secret.as_ref(),
                 )
                 .unwrap();
-            self.application_secret.response_direction.encryption_key = res.0;
-            self.application_secret.response_direction.salt = res.1;
-            debug!(
+            *self.application_secret.response_direction.encryption_key = res.0;
+            *self.application_secret.response_direction.salt = res.1;
+            debug_secret!(
                 "!!! response_direction.encryption_key !!!: {:02x?}\n",
                 self.application_secret
                     .response_direction
                     .encryption_key
                     .as_ref()
             );
-            debug!(
+            debug_secret!(
                 "!!! response_direction.salt !!!: {:02x?}\n",
                 self.application_secret.response_direction.salt.as_ref()
             );
             self.application_secret.response_direction.sequence_number = 0;
+            self.key_update_info.pending_responder = true;
         }
         Ok(())
     }
+
+    /// Keep the updated keys, or restore the keys before the update.
     pub fn activate_data_secret_update(
         &mut self,
         update_requester: bool,
@@ -497,28 +606,34 @@ impl SpdmSession {
         if !use_new_key {
             if update_requester {
                 self.application_secret.request_data_secret =
-                    self.application_secret_backup.request_data_secret;
+                    core::mem::take(&mut self.application_secret_backup.request_data_secret);
                 self.application_secret.request_direction =
-                    self.application_secret_backup.request_direction;
+                    core::mem::take(&mut self.application_secret_backup.request_direction);
             }
             if update_responder {
                 self.application_secret.response_data_secret =
-                    self.application_secret_backup.response_data_secret;
+                    core::mem::take(&mut self.application_secret_backup.response_data_secret);
                 self.application_secret.response_direction =
-                    self.application_secret_backup.response_direction;
+                    core::mem::take(&mut self.application_secret_backup.response_direction);
             }
         } else {
             if update_requester {
-                self.application_secret_backup.request_data_secret = SpdmDigestStruct::default();
+                self.application_secret_backup.request_data_secret = SpdmSecret::default();
                 self.application_secret_backup.request_direction =
                     SpdmSessionSecretParam::default();
             }
             if update_responder {
-                self.application_secret_backup.response_data_secret = SpdmDigestStruct::default();
+                self.application_secret_backup.response_data_secret = SpdmSecret::default();
                 self.application_secret_backup.response_direction =
                     SpdmSessionSecretParam::default();
             }
         }
+        if update_requester {
+            self.key_update_info.pending_requester = false;
+        }
+        if update_responder {
+            self.key_update_info.pending_responder = false;
+        }
         Ok(())
     }
 
@@ -572,69 +687,159 @@ impl SpdmSession {
         )
     }
 
+    /// Derive keying material for the application from the export master
+    /// secret of an established session: HKDF-Expand(export_master_secret,
+    /// BinConcat(length, version, label, context), length).
+    pub fn export_keying_material(
+        &self,
+        label: &[u8],
+        context: &[u8],
+        length: usize,
+    ) -> SpdmResult<SpdmSecret<SpdmDigestStruct>> {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return spdm_result_err!(EINVAL);
+        }
+        if length == 0 || length > SPDM_MAX_HASH_SIZE {
+            return spdm_result_err!(EINVAL);
+        }
+        self.key_schedule
+            .derive_exported_key(
+                self.crypto_param.base_hash_algo,
+                self.handshake_secret.export_master_secret.as_ref(),
+                label,
+                context,
+                length as u16,
+            )
+            .map(SpdmSecret::new)
+            .ok_or(spdm_err!(EFAULT))
+    }
+
     pub fn export_keys(&mut self) -> (SpdmSessionSecretParam, SpdmSessionSecretParam) {
         (
-            SpdmSessionSecretParam {
-                encryption_key: self.application_secret.request_direction.encryption_key,
-                salt: self.application_secret.request_direction.salt,
-                sequence_number: self.application_secret.request_direction.sequence_number,
-            },
-            SpdmSessionSecretParam {
-                encryption_key: self.application_secret.response_direction.encryption_key,
-                salt: self.application_secret.response_direction.salt,
-                sequence_number: self.application_secret.response_direction.sequence_number,
-            },
+            self.application_secret.request_direction.clone(),
+            self.application_secret.response_direction.clone(),
         )
     }
 
+    /// Write what an established session needs to carry on in another
+    /// context: its parameters, application secrets and sequence numbers.
+    pub fn encode_resume_state(&self, writer: &mut Writer) -> SpdmResult {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return spdm_result_err!(EINVAL);
+        }
+        if self.key_update_info.pending_requester || self.key_update_info.pending_responder {
+            return spdm_result_err!(EBUSY);
+        }
+        encode_resume_field(writer, &self.session_id)?;
+        encode_resume_field(writer, &(self.use_psk as u8))?;
+        encode_resume_field(writer, &self.crypto_param.base_hash_algo)?;
+        encode_resume_field(writer, &self.crypto_param.dhe_algo)?;
+        encode_resume_field(writer, &self.crypto_param.aead_algo)?;
+        encode_resume_field(writer, &self.crypto_param.key_schedule_algo)?;
+        encode_resume_field(writer, &self.secured_message_version)?;
+        encode_resume_field(writer, &self.transport_param.sequence_number_count)?;
+        encode_resume_field(writer, &self.transport_param.max_random_count)?;
+        encode_resume_field(writer, &self.session_policy)?;
+        encode_resume_field(writer, &self.heartbeat_info.heartbeat_period)?;
+        encode_resume_field(writer, &self.rekey_info.policy.max_message_count)?;
+        encode_resume_field(writer, &self.rekey_info.policy.max_byte_count)?;
+        encode_resume_field(writer, &self.rekey_info.policy.max_key_lifetime_ms)?;
+        encode_resume_field(writer, &self.rekey_info.message_count)?;
+        encode_resume_field(writer, &self.rekey_info.byte_count)?;
+        encode_secret_data(writer, self.handshake_secret.export_master_secret.as_ref())?;
+        for (data_secret, direction) in [
+            (
+                &self.application_secret.request_data_secret,
+                &self.application_secret.request_direction,
+            ),
+            (
+                &self.application_secret.response_data_secret,
+                &self.application_secret.response_direction,
+            ),
+        ] {
+            encode_secret_data(writer, data_secret.as_ref())?;
+            encode_secret_data(writer, direction.encryption_key.as_ref())?;
+            encode_secret_data(writer, direction.salt.as_ref())?;
+            encode_resume_field(writer, &direction.sequence_number)?;
+        }
+        Ok(())
+    }
+
+    /// Restore an established session of encode_resume_state into this free
+    /// session. Nothing is kept on failure.
+    pub fn read_resume_state(&mut self, reader: &mut Reader) -> SpdmResult {
+        if self.session_id != 0 {
+            return spdm_result_err!(EBUSY);
+        }
+        if self.read_resume_state_fields(reader).is_none() || self.session_id == 0 {
+            self.set_default();
+            return spdm_result_err!(EINVAL);
+        }
+        self.session_state = SpdmSessionState::SpdmSessionEstablished;
+        Ok(())
+    }
+
+    fn read_resume_state_fields(&mut self, reader: &mut Reader) -> Option<()> {
+        self.set_default();
+        self.session_id = u32::read(reader)?;
+        self.use_psk = u8::read(reader)? != 0;
+        self.crypto_param.base_hash_algo = SpdmBaseHashAlgo::read(reader)?;
+        self.crypto_param.dhe_algo = SpdmDheAlgo::read(reader)?;
+        self.crypto_param.aead_algo = SpdmAeadAlgo::read(reader)?;
+        self.crypto_param.key_schedule_algo = SpdmKeyScheduleAlgo::read(reader)?;
+        self.secured_message_version = SpdmSecuredMessageVersion::read(reader)?;
+        self.transport_param.sequence_number_count = u8::read(reader)?;
+        self.transport_param.max_random_count = u16::read(reader)?;
+        self.session_policy = SpdmSessionPolicy::read(reader)?;
+        self.heartbeat_info.heartbeat_period = u8::read(reader)?;
+        self.rekey_info.policy.max_message_count = u64::read(reader)?;
+        self.rekey_info.policy.max_byte_count = u64::read(reader)?;
+        self.rekey_info.policy.max_key_lifetime_ms = u64::read(reader)?;
+        self.rekey_info.message_count = u64::read(reader)?;
+        self.rekey_info.byte_count = u64::read(reader)?;
+        let export_master_secret = &mut *self.handshake_secret.export_master_secret;
+        read_secret_data(
+            reader,
+            &mut export_master_secret.data_size,
+            &mut export_master_secret.data,
+        )?;
+        for (data_secret, direction) in [
+            (
+                &mut self.application_secret.request_data_secret,
+                &mut self.application_secret.request_direction,
+            ),
+            (
+                &mut self.application_secret.response_data_secret,
+                &mut self.application_secret.response_direction,
+            ),
+        ] {
+            let data_secret = &mut **data_secret;
+            read_secret_data(reader, &mut data_secret.data_size, &mut data_secret.data)?;
+            let encryption_key = &mut *direction.encryption_key;
+            read_secret_data(
+                reader,
+                &mut encryption_key.data_size,
+                &mut encryption_key.data,
+            )?;
+            let salt = &mut *direction.salt;
+            read_secret_data(reader, &mut salt.data_size, &mut salt.data)?;
+            direction.sequence_number = u64::read(reader)?;
+        }
+        Some(())
+    }
+
     pub fn encode_spdm_secured_message(
         &mut self,
         app_buffer: &[u8],
         secured_buffer: &mut [u8],
         is_requester: bool,
     ) -> SpdmResult<usize> {
-        match self.session_state {
-            SpdmSessionState::SpdmSessionNotStarted => spdm_result_err!(EINVAL),
-            SpdmSessionState::SpdmSessionHandshaking => {
-                if is_requester {
-                    let r = self.encode_msg(
-                        app_buffer,
-                        secured_buffer,
-                        &self.handshake_secret.request_direction,
-                    );
-                    self.handshake_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.encode_msg(
-                        app_buffer,
-                        secured_buffer,
-                        &self.handshake_secret.response_direction,
-                    );
-                    self.handshake_secret.response_direction.sequence_number += 1;
-                    r
-                }
-            }
-            SpdmSessionState::SpdmSessionEstablished => {
-                if is_requester {
-                    let r = self.encode_msg(
-                        app_buffer,
-                        secured_buffer,
-                        &self.application_secret.request_direction,
-                    );
-                    self.application_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.encode_msg(
-                        app_buffer,
-                        secured_buffer,
-                        &self.application_secret.response_direction,
-                    );
-                    self.application_secret.response_direction.sequence_number += 1;
-                    r
-                }
-            }
-            _ => panic!("unknown session state"),
+        let secret_param = self.next_secret_param(is_requester)?;
+        let r = self.encode_msg(app_buffer, secured_buffer, &secret_param);
+        if r.is_ok() {
+            self.record_rekey_usage(app_buffer.len());
         }
+        r
     }
 
     pub fn decode_spdm_secured_message(
@@ -643,48 +848,131 @@ impl SpdmSession {
         app_buffer: &mut [u8],
         is_requester: bool,
     ) -> SpdmResult<usize> {
-        match self.session_state {
-            SpdmSessionState::SpdmSessionNotStarted => spdm_result_err!(EINVAL),
-            SpdmSessionState::SpdmSessionHandshaking => {
-                if is_requester {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.handshake_secret.request_direction,
-                    );
-                    self.handshake_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.handshake_secret.response_direction,
-                    );
-                    self.handshake_secret.response_direction.sequence_number += 1;
-                    r
-                }
+        let secret_param = self.next_secret_param(is_requester)?;
+        let mut r = self.decode_msg(secured_buffer, app_buffer, &secret_param);
+        if r.is_err() && is_requester && self.key_update_info.pending_requester {
+            // the requester did not get KEY_UPDATE_ACK and still uses the
+            // request key before the update, so the update is rolled back
+            let backup = self.application_secret_backup.request_direction.clone();
+            r = self.decode_msg(secured_buffer, app_buffer, &backup);
+            if r.is_ok() {
+                info!("!!! key update rolled back !!!\n");
+                let update_responder = self.key_update_info.pending_responder;
+                self.activate_data_secret_update(true, update_responder, false)?;
+                self.application_secret.request_direction.sequence_number += 1;
+                self.key_update_info.last_request = None;
             }
-            SpdmSessionState::SpdmSessionEstablished => {
-                if is_requester {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.application_secret.request_direction,
-                    );
-                    self.application_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.application_secret.response_direction,
-                    );
-                    self.application_secret.response_direction.sequence_number += 1;
-                    r
-                }
+        }
+        if let Ok(app_length) = r {
+            self.record_rekey_usage(app_length);
+        }
+        r
+    }
+
+    // Return the keys of the current session state for the request or the
+    // response direction, and advance the sequence number of the direction.
+    fn next_secret_param(&mut self, request_direction: bool) -> SpdmResult<SpdmSessionSecretParam> {
+        let sequence_number_limit = self.get_sequence_number_limit();
+        let secret_param = match (self.session_state, request_direction) {
+            (SpdmSessionState::SpdmSessionHandshaking, true) => {
+                &mut self.handshake_secret.request_direction
+            }
+            (SpdmSessionState::SpdmSessionHandshaking, false) => {
+                &mut self.handshake_secret.response_direction
+            }
+            (SpdmSessionState::SpdmSessionEstablished, true) => {
+                &mut self.application_secret.request_direction
+            }
+            (SpdmSessionState::SpdmSessionEstablished, false) => {
+                &mut self.application_secret.response_direction
+            }
+            _ => return spdm_result_err!(EINVAL),
+        };
+        // neither the nonce nor the transport sequence number may repeat,
+        // the keys have to be updated instead
+        if secret_param.sequence_number >= sequence_number_limit {
+            error!("sequence number exhausted!\n");
+            return spdm_result_err!(ERANGE);
+        }
+        let current = secret_param.clone();
+        secret_param.sequence_number += 1;
+        Ok(current)
+    }
+
+    fn record_rekey_usage(&mut self, app_length: usize) {
+        if self.session_state == SpdmSessionState::SpdmSessionEstablished {
+            self.rekey_info.message_count += 1;
+            self.rekey_info.byte_count += app_length as u64;
+        }
+    }
+
+    /// The sequence number from which the transport sequence number of
+    /// sequence_number_count bytes repeats, u64::MAX without a wrap.
+    fn get_sequence_number_limit(&self) -> u64 {
+        match self.transport_param.sequence_number_count {
+            count @ 1..=7 => 1u64 << (8 * count as u32),
+            _ => u64::MAX,
+        }
+    }
+
+    /// Whether the requester should update the keys of the session before
+    /// sending another message, according to the rekey policy, or because
+    /// the sequence numbers get close to the limit of the transport.
+    /// now is the current time in milliseconds, if known.
+    pub fn is_rekey_due(&self, now: Option<u64>) -> bool {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return false;
+        }
+        let sequence_number_limit =
+            self.get_sequence_number_limit() - SPDM_REKEY_SEQUENCE_NUMBER_MARGIN;
+        if self.application_secret.request_direction.sequence_number >= sequence_number_limit
+            || self.application_secret.response_direction.sequence_number >= sequence_number_limit
+        {
+            return true;
+        }
+
+        let rekey_info = &self.rekey_info;
+        let policy = &rekey_info.policy;
+        if policy.max_message_count != 0 && rekey_info.message_count >= policy.max_message_count {
+            return true;
+        }
+        if policy.max_byte_count != 0 && rekey_info.byte_count >= policy.max_byte_count {
+            return true;
+        }
+        match now {
+            Some(now) if policy.max_key_lifetime_ms != 0 => {
+                now.saturating_sub(rekey_info.key_start_time) >= policy.max_key_lifetime_ms
+            }
+            _ => false,
+        }
+    }
+
+    /// Restart the rekey policy counters after the keys are updated.
+    pub fn reset_rekey_info(&mut self, now: Option<u64>) {
+        self.rekey_info.message_count = 0;
+        self.rekey_info.byte_count = 0;
+        self.rekey_info.key_start_time = now.unwrap_or(0);
+    }
+
+    /// A random padding length up to max_random_count and the space left.
+    fn get_random_count(&self, space: usize) -> SpdmResult<usize> {
+        let max_random_count = (self.transport_param.max_random_count as usize).min(space);
+        if max_random_count == 0 {
+            return Ok(0);
+        }
+        // the values below 2^16 % range are rejected, so that the others
+        // make whole ranges and every length is equally likely
+        let range = max_random_count as u32 + 1;
+        let threshold = 0x1_0000 % range;
+        for _ in 0..SPDM_RANDOM_COUNT_MAX_ATTEMPTS {
+            let mut random = [0u8; 2];
+            crypto::rand::get_random(&mut random)?;
+            let random = u16::from_le_bytes(random) as u32;
+            if random >= threshold {
+                return Ok((random % range) as usize);
             }
-            _ => return spdm_result_err!(ENOMEM),
         }
+        spdm_result_err!(EFAULT)
     }
 
     fn encode_msg(
@@ -697,8 +985,18 @@ impl SpdmSession {
         let aead_algo = self.crypto_param.aead_algo;
         let transport_param = &self.transport_param;
 
-        let cipher_text_size = app_buffer.len() + 2;
         let tag_size = aead_algo.get_tag_size() as usize;
+        let aad_size = 6 + transport_param.sequence_number_count as usize;
+        if app_buffer.len() + 2 > config::MAX_SPDM_MESSAGE_BUFFER_SIZE
+            || aad_size + app_buffer.len() + 2 + tag_size > secured_buffer.len()
+        {
+            return spdm_result_err!(ENOMEM);
+        }
+        let random_count = self.get_random_count(
+            (secured_buffer.len() - aad_size - app_buffer.len() - 2 - tag_size)
+                .min(config::MAX_SPDM_MESSAGE_BUFFER_SIZE - app_buffer.len() - 2),
+        )?;
+        let cipher_text_size = app_buffer.len() + 2 + random_count;
 
         let mut aad_buffer = [0u8; 6 + 8];
         let mut writer = Writer::init(&mut aad_buffer);
@@ -713,8 +1011,7 @@ impl SpdmSession {
             }
         }
         length.encode(&mut writer);
-        let aad_size = writer.used();
-        assert_eq!(aad_size, 6 + transport_param.sequence_number_count as usize);
+        assert_eq!(writer.used(), aad_size);
 
         let mut plain_text_buf = [0; config::MAX_SPDM_MESSAGE_BUFFER_SIZE];
         let mut writer = Writer::init(&mut plain_text_buf);
@@ -722,6 +1019,11 @@ impl SpdmSession {
         let head_size = writer.used();
         assert_eq!(head_size, 2);
         plain_text_buf[head_size..(head_size + app_buffer.len())].copy_from_slice(app_buffer);
+        if random_count != 0 {
+            crypto::rand::get_random(
+                &mut plain_text_buf[(head_size + app_buffer.len())..cipher_text_size],
+            )?;
+        }
 
         let mut tag_buffer = [0u8; 16];
 
@@ -800,6 +1102,9 @@ impl SpdmSession {
         }
 
         let cipher_text_size = length as usize - tag_size;
+        if cipher_text_size > config::MAX_SPDM_MESSAGE_BUFFER_SIZE {
+            return spdm_result_err!(EINVAL);
+        }
 
         let mut plain_text_buf = [0; config::MAX_SPDM_MESSAGE_BUFFER_SIZE];
 
@@ -825,17 +1130,50 @@ impl SpdmSession {
             &mut plain_text_buf[..cipher_text_size],
         )?;
 
-        let mut reader = Reader::init(&plain_text_buf);
+        // the random data after the application data is ignored
+        let mut reader = Reader::init(&plain_text_buf[..ret_plain_text_size]);
         let app_length = u16::read(&mut reader).ok_or(spdm_err!(EIO))? as usize;
         if ret_plain_text_size < app_length + 2 {
             return spdm_result_err!(EINVAL);
         }
+        if app_buffer.len() < app_length {
+            return spdm_result_err!(ENOMEM);
+        }
 
         app_buffer[..app_length].copy_from_slice(&plain_text_buf[2..(app_length + 2)]);
         Ok(app_length)
     }
 }
 
+/// Encode a field of the resume state, or fail if it does not fit. The
+/// codecs would silently drop what does not fit in the writer.
+pub(crate) fn encode_resume_field<T: Codec>(writer: &mut Writer, field: &T) -> SpdmResult {
+    // the fields are at most a u64
+    let mut field_buffer = [0u8; 8];
+    let mut field_writer = Writer::init(&mut field_buffer);
+    field.encode(&mut field_writer);
+    writer
+        .extend_from_slice(field_writer.used_slice())
+        .ok_or(spdm_err!(ENOMEM))?;
+    Ok(())
+}
+
+fn encode_secret_data(writer: &mut Writer, data: &[u8]) -> SpdmResult {
+    encode_resume_field(writer, &(data.len() as u16))?;
+    writer.extend_from_slice(data).ok_or(spdm_err!(ENOMEM))?;
+    Ok(())
+}
+
+fn read_secret_data(reader: &mut Reader, data_size: &mut u16, data: &mut [u8]) -> Option<()> {
+    let size = u16::read(reader)?;
+    if size as usize > data.len() {
+        return None;
+    }
+    data[..size as usize].copy_from_slice(reader.take(size as usize)?);
+    *data_size = size;
+    Some(())
+}
+
 #[cfg(test)]
 mod tests_session {
     use super::*;
@@ -878,11 +1216,13 @@ mod tests_session {
             encryption_key: SpdmAeadKeyStruct {
                 data_size: 50,
                 data: [10u8; SPDM_MAX_AEAD_KEY_SIZE],
-            },
+            }
+            .into(),
             salt: SpdmAeadIvStruct {
                 data_size: 50,
                 data: [10u8; SPDM_MAX_AEAD_IV_SIZE],
-            },
+            }
+            .into(),
             sequence_number: 100u64,
         };
         session.transport_param.sequence_number_count = 1;
@@ -934,6 +1274,346 @@ mod tests_session {
         assert!(status);
     }
     #[test]
+    fn test_case0_random_padding() {
+        let mut requester = SpdmSession::default();
+        requester.setup(4294901758u32).unwrap();
+        requester.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        requester.set_transport_param(2, 32);
+        let mut responder = requester.clone();
+        let mut secured_buffer = [0u8; 128];
+        let mut app_buffer = [0u8; 64];
+
+        // aad 8, app_length 2, app data 3 and tag 16
+        for _ in 0..8 {
+            let used = requester
+                .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer, true)
+                .unwrap();
+            assert!((29..=29 + 32).contains(&used));
+            let app_length = responder
+                .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+                .unwrap();
+            assert_eq!(&app_buffer[..app_length], &[1, 2, 3]);
+        }
+
+        // no room for padding
+        let used = requester
+            .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer[..29], true)
+            .unwrap();
+        assert_eq!(used, 29);
+        assert!(requester
+            .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer[..28], true)
+            .is_err());
+
+        // the application data does not fit
+        let used = responder
+            .encode_spdm_secured_message(&[4, 5, 6], &mut secured_buffer, false)
+            .unwrap();
+        assert!(requester
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer[..2], false)
+            .is_err());
+    }
+    #[test]
+    fn test_case0_key_update_rollback() {
+        let mut requester = SpdmSession::default();
+        requester.setup(4294901758u32).unwrap();
+        requester.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        let mut responder = requester.clone();
+        let mut secured_buffer = [0u8; 64];
+        let mut app_buffer = [0u8; 64];
+
+        // the requester did not get the KEY_UPDATE_ACK of UpdateAllKeys
+        responder.create_data_secret_update(true, true).unwrap();
+        responder.key_update_info.last_request =
+            Some((SpdmKeyUpdateOperation::SpdmUpdateAllKeys, 1));
+        let used = requester
+            .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer, true)
+            .unwrap();
+        let app_length = responder
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+            .unwrap();
+        assert_eq!(&app_buffer[..app_length], &[1, 2, 3]);
+        assert!(!responder.key_update_info.pending_requester);
+        assert!(!responder.key_update_info.pending_responder);
+        assert!(responder.key_update_info.last_request.is_none());
+        let used = responder
+            .encode_spdm_secured_message(&[4, 5], &mut secured_buffer, false)
+            .unwrap();
+        assert!(requester
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, false)
+            .is_ok());
+
+        // the requester uses the new key, the old one is kept until VerifyNewKey
+        responder.create_data_secret_update(true, false).unwrap();
+        requester.create_data_secret_update(true, false).unwrap();
+        requester
+            .activate_data_secret_update(true, false, true)
+            .unwrap();
+        let used = requester
+            .encode_spdm_secured_message(&[6], &mut secured_buffer, true)
+            .unwrap();
+        assert!(responder
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+            .is_ok());
+        assert!(responder.key_update_info.pending_requester);
+
+        // a message protected by neither key
+        let used = requester
+            .encode_spdm_secured_message(&[7], &mut secured_buffer, true)
+            .unwrap();
+        secured_buffer[used - 1] ^= 0xff;
+        assert!(responder
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+            .is_err());
+        assert!(responder.key_update_info.pending_requester);
+    }
+    #[test]
+    fn test_case0_sequence_number_exhausted() {
+        let mut session = SpdmSession::default();
+        let mut secured_buffer = [0u8; 64];
+        let status = session
+            .encode_spdm_secured_message(&[0u8; 4], &mut secured_buffer, true)
+            .is_err();
+        assert!(status);
+
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.application_secret.request_direction.sequence_number = u64::MAX;
+        let status = session
+            .encode_spdm_secured_message(&[0u8; 4], &mut secured_buffer, true)
+            .is_err();
+        assert!(status);
+        assert_eq!(
+            session.application_secret.request_direction.sequence_number,
+            u64::MAX
+        );
+        let status = session
+            .decode_spdm_secured_message(&secured_buffer, &mut [0u8; 64], true)
+            .is_err();
+        assert!(status);
+    }
+    #[test]
+    fn test_case1_sequence_number_exhausted() {
+        let mut requester = SpdmSession::default();
+        requester.setup(4294901758u32).unwrap();
+        requester.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        requester.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        // a 16 bit transport sequence number
+        requester.set_transport_param(2, 0);
+        requester
+            .application_secret
+            .request_direction
+            .sequence_number = 0xFFFF;
+        requester
+            .application_secret
+            .response_direction
+            .sequence_number = 0xFFFF;
+        let mut responder = requester.clone();
+        let mut secured_buffer = [0u8; 64];
+        let mut app_buffer = [0u8; 64];
+
+        let used = requester
+            .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer, true)
+            .unwrap();
+        assert!(responder
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+            .is_ok());
+        let used = responder
+            .encode_spdm_secured_message(&[4, 5, 6], &mut secured_buffer, false)
+            .unwrap();
+        assert!(requester
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, false)
+            .is_ok());
+
+        // both sides refuse to go on in either direction
+        assert!(requester
+            .encode_spdm_secured_message(&[1, 2, 3], &mut secured_buffer, true)
+            .is_err());
+        assert!(responder
+            .encode_spdm_secured_message(&[4, 5, 6], &mut secured_buffer, false)
+            .is_err());
+        assert!(responder
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)
+            .is_err());
+        assert!(requester
+            .decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, false)
+            .is_err());
+        assert_eq!(
+            responder
+                .application_secret
+                .request_direction
+                .sequence_number,
+            0x10000
+        );
+    }
+    #[test]
+    fn test_case0_is_rekey_due() {
+        let mut session = SpdmSession::default();
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.set_transport_param(2, 32);
+        assert!(!session.is_rekey_due(None));
+
+        // before the 2 byte transport sequence number wraps
+        session
+            .application_secret
+            .response_direction
+            .sequence_number = 0x10000 - SPDM_REKEY_SEQUENCE_NUMBER_MARGIN;
+        assert!(session.is_rekey_due(None));
+        session
+            .application_secret
+            .response_direction
+            .sequence_number = 0;
+
+        session.rekey_info.policy = SpdmRekeyPolicy {
+            max_message_count: 3,
+            max_byte_count: 100,
+            max_key_lifetime_ms: 1000,
+        };
+        session.reset_rekey_info(Some(500));
+        session.record_rekey_usage(10);
+        session.record_rekey_usage(10);
+        assert!(!session.is_rekey_due(Some(1499)));
+        assert!(session.is_rekey_due(Some(1500)));
+        session.record_rekey_usage(10);
+        assert!(session.is_rekey_due(None));
+
+        session.reset_rekey_info(None);
+        session.record_rekey_usage(100);
+        assert!(session.is_rekey_due(None));
+
+        session.set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+        assert!(!session.is_rekey_due(None));
+    }
+    #[test]
+    fn test_case0_export_keying_material() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        *session.master_secret.handshake_secret = SpdmDigestStruct::from(&[0xa5u8; 48][..]);
+        *session.master_secret.master_secret = SpdmDigestStruct::from(&[0x5au8; 48][..]);
+        let th1 = SpdmDigestStruct::from(&[0x11u8; 48][..]);
+        session.generate_handshake_secret(&th1).unwrap();
+        let th2 = SpdmDigestStruct::from(&[0x22u8; 48][..]);
+        session.generate_data_secret(&th2).unwrap();
+
+        // Computed with OpenSSL HKDF-Expand (python cryptography), BinConcat as in
+        // libspdm: u16 length | "spdm1.1 " | label | context.
+        // HKDF-Expand-SHA384(master_secret, 0x3000 | "spdm1.1 " | "exp master" | TH2, 48)
+        let export_master_secret = [
+            0x66, 0x6b, 0x5f, 0x9f, 0x6c, 0x2a, 0x60, 0xe5, 0xfa, 0xa9, 0xed, 0xa3, 0xb2, 0x2a,
+            0xf4, 0x57, 0x2b, 0xee, 0xc3, 0x52, 0x18, 0x74, 0xdf, 0x03, 0xc9, 0x26, 0x0b, 0x5f,
+            0x53, 0xe5, 0x30, 0x0d, 0xbb, 0x60, 0x85, 0x75, 0xa1, 0x08, 0x4f, 0xee, 0x1e, 0x9b,
+            0xfa, 0x19, 0x10, 0x7f, 0xb0, 0xd0,
+        ];
+        assert_eq!(
+            session.handshake_secret.export_master_secret.as_ref(),
+            &export_master_secret[..]
+        );
+
+        let label = b"ide key";
+        let context = [1u8, 2, 3, 4];
+        assert!(session.export_keying_material(label, &context, 32).is_err());
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+        // HKDF-Expand-SHA384(export_master_secret, 0x2000 | "spdm1.1 " | label | context, 32)
+        let exported_key = [
+            0x1a, 0x4a, 0xb7, 0xcc, 0xc4, 0x36, 0x62, 0xb5, 0xf1, 0x5c, 0x52, 0x0f, 0x33, 0xe0,
+            0x95, 0xd4, 0x46, 0x18, 0xd1, 0x43, 0xd7, 0x2a, 0xe5, 0xc9, 0x23, 0xe5, 0x4a, 0x3b,
+            0x2a, 0xb0, 0xa3, 0xa6,
+        ];
+        let key = session.export_keying_material(label, &context, 32).unwrap();
+        assert_eq!(key.as_ref(), &exported_key[..]);
+        assert_ne!(
+            session
+                .export_keying_material(b"other key", &context, 32)
+                .unwrap()
+                .as_ref(),
+            &exported_key[..]
+        );
+        assert!(session.export_keying_material(label, &context, 0).is_err());
+        assert!(session
+            .export_keying_material(label, &context, SPDM_MAX_HASH_SIZE + 1)
+            .is_err());
+    }
+    #[test]
+    fn test_case0_encode_resume_state() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.session_policy = SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE;
+        session.rekey_info.policy.max_message_count = 1000;
+        *session.application_secret.request_data_secret = SpdmDigestStruct::from(&[0xa5u8; 48][..]);
+        session
+            .application_secret
+            .response_direction
+            .sequence_number = 7;
+
+        let mut state = [0u8; 512];
+        let mut writer = Writer::init(&mut state);
+        session.encode_resume_state(&mut writer).unwrap();
+        let size = writer.used();
+
+        // a state which exactly fills the buffer
+        let mut state = [0u8; 512];
+        let mut writer = Writer::init(&mut state[..size]);
+        assert!(session.encode_resume_state(&mut writer).is_ok());
+        assert_eq!(writer.used(), size);
+        let mut resumed = SpdmSession::default();
+        let mut reader = Reader::init(&state[..size]);
+        assert!(resumed.read_resume_state(&mut reader).is_ok());
+        assert!(!reader.any_left());
+        assert_eq!(resumed.get_session_id(), 4294901758u32);
+        assert_eq!(
+            resumed.session_policy,
+            SpdmSessionPolicy::TERMINATION_POLICY_RUNTIME_UPDATE
+        );
+        assert_eq!(resumed.rekey_info.policy.max_message_count, 1000);
+        assert_eq!(
+            resumed.application_secret.request_data_secret.as_ref(),
+            &[0xa5u8; 48][..]
+        );
+        assert_eq!(
+            resumed
+                .application_secret
+                .response_direction
+                .sequence_number,
+            7
+        );
+
+        // no field is silently dropped from a truncated state
+        for truncated_size in 0..size {
+            let mut state = [0u8; 512];
+            let mut writer = Writer::init(&mut state[..truncated_size]);
+            assert!(session.encode_resume_state(&mut writer).is_err());
+        }
+    }
+    #[test]
     #[should_panic]
     fn test_case0_setup() {
         let mut session = SpdmSession::default();
@@ -949,4 +1629,65 @@ mod tests_session {
         let session_id = 4294901758u32;
         let _ = session.teardown(session_id).is_err();
     }
+    #[test]
+    fn test_case1_teardown() {
+        let mut session = SpdmSession::default();
+        let session_id = 4294901758u32;
+        session.setup(session_id).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        let dhe_secret = SpdmDheFinalKeyStruct {
+            data_size: 48,
+            data: [0x5au8; SPDM_MAX_DHE_KEY_SIZE],
+        };
+        let th = SpdmDigestStruct::from(&[0xa5u8; 48][..]);
+        session.set_dhe_secret(&dhe_secret);
+        session.generate_handshake_secret(&th).unwrap();
+        session.generate_data_secret(&th).unwrap();
+        session.create_data_secret_update(true, true).unwrap();
+
+        let is_zero = |data: &[u8]| data.iter().all(|b| *b == 0);
+        let is_wiped = |session: &SpdmSession| {
+            let master = &session.master_secret;
+            let handshake = &session.handshake_secret;
+            is_zero(&master.dhe_secret.data)
+                && is_zero(&master.handshake_secret.data)
+                && is_zero(&master.master_secret.data)
+                && is_zero(&handshake.request_handshake_secret.data)
+                && is_zero(&handshake.response_handshake_secret.data)
+                && is_zero(&handshake.request_finished_key.data)
+                && is_zero(&handshake.response_finished_key.data)
+                && [
+                    &handshake.request_direction,
+                    &handshake.response_direction,
+                    &session.application_secret.request_direction,
+                    &session.application_secret.response_direction,
+                    &session.application_secret_backup.request_direction,
+                    &session.application_secret_backup.response_direction,
+                ]
+                .iter()
+                .all(|param| is_zero(&param.encryption_key.data) && is_zero(&param.salt.data))
+                && [
+                    &session.application_secret,
+                    &session.application_secret_backup,
+                ]
+                .iter()
+                .all(|secret| {
+                    is_zero(&secret.request_data_secret.data)
+                        && is_zero(&secret.response_data_secret.data)
+                })
+        };
+        assert!(!is_wiped(&session));
+        assert!(!is_zero(
+            &session.application_secret_backup.request_data_secret.data
+        ));
+
+        session.teardown(session_id).unwrap();
+        assert!(is_wiped(&session));
+        assert_eq!(session.get_session_id(), 0);
+    }
 }
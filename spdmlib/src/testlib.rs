@@ -11,7 +11,7 @@ use crate::msgs::*;
 use crate::{spdm_err, spdm_result_err};
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::path::PathBuf;
 
@@ -40,7 +40,8 @@ pub fn new_spdm_message(value: SpdmMessage, mut context: SpdmContext) -> SpdmMes
     let mut writer = Writer::init(u8_slice);
     value.spdm_encode(&mut context, &mut writer);
     let mut reader = Reader::init(u8_slice);
-    let spdm_message: SpdmMessage = SpdmMessage::spdm_read(&mut context, &mut reader).unwrap();
+    let spdm_message: SpdmMessage =
+        SpdmMessage::spdm_read(&context.get_decode_info(), &mut reader).unwrap();
     spdm_message
 }
 
@@ -68,6 +69,10 @@ pub fn create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo) {
         aead_algo: SpdmAeadAlgo::AES_256_GCM,
         req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
         key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        secured_message_version: [
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+            SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+        ],
         ..Default::default()
     };
 
@@ -98,6 +103,8 @@ pub fn create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo) {
         my_cert_chain: None,
         peer_cert_chain_data: Some(my_cert_chain_data),
         peer_cert_chain_root_hash: None,
+        psk: None,
+        psk_hint: None,
     };
 
     (config_info, provision_info)
@@ -243,8 +250,12 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        _is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
+        // the binding carries no application message type
+        if app_message_type.is_some() {
+            return spdm_result_err!(EINVAL);
+        }
         app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
         Ok(spdm_buffer.len())
     }
@@ -253,9 +264,9 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
-        Ok((app_buffer.len(), false))
+        Ok((app_buffer.len(), None))
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
@@ -316,13 +327,13 @@ fn sign_ecdsa_asym_algo(
     })
 }
 
-pub struct FakeSpdmDeviceIo<'a> {
+pub struct FakeSpdmDeviceIo<'a, 'b> {
     pub data: &'a SharedBuffer,
-    pub responder: &'a mut responder::ResponderContext<'a>,
+    pub responder: &'a mut responder::ResponderContext<'b>,
 }
 
-impl<'a> FakeSpdmDeviceIo<'a> {
-    pub fn new(data: &'a SharedBuffer, responder: &'a mut responder::ResponderContext<'a>) -> Self {
+impl<'a, 'b> FakeSpdmDeviceIo<'a, 'b> {
+    pub fn new(data: &'a SharedBuffer, responder: &'a mut responder::ResponderContext<'b>) -> Self {
         FakeSpdmDeviceIo {
             data: data,
             responder,
@@ -330,7 +341,7 @@ impl<'a> FakeSpdmDeviceIo<'a> {
     }
 }
 
-impl SpdmDeviceIo for FakeSpdmDeviceIo<'_> {
+impl SpdmDeviceIo for FakeSpdmDeviceIo<'_, '_> {
     fn receive(&mut self, read_buffer: &mut [u8]) -> Result<usize, usize> {
         let len = self.data.get_buffer(read_buffer);
         log::info!("requester receive RAW - {:02x?}\n", &read_buffer[0..len]);
@@ -352,6 +363,60 @@ impl SpdmDeviceIo for FakeSpdmDeviceIo<'_> {
     }
 }
 
+pub const FAKE_ENDPOINT_INFO: &[u8] = &[
+    0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x5a, 0x5a, 0x5a, 0x5a,
+];
+pub const FAKE_MEL_SIZE: usize = 1300;
+
+pub struct FakeSpdmEndpointInfoProvider {
+    pub mel: [u8; FAKE_MEL_SIZE],
+}
+
+impl FakeSpdmEndpointInfoProvider {
+    pub fn new() -> Self {
+        let mut mel = [0u8; FAKE_MEL_SIZE];
+        for (i, d) in mel.iter_mut().enumerate() {
+            *d = i as u8;
+        }
+        FakeSpdmEndpointInfoProvider { mel }
+    }
+}
+
+impl responder::SpdmEndpointInfoProvider for FakeSpdmEndpointInfoProvider {
+    fn get_endpoint_info(
+        &mut self,
+        sub_code: SpdmEndpointInfoSubCode,
+        ep_info: &mut [u8],
+    ) -> SpdmResult<usize> {
+        if sub_code != SpdmEndpointInfoSubCode::SpdmEndpointInfoSubCodeDeviceClassIdentifier {
+            return spdm_result_err!(EINVAL);
+        }
+        ep_info[..FAKE_ENDPOINT_INFO.len()].copy_from_slice(FAKE_ENDPOINT_INFO);
+        Ok(FAKE_ENDPOINT_INFO.len())
+    }
+
+    fn get_mel_size(&mut self) -> u32 {
+        FAKE_MEL_SIZE as u32
+    }
+
+    fn read_mel(&mut self, offset: u32, mel_portion: &mut [u8]) -> SpdmResult {
+        let offset = offset as usize;
+        mel_portion.copy_from_slice(&self.mel[offset..(offset + mel_portion.len())]);
+        Ok(())
+    }
+}
+
+/// Time source reading a clock the test moves forward.
+pub struct FakeSpdmTimeSource<'a> {
+    pub now: &'a Cell<u64>,
+}
+
+impl SpdmTimeSource for FakeSpdmTimeSource<'_> {
+    fn get_time_ms(&mut self) -> u64 {
+        self.now.get()
+    }
+}
+
 pub struct SpdmDeviceIoReceve<'a> {
     data: &'a SharedBuffer,
     fuzzdata: &'a [u8],
@@ -6,7 +6,9 @@ mod common;
 use common::fake_device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve};
 use common::shared_buffer::SharedBuffer;
 
+use mctp_transport::{MctpMessageType, MctpTransportEncap};
 use pcidoe_transport::PciDoeTransportEncap;
+use spdmlib::error::SpdmResult;
 use spdmlib::msgs::SpdmMeasurementSummaryHashType;
 use spdmlib::requester;
 use spdmlib::responder;
@@ -78,7 +80,91 @@ fn intergration_client_server() {
             "response_direction.salt {:0x?}\n",
             response_direction.salt.as_ref()
         );
+
+        let key = requester_context
+            .export_keying_material(session_id, b"ide key", &[0], 32)
+            .expect("export keying material failed!");
+        assert_eq!(key.as_ref().len(), 32);
     } else {
         log::info!("\nSession session_id not got ????? \n");
     }
 }
+
+struct EchoAppMessageHandler {}
+
+impl responder::SpdmAppMessageHandler for EchoAppMessageHandler {
+    fn dispatch(
+        &mut self,
+        _session_id: u32,
+        _app_message_type: u8,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> SpdmResult<usize> {
+        response[..request.len()].copy_from_slice(request);
+        response[0] |= 0x80;
+        Ok(request.len())
+    }
+}
+
+#[test]
+fn intergration_client_server_app_message() {
+    spdmlib::crypto::asym_sign::register(common::crypto_callbacks::ASYM_SIGN_IMPL);
+
+    let pldm = MctpMessageType::MctpMessageTypePldm.get_u8();
+    let nvme = MctpMessageType::MctpMessageTypeNvme.get_u8();
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut MctpTransportEncap {};
+    let handler = &mut EchoAppMessageHandler {};
+
+    let (config_info, provision_info) = common::utils::rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+    responder_context
+        .register_app_message_handler(pldm, handler)
+        .unwrap();
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut MctpTransportEncap {};
+
+    let (config_info, provision_info) = common::utils::req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest().is_ok());
+    assert!(requester_context.send_receive_spdm_certificate(0).is_ok());
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+
+    let request = [0x00u8, 0x00, 0x02];
+    let mut response = [0u8; 64];
+    let used = requester_context
+        .send_receive_app_message(session_id, pldm, &request, &mut response)
+        .unwrap();
+    assert_eq!(&response[..used], &[0x80, 0x00, 0x02]);
+
+    // no handler for NVMe-MI, the responder does not answer
+    assert!(requester_context
+        .send_receive_app_message(session_id, nvme, &request, &mut response)
+        .is_err());
+
+    // the session still works for SPDM
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(session_id)
+        .is_ok());
+}
@@ -0,0 +1,351 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmTransportEncap;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+/// Security protocol assigned to DMTF SPDM, used in Security Send/Receive.
+pub const STORAGE_SECURITY_PROTOCOL_DMTF_SPDM: u8 = 0xE8;
+pub const STORAGE_SPDM_BINDING_VERSION: u8 = 0x10;
+pub const STORAGE_SPDM_CONNECTION_ID_MASK: u8 = 0x3;
+pub const STORAGE_TRANSPORT_HEADER_SIZE: usize = 8;
+pub const STORAGE_SEQUENCE_NUMBER_COUNT: u8 = 8;
+pub const STORAGE_MAX_RANDOM_COUNT: u16 = 0;
+
+enum_builder! {
+    @U8
+    EnumName: StorageSpdmOperationCode;
+    EnumVal{
+        StorageSpdmOperationCodeDiscovery => 0x01,
+        StorageSpdmOperationCodePendingInfo => 0x02,
+        StorageSpdmOperationCodeMessage => 0x05,
+        StorageSpdmOperationCodeSecuredMessage => 0x06
+    }
+}
+
+/// SP Specific field of Security Send/Receive.
+/// SPSP0 holds the connection ID in bits 1:0 and the operation code in bits 7:2, SPSP1 is reserved.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct StorageSecurityProtocolSpecific {
+    pub connection_id: u8,
+    pub operation: StorageSpdmOperationCode,
+}
+
+impl Codec for StorageSecurityProtocolSpecific {
+    fn encode(&self, bytes: &mut Writer) {
+        let spsp0 =
+            (self.operation.get_u8() << 2) | (self.connection_id & STORAGE_SPDM_CONNECTION_ID_MASK);
+        spsp0.encode(bytes);
+        0u8.encode(bytes); // spsp1
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageSecurityProtocolSpecific> {
+        let spsp0 = u8::read(r)?;
+        u8::read(r)?; // spsp1
+        let operation = StorageSpdmOperationCode::read_bytes(&[spsp0 >> 2])?;
+        if let StorageSpdmOperationCode::Unknown(_) = operation {
+            return None;
+        }
+        Some(StorageSecurityProtocolSpecific {
+            connection_id: spsp0 & STORAGE_SPDM_CONNECTION_ID_MASK,
+            operation,
+        })
+    }
+}
+
+/// The Security Send/Receive command fields carrying an SPDM message.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageTransportHeader {
+    pub security_protocol: u8,
+    pub security_protocol_specific: StorageSecurityProtocolSpecific,
+    pub inc_512: bool,
+    pub length: u32, // in bytes, or in 512 bytes units when inc_512 is set
+}
+
+impl StorageTransportHeader {
+    pub fn get_transfer_length(&self) -> usize {
+        if self.inc_512 {
+            self.length as usize * 512
+        } else {
+            self.length as usize
+        }
+    }
+}
+
+impl Codec for StorageTransportHeader {
+    fn encode(&self, bytes: &mut Writer) {
+        self.security_protocol.encode(bytes);
+        self.security_protocol_specific.encode(bytes);
+        (self.inc_512 as u8).encode(bytes);
+        self.length.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageTransportHeader> {
+        let security_protocol = u8::read(r)?;
+        let security_protocol_specific = StorageSecurityProtocolSpecific::read(r)?;
+        let inc_512 = match u8::read(r)? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let length = u32::read(r)?;
+        Some(StorageTransportHeader {
+            security_protocol,
+            security_protocol_specific,
+            inc_512,
+            length,
+        })
+    }
+}
+
+/// Response to a Security Receive with the discovery operation.
+/// supported_operations lists the operation codes, unused entries are 0.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageSpdmDiscoveryResponse {
+    pub binding_version: u8,
+    pub max_connection_id: u8,
+    pub supported_operations: [u8; 8],
+}
+
+pub const STORAGE_SPDM_DISCOVERY_RESPONSE_SIZE: usize = 16;
+
+impl Codec for StorageSpdmDiscoveryResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        (STORAGE_SPDM_DISCOVERY_RESPONSE_SIZE as u16).encode(bytes); // data_length
+        self.binding_version.encode(bytes);
+        self.max_connection_id.encode(bytes);
+        0u32.encode(bytes); // reserved
+        for operation in self.supported_operations.iter() {
+            operation.encode(bytes);
+        }
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageSpdmDiscoveryResponse> {
+        let data_length = u16::read(r)?;
+        if data_length as usize != STORAGE_SPDM_DISCOVERY_RESPONSE_SIZE {
+            return None;
+        }
+        let binding_version = u8::read(r)?;
+        let max_connection_id = u8::read(r)?;
+        u32::read(r)?; // reserved
+        let mut supported_operations = [0u8; 8];
+        for operation in supported_operations.iter_mut() {
+            *operation = u8::read(r)?;
+        }
+        Some(StorageSpdmDiscoveryResponse {
+            binding_version,
+            max_connection_id,
+            supported_operations,
+        })
+    }
+}
+
+pub const STORAGE_SPDM_PENDING_INFO_FLAG_RESPONSE_PENDING: u32 = 0x1;
+pub const STORAGE_SPDM_PENDING_INFO_RESPONSE_SIZE: usize = 12;
+
+/// Response to a Security Receive with the pending info operation.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageSpdmPendingInfoResponse {
+    pub binding_version: u8,
+    pub pending_info_flag: u32,
+    pub response_length: u32,
+}
+
+impl Codec for StorageSpdmPendingInfoResponse {
+    fn encode(&self, bytes: &mut Writer) {
+        (STORAGE_SPDM_PENDING_INFO_RESPONSE_SIZE as u16).encode(bytes); // data_length
+        self.binding_version.encode(bytes);
+        0u8.encode(bytes); // reserved
+        self.pending_info_flag.encode(bytes);
+        self.response_length.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageSpdmPendingInfoResponse> {
+        let data_length = u16::read(r)?;
+        if data_length as usize != STORAGE_SPDM_PENDING_INFO_RESPONSE_SIZE {
+            return None;
+        }
+        let binding_version = u8::read(r)?;
+        u8::read(r)?; // reserved
+        let pending_info_flag = u32::read(r)?;
+        let response_length = u32::read(r)?;
+        Some(StorageSpdmPendingInfoResponse {
+            binding_version,
+            pending_info_flag,
+            response_length,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageTransportEncap {
+    pub connection_id: u8,
+}
+
+impl SpdmTransportEncap for StorageTransportEncap {
+    fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        let payload_len = spdm_buffer.len();
+        if transport_buffer.len() < STORAGE_TRANSPORT_HEADER_SIZE + payload_len {
+            return spdm_result_err!(EINVAL);
+        }
+        let mut writer = Writer::init(&mut *transport_buffer);
+        let storage_header = StorageTransportHeader {
+            security_protocol: STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+            security_protocol_specific: StorageSecurityProtocolSpecific {
+                connection_id: self.connection_id,
+                operation: if secured_message {
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage
+                } else {
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeMessage
+                },
+            },
+            inc_512: false,
+            length: payload_len as u32,
+        };
+        storage_header.encode(&mut writer);
+        let header_size = writer.used();
+        transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(spdm_buffer);
+        Ok(header_size + payload_len)
+    }
+
+    fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        let mut reader = Reader::init(transport_buffer);
+        let storage_header = StorageTransportHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if storage_header.security_protocol != STORAGE_SECURITY_PROTOCOL_DMTF_SPDM {
+            return spdm_result_err!(EINVAL);
+        }
+        let spsp = storage_header.security_protocol_specific;
+        if spsp.connection_id != self.connection_id {
+            return spdm_result_err!(EINVAL);
+        }
+        let secured_message = match spsp.operation {
+            StorageSpdmOperationCode::StorageSpdmOperationCodeMessage => false,
+            StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage => true,
+            _ => return spdm_result_err!(EINVAL),
+        };
+        let header_size = reader.used();
+        let payload_len = storage_header.get_transfer_length();
+        if transport_buffer.len() - header_size != payload_len {
+            return spdm_result_err!(EINVAL);
+        }
+        if spdm_buffer.len() < payload_len {
+            return spdm_result_err!(EINVAL);
+        }
+        let payload = &transport_buffer[header_size..];
+        spdm_buffer[..payload_len].copy_from_slice(payload);
+        Ok((payload_len, secured_message))
+    }
+
+    fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult<usize> {
+        // the binding carries no application message type
+        if app_message_type.is_some() {
+            return spdm_result_err!(EINVAL);
+        }
+        app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
+        Ok(spdm_buffer.len())
+    }
+
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_sequence_number_count(&mut self) -> u8 {
+        STORAGE_SEQUENCE_NUMBER_COUNT
+    }
+    fn get_max_random_count(&mut self) -> u16 {
+        STORAGE_MAX_RANDOM_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_storage_transport_header() {
+        let u8_slice = &mut [0u8; STORAGE_TRANSPORT_HEADER_SIZE];
+        let mut writer = Writer::init(u8_slice);
+        let value = StorageTransportHeader {
+            security_protocol: STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+            security_protocol_specific: StorageSecurityProtocolSpecific {
+                connection_id: 2,
+                operation: StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage,
+            },
+            inc_512: true,
+            length: 2,
+        };
+        value.encode(&mut writer);
+        assert_eq!(u8_slice, &[0xE8, 0x1A, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00]);
+
+        let mut reader = Reader::init(u8_slice);
+        let storage_header = StorageTransportHeader::read(&mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(storage_header.security_protocol_specific.connection_id, 2);
+        assert_eq!(storage_header.get_transfer_length(), 1024);
+
+        // unknown operation code
+        u8_slice[1] = 0x0E;
+        let mut reader = Reader::init(u8_slice);
+        assert!(StorageTransportHeader::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_encap_decap() {
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 1 };
+        let spdm_buffer = [0x11, 0x84, 0x00, 0x00, 0x01];
+        let mut transport_buffer = [0u8; 32];
+
+        let used = storage_transport_encap
+            .encap(&spdm_buffer, &mut transport_buffer, true)
+            .unwrap();
+        assert_eq!(used, STORAGE_TRANSPORT_HEADER_SIZE + spdm_buffer.len());
+
+        let mut buffer = [0u8; 32];
+        let (size, secured_message) = storage_transport_encap
+            .decap(&transport_buffer[..used], &mut buffer)
+            .unwrap();
+        assert!(secured_message);
+        assert_eq!(&buffer[..size], &spdm_buffer);
+
+        // length does not match
+        assert!(storage_transport_encap
+            .decap(&transport_buffer[..used - 1], &mut buffer)
+            .is_err());
+        // another connection
+        let mut other_encap = StorageTransportEncap { connection_id: 2 };
+        assert!(other_encap
+            .decap(&transport_buffer[..used], &mut buffer)
+            .is_err());
+        // another security protocol
+        transport_buffer[0] = 0xEA;
+        assert!(storage_transport_encap
+            .decap(&transport_buffer[..used], &mut buffer)
+            .is_err());
+
+        assert!(storage_transport_encap
+            .encap(&spdm_buffer, &mut transport_buffer[..12], false)
+            .is_err());
+    }
+}
@@ -0,0 +1,463 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::*;
+use codec::{Codec, Reader, Writer};
+use core::cell::RefCell;
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::config;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const STORAGE_SECURITY_QUEUE_DEPTH: usize = 4;
+
+const STORAGE_SECURITY_DATA_SIZE: usize = config::MAX_SPDM_TRANSPORT_SIZE;
+
+#[derive(Copy, Clone)]
+struct StorageSecurityData {
+    spsp: StorageSecurityProtocolSpecific,
+    size: usize,
+    data: [u8; STORAGE_SECURITY_DATA_SIZE],
+}
+
+impl Default for StorageSecurityData {
+    fn default() -> Self {
+        StorageSecurityData {
+            spsp: StorageSecurityProtocolSpecific::default(),
+            size: 0,
+            data: [0u8; STORAGE_SECURITY_DATA_SIZE],
+        }
+    }
+}
+
+/// Model of the Security Send/Receive command queue of a storage device.
+///
+/// The host side issues Security Send and Security Receive commands,
+/// the device side fetches the data of each Security Send and posts the
+/// response returned by the next Security Receive. Discovery and pending
+/// info are answered by the queue itself, as the storage controller would.
+pub struct StorageSecurityCommandQueue {
+    max_connection_id: u8,
+    commands: [StorageSecurityData; STORAGE_SECURITY_QUEUE_DEPTH],
+    head: usize,
+    count: usize,
+    response: StorageSecurityData,
+    response_pending: bool,
+}
+
+impl StorageSecurityCommandQueue {
+    pub fn new(max_connection_id: u8) -> Self {
+        StorageSecurityCommandQueue {
+            max_connection_id: max_connection_id & STORAGE_SPDM_CONNECTION_ID_MASK,
+            commands: [StorageSecurityData::default(); STORAGE_SECURITY_QUEUE_DEPTH],
+            head: 0,
+            count: 0,
+            response: StorageSecurityData::default(),
+            response_pending: false,
+        }
+    }
+
+    fn check_command(
+        &self,
+        security_protocol: u8,
+        spsp: StorageSecurityProtocolSpecific,
+    ) -> SpdmResult {
+        if security_protocol != STORAGE_SECURITY_PROTOCOL_DMTF_SPDM {
+            return spdm_result_err!(EINVAL);
+        }
+        if spsp.connection_id > self.max_connection_id {
+            return spdm_result_err!(EINVAL);
+        }
+        Ok(())
+    }
+
+    /// Security Send, only SPDM messages and secured messages can be sent.
+    pub fn security_send(
+        &mut self,
+        security_protocol: u8,
+        spsp: StorageSecurityProtocolSpecific,
+        data: &[u8],
+    ) -> SpdmResult {
+        self.check_command(security_protocol, spsp)?;
+        match spsp.operation {
+            StorageSpdmOperationCode::StorageSpdmOperationCodeMessage
+            | StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage => {}
+            _ => return spdm_result_err!(EINVAL),
+        }
+        if data.is_empty() || data.len() > STORAGE_SECURITY_DATA_SIZE {
+            return spdm_result_err!(EINVAL);
+        }
+        if self.count == STORAGE_SECURITY_QUEUE_DEPTH {
+            return spdm_result_err!(EBUSY);
+        }
+        let command = &mut self.commands[(self.head + self.count) % STORAGE_SECURITY_QUEUE_DEPTH];
+        command.spsp = spsp;
+        command.size = data.len();
+        command.data[..data.len()].copy_from_slice(data);
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Security Receive, return the number of bytes transferred.
+    ///
+    /// A message receive returns 0 bytes if no response is pending, and fails
+    /// if the allocation length is too small, leaving the response pending.
+    pub fn security_receive(
+        &mut self,
+        security_protocol: u8,
+        spsp: StorageSecurityProtocolSpecific,
+        data: &mut [u8],
+    ) -> SpdmResult<usize> {
+        self.check_command(security_protocol, spsp)?;
+        match spsp.operation {
+            StorageSpdmOperationCode::StorageSpdmOperationCodeDiscovery => {
+                let mut supported_operations = [0u8; 8];
+                supported_operations[..4].copy_from_slice(&[
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeDiscovery.get_u8(),
+                    StorageSpdmOperationCode::StorageSpdmOperationCodePendingInfo.get_u8(),
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeMessage.get_u8(),
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage.get_u8(),
+                ]);
+                let response = StorageSpdmDiscoveryResponse {
+                    binding_version: STORAGE_SPDM_BINDING_VERSION,
+                    max_connection_id: self.max_connection_id,
+                    supported_operations,
+                };
+                Self::encode_response(&response, STORAGE_SPDM_DISCOVERY_RESPONSE_SIZE, data)
+            }
+            StorageSpdmOperationCode::StorageSpdmOperationCodePendingInfo => {
+                let pending =
+                    self.response_pending && self.response.spsp.connection_id == spsp.connection_id;
+                let response = StorageSpdmPendingInfoResponse {
+                    binding_version: STORAGE_SPDM_BINDING_VERSION,
+                    pending_info_flag: if pending {
+                        STORAGE_SPDM_PENDING_INFO_FLAG_RESPONSE_PENDING
+                    } else {
+                        0
+                    },
+                    response_length: if pending {
+                        self.response.size as u32
+                    } else {
+                        0
+                    },
+                };
+                Self::encode_response(&response, STORAGE_SPDM_PENDING_INFO_RESPONSE_SIZE, data)
+            }
+            StorageSpdmOperationCode::StorageSpdmOperationCodeMessage
+            | StorageSpdmOperationCode::StorageSpdmOperationCodeSecuredMessage => {
+                if !self.response_pending {
+                    return Ok(0);
+                }
+                if self.response.spsp != spsp {
+                    return spdm_result_err!(EINVAL);
+                }
+                if data.len() < self.response.size {
+                    return spdm_result_err!(ERANGE);
+                }
+                let size = self.response.size;
+                data[..size].copy_from_slice(&self.response.data[..size]);
+                self.response_pending = false;
+                Ok(size)
+            }
+            _ => spdm_result_err!(EINVAL),
+        }
+    }
+
+    fn encode_response<T: Codec>(response: &T, size: usize, data: &mut [u8]) -> SpdmResult<usize> {
+        if data.len() < size {
+            return spdm_result_err!(ERANGE);
+        }
+        let mut writer = Writer::init(data);
+        response.encode(&mut writer);
+        Ok(writer.used())
+    }
+
+    /// Device side: take the data of the oldest Security Send.
+    pub fn fetch_command(
+        &mut self,
+        data: &mut [u8],
+    ) -> SpdmResult<Option<(StorageSecurityProtocolSpecific, usize)>> {
+        if self.count == 0 {
+            return Ok(None);
+        }
+        let command = &self.commands[self.head];
+        if data.len() < command.size {
+            return spdm_result_err!(ERANGE);
+        }
+        data[..command.size].copy_from_slice(&command.data[..command.size]);
+        let res = (command.spsp, command.size);
+        self.head = (self.head + 1) % STORAGE_SECURITY_QUEUE_DEPTH;
+        self.count -= 1;
+        Ok(Some(res))
+    }
+
+    /// Device side: post the data returned by the next Security Receive.
+    pub fn post_response(
+        &mut self,
+        spsp: StorageSecurityProtocolSpecific,
+        data: &[u8],
+    ) -> SpdmResult {
+        if self.response_pending {
+            return spdm_result_err!(EBUSY);
+        }
+        if data.len() > STORAGE_SECURITY_DATA_SIZE {
+            return spdm_result_err!(EINVAL);
+        }
+        self.response.spsp = spsp;
+        self.response.size = data.len();
+        self.response.data[..data.len()].copy_from_slice(data);
+        self.response_pending = true;
+        Ok(())
+    }
+}
+
+fn read_transport_header(buffer: &[u8]) -> SpdmResult<StorageTransportHeader> {
+    let mut reader = Reader::init(buffer);
+    let storage_header = StorageTransportHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+    if buffer.len() - reader.used() != storage_header.get_transfer_length() {
+        return spdm_result_err!(EINVAL);
+    }
+    Ok(storage_header)
+}
+
+fn write_transport_header(
+    spsp: StorageSecurityProtocolSpecific,
+    size: usize,
+    buffer: &mut [u8],
+) -> usize {
+    let mut writer = Writer::init(buffer);
+    StorageTransportHeader {
+        security_protocol: STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+        security_protocol_specific: spsp,
+        inc_512: false,
+        length: size as u32,
+    }
+    .encode(&mut writer);
+    writer.used()
+}
+
+/// Host side of the loopback, it turns the StorageTransportEncap output
+/// into Security Send commands and gets responses with Security Receive.
+pub struct StorageLoopbackHostIo<'a> {
+    queue: &'a RefCell<StorageSecurityCommandQueue>,
+    spsp: StorageSecurityProtocolSpecific,
+}
+
+impl<'a> StorageLoopbackHostIo<'a> {
+    pub fn new(queue: &'a RefCell<StorageSecurityCommandQueue>) -> Self {
+        StorageLoopbackHostIo {
+            queue,
+            spsp: StorageSecurityProtocolSpecific::default(),
+        }
+    }
+}
+
+impl SpdmDeviceIo for StorageLoopbackHostIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        let storage_header = read_transport_header(buffer)?;
+        self.queue.borrow_mut().security_send(
+            storage_header.security_protocol,
+            storage_header.security_protocol_specific,
+            &buffer[STORAGE_TRANSPORT_HEADER_SIZE..],
+        )?;
+        // the response comes back on the same connection with the same operation.
+        self.spsp = storage_header.security_protocol_specific;
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        if buffer.len() < STORAGE_TRANSPORT_HEADER_SIZE {
+            return Err(0);
+        }
+        let size = self
+            .queue
+            .borrow_mut()
+            .security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                self.spsp,
+                &mut buffer[STORAGE_TRANSPORT_HEADER_SIZE..],
+            )
+            .map_err(|_| 0usize)?;
+        if size == 0 {
+            return Err(0);
+        }
+        let header_size = write_transport_header(self.spsp, size, buffer);
+        Ok(header_size + size)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+/// Device side of the loopback, for the responder.
+pub struct StorageLoopbackDeviceIo<'a> {
+    queue: &'a RefCell<StorageSecurityCommandQueue>,
+}
+
+impl<'a> StorageLoopbackDeviceIo<'a> {
+    pub fn new(queue: &'a RefCell<StorageSecurityCommandQueue>) -> Self {
+        StorageLoopbackDeviceIo { queue }
+    }
+}
+
+impl SpdmDeviceIo for StorageLoopbackDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        let storage_header = read_transport_header(buffer)?;
+        self.queue.borrow_mut().post_response(
+            storage_header.security_protocol_specific,
+            &buffer[STORAGE_TRANSPORT_HEADER_SIZE..],
+        )
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        if buffer.len() < STORAGE_TRANSPORT_HEADER_SIZE {
+            return Err(0);
+        }
+        let res = self
+            .queue
+            .borrow_mut()
+            .fetch_command(&mut buffer[STORAGE_TRANSPORT_HEADER_SIZE..])
+            .map_err(|_| 0usize)?;
+        let (spsp, size) = res.ok_or(0usize)?;
+        let header_size = write_transport_header(spsp, size, buffer);
+        Ok(header_size + size)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spdmlib::common::SpdmTransportEncap;
+
+    fn spsp(
+        connection_id: u8,
+        operation: StorageSpdmOperationCode,
+    ) -> StorageSecurityProtocolSpecific {
+        StorageSecurityProtocolSpecific {
+            connection_id,
+            operation,
+        }
+    }
+
+    #[test]
+    fn test_case0_storage_security_command_queue() {
+        let mut queue = StorageSecurityCommandQueue::new(1);
+        let mut data = [0u8; 32];
+
+        let used = queue
+            .security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp(
+                    0,
+                    StorageSpdmOperationCode::StorageSpdmOperationCodeDiscovery,
+                ),
+                &mut data,
+            )
+            .unwrap();
+        let discovery = StorageSpdmDiscoveryResponse::read_bytes(&data[..used]).unwrap();
+        assert_eq!(discovery.max_connection_id, 1);
+        assert_eq!(
+            &discovery.supported_operations[..4],
+            &[0x01, 0x02, 0x05, 0x06]
+        );
+
+        let message = spsp(1, StorageSpdmOperationCode::StorageSpdmOperationCodeMessage);
+        for _ in 0..STORAGE_SECURITY_QUEUE_DEPTH {
+            queue
+                .security_send(STORAGE_SECURITY_PROTOCOL_DMTF_SPDM, message, &[0x11, 0x84])
+                .unwrap();
+        }
+        assert!(queue
+            .security_send(STORAGE_SECURITY_PROTOCOL_DMTF_SPDM, message, &[0x11, 0x84])
+            .is_err());
+        // unsupported connection and protocol
+        assert!(queue
+            .security_send(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp(2, StorageSpdmOperationCode::StorageSpdmOperationCodeMessage),
+                &[0x11, 0x84]
+            )
+            .is_err());
+        assert!(queue.security_send(0xEA, message, &[0x11, 0x84]).is_err());
+
+        assert_eq!(queue.fetch_command(&mut data).unwrap(), Some((message, 2)));
+        queue
+            .post_response(message, &[0x11, 0x04, 0x00, 0x00])
+            .unwrap();
+        assert!(queue.post_response(message, &[0x11, 0x04]).is_err());
+
+        let used = queue
+            .security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp(
+                    1,
+                    StorageSpdmOperationCode::StorageSpdmOperationCodePendingInfo,
+                ),
+                &mut data,
+            )
+            .unwrap();
+        let pending_info = StorageSpdmPendingInfoResponse::read_bytes(&data[..used]).unwrap();
+        assert_eq!(
+            pending_info.pending_info_flag,
+            STORAGE_SPDM_PENDING_INFO_FLAG_RESPONSE_PENDING
+        );
+        assert_eq!(pending_info.response_length, 4);
+
+        // allocation length too small, the response stays pending.
+        assert!(queue
+            .security_receive(STORAGE_SECURITY_PROTOCOL_DMTF_SPDM, message, &mut data[..2])
+            .is_err());
+        assert_eq!(
+            queue
+                .security_receive(STORAGE_SECURITY_PROTOCOL_DMTF_SPDM, message, &mut data)
+                .unwrap(),
+            4
+        );
+        assert_eq!(
+            queue
+                .security_receive(STORAGE_SECURITY_PROTOCOL_DMTF_SPDM, message, &mut data)
+                .unwrap(),
+            0
+        );
+    }
+    #[test]
+    fn test_case0_storage_loopback_io() {
+        let queue = RefCell::new(StorageSecurityCommandQueue::new(0));
+        let mut host_io = StorageLoopbackHostIo::new(&queue);
+        let mut device_io = StorageLoopbackDeviceIo::new(&queue);
+        let mut encap = StorageTransportEncap::default();
+        let mut transport_buffer = [0u8; 64];
+        let mut spdm_buffer = [0u8; 64];
+
+        let request = [0x11, 0x84, 0x00, 0x00];
+        let used = encap.encap(&request, &mut transport_buffer, false).unwrap();
+        host_io.send(&transport_buffer[..used]).unwrap();
+        assert!(host_io.receive(&mut transport_buffer).is_err());
+
+        let used = device_io.receive(&mut transport_buffer).unwrap();
+        let (size, secured_message) = encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .unwrap();
+        assert!(!secured_message);
+        assert_eq!(&spdm_buffer[..size], &request);
+        assert!(device_io.receive(&mut transport_buffer).is_err());
+
+        let response = [0x11, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x11];
+        let used = encap
+            .encap(&response, &mut transport_buffer, false)
+            .unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        let used = host_io.receive(&mut transport_buffer).unwrap();
+        let (size, _) = encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .unwrap();
+        assert_eq!(&spdm_buffer[..size], &response);
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! SPDM over TCP transport binding, see DSP0287.
+
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmTransportEncap;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+
+pub const SPDM_TCP_BINDING_VERSION: u8 = 0x01;
+pub const SPDM_TCP_BINDING_HEADER_SIZE: usize = 4;
+pub const SPDM_TCP_DEFAULT_PORT: u16 = 4194;
+pub const SPDM_TCP_SEQUENCE_NUMBER_COUNT: u8 = 0;
+pub const SPDM_TCP_MAX_RANDOM_COUNT: u16 = 0;
+
+enum_builder! {
+    @U8
+    EnumName: SpdmTcpMessageType;
+    EnumVal{
+        SpdmTcpMessageTypeOutOfSession => 0x05,
+        SpdmTcpMessageTypeInSession => 0x06,
+        SpdmTcpMessageTypeRoleInquiry => 0xBF,
+        SpdmTcpMessageTypeErrorTooLarge => 0xC0,
+        SpdmTcpMessageTypeErrorNotSupported => 0xC1,
+        SpdmTcpMessageTypeErrorCannotOperateAsRequester => 0xC2,
+        SpdmTcpMessageTypeErrorCannotOperateAsResponder => 0xC3
+    }
+}
+
+impl SpdmTcpMessageType {
+    pub fn is_error(&self) -> bool {
+        self.get_u8() >= 0xC0
+    }
+}
+
+/// Binding header in front of every message on the TCP stream.
+/// payload_length is the number of bytes following the header.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SpdmTcpBindingHeader {
+    pub payload_length: u16,
+    pub binding_version: u8,
+    pub message_type: SpdmTcpMessageType,
+}
+
+impl Codec for SpdmTcpBindingHeader {
+    fn encode(&self, bytes: &mut Writer) {
+        self.payload_length.encode(bytes);
+        self.binding_version.encode(bytes);
+        self.message_type.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmTcpBindingHeader> {
+        let payload_length = u16::read(r)?;
+        let binding_version = u8::read(r)?;
+        let message_type = SpdmTcpMessageType::read(r)?;
+        Some(SpdmTcpBindingHeader {
+            payload_length,
+            binding_version,
+            message_type,
+        })
+    }
+}
+
+/// The role a TCP endpoint plays for SPDM, independent of who opened the connection.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpdmTcpRole {
+    Requester,
+    Responder,
+}
+
+/// Write a binding message without payload, such as a role inquiry or an error.
+pub fn spdm_tcp_encode_control_message(
+    message_type: SpdmTcpMessageType,
+    buffer: &mut [u8],
+) -> SpdmResult<usize> {
+    if buffer.len() < SPDM_TCP_BINDING_HEADER_SIZE {
+        return spdm_result_err!(ENOMEM);
+    }
+    let mut writer = Writer::init(buffer);
+    SpdmTcpBindingHeader {
+        payload_length: 0,
+        binding_version: SPDM_TCP_BINDING_VERSION,
+        message_type,
+    }
+    .encode(&mut writer);
+    Ok(writer.used())
+}
+
+/// Answer a role inquiry.
+///
+/// Either end of the connection may ask whether the other one operates as
+/// the SPDM responder. A responder echoes the inquiry, a requester answers
+/// that it cannot operate as a responder.
+pub fn spdm_tcp_process_role_inquiry(
+    local_role: SpdmTcpRole,
+    response: &mut [u8],
+) -> SpdmResult<usize> {
+    let message_type = match local_role {
+        SpdmTcpRole::Responder => SpdmTcpMessageType::SpdmTcpMessageTypeRoleInquiry,
+        SpdmTcpRole::Requester => {
+            SpdmTcpMessageType::SpdmTcpMessageTypeErrorCannotOperateAsResponder
+        }
+    };
+    spdm_tcp_encode_control_message(message_type, response)
+}
+
+/// The peer role from the answer to a role inquiry.
+pub fn spdm_tcp_parse_role_inquiry_response(response: &[u8]) -> SpdmResult<SpdmTcpRole> {
+    let mut reader = Reader::init(response);
+    let tcp_header = SpdmTcpBindingHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+    if tcp_header.binding_version != SPDM_TCP_BINDING_VERSION
+        || tcp_header.payload_length != 0
+        || response.len() != SPDM_TCP_BINDING_HEADER_SIZE
+    {
+        return spdm_result_err!(EINVAL);
+    }
+    match tcp_header.message_type {
+        SpdmTcpMessageType::SpdmTcpMessageTypeRoleInquiry => Ok(SpdmTcpRole::Responder),
+        SpdmTcpMessageType::SpdmTcpMessageTypeErrorCannotOperateAsResponder => {
+            Ok(SpdmTcpRole::Requester)
+        }
+        _ => spdm_result_err!(EINVAL),
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpTransportEncap {}
+
+impl SpdmTransportEncap for TcpTransportEncap {
+    fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        let payload_len = spdm_buffer.len();
+        if payload_len > u16::MAX as usize
+            || transport_buffer.len() < SPDM_TCP_BINDING_HEADER_SIZE + payload_len
+        {
+            return spdm_result_err!(EINVAL);
+        }
+        let mut writer = Writer::init(&mut *transport_buffer);
+        let tcp_header = SpdmTcpBindingHeader {
+            payload_length: payload_len as u16,
+            binding_version: SPDM_TCP_BINDING_VERSION,
+            message_type: if secured_message {
+                SpdmTcpMessageType::SpdmTcpMessageTypeInSession
+            } else {
+                SpdmTcpMessageType::SpdmTcpMessageTypeOutOfSession
+            },
+        };
+        tcp_header.encode(&mut writer);
+        let header_size = writer.used();
+        transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(spdm_buffer);
+        Ok(header_size + payload_len)
+    }
+
+    fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        let mut reader = Reader::init(transport_buffer);
+        let tcp_header = SpdmTcpBindingHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if tcp_header.binding_version != SPDM_TCP_BINDING_VERSION {
+            return spdm_result_err!(EINVAL);
+        }
+        let secured_message = match tcp_header.message_type {
+            SpdmTcpMessageType::SpdmTcpMessageTypeOutOfSession => false,
+            SpdmTcpMessageType::SpdmTcpMessageTypeInSession => true,
+            _ => return spdm_result_err!(EINVAL),
+        };
+        let header_size = reader.used();
+        let payload_len = tcp_header.payload_length as usize;
+        if transport_buffer.len() - header_size != payload_len {
+            return spdm_result_err!(EINVAL);
+        }
+        if spdm_buffer.len() < payload_len {
+            return spdm_result_err!(EINVAL);
+        }
+        spdm_buffer[..payload_len].copy_from_slice(&transport_buffer[header_size..]);
+        Ok((payload_len, secured_message))
+    }
+
+    fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult<usize> {
+        // the binding carries no application message type
+        if app_message_type.is_some() {
+            return spdm_result_err!(EINVAL);
+        }
+        app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
+        Ok(spdm_buffer.len())
+    }
+
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_sequence_number_count(&mut self) -> u8 {
+        SPDM_TCP_SEQUENCE_NUMBER_COUNT
+    }
+    fn get_max_random_count(&mut self) -> u16 {
+        SPDM_TCP_MAX_RANDOM_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_spdm_tcp_binding_header() {
+        let u8_slice = &mut [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmTcpBindingHeader {
+            payload_length: 0x104,
+            binding_version: SPDM_TCP_BINDING_VERSION,
+            message_type: SpdmTcpMessageType::SpdmTcpMessageTypeInSession,
+        };
+        value.encode(&mut writer);
+        assert_eq!(u8_slice, &[0x04, 0x01, 0x01, 0x06]);
+
+        let mut reader = Reader::init(u8_slice);
+        let tcp_header = SpdmTcpBindingHeader::read(&mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(tcp_header.payload_length, 0x104);
+        assert!(!tcp_header.message_type.is_error());
+    }
+    #[test]
+    fn test_case0_encap_decap() {
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let spdm_buffer = [0x11, 0xE0, 0x00, 0x00];
+        let mut transport_buffer = [0u8; 16];
+
+        let used = tcp_transport_encap
+            .encap(&spdm_buffer, &mut transport_buffer, false)
+            .unwrap();
+        assert_eq!(&transport_buffer[..4], &[0x04, 0x00, 0x01, 0x05]);
+
+        let mut buffer = [0u8; 16];
+        let (size, secured_message) = tcp_transport_encap
+            .decap(&transport_buffer[..used], &mut buffer)
+            .unwrap();
+        assert!(!secured_message);
+        assert_eq!(&buffer[..size], &spdm_buffer);
+
+        // length does not match
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..used - 1], &mut buffer)
+            .is_err());
+        // binding version
+        let mut bad_buffer = transport_buffer;
+        bad_buffer[2] = 0x02;
+        assert!(tcp_transport_encap
+            .decap(&bad_buffer[..used], &mut buffer)
+            .is_err());
+        // error message
+        let used = spdm_tcp_encode_control_message(
+            SpdmTcpMessageType::SpdmTcpMessageTypeErrorTooLarge,
+            &mut transport_buffer,
+        )
+        .unwrap();
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..used], &mut buffer)
+            .is_err());
+    }
+    #[test]
+    fn test_case0_role_inquiry() {
+        let mut response = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        spdm_tcp_process_role_inquiry(SpdmTcpRole::Responder, &mut response).unwrap();
+        assert_eq!(response, [0x00, 0x00, 0x01, 0xBF]);
+        assert_eq!(
+            spdm_tcp_parse_role_inquiry_response(&response).unwrap(),
+            SpdmTcpRole::Responder
+        );
+
+        spdm_tcp_process_role_inquiry(SpdmTcpRole::Requester, &mut response).unwrap();
+        assert_eq!(response[3], 0xC3);
+        assert_eq!(
+            spdm_tcp_parse_role_inquiry_response(&response).unwrap(),
+            SpdmTcpRole::Requester
+        );
+
+        response[3] = 0xC1;
+        assert!(spdm_tcp_parse_role_inquiry_response(&response).is_err());
+        assert!(spdm_tcp_process_role_inquiry(SpdmTcpRole::Requester, &mut response[..2]).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::*;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::SpdmResult;
+use spdmlib::{spdm_err, spdm_result_err};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// SpdmDeviceIo over a TCP connection, with DSP0287 framing.
+///
+/// The SPDM role does not depend on the TCP role: a client may be either
+/// a requester or a responder. Role inquiries from the peer are answered
+/// while receiving.
+pub struct TcpDeviceIo {
+    stream: TcpStream,
+    local_role: SpdmTcpRole,
+}
+
+impl TcpDeviceIo {
+    pub fn new(stream: TcpStream, local_role: SpdmTcpRole) -> Self {
+        TcpDeviceIo { stream, local_role }
+    }
+
+    /// Client role, open a connection to a listening peer.
+    pub fn connect<A: ToSocketAddrs>(addr: A, local_role: SpdmTcpRole) -> SpdmResult<Self> {
+        let stream = TcpStream::connect(addr).map_err(|_| spdm_err!(EIO))?;
+        Ok(TcpDeviceIo::new(stream, local_role))
+    }
+
+    /// Listener role, wait for the next peer.
+    pub fn accept(listener: &TcpListener, local_role: SpdmTcpRole) -> SpdmResult<Self> {
+        let (stream, _) = listener.accept().map_err(|_| spdm_err!(EIO))?;
+        Ok(TcpDeviceIo::new(stream, local_role))
+    }
+
+    pub fn get_local_role(&self) -> SpdmTcpRole {
+        self.local_role
+    }
+
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> SpdmResult {
+        self.stream
+            .set_read_timeout(timeout)
+            .map_err(|_| spdm_err!(EIO))
+    }
+
+    /// Ask whether the peer operates as the SPDM responder.
+    pub fn role_inquiry(&mut self) -> SpdmResult<SpdmTcpRole> {
+        let mut buffer = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        let used = spdm_tcp_encode_control_message(
+            SpdmTcpMessageType::SpdmTcpMessageTypeRoleInquiry,
+            &mut buffer,
+        )?;
+        self.send(&buffer[..used])?;
+
+        let mut response = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        self.stream
+            .read_exact(&mut response)
+            .map_err(|_| spdm_err!(EIO))?;
+        spdm_tcp_parse_role_inquiry_response(&response)
+    }
+
+    pub fn into_inner(self) -> TcpStream {
+        self.stream
+    }
+
+    fn read_header(&mut self) -> SpdmResult<SpdmTcpBindingHeader> {
+        let mut buffer = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        self.stream
+            .read_exact(&mut buffer)
+            .map_err(|_| spdm_err!(EIO))?;
+        let mut reader = Reader::init(&buffer);
+        let header = SpdmTcpBindingHeader::read(&mut reader).ok_or(spdm_err!(EIO))?;
+        if header.binding_version != SPDM_TCP_BINDING_VERSION {
+            return spdm_result_err!(EINVAL);
+        }
+        Ok(header)
+    }
+
+    fn send_control_message(&mut self, message_type: SpdmTcpMessageType) -> SpdmResult {
+        let mut buffer = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+        let used = spdm_tcp_encode_control_message(message_type, &mut buffer)?;
+        self.send(&buffer[..used])
+    }
+
+    /// Drop a payload that does not fit, the stream stays in sync.
+    fn discard(&mut self, size: usize) -> SpdmResult {
+        let mut buffer = [0u8; 256];
+        let mut left = size;
+        while left != 0 {
+            let chunk = left.min(buffer.len());
+            self.stream
+                .read_exact(&mut buffer[..chunk])
+                .map_err(|_| spdm_err!(EIO))?;
+            left -= chunk;
+        }
+        Ok(())
+    }
+}
+
+impl SpdmDeviceIo for TcpDeviceIo {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.stream.write_all(buffer).map_err(|_| spdm_err!(EIO))?;
+        self.stream.flush().map_err(|_| spdm_err!(EIO))
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, usize> {
+        loop {
+            let header = self.read_header().map_err(|_| 0usize)?;
+            let payload_length = header.payload_length as usize;
+            match header.message_type {
+                SpdmTcpMessageType::SpdmTcpMessageTypeOutOfSession
+                | SpdmTcpMessageType::SpdmTcpMessageTypeInSession => {}
+                SpdmTcpMessageType::SpdmTcpMessageTypeRoleInquiry => {
+                    self.discard(payload_length).map_err(|_| 0usize)?;
+                    let mut response = [0u8; SPDM_TCP_BINDING_HEADER_SIZE];
+                    let used = spdm_tcp_process_role_inquiry(self.local_role, &mut response)
+                        .map_err(|_| 0usize)?;
+                    self.send(&response[..used]).map_err(|_| 0usize)?;
+                    continue;
+                }
+                message_type => {
+                    self.discard(payload_length).map_err(|_| 0usize)?;
+                    if !message_type.is_error() {
+                        self.send_control_message(
+                            SpdmTcpMessageType::SpdmTcpMessageTypeErrorNotSupported,
+                        )
+                        .map_err(|_| 0usize)?;
+                    }
+                    return Err(0);
+                }
+            }
+
+            let used = SPDM_TCP_BINDING_HEADER_SIZE + payload_length;
+            if buffer.len() < used {
+                self.discard(payload_length).map_err(|_| 0usize)?;
+                self.send_control_message(SpdmTcpMessageType::SpdmTcpMessageTypeErrorTooLarge)
+                    .map_err(|_| 0usize)?;
+                return Err(0);
+            }
+            let mut writer = Writer::init(buffer);
+            header.encode(&mut writer);
+            self.stream
+                .read_exact(&mut buffer[SPDM_TCP_BINDING_HEADER_SIZE..used])
+                .map_err(|_| 0usize)?;
+            return Ok(used);
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.stream.flush().map_err(|_| spdm_err!(EIO))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spdmlib::common::SpdmTransportEncap;
+    use std::thread;
+
+    #[test]
+    fn test_case0_tcp_device_io() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let responder = thread::spawn(move || {
+            let mut device_io = TcpDeviceIo::accept(&listener, SpdmTcpRole::Responder).unwrap();
+            let mut encap = TcpTransportEncap {};
+            let mut transport_buffer = [0u8; 64];
+            let mut spdm_buffer = [0u8; 64];
+
+            // the role inquiry is answered while waiting for the request.
+            let used = device_io.receive(&mut transport_buffer).unwrap();
+            let (size, secured_message) = encap
+                .decap(&transport_buffer[..used], &mut spdm_buffer)
+                .unwrap();
+            let used = encap
+                .encap(&spdm_buffer[..size], &mut transport_buffer, secured_message)
+                .unwrap();
+            device_io.send(&transport_buffer[..used]).unwrap();
+
+            // too large for the receive buffer
+            assert!(device_io.receive(&mut transport_buffer[..8]).is_err());
+        });
+
+        let mut device_io = TcpDeviceIo::connect(addr, SpdmTcpRole::Requester).unwrap();
+        assert_eq!(device_io.role_inquiry().unwrap(), SpdmTcpRole::Responder);
+
+        let mut encap = TcpTransportEncap {};
+        let mut transport_buffer = [0u8; 64];
+        let mut spdm_buffer = [0u8; 64];
+        let request = [0x11, 0x84, 0x00, 0x00];
+        let used = encap.encap(&request, &mut transport_buffer, false).unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+        let used = device_io.receive(&mut transport_buffer).unwrap();
+        let (size, _) = encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)
+            .unwrap();
+        assert_eq!(&spdm_buffer[..size], &request);
+
+        let used = encap
+            .encap(&[0u8; 16], &mut transport_buffer, true)
+            .unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+        // the peer answers with an error
+        assert!(device_io.receive(&mut transport_buffer).is_err());
+
+        responder.join().unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Transport framing and the negotiated state of a message stream.
+
+use std::io::{self, Write};
+
+use codec::{Codec, Reader};
+use mctp_transport::{MctpMessageHeader, MctpMessageType, MctpTransportEncap};
+use pcidoe_transport::{PciDoeDataObjectType, PciDoeMessageHeader};
+use spdmlib::common::{SpdmDecodeInfo, SpdmTransportEncap};
+use spdmlib::msgs::*;
+
+use crate::printer::{print_message, FieldWriter};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DissectorFraming {
+    None,
+    Mctp,
+    PciDoe,
+}
+
+/// Decodes the messages of one connection in order.
+/// The algorithms and capabilities negotiated by earlier messages, and the
+/// signature or hash requested by a request, are used to decode the messages
+/// that follow, like the requester and responder contexts do.
+pub struct Dissector {
+    pub framing: DissectorFraming,
+    pub decode_info: SpdmDecodeInfo,
+}
+
+impl Dissector {
+    pub fn new(framing: DissectorFraming, decode_info: SpdmDecodeInfo) -> Self {
+        Dissector {
+            framing,
+            decode_info,
+        }
+    }
+
+    /// Print one transport message.
+    pub fn dissect(&mut self, data: &[u8], out: &mut dyn Write) -> io::Result<()> {
+        let w = &mut FieldWriter::new(out);
+        match self.framing {
+            DissectorFraming::None => self.dissect_spdm(w, data),
+            DissectorFraming::Mctp => self.dissect_mctp(w, data),
+            DissectorFraming::PciDoe => self.dissect_pcidoe(w, data),
+        }
+    }
+
+    fn dissect_mctp(&mut self, w: &mut FieldWriter, data: &[u8]) -> io::Result<()> {
+        let mut reader = Reader::init(data);
+        let mctp_header = match MctpMessageHeader::read(&mut reader) {
+            Some(mctp_header) => mctp_header,
+            None => return w.bytes("truncated MCTP header", data),
+        };
+        w.begin("MCTP header")?;
+        w.debug("message type", mctp_header.r#type)?;
+        w.end();
+        let payload = &data[reader.used()..];
+        match mctp_header.r#type {
+            MctpMessageType::MctpMessageTypeSpdm => self.dissect_spdm(w, payload),
+            MctpMessageType::MctpMessageTypeSecuredMctp => {
+                let sequence_number_count = MctpTransportEncap {}.get_sequence_number_count();
+                dissect_secured(w, payload, sequence_number_count as usize)
+            }
+            _ => w.bytes("payload", payload),
+        }
+    }
+
+    fn dissect_pcidoe(&mut self, w: &mut FieldWriter, data: &[u8]) -> io::Result<()> {
+        let mut reader = Reader::init(data);
+        let pcidoe_header = match PciDoeMessageHeader::read(&mut reader) {
+            Some(pcidoe_header) => pcidoe_header,
+            None => return w.bytes("truncated PCI DOE header", data),
+        };
+        let payload = &data[reader.used()..];
+        w.begin("PCI DOE header")?;
+        w.debug("vendor id", pcidoe_header.vendor_id)?;
+        w.debug("data object type", pcidoe_header.data_object_type)?;
+        w.field("payload length", pcidoe_header.payload_length)?;
+        if pcidoe_header.payload_length as usize != payload.len() {
+            w.field(
+                "warning",
+                format!("{} payload bytes received", payload.len()),
+            )?;
+        }
+        w.end();
+        match pcidoe_header.data_object_type {
+            PciDoeDataObjectType::PciDoeDataObjectTypeSpdm => self.dissect_spdm(w, payload),
+            PciDoeDataObjectType::PciDoeDataObjectTypeSecuredSpdm => dissect_secured(w, payload, 0),
+            _ => w.bytes("payload", payload),
+        }
+    }
+
+    fn dissect_spdm(&mut self, w: &mut FieldWriter, data: &[u8]) -> io::Result<()> {
+        let mut reader = Reader::init(data);
+        if let Some(message) = SpdmMessage::read_with_detailed_error(&self.decode_info, &mut reader)
+        {
+            w.begin(&format!("SPDM message ({} bytes)", reader.used()))?;
+            print_message(w, &message)?;
+            w.end();
+            if reader.any_left() {
+                w.bytes("trailing bytes", &data[reader.used()..])?;
+            }
+            self.update_decode_info(&message);
+            return Ok(());
+        }
+
+        let mut reader = Reader::init(data);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(header) => {
+                w.begin(&format!("SPDM message ({} bytes)", data.len()))?;
+                w.debug("version", header.version)?;
+                w.debug("request response code", header.request_response_code)?;
+                w.field("error", "unable to decode the payload")?;
+                w.bytes("payload", &data[reader.used()..])?;
+                w.end();
+                Ok(())
+            }
+            None => w.bytes("truncated SPDM header", data),
+        }
+    }
+
+    fn update_decode_info(&mut self, message: &SpdmMessage) {
+        let decode_info = &mut self.decode_info;
+        match &message.payload {
+            SpdmMessagePayload::SpdmGetCapabilitiesRequest(payload) => {
+                decode_info.req_capabilities_sel = payload.flags;
+            }
+            SpdmMessagePayload::SpdmCapabilitiesResponse(payload) => {
+                decode_info.rsp_capabilities_sel = payload.flags;
+            }
+            // only a single selected algorithm has a size to decode with
+            SpdmMessagePayload::SpdmAlgorithmsResponse(payload) => {
+                if payload.base_hash_sel.bits().count_ones() == 1 {
+                    decode_info.base_hash_sel = payload.base_hash_sel;
+                }
+                if payload.base_asym_sel.bits().count_ones() == 1 {
+                    decode_info.base_asym_sel = payload.base_asym_sel;
+                }
+                for alg_struct in payload
+                    .alg_struct
+                    .iter()
+                    .take(payload.alg_struct_count as usize)
+                {
+                    if let SpdmAlg::SpdmAlgoDhe(dhe) = alg_struct.alg_supported {
+                        if dhe.bits().count_ones() == 1 {
+                            decode_info.dhe_sel = dhe;
+                        }
+                    }
+                }
+            }
+            SpdmMessagePayload::SpdmChallengeRequest(payload) => {
+                decode_info.need_measurement_summary_hash =
+                    need_measurement_summary_hash(payload.measurement_summary_hash_type);
+            }
+            SpdmMessagePayload::SpdmKeyExchangeRequest(payload) => {
+                decode_info.need_measurement_summary_hash =
+                    need_measurement_summary_hash(payload.measurement_summary_hash_type);
+            }
+            SpdmMessagePayload::SpdmPskExchangeRequest(payload) => {
+                decode_info.need_measurement_summary_hash =
+                    need_measurement_summary_hash(payload.measurement_summary_hash_type);
+            }
+            SpdmMessagePayload::SpdmGetMeasurementsRequest(payload) => {
+                decode_info.need_measurement_signature = payload
+                    .measurement_attributes
+                    .contains(SpdmMeasurementeAttributes::INCLUDE_SIGNATURE);
+            }
+            SpdmMessagePayload::SpdmGetEndpointInfoRequest(payload) => {
+                decode_info.need_endpoint_info_signature = payload
+                    .request_attributes
+                    .contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn need_measurement_summary_hash(
+    measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+) -> bool {
+    matches!(
+        measurement_summary_hash_type,
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeTcb
+            | SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll
+    )
+}
+
+// The record of a secured message (DSP0277) can not be decrypted without the
+// session keys, only its clear text header is printed.
+fn dissect_secured(
+    w: &mut FieldWriter,
+    data: &[u8],
+    sequence_number_count: usize,
+) -> io::Result<()> {
+    let mut reader = Reader::init(data);
+    let session_id = match u32::read(&mut reader) {
+        Some(session_id) => session_id,
+        None => return w.bytes("truncated secured message", data),
+    };
+    w.begin("secured message")?;
+    w.field("session id", format!("{:#010x}", session_id))?;
+    if let Some(sequence_number) = reader.take(sequence_number_count) {
+        if sequence_number_count != 0 {
+            w.bytes("sequence number", sequence_number)?;
+        }
+        if let Some(length) = u16::read(&mut reader) {
+            w.field("length", length)?;
+        }
+    }
+    w.bytes("encrypted data and MAC", &data[reader.used()..])?;
+    w.end();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_decode_info() -> SpdmDecodeInfo {
+        SpdmDecodeInfo {
+            base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            base_asym_sel: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+            dhe_sel: SpdmDheAlgo::SECP_256_R1,
+            ..Default::default()
+        }
+    }
+
+    fn dissect_to_string(dissector: &mut Dissector, data: &[u8]) -> String {
+        let mut out = Vec::new();
+        dissector.dissect(data, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_case0_dissect_mctp_version() {
+        let dissector = &mut Dissector::new(DissectorFraming::Mctp, new_decode_info());
+        let text = dissect_to_string(
+            dissector,
+            &[
+                0x05, 0x10, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x10, 0x00, 0x11,
+            ],
+        );
+        assert!(text.contains("message type: MctpMessageTypeSpdm"));
+        assert!(text.contains("request response code: SpdmResponseVersion"));
+        assert!(text.contains("version: SpdmVersion11 update 0"));
+    }
+
+    #[test]
+    fn test_case0_dissect_error() {
+        let dissector = &mut Dissector::new(DissectorFraming::None, new_decode_info());
+        let text = dissect_to_string(dissector, &[0x11, 0x7f, 0x42, 0x00, 0x01, 0xe2, 0x05, 0x01]);
+        assert!(text.contains("error code: SpdmErrorResponseNotReady"));
+        assert!(text.contains("request code: 0xe2"));
+        assert!(text.contains("token: 5"));
+    }
+
+    #[test]
+    fn test_case0_dissect_secured() {
+        let dissector = &mut Dissector::new(DissectorFraming::Mctp, new_decode_info());
+        let text = dissect_to_string(
+            dissector,
+            &[
+                0x06, 0xfe, 0xff, 0xfd, 0xff, 0x00, 0x00, 0x02, 0x00, 0xaa, 0xbb,
+            ],
+        );
+        assert!(text.contains("session id: 0xfffdfffe"));
+        assert!(text.contains("length: 2"));
+        assert!(text.contains("encrypted data and MAC (2 bytes)"));
+    }
+
+    #[test]
+    fn test_case0_update_decode_info() {
+        let dissector = &mut Dissector::new(DissectorFraming::None, new_decode_info());
+        // ALGORITHMS selecting SHA_384 and ECDSA_P384
+        let mut algorithms = vec![0x11u8, 0x63, 0x00, 0x00, 0x24, 0x00, 0x01, 0x00];
+        algorithms.extend_from_slice(&0x04u32.to_le_bytes()); // measurement hash algo
+        algorithms.extend_from_slice(&0x80u32.to_le_bytes()); // base asym sel
+        algorithms.extend_from_slice(&0x02u32.to_le_bytes()); // base hash sel
+        algorithms.extend_from_slice(&[0u8; 16]);
+        let text = dissect_to_string(dissector, &algorithms);
+        assert!(text.contains("base hash sel: TPM_ALG_SHA_384"));
+        assert_eq!(
+            dissector.decode_info.base_hash_sel,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384
+        );
+        assert_eq!(
+            dissector.decode_info.base_asym_sel,
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+        );
+
+        // PSK_FINISH with the SHA_384 verify data
+        let mut psk_finish = vec![0x11u8, 0xe7, 0x00, 0x00];
+        psk_finish.extend_from_slice(&[0x5au8; 48]);
+        let text = dissect_to_string(dissector, &psk_finish);
+        assert!(text.contains("verify data (48 bytes)"));
+        assert!(!text.contains("trailing bytes"));
+    }
+}
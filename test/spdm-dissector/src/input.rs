@@ -0,0 +1,248 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Options and input parsing of the dissector.
+
+use spdm_emu::emu_config::{parse_number, EmuConfig};
+use spdmlib::common::SpdmDecodeInfo;
+
+use crate::dissector::DissectorFraming;
+
+pub const DISSECTOR_USAGE: &str = "\
+[options] [file]
+
+   [--input HEX|BIN]
+   [--trans NONE|MCTP|PCI_DOE]
+   [--skip <count>]
+   [--hash SHA_256|SHA_384|SHA_512|SHA3_256|SHA3_384|SHA3_512]
+   [--asym RSASSA_2048|RSASSA_3072|RSASSA_4096|RSAPSS_2048|RSAPSS_3072|RSAPSS_4096|ECDSA_P256|ECDSA_P384|ECDSA_P521]
+   [--dhe FFDHE_2048|FFDHE_3072|FFDHE_4096|SECP_256_R1|SECP_384_R1|SECP_521_R1]
+
+   The messages are read from the file, or from stdin without a file.
+   --input HEX, the default, reads one message per line. Text up to the last ':' of a
+     line is skipped, so the read:/write: lines of the emulator debug log can be used.
+   --input BIN reads one message.
+   --trans is the framing of each message, NONE for a bare SPDM message.
+   --skip drops the bytes before each message, e.g. 12 for the emulator socket header.
+   --hash, --asym and --dhe decode the messages until an ALGORITHMS response selects
+     others, SHA_384, ECDSA_P384 and SECP_384_R1 by default.";
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputFormat {
+    Hex,
+    Binary,
+}
+
+#[derive(Debug)]
+pub struct DissectorOptions {
+    pub input_format: InputFormat,
+    pub framing: DissectorFraming,
+    pub skip: usize,
+    pub decode_info: SpdmDecodeInfo,
+    /// Read stdin if None.
+    pub path: Option<String>,
+}
+
+impl Default for DissectorOptions {
+    fn default() -> Self {
+        let emu_config = EmuConfig::default();
+        DissectorOptions {
+            input_format: InputFormat::Hex,
+            framing: DissectorFraming::None,
+            skip: 0,
+            decode_info: SpdmDecodeInfo {
+                req_capabilities_sel: emu_config.req_capabilities,
+                rsp_capabilities_sel: emu_config.rsp_capabilities,
+                base_hash_sel: emu_config.base_hash_algo,
+                base_asym_sel: emu_config.base_asym_algo,
+                dhe_sel: emu_config.dhe_algo,
+                ..Default::default()
+            },
+            path: None,
+        }
+    }
+}
+
+/// Parse the command line arguments, without the program name.
+pub fn parse_args(args: &[String]) -> Result<DissectorOptions, String> {
+    let mut options = DissectorOptions::default();
+    // the algorithm names are the ones of the emulators
+    let mut emu_config = EmuConfig::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = match arg.strip_prefix("--") {
+            Some(name) => name,
+            None => {
+                if options.path.is_some() {
+                    return Err(format!("unexpected argument {}", arg));
+                }
+                options.path = Some(arg.clone());
+                continue;
+            }
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for --{}", name))?;
+        match name {
+            "input" => {
+                options.input_format = match value.as_str() {
+                    "HEX" => InputFormat::Hex,
+                    "BIN" => InputFormat::Binary,
+                    _ => return Err(format!("invalid value {} for --input", value)),
+                }
+            }
+            "trans" => {
+                options.framing = match value.as_str() {
+                    "NONE" => DissectorFraming::None,
+                    "MCTP" => DissectorFraming::Mctp,
+                    "PCI_DOE" => DissectorFraming::PciDoe,
+                    _ => return Err(format!("invalid value {} for --trans", value)),
+                }
+            }
+            "skip" => options.skip = parse_number(name, value)? as usize,
+            "hash" => {
+                emu_config.set_option(name, value)?;
+                if emu_config.base_hash_algo.bits().count_ones() != 1 {
+                    return Err("one algorithm for --hash".to_string());
+                }
+                options.decode_info.base_hash_sel = emu_config.base_hash_algo;
+            }
+            "asym" => {
+                emu_config.set_option(name, value)?;
+                if emu_config.base_asym_algo.bits().count_ones() != 1 {
+                    return Err("one algorithm for --asym".to_string());
+                }
+                options.decode_info.base_asym_sel = emu_config.base_asym_algo;
+            }
+            "dhe" => {
+                emu_config.set_option(name, value)?;
+                if emu_config.dhe_algo.bits().count_ones() != 1 {
+                    return Err("one algorithm for --dhe".to_string());
+                }
+                options.decode_info.dhe_sel = emu_config.dhe_algo;
+            }
+            _ => return Err(format!("unknown option --{}", name)),
+        }
+    }
+    Ok(options)
+}
+
+/// Parse the hex dump text, one message per line.
+/// The bytes may be separated by spaces or commas, with an optional 0x,
+/// or run together. Brackets are ignored, so `{:02x?}` dumps can be pasted.
+/// Empty lines and lines starting with '#' are skipped.
+pub fn parse_hex_messages(text: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut messages = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let dump = match line.rfind(':') {
+            Some(position) => &line[(position + 1)..],
+            None => line,
+        };
+        let mut message = Vec::new();
+        for token in dump
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']'))
+            .filter(|token| !token.is_empty())
+        {
+            let digits = token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+                .unwrap_or(token);
+            let bytes = if digits.len() == 1 {
+                format!("0{}", digits)
+            } else {
+                digits.to_string()
+            };
+            if bytes.len() % 2 != 0 || !bytes.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("line {}: invalid hex {}", line_number + 1, token));
+            }
+            for i in (0..bytes.len()).step_by(2) {
+                message.push(u8::from_str_radix(&bytes[i..(i + 2)], 16).unwrap());
+            }
+        }
+        if !message.is_empty() {
+            messages.push(message);
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spdmlib::msgs::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo};
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_case0_parse_hex_messages() {
+        let messages = parse_hex_messages(
+            "# comment\n\
+             10 84 00 00\n\
+             \n\
+             0x11,0xE1 , 0, 0\n\
+             123.456 DEBUG [conn 1] [spdm_emu::spdm_emu] read: [00, 01][05, 11]\n\
+             1104a0b1\n",
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], vec![0x10u8, 0x84, 0x00, 0x00]);
+        assert_eq!(messages[1], vec![0x11u8, 0xe1, 0x00, 0x00]);
+        assert_eq!(messages[2], vec![0x00u8, 0x01, 0x05, 0x11]);
+        assert_eq!(messages[3], vec![0x11u8, 0x04, 0xa0, 0xb1]);
+
+        assert!(parse_hex_messages("10 8").is_ok());
+        assert!(parse_hex_messages("10 845").is_err());
+        assert!(parse_hex_messages("10 zz").is_err());
+    }
+
+    #[test]
+    fn test_case0_parse_args() {
+        let options = parse_args(&to_args(&[])).unwrap();
+        assert_eq!(options.input_format, InputFormat::Hex);
+        assert_eq!(options.framing, DissectorFraming::None);
+        assert_eq!(
+            options.decode_info.base_hash_sel,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384
+        );
+        assert!(options.path.is_none());
+
+        let options = parse_args(&to_args(&[
+            "--input",
+            "BIN",
+            "--trans",
+            "PCI_DOE",
+            "--skip",
+            "12",
+            "--hash",
+            "SHA_256",
+            "--asym",
+            "ECDSA_P256",
+            "dump.bin",
+        ]))
+        .unwrap();
+        assert_eq!(options.input_format, InputFormat::Binary);
+        assert_eq!(options.framing, DissectorFraming::PciDoe);
+        assert_eq!(options.skip, 12);
+        assert_eq!(
+            options.decode_info.base_hash_sel,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_256
+        );
+        assert_eq!(
+            options.decode_info.base_asym_sel,
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256
+        );
+        assert_eq!(options.path.as_deref(), Some("dump.bin"));
+
+        assert!(parse_args(&to_args(&["--hash", "SHA_256,SHA_384"])).is_err());
+        assert!(parse_args(&to_args(&["--trans"])).is_err());
+        assert!(parse_args(&to_args(&["--port", "2323"])).is_err());
+        assert!(parse_args(&to_args(&["a.txt", "b.txt"])).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! spdm-dissector prints the fields of raw SPDM messages.
+//!
+//! The messages are decoded with the spdmlib codecs and a `SpdmDecodeInfo`,
+//! no `SpdmContext` is needed, e.g.
+//! `spdm-dissector --trans MCTP --skip 12 dump.txt`.
+
+#![forbid(unsafe_code)]
+
+pub mod dissector;
+pub mod input;
+pub mod printer;
+
+pub use dissector::{Dissector, DissectorFraming};
+pub use input::{parse_args, parse_hex_messages, DissectorOptions, InputFormat, DISSECTOR_USAGE};
+
+use std::io::Write;
+
+/// Print every message of the input, in order.
+pub fn dissect_input(
+    options: &DissectorOptions,
+    input: &[u8],
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let messages = match options.input_format {
+        InputFormat::Hex => {
+            let text = core::str::from_utf8(input).map_err(|_| "input is not text".to_string())?;
+            parse_hex_messages(text)?
+        }
+        InputFormat::Binary => vec![input.to_vec()],
+    };
+    let mut dissector = Dissector::new(options.framing, options.decode_info);
+    for (i, message) in messages.iter().enumerate() {
+        let message = &message[options.skip.min(message.len())..];
+        writeln!(out, "message {} ({} bytes)", i, message.len()).map_err(|e| e.to_string())?;
+        dissector
+            .dissect(message, &mut *out)
+            .map_err(|e| e.to_string())?;
+        writeln!(out).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_dissect_input() {
+        let options = DissectorOptions {
+            framing: DissectorFraming::Mctp,
+            skip: 2,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dissect_input(
+            &options,
+            b"ff ff 05 10 84 00 00\nff ff 05 10 04\n",
+            &mut out,
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("message 0 (5 bytes)"));
+        assert!(text.contains("request response code: SpdmRequestGetVersion"));
+        assert!(text.contains("message 1 (3 bytes)"));
+        assert!(text.contains("error: unable to decode the payload"));
+
+        let options = DissectorOptions {
+            input_format: InputFormat::Binary,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dissect_input(&options, &[0x10, 0x84, 0x00, 0x00], &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("SPDM message (4 bytes)"));
+    }
+}
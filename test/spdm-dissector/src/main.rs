@@ -0,0 +1,41 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+#![forbid(unsafe_code)]
+
+use std::io::Read;
+
+use spdm_dissector::{dissect_input, parse_args, DISSECTOR_USAGE};
+
+fn main() {
+    let mut args = std::env::args();
+    let program = args.next().unwrap_or_default();
+    let args: Vec<String> = args.collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{} {}", program, DISSECTOR_USAGE);
+        return;
+    }
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{} {}", err, program, DISSECTOR_USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let mut input = Vec::new();
+    let result = match &options.path {
+        Some(path) => std::fs::File::open(path).and_then(|mut file| file.read_to_end(&mut input)),
+        None => std::io::stdin().read_to_end(&mut input),
+    };
+    if let Err(e) = result {
+        eprintln!("unable to read the input - {}", e);
+        std::process::exit(1);
+    }
+
+    if let Err(err) = dissect_input(&options, &input, &mut std::io::stdout()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,521 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Human readable printing of the decoded SPDM message fields.
+
+use core::fmt::{Debug, Display};
+use std::io::{self, Write};
+
+use spdmlib::msgs::*;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Writes one `name: value` line per field, indented by the nesting level.
+pub struct FieldWriter<'a> {
+    out: &'a mut dyn Write,
+    level: usize,
+}
+
+impl<'a> FieldWriter<'a> {
+    pub fn new(out: &'a mut dyn Write) -> Self {
+        FieldWriter { out, level: 0 }
+    }
+
+    fn write_indent(&mut self, level: usize) -> io::Result<()> {
+        write!(self.out, "{:1$}", "", level * 2)
+    }
+
+    pub fn field<T: Display>(&mut self, name: &str, value: T) -> io::Result<()> {
+        self.write_indent(self.level)?;
+        writeln!(self.out, "{}: {}", name, value)
+    }
+
+    pub fn debug<T: Debug>(&mut self, name: &str, value: T) -> io::Result<()> {
+        self.write_indent(self.level)?;
+        writeln!(self.out, "{}: {:?}", name, value)
+    }
+
+    /// Print the bytes in hex, 16 bytes per line.
+    pub fn bytes(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        if data.is_empty() {
+            return self.field(name, "(empty)");
+        }
+        self.write_indent(self.level)?;
+        writeln!(self.out, "{} ({} bytes):", name, data.len())?;
+        for line in data.chunks(BYTES_PER_LINE) {
+            self.write_indent(self.level + 1)?;
+            writeln!(self.out, "{}", hex_string(line))?;
+        }
+        Ok(())
+    }
+
+    /// Start a group of fields, printed one level deeper until `end`.
+    pub fn begin(&mut self, name: &str) -> io::Result<()> {
+        self.write_indent(self.level)?;
+        writeln!(self.out, "{}", name)?;
+        self.level += 1;
+        Ok(())
+    }
+
+    pub fn end(&mut self) {
+        self.level = self.level.saturating_sub(1);
+    }
+}
+
+// The sizes come from the wire, never trust them to fit the array.
+fn sized<T>(data: &[T], size: usize) -> &[T] {
+    &data[..size.min(data.len())]
+}
+
+pub fn hex_string(data: &[u8]) -> String {
+    data.iter()
+        .map(|d| format!("{:02x}", d))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn print_svh(w: &mut FieldWriter, name: &str, svh: &SpdmSvhStruct) -> io::Result<()> {
+    let vendor_id = sized(&svh.vendor_id, svh.vendor_id_len as usize);
+    if vendor_id.is_empty() {
+        w.debug(name, svh.id)
+    } else {
+        w.field(
+            name,
+            format!("{:?} vendor id {}", svh.id, hex_string(vendor_id)),
+        )
+    }
+}
+
+fn print_digest(w: &mut FieldWriter, name: &str, digest: &SpdmDigestStruct) -> io::Result<()> {
+    w.bytes(name, sized(&digest.data, digest.data_size as usize))
+}
+
+fn print_signature(
+    w: &mut FieldWriter,
+    name: &str,
+    signature: &SpdmSignatureStruct,
+) -> io::Result<()> {
+    w.bytes(name, sized(&signature.data, signature.data_size as usize))
+}
+
+/// The opaque data is printed element by element when it follows the DMTF
+/// general opaque data format, the secured message versions are decoded.
+fn print_opaque(w: &mut FieldWriter, opaque: &SpdmOpaqueStruct) -> io::Result<()> {
+    let data = sized(&opaque.data, opaque.data_size as usize);
+    let general_opaque_data = match SpdmGeneralOpaqueData::from_opaque(opaque) {
+        Some(general_opaque_data) if !data.is_empty() => general_opaque_data,
+        _ => return w.bytes("opaque data", data),
+    };
+    w.begin(&format!("opaque data ({} bytes)", data.len()))?;
+    w.field("element count", general_opaque_data.element_count)?;
+    for element in general_opaque_data.iter() {
+        w.begin("element")?;
+        print_svh(w, "id", &element.id)?;
+        if let Some((versions, count)) = element.get_supported_version() {
+            w.debug("secured message supported versions", &versions[..count])?;
+        } else if let Some(version) = element.get_version_selection() {
+            w.debug("secured message version selection", version)?;
+        } else {
+            w.bytes("data", sized(&element.data, element.data_size as usize))?;
+        }
+        w.end();
+    }
+    w.end();
+    Ok(())
+}
+
+fn print_alg_structs(w: &mut FieldWriter, alg_structs: &[SpdmAlgStruct]) -> io::Result<()> {
+    for alg_struct in alg_structs {
+        w.begin("alg struct")?;
+        w.debug("alg type", alg_struct.alg_type)?;
+        w.debug("alg supported", alg_struct.alg_supported)?;
+        w.end();
+    }
+    Ok(())
+}
+
+fn print_measurement_record(
+    w: &mut FieldWriter,
+    measurement_record: &SpdmMeasurementRecordStructure,
+) -> io::Result<()> {
+    for block in measurement_record
+        .record
+        .iter()
+        .take(measurement_record.number_of_blocks as usize)
+    {
+        w.begin("measurement block")?;
+        w.field("index", block.index)?;
+        w.debug("measurement specification", block.measurement_specification)?;
+        w.field("measurement size", block.measurement_size)?;
+        w.debug("type", block.measurement.r#type)?;
+        w.debug("representation", block.measurement.representation)?;
+        w.bytes(
+            "value",
+            sized(
+                &block.measurement.value,
+                block.measurement.value_size as usize,
+            ),
+        )?;
+        w.end();
+    }
+    Ok(())
+}
+
+fn print_event_groups(
+    w: &mut FieldWriter,
+    event_groups: &[SpdmEventGroupStruct],
+) -> io::Result<()> {
+    for event_group in event_groups {
+        w.begin("event group")?;
+        print_svh(w, "event group id", &event_group.event_group_id)?;
+        w.field("event group version", event_group.event_group_ver)?;
+        w.debug("attributes", event_group.attributes)?;
+        w.debug(
+            "event types",
+            sized(
+                &event_group.event_types,
+                event_group.event_type_count as usize,
+            ),
+        )?;
+        w.end();
+    }
+    Ok(())
+}
+
+fn print_error(w: &mut FieldWriter, payload: &SpdmErrorResponsePayload) -> io::Result<()> {
+    w.debug("error code", payload.error_code)?;
+    w.field("error data", format!("{:#04x}", payload.error_data))?;
+    match &payload.extended_data {
+        SpdmErrorResponseExtData::SpdmErrorExtDataNone(_) => Ok(()),
+        SpdmErrorResponseExtData::SpdmErrorExtDataNotReady(ext_data) => {
+            w.begin("extended data (response not ready)")?;
+            w.field("rdt exponent", ext_data.rdt_exponent)?;
+            w.field("request code", format!("{:#04x}", ext_data.request_code))?;
+            w.field("token", ext_data.token)?;
+            w.field("rdtm", ext_data.tdtm)?;
+            w.end();
+            Ok(())
+        }
+        SpdmErrorResponseExtData::SpdmErrorExtDataVendorDefined(ext_data) => w.bytes(
+            "extended data (vendor defined)",
+            sized(&ext_data.data, ext_data.data_size as usize),
+        ),
+    }
+}
+
+/// Print the header and payload fields of a decoded message.
+pub fn print_message(w: &mut FieldWriter, message: &SpdmMessage) -> io::Result<()> {
+    w.debug("version", message.header.version)?;
+    w.debug(
+        "request response code",
+        message.header.request_response_code,
+    )?;
+    match &message.payload {
+        SpdmMessagePayload::SpdmGetVersionRequest(_) => {}
+        SpdmMessagePayload::SpdmVersionResponse(payload) => {
+            w.field(
+                "version number entry count",
+                payload.version_number_entry_count,
+            )?;
+            for version in payload
+                .versions
+                .iter()
+                .take(payload.version_number_entry_count as usize)
+            {
+                w.field(
+                    "version",
+                    format!("{:?} update {}", version.version, version.update),
+                )?;
+            }
+        }
+
+        SpdmMessagePayload::SpdmGetCapabilitiesRequest(payload) => {
+            w.field("ct exponent", payload.ct_exponent)?;
+            w.debug("flags", payload.flags)?;
+        }
+        SpdmMessagePayload::SpdmCapabilitiesResponse(payload) => {
+            w.field("ct exponent", payload.ct_exponent)?;
+            w.debug("flags", payload.flags)?;
+        }
+
+        SpdmMessagePayload::SpdmNegotiateAlgorithmsRequest(payload) => {
+            w.debug(
+                "measurement specification",
+                payload.measurement_specification,
+            )?;
+            w.debug("base asym algo", payload.base_asym_algo)?;
+            w.debug("base hash algo", payload.base_hash_algo)?;
+            print_alg_structs(
+                w,
+                sized(&payload.alg_struct, payload.alg_struct_count as usize),
+            )?;
+        }
+        SpdmMessagePayload::SpdmAlgorithmsResponse(payload) => {
+            w.debug(
+                "measurement specification sel",
+                payload.measurement_specification_sel,
+            )?;
+            w.debug("measurement hash algo", payload.measurement_hash_algo)?;
+            w.debug("base asym sel", payload.base_asym_sel)?;
+            w.debug("base hash sel", payload.base_hash_sel)?;
+            print_alg_structs(
+                w,
+                sized(&payload.alg_struct, payload.alg_struct_count as usize),
+            )?;
+        }
+
+        SpdmMessagePayload::SpdmGetDigestsRequest(_) => {}
+        SpdmMessagePayload::SpdmDigestsResponse(payload) => {
+            w.field("slot mask", format!("{:#04x}", payload.slot_mask))?;
+            for digest in payload.digests.iter().take(payload.slot_count as usize) {
+                print_digest(w, "digest", digest)?;
+            }
+        }
+
+        SpdmMessagePayload::SpdmGetCertificateRequest(payload) => {
+            w.field("slot id", payload.slot_id)?;
+            w.field("offset", payload.offset)?;
+            w.field("length", payload.length)?;
+        }
+        SpdmMessagePayload::SpdmCertificateResponse(payload) => {
+            w.field("slot id", payload.slot_id)?;
+            w.field("portion length", payload.portion_length)?;
+            w.field("remainder length", payload.remainder_length)?;
+            w.bytes(
+                "cert chain",
+                sized(&payload.cert_chain, payload.portion_length as usize),
+            )?;
+        }
+
+        SpdmMessagePayload::SpdmChallengeRequest(payload) => {
+            w.field("slot id", payload.slot_id)?;
+            w.debug(
+                "measurement summary hash type",
+                payload.measurement_summary_hash_type,
+            )?;
+            w.bytes("nonce", &payload.nonce.data)?;
+        }
+        SpdmMessagePayload::SpdmChallengeAuthResponse(payload) => {
+            w.field("slot id", payload.slot_id)?;
+            w.field("slot mask", format!("{:#04x}", payload.slot_mask))?;
+            w.debug("attribute", payload.challenge_auth_attribute)?;
+            print_digest(w, "cert chain hash", &payload.cert_chain_hash)?;
+            w.bytes("nonce", &payload.nonce.data)?;
+            print_digest(
+                w,
+                "measurement summary hash",
+                &payload.measurement_summary_hash,
+            )?;
+            print_opaque(w, &payload.opaque)?;
+            print_signature(w, "signature", &payload.signature)?;
+        }
+
+        SpdmMessagePayload::SpdmGetMeasurementsRequest(payload) => {
+            w.debug("attributes", payload.measurement_attributes)?;
+            w.debug("operation", payload.measurement_operation)?;
+            if payload
+                .measurement_attributes
+                .contains(SpdmMeasurementeAttributes::INCLUDE_SIGNATURE)
+            {
+                w.bytes("nonce", &payload.nonce.data)?;
+                w.field("slot id", payload.slot_id)?;
+            }
+        }
+        SpdmMessagePayload::SpdmMeasurementsResponse(payload) => {
+            w.field("number of measurement", payload.number_of_measurement)?;
+            w.field("slot id", payload.slot_id)?;
+            w.field(
+                "number of blocks",
+                payload.measurement_record.number_of_blocks,
+            )?;
+            print_measurement_record(w, &payload.measurement_record)?;
+            w.bytes("nonce", &payload.nonce.data)?;
+            print_opaque(w, &payload.opaque)?;
+            print_signature(w, "signature", &payload.signature)?;
+        }
+
+        SpdmMessagePayload::SpdmKeyExchangeRequest(payload) => {
+            w.debug(
+                "measurement summary hash type",
+                payload.measurement_summary_hash_type,
+            )?;
+            w.field("slot id", payload.slot_id)?;
+            w.field("req session id", format!("{:#06x}", payload.req_session_id))?;
+            w.debug("session policy", payload.session_policy)?;
+            w.bytes("random", &payload.random.data)?;
+            w.bytes(
+                "exchange data",
+                sized(&payload.exchange.data, payload.exchange.data_size as usize),
+            )?;
+            print_opaque(w, &payload.opaque)?;
+        }
+        SpdmMessagePayload::SpdmKeyExchangeResponse(payload) => {
+            w.field("heartbeat period", payload.heartbeat_period)?;
+            w.field("rsp session id", format!("{:#06x}", payload.rsp_session_id))?;
+            w.debug("mut auth requested", payload.mut_auth_req)?;
+            w.field("req slot id", payload.req_slot_id)?;
+            w.bytes("random", &payload.random.data)?;
+            w.bytes(
+                "exchange data",
+                sized(&payload.exchange.data, payload.exchange.data_size as usize),
+            )?;
+            print_digest(
+                w,
+                "measurement summary hash",
+                &payload.measurement_summary_hash,
+            )?;
+            print_opaque(w, &payload.opaque)?;
+            print_signature(w, "signature", &payload.signature)?;
+            print_digest(w, "verify data", &payload.verify_data)?;
+        }
+
+        SpdmMessagePayload::SpdmFinishRequest(payload) => {
+            w.debug("attributes", payload.finish_request_attributes)?;
+            w.field("req slot id", payload.req_slot_id)?;
+            print_signature(w, "signature", &payload.signature)?;
+            print_digest(w, "verify data", &payload.verify_data)?;
+        }
+        SpdmMessagePayload::SpdmFinishResponse(payload) => {
+            print_digest(w, "verify data", &payload.verify_data)?;
+        }
+
+        SpdmMessagePayload::SpdmPskExchangeRequest(payload) => {
+            w.debug(
+                "measurement summary hash type",
+                payload.measurement_summary_hash_type,
+            )?;
+            w.debug("session policy", payload.session_policy)?;
+            w.field("req session id", format!("{:#06x}", payload.req_session_id))?;
+            w.bytes(
+                "psk hint",
+                sized(&payload.psk_hint.data, payload.psk_hint.data_size as usize),
+            )?;
+            w.bytes(
+                "psk context",
+                sized(
+                    &payload.psk_context.data,
+                    payload.psk_context.data_size as usize,
+                ),
+            )?;
+            print_opaque(w, &payload.opaque)?;
+        }
+        SpdmMessagePayload::SpdmPskExchangeResponse(payload) => {
+            w.field("heartbeat period", payload.heartbeat_period)?;
+            w.field("rsp session id", format!("{:#06x}", payload.rsp_session_id))?;
+            print_digest(
+                w,
+                "measurement summary hash",
+                &payload.measurement_summary_hash,
+            )?;
+            w.bytes(
+                "psk context",
+                sized(
+                    &payload.psk_context.data,
+                    payload.psk_context.data_size as usize,
+                ),
+            )?;
+            print_opaque(w, &payload.opaque)?;
+            print_digest(w, "verify data", &payload.verify_data)?;
+        }
+
+        SpdmMessagePayload::SpdmPskFinishRequest(payload) => {
+            print_digest(w, "verify data", &payload.verify_data)?;
+        }
+        SpdmMessagePayload::SpdmPskFinishResponse(_) => {}
+
+        SpdmMessagePayload::SpdmHeartbeatRequest(_) => {}
+        SpdmMessagePayload::SpdmHeartbeatResponse(_) => {}
+
+        SpdmMessagePayload::SpdmKeyUpdateRequest(payload) => {
+            w.debug("operation", payload.key_update_operation)?;
+            w.field("tag", payload.tag)?;
+        }
+        SpdmMessagePayload::SpdmKeyUpdateResponse(payload) => {
+            w.debug("operation", payload.key_update_operation)?;
+            w.field("tag", payload.tag)?;
+        }
+
+        SpdmMessagePayload::SpdmEndSessionRequest(payload) => {
+            w.debug("attributes", payload.end_session_request_attributes)?;
+        }
+        SpdmMessagePayload::SpdmEndSessionResponse(_) => {}
+
+        SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(_) => {}
+        SpdmMessagePayload::SpdmSupportedEventTypesResponse(payload) => {
+            w.field("event group count", payload.event_group_count)?;
+            print_event_groups(
+                w,
+                sized(&payload.event_groups, payload.event_group_count as usize),
+            )?;
+        }
+
+        SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload) => {
+            w.field("event group count", payload.event_group_count)?;
+            print_event_groups(
+                w,
+                sized(&payload.event_groups, payload.event_group_count as usize),
+            )?;
+        }
+        SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(_) => {}
+
+        SpdmMessagePayload::SpdmSendEventRequest(payload) => {
+            w.field("event count", payload.event_count)?;
+            for event in payload.events.iter().take(payload.event_count as usize) {
+                w.begin("event")?;
+                w.field("event instance id", event.event_instance_id)?;
+                print_svh(w, "event group id", &event.event_group_id)?;
+                w.field("event type id", event.event_type_id)?;
+                w.bytes(
+                    "event detail",
+                    sized(&event.event_detail, event.event_detail_size as usize),
+                )?;
+                w.end();
+            }
+        }
+        SpdmMessagePayload::SpdmEventAckResponse(_) => {}
+
+        SpdmMessagePayload::SpdmGetEndpointInfoRequest(payload) => {
+            w.debug("sub code", payload.sub_code)?;
+            w.debug("attributes", payload.request_attributes)?;
+            w.field("slot id", payload.slot_id)?;
+            if payload
+                .request_attributes
+                .contains(SpdmEndpointInfoRequestAttributes::SIGNATURE_REQUESTED)
+            {
+                w.bytes("nonce", &payload.nonce.data)?;
+            }
+        }
+        SpdmMessagePayload::SpdmEndpointInfoResponse(payload) => {
+            w.field("slot id", payload.slot_id)?;
+            if payload.signature.data_size != 0 {
+                w.bytes("nonce", &payload.nonce.data)?;
+            }
+            w.bytes(
+                "endpoint info",
+                sized(&payload.ep_info, payload.ep_info_len as usize),
+            )?;
+            if payload.signature.data_size != 0 {
+                print_signature(w, "signature", &payload.signature)?;
+            }
+        }
+
+        SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(payload) => {
+            w.field("offset", payload.offset)?;
+            w.field("length", payload.length)?;
+        }
+        SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(payload) => {
+            w.field("portion length", payload.portion_length)?;
+            w.field("remainder length", payload.remainder_length)?;
+            w.bytes(
+                "measurement extension log",
+                sized(&payload.mel_portion, payload.portion_length as usize),
+            )?;
+        }
+
+        SpdmMessagePayload::SpdmErrorResponse(payload) => {
+            print_error(w, payload)?;
+        }
+    }
+    Ok(())
+}
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use conquer_once::spin::OnceCell;
 use spdmlib::crypto::SpdmAsymSign;
 
 use spdmlib::msgs::{
@@ -10,6 +11,30 @@ use spdmlib::msgs::{
 
 pub static ASYM_SIGN_IMPL: SpdmAsymSign = SpdmAsymSign { sign_cb: asym_sign };
 
+static PRIVATE_KEY_DIR: OnceCell<String> = OnceCell::uninit();
+
+/// Set the key directory holding the signing keys, see `--key_dir`.
+/// Only the first call takes effect.
+pub fn set_private_key_dir(key_dir: &str) {
+    let _ = PRIVATE_KEY_DIR.try_init_once(|| key_dir.to_string());
+}
+
+fn get_private_key_file_path(base_asym_algo: SpdmBaseAsymAlgo) -> String {
+    let key_dir = PRIVATE_KEY_DIR
+        .get()
+        .map(|key_dir| key_dir.as_str())
+        .unwrap_or(crate::emu_config::EMU_DEFAULT_KEY_DIR);
+    let asym_dir = crate::emu_config::get_asym_key_dir_name(base_asym_algo)
+        .expect("no test key for the asym algorithm!");
+    // ring reads ECDSA keys in PKCS#8 and RSA keys in DER
+    let key_file = if asym_dir.starts_with("Ec") {
+        "end_responder.key.p8"
+    } else {
+        "end_responder.key.der"
+    };
+    format!("{}/{}/{}", key_dir, asym_dir, key_file)
+}
+
 fn asym_sign(
     base_hash_algo: SpdmBaseHashAlgo,
     base_asym_algo: SpdmBaseAsymAlgo,
@@ -17,15 +42,24 @@ fn asym_sign(
 ) -> Option<SpdmSignatureStruct> {
     match (base_hash_algo, base_asym_algo) {
         (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) => {
-            sign_ecdsa_asym_algo(&ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING, data)
+            sign_ecdsa_asym_algo(
+                base_asym_algo,
+                &ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                data,
+            )
         }
         (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
-            sign_ecdsa_asym_algo(&ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING, data)
+            sign_ecdsa_asym_algo(
+                base_asym_algo,
+                &ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                data,
+            )
         }
         (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PKCS1_SHA256,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -35,6 +69,7 @@ fn asym_sign(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PSS_SHA256,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -44,6 +79,7 @@ fn asym_sign(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PKCS1_SHA384,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -53,6 +89,7 @@ fn asym_sign(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PSS_SHA384,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -62,6 +99,7 @@ fn asym_sign(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PKCS1_SHA512,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -71,6 +109,7 @@ fn asym_sign(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
             sign_rsa_asym_algo(
+                base_asym_algo,
                 &ring::signature::RSA_PSS_SHA512,
                 base_asym_algo.get_size() as usize,
                 data,
@@ -83,6 +122,7 @@ fn asym_sign(
 }
 
 fn sign_ecdsa_asym_algo(
+    base_asym_algo: SpdmBaseAsymAlgo,
     algorithm: &'static ring::signature::EcdsaSigningAlgorithm,
     data: &[u8],
 ) -> Option<SpdmSignatureStruct> {
@@ -90,11 +130,7 @@ fn sign_ecdsa_asym_algo(
     // or  openssl.exe ecparam -name prime256v1 -genkey -out private.der -outform der
     // openssl.exe pkcs8 -in private.der -inform DER -topk8 -nocrypt -outform DER > private.p8
 
-    let key_file_path = if crate::spdm_emu::USE_ECDSA {
-        "test_key/EcP384/end_responder.key.p8"
-    } else {
-        "test_key/Rsa3072/end_responder.key.der"
-    };
+    let key_file_path = get_private_key_file_path(base_asym_algo);
     let der_file = std::fs::read(key_file_path).expect("unable to read key der!");
     let key_bytes = der_file.as_slice();
 
@@ -119,17 +155,14 @@ fn sign_ecdsa_asym_algo(
 }
 
 fn sign_rsa_asym_algo(
+    base_asym_algo: SpdmBaseAsymAlgo,
     padding_alg: &'static dyn ring::signature::RsaEncoding,
     key_len: usize,
     data: &[u8],
 ) -> Option<SpdmSignatureStruct> {
     // openssl.exe genpkey -algorithm rsa -pkeyopt rsa_keygen_bits:2048 -pkeyopt rsa_keygen_pubexp:65537 -outform DER > private.der
 
-    let key_file_path = if crate::spdm_emu::USE_ECDSA {
-        "test_key/EcP384/end_responder.key.p8"
-    } else {
-        "test_key/Rsa3072/end_responder.key.der"
-    };
+    let key_file_path = get_private_key_file_path(base_asym_algo);
     let der_file = std::fs::read(key_file_path).expect("unable to read key der!");
     let key_bytes = der_file.as_slice();
 
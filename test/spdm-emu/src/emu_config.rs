@@ -0,0 +1,866 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Emulator options.
+//!
+//! The option names and values follow the DMTF spdm-emu, e.g.
+//! `--trans MCTP --ver 1.1 --hash SHA_256,SHA_384 --exe_session KEY_EX,HEARTBEAT`.
+//! The same options may be given in a JSON config file with `--config <file>`,
+//! e.g. `{"trans": "MCTP", "hash": ["SHA_256", "SHA_384"], "port": 2323}`.
+//! Command line options override the config file.
+
+use bitflags::bitflags;
+use codec::Codec;
+use serde_json::Value;
+
+use spdmlib::common::SpdmConfigInfo;
+use spdmlib::config;
+use spdmlib::msgs::*;
+
+use crate::spdm_emu::{SOCKET_TRANSPORT_TYPE_MCTP, SOCKET_TRANSPORT_TYPE_PCI_DOE};
+
+pub const EMU_DEFAULT_HOST: &str = "127.0.0.1";
+pub const EMU_DEFAULT_PORT: u16 = 2323;
+pub const EMU_DEFAULT_KEY_DIR: &str = "test_key";
+pub const EMU_DEFAULT_IDLE_TIMEOUT: u32 = 60;
+
+pub const EMU_USAGE: &str = "\
+   [--trans MCTP|PCI_DOE]
+   [--ver 1.0|1.1] [--sec_ver 1.0|1.1]
+   [--cap CACHE|CERT|CHAL|MEAS_NO_SIG|MEAS_SIG|MEAS_FRESH|ENCRYPT|MAC|MUT_AUTH|KEY_EX|PSK|PSK_WITH_CONTEXT|ENCAP|HBEAT|KEY_UPD|HANDSHAKE_IN_CLEAR|PUB_KEY_ID]
+   [--hash SHA_256|SHA_384|SHA_512|SHA3_256|SHA3_384|SHA3_512]
+   [--meas_spec DMTF]
+   [--meas_hash RAW_BIT|SHA_256|SHA_384|SHA_512|SHA3_256|SHA3_384|SHA3_512]
+   [--asym RSASSA_2048|RSASSA_3072|RSASSA_4096|RSAPSS_2048|RSAPSS_3072|RSAPSS_4096|ECDSA_P256|ECDSA_P384|ECDSA_P521]
+   [--req_asym RSASSA_2048|RSASSA_3072|RSASSA_4096|RSAPSS_2048|RSAPSS_3072|RSAPSS_4096|ECDSA_P256|ECDSA_P384|ECDSA_P521]
+   [--dhe FFDHE_2048|FFDHE_3072|FFDHE_4096|SECP_256_R1|SECP_384_R1|SECP_521_R1]
+   [--aead AES_128_GCM|AES_256_GCM|CHACHA20_POLY1305]
+   [--key_schedule HMAC_HASH]
+   [--meas_sum NO|TCB|ALL]
+   [--key_upd REQ|ALL]
+   [--slot_id <0~7>]
+   [--exe_conn VER_ONLY|DIGEST|CERT|CHAL|MEAS]
+   [--exe_session KEY_EX|PSK|NO_END|KEY_UPDATE|HEARTBEAT]
+   [--key_dir <dir>] [--psk <hex>] [--psk_hint <string>]
+   [--host <address>] [--port <port>]
+   [--idle_timeout <seconds>] [--max_conn_count <count>]
+   [--heartbeat_period <seconds>]
+   [--config <json file>]
+
+   Multiple values are separated by ','.
+   --key_dir holds the EcP256, EcP384, Rsa2048 and Rsa3072 test key directories.
+   --idle_timeout closes a responder connection without traffic, 0 never closes it.
+   --max_conn_count stops the responder after serving the count of connections, 0 serves forever.
+   --heartbeat_period is advertised by the responder with HBEAT, a session without traffic
+     for twice the period is ended. 0, the default, disables the heartbeat.";
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EmuTransport {
+    Mctp,
+    PciDoe,
+}
+
+impl EmuTransport {
+    pub fn get_socket_transport_type(&self) -> u32 {
+        match self {
+            EmuTransport::Mctp => SOCKET_TRANSPORT_TYPE_MCTP,
+            EmuTransport::PciDoe => SOCKET_TRANSPORT_TYPE_PCI_DOE,
+        }
+    }
+}
+
+bitflags! {
+    /// Requester flows run before a session, see `--exe_conn`.
+    #[derive(Default)]
+    pub struct EmuConnectionFlags: u32 {
+        const VER_ONLY = 0b0000_0001;
+        const DIGEST = 0b0000_0010;
+        const CERT = 0b0000_0100;
+        const CHAL = 0b0000_1000;
+        const MEAS = 0b0001_0000;
+    }
+}
+
+bitflags! {
+    /// Requester session flows, see `--exe_session`.
+    #[derive(Default)]
+    pub struct EmuSessionFlags: u32 {
+        const KEY_EX = 0b0000_0001;
+        const PSK = 0b0000_0010;
+        const NO_END = 0b0000_0100;
+        const KEY_UPDATE = 0b0000_1000;
+        const HEARTBEAT = 0b0001_0000;
+    }
+}
+
+const VERSION_TABLE: &[(&str, u32)] = &[("1.0", 0x10), ("1.1", 0x11)];
+
+const SECURED_MESSAGE_VERSION_TABLE: &[(&str, u32)] = &[("1.0", 0x1000), ("1.1", 0x1100)];
+
+const REQ_CAP_TABLE: &[(&str, u32)] = &[
+    ("CERT", SpdmRequestCapabilityFlags::CERT_CAP.bits()),
+    ("CHAL", SpdmRequestCapabilityFlags::CHAL_CAP.bits()),
+    ("ENCRYPT", SpdmRequestCapabilityFlags::ENCRYPT_CAP.bits()),
+    ("MAC", SpdmRequestCapabilityFlags::MAC_CAP.bits()),
+    ("MUT_AUTH", SpdmRequestCapabilityFlags::MUT_AUTH_CAP.bits()),
+    ("KEY_EX", SpdmRequestCapabilityFlags::KEY_EX_CAP.bits()),
+    ("PSK", SpdmRequestCapabilityFlags::PSK_CAP.bits()),
+    ("ENCAP", SpdmRequestCapabilityFlags::ENCAP_CAP.bits()),
+    ("HBEAT", SpdmRequestCapabilityFlags::HBEAT_CAP.bits()),
+    ("KEY_UPD", SpdmRequestCapabilityFlags::KEY_UPD_CAP.bits()),
+    (
+        "HANDSHAKE_IN_CLEAR",
+        SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP.bits(),
+    ),
+    (
+        "PUB_KEY_ID",
+        SpdmRequestCapabilityFlags::PUB_KEY_ID_CAP.bits(),
+    ),
+];
+
+const RSP_CAP_TABLE: &[(&str, u32)] = &[
+    ("CACHE", SpdmResponseCapabilityFlags::CACHE_CAP.bits()),
+    ("CERT", SpdmResponseCapabilityFlags::CERT_CAP.bits()),
+    ("CHAL", SpdmResponseCapabilityFlags::CHAL_CAP.bits()),
+    (
+        "MEAS_NO_SIG",
+        SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG.bits(),
+    ),
+    ("MEAS_SIG", SpdmResponseCapabilityFlags::MEAS_CAP_SIG.bits()),
+    (
+        "MEAS_FRESH",
+        SpdmResponseCapabilityFlags::MEAS_FRESH_CAP.bits(),
+    ),
+    ("ENCRYPT", SpdmResponseCapabilityFlags::ENCRYPT_CAP.bits()),
+    ("MAC", SpdmResponseCapabilityFlags::MAC_CAP.bits()),
+    ("MUT_AUTH", SpdmResponseCapabilityFlags::MUT_AUTH_CAP.bits()),
+    ("KEY_EX", SpdmResponseCapabilityFlags::KEY_EX_CAP.bits()),
+    ("PSK", SpdmResponseCapabilityFlags::PSK_CAP.bits()),
+    (
+        "PSK_WITH_CONTEXT",
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT.bits(),
+    ),
+    ("ENCAP", SpdmResponseCapabilityFlags::ENCAP_CAP.bits()),
+    ("HBEAT", SpdmResponseCapabilityFlags::HBEAT_CAP.bits()),
+    ("KEY_UPD", SpdmResponseCapabilityFlags::KEY_UPD_CAP.bits()),
+    (
+        "HANDSHAKE_IN_CLEAR",
+        SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP.bits(),
+    ),
+    (
+        "PUB_KEY_ID",
+        SpdmResponseCapabilityFlags::PUB_KEY_ID_CAP.bits(),
+    ),
+];
+
+const HASH_TABLE: &[(&str, u32)] = &[
+    ("SHA_256", SpdmBaseHashAlgo::TPM_ALG_SHA_256.bits()),
+    ("SHA_384", SpdmBaseHashAlgo::TPM_ALG_SHA_384.bits()),
+    ("SHA_512", SpdmBaseHashAlgo::TPM_ALG_SHA_512.bits()),
+    ("SHA3_256", SpdmBaseHashAlgo::TPM_ALG_SHA3_256.bits()),
+    ("SHA3_384", SpdmBaseHashAlgo::TPM_ALG_SHA3_384.bits()),
+    ("SHA3_512", SpdmBaseHashAlgo::TPM_ALG_SHA3_512.bits()),
+];
+
+const MEAS_SPEC_TABLE: &[(&str, u32)] =
+    &[("DMTF", SpdmMeasurementSpecification::DMTF.bits() as u32)];
+
+const MEAS_HASH_TABLE: &[(&str, u32)] = &[
+    ("RAW_BIT", SpdmMeasurementHashAlgo::RAW_BIT_STREAM.bits()),
+    ("SHA_256", SpdmMeasurementHashAlgo::TPM_ALG_SHA_256.bits()),
+    ("SHA_384", SpdmMeasurementHashAlgo::TPM_ALG_SHA_384.bits()),
+    ("SHA_512", SpdmMeasurementHashAlgo::TPM_ALG_SHA_512.bits()),
+    ("SHA3_256", SpdmMeasurementHashAlgo::TPM_ALG_SHA3_256.bits()),
+    ("SHA3_384", SpdmMeasurementHashAlgo::TPM_ALG_SHA3_384.bits()),
+    ("SHA3_512", SpdmMeasurementHashAlgo::TPM_ALG_SHA3_512.bits()),
+];
+
+const ASYM_TABLE: &[(&str, u32)] = &[
+    ("RSASSA_2048", SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048.bits()),
+    ("RSAPSS_2048", SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048.bits()),
+    ("RSASSA_3072", SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072.bits()),
+    ("RSAPSS_3072", SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072.bits()),
+    (
+        "ECDSA_P256",
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256.bits(),
+    ),
+    ("RSASSA_4096", SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096.bits()),
+    ("RSAPSS_4096", SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096.bits()),
+    (
+        "ECDSA_P384",
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384.bits(),
+    ),
+    (
+        "ECDSA_P521",
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P521.bits(),
+    ),
+];
+
+const DHE_TABLE: &[(&str, u32)] = &[
+    ("FFDHE_2048", SpdmDheAlgo::FFDHE_2048.bits() as u32),
+    ("FFDHE_3072", SpdmDheAlgo::FFDHE_3072.bits() as u32),
+    ("FFDHE_4096", SpdmDheAlgo::FFDHE_4096.bits() as u32),
+    ("SECP_256_R1", SpdmDheAlgo::SECP_256_R1.bits() as u32),
+    ("SECP_384_R1", SpdmDheAlgo::SECP_384_R1.bits() as u32),
+    ("SECP_521_R1", SpdmDheAlgo::SECP_521_R1.bits() as u32),
+];
+
+const AEAD_TABLE: &[(&str, u32)] = &[
+    ("AES_128_GCM", SpdmAeadAlgo::AES_128_GCM.bits() as u32),
+    ("AES_256_GCM", SpdmAeadAlgo::AES_256_GCM.bits() as u32),
+    (
+        "CHACHA20_POLY1305",
+        SpdmAeadAlgo::CHACHA20_POLY1305.bits() as u32,
+    ),
+];
+
+const KEY_SCHEDULE_TABLE: &[(&str, u32)] = &[(
+    "HMAC_HASH",
+    SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE.bits() as u32,
+)];
+
+const MEAS_SUM_TABLE: &[(&str, u32)] = &[("NO", 0x0), ("TCB", 0x1), ("ALL", 0xFF)];
+
+const KEY_UPD_TABLE: &[(&str, u32)] = &[("REQ", 0x1), ("ALL", 0x2)];
+
+const EXE_CONN_TABLE: &[(&str, u32)] = &[
+    ("VER_ONLY", EmuConnectionFlags::VER_ONLY.bits()),
+    ("DIGEST", EmuConnectionFlags::DIGEST.bits()),
+    ("CERT", EmuConnectionFlags::CERT.bits()),
+    ("CHAL", EmuConnectionFlags::CHAL.bits()),
+    ("MEAS", EmuConnectionFlags::MEAS.bits()),
+];
+
+const EXE_SESSION_TABLE: &[(&str, u32)] = &[
+    ("KEY_EX", EmuSessionFlags::KEY_EX.bits()),
+    ("PSK", EmuSessionFlags::PSK.bits()),
+    ("NO_END", EmuSessionFlags::NO_END.bits()),
+    ("KEY_UPDATE", EmuSessionFlags::KEY_UPDATE.bits()),
+    ("HEARTBEAT", EmuSessionFlags::HEARTBEAT.bits()),
+];
+
+/// Look up every ',' separated name of value in table.
+fn parse_names(name: &str, value: &str, table: &[(&str, u32)]) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            let v = v.trim();
+            table
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(v))
+                .map(|(_, bits)| *bits)
+                .ok_or_else(|| format!("invalid value {} for --{}", v, name))
+        })
+        .collect()
+}
+
+fn parse_flags(name: &str, value: &str, table: &[(&str, u32)]) -> Result<u32, String> {
+    Ok(parse_names(name, value, table)?
+        .iter()
+        .fold(0, |flags, bits| flags | bits))
+}
+
+fn parse_single(name: &str, value: &str, table: &[(&str, u32)]) -> Result<u32, String> {
+    let values = parse_names(name, value, table)?;
+    if values.len() != 1 {
+        return Err(format!("--{} takes a single value", name));
+    }
+    Ok(values[0])
+}
+
+/// Parse a decimal or 0x prefixed hex number given for --name.
+pub fn parse_number(name: &str, value: &str) -> Result<u32, String> {
+    let parsed = if let Some(hex) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        u32::from_str_radix(hex, 16)
+    } else {
+        value.parse::<u32>()
+    };
+    parsed.map_err(|_| format!("invalid number {} for --{}", value, name))
+}
+
+/// Parse a hex string such as 0102ff given for --name.
+pub fn parse_hex(name: &str, value: &str) -> Result<Vec<u8>, String> {
+    let value = value.trim();
+    if value.len() % 2 != 0 {
+        return Err(format!("invalid hex string for --{}", name));
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex string for --{}", name))
+        })
+        .collect()
+}
+
+/// Subdirectory of the key directory holding the keys for base_asym_algo.
+/// With several algorithms, the first of P384, P256, RSA 3072 and RSA 2048 wins.
+pub fn get_asym_key_dir_name(base_asym_algo: SpdmBaseAsymAlgo) -> Option<&'static str> {
+    if base_asym_algo.contains(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) {
+        Some("EcP384")
+    } else if base_asym_algo.contains(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) {
+        Some("EcP256")
+    } else if base_asym_algo
+        .intersects(SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072 | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
+    {
+        Some("Rsa3072")
+    } else if base_asym_algo
+        .intersects(SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048 | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
+    {
+        Some("Rsa2048")
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmuConfig {
+    pub transport: EmuTransport,
+    pub host: String,
+    pub port: u16,
+    pub spdm_version: Vec<SpdmVersion>,
+    pub secured_message_version: Vec<SpdmSecuredMessageVersion>,
+    pub req_capabilities: SpdmRequestCapabilityFlags,
+    pub rsp_capabilities: SpdmResponseCapabilityFlags,
+    pub base_hash_algo: SpdmBaseHashAlgo,
+    pub measurement_specification: SpdmMeasurementSpecification,
+    pub measurement_hash_algo: SpdmMeasurementHashAlgo,
+    pub base_asym_algo: SpdmBaseAsymAlgo,
+    pub req_asym_algo: SpdmReqAsymAlgo,
+    pub dhe_algo: SpdmDheAlgo,
+    pub aead_algo: SpdmAeadAlgo,
+    pub key_schedule_algo: SpdmKeyScheduleAlgo,
+    pub measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    pub key_update_operation: SpdmKeyUpdateOperation,
+    pub slot_id: u8,
+    pub exe_conn: EmuConnectionFlags,
+    pub exe_session: EmuSessionFlags,
+    pub key_dir: String,
+    pub psk: Option<Vec<u8>>,
+    pub psk_hint: Option<Vec<u8>>,
+    /// Seconds a responder connection may stay idle, 0 for no timeout.
+    pub idle_timeout: u32,
+    /// Connections the responder serves before it exits, 0 for no limit.
+    pub max_conn_count: u32,
+    /// Heartbeat period in seconds the responder advertises, 0 for none.
+    pub heartbeat_period: u8,
+}
+
+impl Default for EmuConfig {
+    fn default() -> Self {
+        EmuConfig {
+            transport: EmuTransport::PciDoe,
+            host: EMU_DEFAULT_HOST.to_string(),
+            port: EMU_DEFAULT_PORT,
+            spdm_version: vec![SpdmVersion::SpdmVersion10, SpdmVersion::SpdmVersion11],
+            secured_message_version: vec![
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion11,
+                SpdmSecuredMessageVersion::SpdmSecuredMessageVersion10,
+            ],
+            req_capabilities: SpdmRequestCapabilityFlags::CERT_CAP
+                | SpdmRequestCapabilityFlags::CHAL_CAP
+                | SpdmRequestCapabilityFlags::ENCRYPT_CAP
+                | SpdmRequestCapabilityFlags::MAC_CAP
+                | SpdmRequestCapabilityFlags::KEY_EX_CAP
+                | SpdmRequestCapabilityFlags::PSK_CAP
+                | SpdmRequestCapabilityFlags::ENCAP_CAP
+                | SpdmRequestCapabilityFlags::HBEAT_CAP
+                | SpdmRequestCapabilityFlags::KEY_UPD_CAP,
+            rsp_capabilities: SpdmResponseCapabilityFlags::CERT_CAP
+                | SpdmResponseCapabilityFlags::CHAL_CAP
+                | SpdmResponseCapabilityFlags::MEAS_CAP_SIG
+                | SpdmResponseCapabilityFlags::MEAS_FRESH_CAP
+                | SpdmResponseCapabilityFlags::ENCRYPT_CAP
+                | SpdmResponseCapabilityFlags::MAC_CAP
+                | SpdmResponseCapabilityFlags::KEY_EX_CAP
+                | SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT
+                | SpdmResponseCapabilityFlags::ENCAP_CAP
+                | SpdmResponseCapabilityFlags::HBEAT_CAP
+                | SpdmResponseCapabilityFlags::KEY_UPD_CAP,
+            base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_hash_algo: SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+            base_asym_algo: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+            req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
+            dhe_algo: SpdmDheAlgo::SECP_384_R1,
+            aead_algo: SpdmAeadAlgo::AES_256_GCM,
+            key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            key_update_operation: SpdmKeyUpdateOperation::SpdmUpdateAllKeys,
+            slot_id: 0,
+            exe_conn: EmuConnectionFlags::all() - EmuConnectionFlags::VER_ONLY,
+            exe_session: EmuSessionFlags::KEY_EX
+                | EmuSessionFlags::PSK
+                | EmuSessionFlags::KEY_UPDATE
+                | EmuSessionFlags::HEARTBEAT,
+            key_dir: EMU_DEFAULT_KEY_DIR.to_string(),
+            psk: None,
+            psk_hint: None,
+            idle_timeout: EMU_DEFAULT_IDLE_TIMEOUT,
+            max_conn_count: 0,
+            heartbeat_period: 0,
+        }
+    }
+}
+
+impl EmuConfig {
+    /// Parse the command line arguments, without the program name.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<EmuConfig, String> {
+        let mut emu_config = EmuConfig::default();
+        let mut options = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unknown argument {}", arg))?
+                .to_string();
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            if name == "config" {
+                emu_config.load_config_file(&value)?;
+            } else {
+                options.push((name, value));
+            }
+        }
+        for (name, value) in options.iter() {
+            emu_config.set_option(name, value)?;
+        }
+        Ok(emu_config)
+    }
+
+    /// Parse the process arguments, print the usage and exit on error.
+    pub fn from_env_or_exit() -> EmuConfig {
+        let mut args = std::env::args();
+        let program = args.next().unwrap_or_default();
+        let args: Vec<String> = args.collect();
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            println!("{}\n{}", program, EMU_USAGE);
+            std::process::exit(0);
+        }
+        match EmuConfig::from_args(args) {
+            Ok(emu_config) => emu_config,
+            Err(err) => {
+                eprintln!("{}\n\n{}\n{}", err, program, EMU_USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Apply the options of a JSON config file. Values may be strings,
+    /// numbers or arrays of strings.
+    pub fn load_config_file(&mut self, path: &str) -> Result<(), String> {
+        let data =
+            std::fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+        self.load_config_json(&data)
+            .map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn load_config_json(&mut self, data: &str) -> Result<(), String> {
+        let json: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        let options = json
+            .as_object()
+            .ok_or_else(|| "config is not a JSON object".to_string())?;
+        for (name, value) in options.iter() {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Array(values) => values
+                    .iter()
+                    .map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or_else(|| format!("invalid value for {}", name))?
+                    .join(","),
+                _ => return Err(format!("invalid value for {}", name)),
+            };
+            self.set_option(name, &value)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "trans" => {
+                self.transport = match value {
+                    "MCTP" => EmuTransport::Mctp,
+                    "PCI_DOE" => EmuTransport::PciDoe,
+                    _ => return Err(format!("invalid value {} for --trans", value)),
+                }
+            }
+            "host" => self.host = value.to_string(),
+            "port" => {
+                let port = parse_number(name, value)?;
+                if port > u16::MAX as u32 {
+                    return Err(format!("invalid port {}", value));
+                }
+                self.port = port as u16;
+            }
+            "ver" => {
+                let versions = parse_names(name, value, VERSION_TABLE)?;
+                if versions.len() > config::MAX_SPDM_VERSION_COUNT {
+                    return Err(format!(
+                        "at most {} versions for --ver",
+                        config::MAX_SPDM_VERSION_COUNT
+                    ));
+                }
+                self.spdm_version = versions
+                    .iter()
+                    .map(|v| SpdmVersion::read_bytes(&[*v as u8]).unwrap())
+                    .collect();
+            }
+            "sec_ver" => {
+                let versions = parse_names(name, value, SECURED_MESSAGE_VERSION_TABLE)?;
+                if versions.len() > SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT {
+                    return Err(format!(
+                        "at most {} versions for --sec_ver",
+                        SPDM_MAX_SECURED_MESSAGE_VERSION_COUNT
+                    ));
+                }
+                self.secured_message_version = versions
+                    .iter()
+                    .map(|v| {
+                        SpdmSecuredMessageVersion::read_bytes(&(*v as u16).to_le_bytes()).unwrap()
+                    })
+                    .collect();
+            }
+            "cap" => {
+                // the names apply to the local role, keep those each role knows
+                let mut req_capabilities = SpdmRequestCapabilityFlags::empty();
+                let mut rsp_capabilities = SpdmResponseCapabilityFlags::empty();
+                for cap in value.split(',') {
+                    let req = parse_flags(name, cap, REQ_CAP_TABLE);
+                    let rsp = parse_flags(name, cap, RSP_CAP_TABLE);
+                    if req.is_err() && rsp.is_err() {
+                        return rsp.map(|_| ());
+                    }
+                    req_capabilities |=
+                        SpdmRequestCapabilityFlags::from_bits_truncate(req.unwrap_or(0));
+                    rsp_capabilities |=
+                        SpdmResponseCapabilityFlags::from_bits_truncate(rsp.unwrap_or(0));
+                }
+                self.req_capabilities = req_capabilities;
+                self.rsp_capabilities = rsp_capabilities;
+            }
+            "hash" => {
+                self.base_hash_algo =
+                    SpdmBaseHashAlgo::from_bits_truncate(parse_flags(name, value, HASH_TABLE)?)
+            }
+            "meas_spec" => {
+                self.measurement_specification = SpdmMeasurementSpecification::from_bits_truncate(
+                    parse_flags(name, value, MEAS_SPEC_TABLE)? as u8,
+                )
+            }
+            "meas_hash" => {
+                self.measurement_hash_algo = SpdmMeasurementHashAlgo::from_bits_truncate(
+                    parse_flags(name, value, MEAS_HASH_TABLE)?,
+                )
+            }
+            "asym" => {
+                self.base_asym_algo =
+                    SpdmBaseAsymAlgo::from_bits_truncate(parse_flags(name, value, ASYM_TABLE)?)
+            }
+            "req_asym" => {
+                self.req_asym_algo = SpdmReqAsymAlgo::from_bits_truncate(parse_flags(
+                    name, value, ASYM_TABLE,
+                )? as u16)
+            }
+            "dhe" => {
+                self.dhe_algo =
+                    SpdmDheAlgo::from_bits_truncate(parse_flags(name, value, DHE_TABLE)? as u16)
+            }
+            "aead" => {
+                self.aead_algo =
+                    SpdmAeadAlgo::from_bits_truncate(parse_flags(name, value, AEAD_TABLE)? as u16)
+            }
+            "key_schedule" => {
+                self.key_schedule_algo = SpdmKeyScheduleAlgo::from_bits_truncate(parse_flags(
+                    name,
+                    value,
+                    KEY_SCHEDULE_TABLE,
+                )?
+                    as u16)
+            }
+            "meas_sum" => {
+                let meas_sum = parse_single(name, value, MEAS_SUM_TABLE)?;
+                self.measurement_summary_hash_type =
+                    SpdmMeasurementSummaryHashType::read_bytes(&[meas_sum as u8]).unwrap();
+            }
+            "key_upd" => {
+                let key_upd = parse_single(name, value, KEY_UPD_TABLE)?;
+                self.key_update_operation =
+                    SpdmKeyUpdateOperation::read_bytes(&[key_upd as u8]).unwrap();
+            }
+            "idle_timeout" => self.idle_timeout = parse_number(name, value)?,
+            "max_conn_count" => self.max_conn_count = parse_number(name, value)?,
+            "heartbeat_period" => {
+                let heartbeat_period = parse_number(name, value)?;
+                if heartbeat_period > u8::MAX as u32 {
+                    return Err(format!("invalid heartbeat period {}", value));
+                }
+                self.heartbeat_period = heartbeat_period as u8;
+            }
+            "slot_id" => {
+                let slot_id = parse_number(name, value)?;
+                if slot_id >= 8 {
+                    return Err(format!("invalid slot {}", value));
+                }
+                self.slot_id = slot_id as u8;
+            }
+            "exe_conn" => {
+                self.exe_conn = EmuConnectionFlags::from_bits_truncate(parse_flags(
+                    name,
+                    value,
+                    EXE_CONN_TABLE,
+                )?)
+            }
+            "exe_session" => {
+                self.exe_session = EmuSessionFlags::from_bits_truncate(parse_flags(
+                    name,
+                    value,
+                    EXE_SESSION_TABLE,
+                )?)
+            }
+            "key_dir" => self.key_dir = value.to_string(),
+            "psk" => {
+                let psk = parse_hex(name, value)?;
+                if psk.is_empty() || psk.len() > SPDM_MAX_DHE_KEY_SIZE {
+                    return Err("invalid --psk size".to_string());
+                }
+                self.psk = Some(psk);
+            }
+            "psk_hint" => {
+                if value.len() > config::MAX_SPDM_PSK_HINT_SIZE {
+                    return Err("invalid --psk_hint size".to_string());
+                }
+                self.psk_hint = Some(value.as_bytes().to_vec());
+            }
+            _ => return Err(format!("unknown option --{}", name)),
+        }
+        Ok(())
+    }
+
+    pub fn get_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    fn get_config_info(&self) -> SpdmConfigInfo {
+        let mut config_info = SpdmConfigInfo {
+            measurement_specification: self.measurement_specification,
+            measurement_hash_algo: self.measurement_hash_algo,
+            base_asym_algo: self.base_asym_algo,
+            base_hash_algo: self.base_hash_algo,
+            dhe_algo: self.dhe_algo,
+            aead_algo: self.aead_algo,
+            req_asym_algo: self.req_asym_algo,
+            key_schedule_algo: self.key_schedule_algo,
+            ..Default::default()
+        };
+        // unused entries repeat the last version
+        for (i, version) in config_info.spdm_version.iter_mut().enumerate() {
+            *version = self.spdm_version[i.min(self.spdm_version.len() - 1)];
+        }
+        config_info.secured_message_version = Default::default();
+        for (version, selected) in config_info
+            .secured_message_version
+            .iter_mut()
+            .zip(self.secured_message_version.iter())
+        {
+            *version = *selected;
+        }
+        config_info
+    }
+
+    pub fn get_requester_config_info(&self) -> SpdmConfigInfo {
+        SpdmConfigInfo {
+            req_capabilities: self.req_capabilities,
+            req_ct_exponent: 0,
+            ..self.get_config_info()
+        }
+    }
+
+    pub fn get_responder_config_info(&self) -> SpdmConfigInfo {
+        SpdmConfigInfo {
+            rsp_capabilities: self.rsp_capabilities,
+            rsp_ct_exponent: 0,
+            heartbeat_period: self.heartbeat_period,
+            ..self.get_config_info()
+        }
+    }
+
+    /// Path of a file in the key directory of the base asym algorithm.
+    pub fn get_key_file_path(&self, file_name: &str) -> Result<String, String> {
+        let dir = get_asym_key_dir_name(self.base_asym_algo)
+            .ok_or_else(|| format!("no test key for {:?}", self.base_asym_algo))?;
+        Ok(format!("{}/{}/{}", self.key_dir, dir, file_name))
+    }
+
+    /// The responder certificate chain, root CA first.
+    pub fn read_responder_cert_chain(&self) -> Result<SpdmCertChainData, String> {
+        let mut cert_chain_data = SpdmCertChainData::default();
+        let mut used = 0usize;
+        for file_name in ["ca.cert.der", "inter.cert.der", "end_responder.cert.der"].iter() {
+            let path = self.get_key_file_path(file_name)?;
+            let cert =
+                std::fs::read(&path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+            if used + cert.len() > config::MAX_SPDM_CERT_CHAIN_DATA_SIZE {
+                return Err("certificate chain too large".to_string());
+            }
+            cert_chain_data.data[used..(used + cert.len())].copy_from_slice(&cert);
+            used += cert.len();
+        }
+        cert_chain_data.data_size = used as u16;
+        Ok(cert_chain_data)
+    }
+
+    pub fn get_psk(&self) -> Option<SpdmDheFinalKeyStruct> {
+        self.psk.as_ref().map(|psk| {
+            let mut psk_key = SpdmDheFinalKeyStruct {
+                data_size: psk.len() as u16,
+                ..Default::default()
+            };
+            psk_key.data[..psk.len()].copy_from_slice(psk);
+            psk_key
+        })
+    }
+
+    pub fn get_psk_hint(&self) -> Option<SpdmPskHintStruct> {
+        self.psk_hint.as_ref().map(|hint| {
+            let mut psk_hint = SpdmPskHintStruct {
+                data_size: hint.len() as u16,
+                ..Default::default()
+            };
+            psk_hint.data[..hint.len()].copy_from_slice(hint);
+            psk_hint
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_case0_default() {
+        let emu_config = EmuConfig::from_args(Vec::new()).unwrap();
+        assert_eq!(emu_config.transport, EmuTransport::PciDoe);
+        assert_eq!(emu_config.get_address(), "127.0.0.1:2323");
+        assert_eq!(emu_config.idle_timeout, EMU_DEFAULT_IDLE_TIMEOUT);
+        assert_eq!(emu_config.max_conn_count, 0);
+        assert_eq!(emu_config.heartbeat_period, 0);
+        let config_info = emu_config.get_responder_config_info();
+        assert_eq!(
+            config_info.spdm_version,
+            [SpdmVersion::SpdmVersion10, SpdmVersion::SpdmVersion11]
+        );
+        assert_eq!(
+            emu_config.get_key_file_path("ca.cert.der").unwrap(),
+            "test_key/EcP384/ca.cert.der"
+        );
+    }
+
+    #[test]
+    fn test_case0_from_args() {
+        let emu_config = EmuConfig::from_args(args(
+            "--trans MCTP --ver 1.1 --cap CERT,CHAL,MEAS_SIG --hash SHA_256,SHA_384 \
+             --asym RSASSA_3072 --dhe FFDHE_3072 --aead AES_128_GCM --meas_sum ALL \
+             --slot_id 0x1 --exe_session KEY_EX,NO_END --psk 0102ff --psk_hint abc \
+             --host 10.0.0.1 --port 4000 --key_dir keys --idle_timeout 5 --max_conn_count 0x10 \
+             --heartbeat_period 30",
+        ))
+        .unwrap();
+        assert_eq!(emu_config.transport, EmuTransport::Mctp);
+        assert_eq!(emu_config.get_address(), "10.0.0.1:4000");
+        assert_eq!(emu_config.idle_timeout, 5);
+        assert_eq!(emu_config.max_conn_count, 16);
+        assert_eq!(emu_config.heartbeat_period, 30);
+        assert_eq!(emu_config.get_responder_config_info().heartbeat_period, 30);
+        assert_eq!(emu_config.spdm_version, vec![SpdmVersion::SpdmVersion11]);
+        assert_eq!(
+            emu_config.rsp_capabilities,
+            SpdmResponseCapabilityFlags::CERT_CAP
+                | SpdmResponseCapabilityFlags::CHAL_CAP
+                | SpdmResponseCapabilityFlags::MEAS_CAP_SIG
+        );
+        assert_eq!(
+            emu_config.req_capabilities,
+            SpdmRequestCapabilityFlags::CERT_CAP | SpdmRequestCapabilityFlags::CHAL_CAP
+        );
+        assert_eq!(
+            emu_config.base_hash_algo,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_256 | SpdmBaseHashAlgo::TPM_ALG_SHA_384
+        );
+        assert_eq!(emu_config.dhe_algo, SpdmDheAlgo::FFDHE_3072);
+        assert_eq!(emu_config.aead_algo, SpdmAeadAlgo::AES_128_GCM);
+        assert_eq!(
+            emu_config.measurement_summary_hash_type,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll
+        );
+        assert_eq!(emu_config.slot_id, 1);
+        assert_eq!(
+            emu_config.exe_session,
+            EmuSessionFlags::KEY_EX | EmuSessionFlags::NO_END
+        );
+        assert_eq!(emu_config.get_psk().unwrap().as_ref(), &[0x01, 0x02, 0xff]);
+        assert_eq!(emu_config.get_psk_hint().unwrap().as_ref(), b"abc");
+        assert_eq!(
+            emu_config.get_key_file_path("ca.cert.der").unwrap(),
+            "keys/Rsa3072/ca.cert.der"
+        );
+        let config_info = emu_config.get_requester_config_info();
+        assert_eq!(
+            config_info.spdm_version,
+            [SpdmVersion::SpdmVersion11, SpdmVersion::SpdmVersion11]
+        );
+    }
+
+    #[test]
+    fn test_case1_from_args() {
+        assert!(EmuConfig::from_args(args("--trans TCP")).is_err());
+        assert!(EmuConfig::from_args(args("--hash SHA_1")).is_err());
+        assert!(EmuConfig::from_args(args("--cap FOO")).is_err());
+        assert!(EmuConfig::from_args(args("--meas_sum TCB,ALL")).is_err());
+        assert!(EmuConfig::from_args(args("--slot_id 8")).is_err());
+        assert!(EmuConfig::from_args(args("--psk 123")).is_err());
+        assert!(EmuConfig::from_args(args("--port 70000")).is_err());
+        assert!(EmuConfig::from_args(args("--heartbeat_period 256")).is_err());
+        assert!(EmuConfig::from_args(args("--ver")).is_err());
+        assert!(EmuConfig::from_args(args("ver 1.0")).is_err());
+        assert!(EmuConfig::from_args(args("--unknown 1")).is_err());
+    }
+
+    #[test]
+    fn test_case0_load_config_json() {
+        let mut emu_config = EmuConfig::default();
+        emu_config
+            .load_config_json(
+                r#"{"trans": "MCTP", "hash": ["SHA_256", "SHA_512"], "port": 4194,
+                    "exe_conn": "DIGEST,CERT"}"#,
+            )
+            .unwrap();
+        assert_eq!(emu_config.transport, EmuTransport::Mctp);
+        assert_eq!(emu_config.port, 4194);
+        assert_eq!(
+            emu_config.base_hash_algo,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_256 | SpdmBaseHashAlgo::TPM_ALG_SHA_512
+        );
+        assert_eq!(
+            emu_config.exe_conn,
+            EmuConnectionFlags::DIGEST | EmuConnectionFlags::CERT
+        );
+
+        assert!(emu_config.load_config_json("[1, 2]").is_err());
+        assert!(emu_config.load_config_json(r#"{"port": true}"#).is_err());
+        assert!(emu_config.load_config_json(r#"{"hash": [1]}"#).is_err());
+    }
+}
@@ -5,6 +5,8 @@
 #![forbid(unsafe_code)]
 
 pub mod crypto_callback;
+pub mod emu_config;
 pub mod socket_io_transport;
 pub mod spdm_emu;
 pub mod tcp_transport;
+pub mod time_source;
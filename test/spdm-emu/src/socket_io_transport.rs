@@ -14,14 +14,10 @@ pub struct SocketIoTransport<'a> {
     transport_type: u32,
 }
 impl<'a> SocketIoTransport<'a> {
-    pub fn new(stream: &'a mut TcpStream) -> Self {
+    pub fn new(stream: &'a mut TcpStream, transport_type: u32) -> Self {
         SocketIoTransport {
             data: stream,
-            transport_type: if USE_PCIDOE {
-                SOCKET_TRANSPORT_TYPE_PCI_DOE
-            } else {
-                SOCKET_TRANSPORT_TYPE_MCTP
-            },
+            transport_type,
         }
     }
 }
@@ -2,16 +2,14 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use std::io::{Read, Write};
+use log::*;
+use std::io::{ErrorKind, Read, Write};
 use std::net::TcpStream;
 
 use codec::{Codec, Reader, Writer};
 use spdmlib::config;
 
 pub const SOCKET_HEADER_LEN: usize = 12;
-pub const USE_PCIDOE: bool = true;
-pub const USE_ECDSA: bool = true;
-pub const USE_ECDH: bool = true;
 
 pub const SOCKET_TRANSPORT_TYPE_MCTP: u32 = 0x01;
 pub const SOCKET_TRANSPORT_TYPE_PCI_DOE: u32 = 0x02;
@@ -49,6 +47,7 @@ impl Codec for SpdmSocketHeader {
 }
 
 // u32 type, u32 command, usize, payload
+// None if the peer closed the connection, the read timed out or failed.
 pub fn receive_message<'a>(
     stream: &mut TcpStream,
     buffer: &'a mut [u8],
@@ -56,9 +55,21 @@ pub fn receive_message<'a>(
     let mut buffer_size = 0;
     let mut expected_size = 0;
     loop {
-        let s = stream
-            .read(&mut buffer[buffer_size..])
-            .expect("socket read error!");
+        let s = match stream.read(&mut buffer[buffer_size..]) {
+            Ok(0) => {
+                info!("connection closed by peer\n");
+                return None;
+            }
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                info!("connection idle timeout\n");
+                return None;
+            }
+            Err(e) => {
+                error!("socket read error - {:?}\n", e);
+                return None;
+            }
+        };
         buffer_size += s;
         // println!("received: {:?}", s);
         if (expected_size == 0) && (buffer_size >= SOCKET_HEADER_LEN) {
@@ -73,7 +84,7 @@ pub fn receive_message<'a>(
             break;
         }
     }
-    println!(
+    debug!(
         "read: {:02X?}{:02X?}",
         &buffer[..SOCKET_HEADER_LEN],
         &buffer[SOCKET_HEADER_LEN..buffer_size]
@@ -118,7 +129,7 @@ pub fn send_message(
         .expect("socket write error!");
     stream.write_all(payload).expect("socket write error!");
     stream.flush().expect("flush error");
-    println!("write: {:02X?}{:02X?}", &buffer[..used], payload);
+    debug!("write: {:02X?}{:02X?}", &buffer[..used], payload);
 
     buffer_size
 }
@@ -0,0 +1,32 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use std::time::Instant;
+
+use spdmlib::common::SpdmTimeSource;
+
+/// Monotonic time in milliseconds since the time source was created.
+pub struct EmuTimeSource {
+    start: Instant,
+}
+
+impl EmuTimeSource {
+    pub fn new() -> Self {
+        EmuTimeSource {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for EmuTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpdmTimeSource for EmuTimeSource {
+    fn get_time_ms(&mut self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
@@ -17,8 +17,10 @@ use spdmlib::requester;
 
 use mctp_transport::MctpTransportEncap;
 use pcidoe_transport::PciDoeTransportEncap;
+use spdm_emu::emu_config::{EmuConfig, EmuConnectionFlags, EmuSessionFlags, EmuTransport};
 use spdm_emu::socket_io_transport::SocketIoTransport;
 use spdm_emu::spdm_emu::*;
+use spdm_emu::time_source::EmuTimeSource;
 use std::net::TcpStream;
 
 fn send_receive_hello(
@@ -69,178 +71,122 @@ fn send_receive_stop(
 fn test_spdm(
     socket_io_transport: &mut SocketIoTransport,
     transport_encap: &mut dyn SpdmTransportEncap,
+    emu_config: &EmuConfig,
 ) {
-    let config_info = common::SpdmConfigInfo {
-        spdm_version: [SpdmVersion::SpdmVersion10, SpdmVersion::SpdmVersion11],
-        req_capabilities: SpdmRequestCapabilityFlags::CERT_CAP
-        | SpdmRequestCapabilityFlags::CHAL_CAP
-        | SpdmRequestCapabilityFlags::ENCRYPT_CAP
-        | SpdmRequestCapabilityFlags::MAC_CAP
-        //| SpdmRequestCapabilityFlags::MUT_AUTH_CAP
-        | SpdmRequestCapabilityFlags::KEY_EX_CAP
-        | SpdmRequestCapabilityFlags::PSK_CAP
-        | SpdmRequestCapabilityFlags::ENCAP_CAP
-        | SpdmRequestCapabilityFlags::HBEAT_CAP
-        | SpdmRequestCapabilityFlags::KEY_UPD_CAP, // | SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP
-        // | SpdmRequestCapabilityFlags::PUB_KEY_ID_CAP
-        req_ct_exponent: 0,
-        measurement_specification: SpdmMeasurementSpecification::DMTF,
-        base_asym_algo: if USE_ECDSA {
-            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
-        } else {
-            SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
-        },
-        base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
-        dhe_algo: if USE_ECDH {
-            SpdmDheAlgo::SECP_384_R1
-        } else {
-            SpdmDheAlgo::FFDHE_3072
-        },
-        aead_algo: SpdmAeadAlgo::AES_256_GCM,
-        req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
-        key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
-        ..Default::default()
-    };
+    let config_info = emu_config.get_requester_config_info();
 
-    let mut peer_cert_chain_data = SpdmCertChainData {
-        ..Default::default()
-    };
-
-    let ca_file_path = if USE_ECDSA {
-        "test_key/EcP384/ca.cert.der"
-    } else {
-        "test_key/Rsa3072/ca.cert.der"
-    };
-    let ca_cert = std::fs::read(ca_file_path).expect("unable to read ca cert!");
-    let inter_file_path = if USE_ECDSA {
-        "test_key/EcP384/inter.cert.der"
-    } else {
-        "test_key/Rsa3072/inter.cert.der"
-    };
-    let inter_cert = std::fs::read(inter_file_path).expect("unable to read inter cert!");
-    let leaf_file_path = if USE_ECDSA {
-        "test_key/EcP384/end_responder.cert.der"
-    } else {
-        "test_key/Rsa3072/end_responder.cert.der"
-    };
-    let leaf_cert = std::fs::read(leaf_file_path).expect("unable to read leaf cert!");
-
-    let ca_len = ca_cert.len();
-    let inter_len = inter_cert.len();
-    let leaf_len = leaf_cert.len();
-    println!(
-        "total cert size - {:?} = {:?} + {:?} + {:?}",
-        ca_len + inter_len + leaf_len,
-        ca_len,
-        inter_len,
-        leaf_len
-    );
-    peer_cert_chain_data.data_size = (ca_len + inter_len + leaf_len) as u16;
-    peer_cert_chain_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
-    peer_cert_chain_data.data[ca_len..(ca_len + inter_len)].copy_from_slice(inter_cert.as_ref());
-    peer_cert_chain_data.data[(ca_len + inter_len)..(ca_len + inter_len + leaf_len)]
-        .copy_from_slice(leaf_cert.as_ref());
+    let peer_cert_chain_data = emu_config
+        .read_responder_cert_chain()
+        .expect("unable to read cert chain!");
+    println!("total cert size - {:?}", peer_cert_chain_data.data_size);
 
     let provision_info = common::SpdmProvisionInfo {
         my_cert_chain_data: None,
         my_cert_chain: None,
         peer_cert_chain_data: Some(peer_cert_chain_data),
         peer_cert_chain_root_hash: None,
+        psk: emu_config.get_psk(),
+        psk_hint: emu_config.get_psk_hint(),
     };
 
+    let mut time_source = EmuTimeSource::new();
     let mut context = requester::RequesterContext::new(
         socket_io_transport,
         transport_encap,
         config_info,
         provision_info,
     );
+    context.common.register_time_source(&mut time_source);
+
+    let slot_id = emu_config.slot_id;
+    let measurement_summary_hash_type = emu_config.measurement_summary_hash_type;
+
+    if emu_config.exe_conn.contains(EmuConnectionFlags::VER_ONLY) {
+        if context.send_receive_spdm_version().is_err() {
+            error!("get version fail\n");
+        }
+        return;
+    }
 
     if context.init_connection().is_err() {
         return;
     }
 
-    if context.send_receive_spdm_digest().is_err() {
+    if emu_config.exe_conn.contains(EmuConnectionFlags::DIGEST)
+        && context.send_receive_spdm_digest().is_err()
+    {
         return;
     }
 
-    if context.send_receive_spdm_certificate(0).is_err() {
+    if emu_config.exe_conn.contains(EmuConnectionFlags::CERT)
+        && context.send_receive_spdm_certificate(slot_id).is_err()
+    {
         return;
     }
 
-    if context
-        .send_receive_spdm_challenge(
-            0,
-            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
-        )
-        .is_err()
+    if emu_config.exe_conn.contains(EmuConnectionFlags::CHAL)
+        && context
+            .send_receive_spdm_challenge(slot_id, measurement_summary_hash_type)
+            .is_err()
     {
         return;
     }
 
-    if context
-        .send_receive_spdm_measurement(SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber, 0)
-        .is_err()
+    if emu_config.exe_conn.contains(EmuConnectionFlags::MEAS)
+        && context
+            .send_receive_spdm_measurement(
+                SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+                slot_id,
+            )
+            .is_err()
     {
         return;
     }
 
-    let result = context.start_session(
-        false,
-        0,
-        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
-    );
-    if let Ok(session_id) = result {
+    for (use_psk, flag) in [
+        (false, EmuSessionFlags::KEY_EX),
+        (true, EmuSessionFlags::PSK),
+    ]
+    .iter()
+    {
+        if !emu_config.exe_session.contains(*flag) {
+            continue;
+        }
+        let result = context.start_session(*use_psk, slot_id, measurement_summary_hash_type);
+        let session_id = match result {
+            Ok(session_id) => session_id,
+            Err(_) => {
+                info!("\nSession session_id not got\n");
+                continue;
+            }
+        };
         info!("\nSession established ... session_id {:0x?}\n", session_id);
-        info!("Key Information ...\n");
-
-        let session = context.common.get_session_via_id(session_id).unwrap();
-        let (request_direction, response_direction) = session.export_keys();
-        info!(
-            "equest_direction.encryption_key {:0x?}\n",
-            request_direction.encryption_key.as_ref()
-        );
-        info!(
-            "equest_direction.salt {:0x?}\n",
-            request_direction.salt.as_ref()
-        );
-        info!(
-            "esponse_direction.encryption_key {:0x?}\n",
-            response_direction.encryption_key.as_ref()
-        );
-        info!(
-            "esponse_direction.salt {:0x?}\n",
-            response_direction.salt.as_ref()
-        );
-
-        if context.send_receive_spdm_heartbeat(session_id).is_err() {
+
+        if emu_config.exe_session.contains(EmuSessionFlags::HEARTBEAT)
+            && context.send_receive_spdm_heartbeat(session_id).is_err()
+        {
             return;
         }
 
-        if context
-            .send_receive_spdm_key_update(session_id, SpdmKeyUpdateOperation::SpdmUpdateAllKeys)
-            .is_err()
+        if emu_config.exe_session.contains(EmuSessionFlags::KEY_UPDATE)
+            && context
+                .send_receive_spdm_key_update(session_id, emu_config.key_update_operation)
+                .is_err()
         {
             return;
         }
 
-        if context.end_session(session_id).is_err() {
+        // send the HEARTBEAT of the sessions which are due
+        if context.poll().is_err() {
             return;
         }
-    } else {
-        info!("\nSession session_id not got\n");
-    }
 
-    let result = context.start_session(
-        true,
-        0,
-        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
-    );
-    if let Ok(session_id) = result {
-        if context.end_session(session_id).is_err() {
+        if !emu_config.exe_session.contains(EmuSessionFlags::NO_END)
+            && context.end_session(session_id).is_err()
+        {
             info!("\nSession session_id is err\n");
+            return;
         }
-    } else {
-        info!("\nSession session_id not got\n");
     }
 }
 
@@ -262,6 +208,7 @@ fn new_logger_from_env() -> SimpleLogger {
 }
 
 fn main() {
+    let emu_config = EmuConfig::from_env_or_exit();
     new_logger_from_env().init().unwrap();
 
     let since_the_epoch = std::time::SystemTime::now()
@@ -270,27 +217,22 @@ fn main() {
     println!("current unit time epoch - {:?}", since_the_epoch.as_secs());
 
     let mut socket =
-        TcpStream::connect("127.0.0.1:2323").expect("Couldn't connect to the server...");
+        TcpStream::connect(emu_config.get_address()).expect("Couldn't connect to the server...");
 
     let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
     let mctp_transport_encap = &mut MctpTransportEncap {};
 
-    let transport_encap: &mut dyn SpdmTransportEncap = if USE_PCIDOE {
-        pcidoe_transport_encap
-    } else {
-        mctp_transport_encap
+    let transport_encap: &mut dyn SpdmTransportEncap = match emu_config.transport {
+        EmuTransport::PciDoe => pcidoe_transport_encap,
+        EmuTransport::Mctp => mctp_transport_encap,
     };
 
-    let transport_type = if USE_PCIDOE {
-        SOCKET_TRANSPORT_TYPE_PCI_DOE
-    } else {
-        SOCKET_TRANSPORT_TYPE_MCTP
-    };
+    let transport_type = emu_config.transport.get_socket_transport_type();
 
     send_receive_hello(&mut socket, transport_encap, transport_type);
 
-    let socket_io_transport = &mut SocketIoTransport::new(&mut socket);
-    test_spdm(socket_io_transport, transport_encap);
+    let socket_io_transport = &mut SocketIoTransport::new(&mut socket, transport_type);
+    test_spdm(socket_io_transport, transport_encap, &emu_config);
 
     send_receive_stop(&mut socket, transport_encap, transport_type);
 }
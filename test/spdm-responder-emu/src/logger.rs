@@ -0,0 +1,71 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! A stdout logger tagging each line with the connection ID of the thread,
+//! so the interleaved logs of concurrent connections can be told apart.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::cell::Cell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static CONNECTION_ID: Cell<Option<u32>> = Cell::new(None);
+}
+
+/// Tag the logs of the calling thread with the connection ID.
+pub fn set_connection_id(connection_id: u32) {
+    CONNECTION_ID.with(|id| id.set(Some(connection_id)));
+}
+
+struct ConnectionLogger {
+    level: LevelFilter,
+}
+
+impl Log for ConnectionLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let connection = match CONNECTION_ID.with(|id| id.get()) {
+            Some(connection_id) => format!("conn {}", connection_id),
+            None => "main".to_string(),
+        };
+        println!(
+            "{}.{:03} {:<5} [{}] [{}] {}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            record.level(),
+            connection,
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+// The user may choose the log level by setting a `SPDM_LOG` environment variable.
+// Use the `Trace` level by default.
+pub fn init_from_env() {
+    let level = match std::env::var("SPDM_LOG") {
+        Ok(x) => match x.to_lowercase().as_str() {
+            "trace" => LevelFilter::Trace,
+            "debug" => LevelFilter::Debug,
+            "info" => LevelFilter::Info,
+            "warn" => LevelFilter::Warn,
+            _ => LevelFilter::Error,
+        },
+        _ => LevelFilter::Trace,
+    };
+
+    log::set_logger(Box::leak(Box::new(ConnectionLogger { level }))).unwrap();
+    log::set_max_level(level);
+}
@@ -4,28 +4,63 @@
 
 #![forbid(unsafe_code)]
 
-use log::LevelFilter;
-use simple_logger::SimpleLogger;
+mod logger;
 
-use std::net::{TcpListener, TcpStream};
+use log::*;
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use std::u32;
 
-use codec::{Codec, Reader, Writer};
+use codec::{Codec, Reader};
 use common::SpdmTransportEncap;
-use mctp_transport::MctpTransportEncap;
-use pcidoe_transport::{
-    PciDoeDataObjectType, PciDoeMessageHeader, PciDoeTransportEncap, PciDoeVendorId,
-};
+use mctp_transport::{MctpMessageType, MctpTransportEncap};
+use pcidoe_transport::{PciDoeDiscoveryResponder, PciDoeTransportEncap};
 use spdm_emu::crypto_callback::ASYM_SIGN_IMPL;
+use spdm_emu::emu_config::{EmuConfig, EmuTransport};
 use spdm_emu::socket_io_transport::SocketIoTransport;
 use spdm_emu::spdm_emu::*;
-use spdmlib::msgs::*;
+use spdm_emu::time_source::EmuTimeSource;
+use spdmlib::error::SpdmResult;
 use spdmlib::{common, responder};
 
+const PLDM_GET_TID_RESPONSE: &[u8; 5] = &[
+    0x00u8, 0x00u8, //PLDM_MESSAGE_TYPE_CONTROL_DISCOVERY
+    0x02u8, //PLDM_CONTROL_DISCOVERY_COMMAND_GET_TID
+    0x00u8, //PLDM_BASE_CODE_SUCCESS
+    0x01u8, //TID
+];
+
+/// Negotiated state kept across connections, as a responder with CACHE_CAP
+/// keeps it across a reset. It is kept per peer address, so that a requester
+/// never gets the state negotiated by another one.
+type NegotiatedStateCache = Arc<Mutex<HashMap<IpAddr, common::SpdmNegotiatedState>>>;
+
+/// Answers every PLDM request in a session with the GetTID response.
+struct PldmResponder {}
+
+impl responder::SpdmAppMessageHandler for PldmResponder {
+    fn dispatch(
+        &mut self,
+        _session_id: u32,
+        _app_message_type: u8,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> SpdmResult<usize> {
+        info!("PLDM request - {:02x?}\n", request);
+        response[..PLDM_GET_TID_RESPONSE.len()].copy_from_slice(PLDM_GET_TID_RESPONSE);
+        Ok(PLDM_GET_TID_RESPONSE.len())
+    }
+}
+
 fn process_socket_message(
     stream: &mut TcpStream,
     transport_encap: &mut dyn SpdmTransportEncap,
     buffer: &[u8],
+    emu_config: &EmuConfig,
 ) -> bool {
     if buffer.len() < SOCKET_HEADER_LEN {
         return false;
@@ -50,7 +85,7 @@ fn process_socket_message(
         }
         SOCKET_SPDM_COMMAND_NORMAL => true,
         _ => {
-            if USE_PCIDOE {
+            if emu_config.transport == EmuTransport::PciDoe {
                 send_pci_discovery(stream, transport_encap, res.0, buffer)
             } else {
                 send_unknown(stream, transport_encap, res.0);
@@ -60,184 +95,177 @@ fn process_socket_message(
     }
 }
 
-// A new logger enables the user to choose log level by setting a `SPDM_LOG` environment variable.
-// Use the `Trace` level by default.
-fn new_logger_from_env() -> SimpleLogger {
-    let level = match std::env::var("SPDM_LOG") {
-        Ok(x) => match x.to_lowercase().as_str() {
-            "trace" => LevelFilter::Trace,
-            "debug" => LevelFilter::Debug,
-            "info" => LevelFilter::Info,
-            "warn" => LevelFilter::Warn,
-            _ => LevelFilter::Error,
-        },
-        _ => LevelFilter::Trace,
-    };
-
-    SimpleLogger::new().with_level(level)
+fn new_transport_encap(transport: EmuTransport) -> Box<dyn SpdmTransportEncap> {
+    match transport {
+        EmuTransport::PciDoe => Box::new(PciDoeTransportEncap {}),
+        EmuTransport::Mctp => Box::new(MctpTransportEncap {}),
+    }
 }
 
 fn main() {
-    new_logger_from_env().init().unwrap();
+    let emu_config = EmuConfig::from_env_or_exit();
+    logger::init_from_env();
 
-    let listener = TcpListener::bind("127.0.0.1:2323").expect("Couldn't bind to the server");
-    println!("server start!");
+    spdm_emu::crypto_callback::set_private_key_dir(&emu_config.key_dir);
+    spdmlib::crypto::asym_sign::register(ASYM_SIGN_IMPL);
 
-    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
-    let mctp_transport_encap = &mut MctpTransportEncap {};
+    let listener =
+        TcpListener::bind(emu_config.get_address()).expect("Couldn't bind to the server");
+    info!("server start on {}!\n", emu_config.get_address());
 
+    let negotiated_state_cache: NegotiatedStateCache = Arc::new(Mutex::new(HashMap::new()));
+    let mut connection_count = 0;
+    let mut handles = Vec::new();
     for stream in listener.incoming() {
-        let mut stream = stream.expect("Read stream error!");
-        println!("new connection!");
-        let mut need_continue;
-        loop {
-            let res = handle_message(
-                &mut stream,
-                if USE_PCIDOE {
-                    pcidoe_transport_encap
-                } else {
-                    mctp_transport_encap
-                },
-            );
-            match res {
-                Ok(_spdm_result) => {
-                    need_continue = true;
-                }
-                Err((used, buffer)) => {
-                    need_continue = process_socket_message(
-                        &mut stream,
-                        if USE_PCIDOE {
-                            pcidoe_transport_encap
-                        } else {
-                            mctp_transport_encap
-                        },
-                        &buffer[0..used],
-                    );
-                }
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("accept error - {:?}\n", e);
+                continue;
             }
-            if !need_continue {
-                // TBD: return or break??
-                return;
+        };
+        connection_count += 1;
+        let connection_id = connection_count;
+        let connection_config = emu_config.clone();
+        let connection_cache = negotiated_state_cache.clone();
+        let handle = thread::Builder::new()
+            .name(format!("conn-{}", connection_id))
+            .spawn(move || {
+                logger::set_connection_id(connection_id);
+                handle_connection(stream, &connection_config, &connection_cache);
+            })
+            .expect("unable to spawn connection thread");
+
+        if emu_config.max_conn_count != 0 {
+            handles.push(handle);
+            if connection_count >= emu_config.max_conn_count {
+                break;
             }
         }
     }
+
+    for handle in handles {
+        let _ = handle.join();
+    }
+    info!("server stop!\n");
 }
 
-fn handle_message(
-    stream: &mut TcpStream,
-    transport_encap: &mut dyn SpdmTransportEncap,
-) -> Result<bool, (usize, [u8; 1024])> {
-    println!("handle_message!");
-    let mut socket_io_transport = SocketIoTransport::new(stream);
-
-    let config_info = common::SpdmConfigInfo {
-        spdm_version: [SpdmVersion::SpdmVersion10, SpdmVersion::SpdmVersion11],
-        rsp_capabilities: SpdmResponseCapabilityFlags::CERT_CAP
-        | SpdmResponseCapabilityFlags::CHAL_CAP
-        | SpdmResponseCapabilityFlags::MEAS_CAP_SIG
-        | SpdmResponseCapabilityFlags::MEAS_FRESH_CAP
-        | SpdmResponseCapabilityFlags::ENCRYPT_CAP
-        | SpdmResponseCapabilityFlags::MAC_CAP
-        //| SpdmResponseCapabilityFlags::MUT_AUTH_CAP
-        | SpdmResponseCapabilityFlags::KEY_EX_CAP
-        | SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT
-        | SpdmResponseCapabilityFlags::ENCAP_CAP
-        | SpdmResponseCapabilityFlags::HBEAT_CAP
-        | SpdmResponseCapabilityFlags::KEY_UPD_CAP, // | SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP
-        // | SpdmResponseCapabilityFlags::PUB_KEY_ID_CAP
-        rsp_ct_exponent: 0,
-        measurement_specification: SpdmMeasurementSpecification::DMTF,
-        measurement_hash_algo: SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
-        base_asym_algo: if USE_ECDSA {
-            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
-        } else {
-            SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
-        },
-        base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
-        dhe_algo: if USE_ECDH {
-            SpdmDheAlgo::SECP_384_R1
-        } else {
-            SpdmDheAlgo::FFDHE_3072
-        },
-        aead_algo: SpdmAeadAlgo::AES_256_GCM,
-        req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
-        key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
-        ..Default::default()
+// Serve one requester with its own responder context and session table until
+// it sends STOP, closes the connection or stays idle for too long.
+fn handle_connection(
+    mut stream: TcpStream,
+    emu_config: &EmuConfig,
+    negotiated_state_cache: &NegotiatedStateCache,
+) {
+    info!("new connection from {:?}!\n", stream.peer_addr());
+    let peer_ip = match stream.peer_addr() {
+        Ok(peer_addr) => peer_addr.ip(),
+        Err(e) => {
+            error!("unable to get peer address - {:?}\n", e);
+            return;
+        }
     };
 
-    let mut my_cert_chain_data = SpdmCertChainData {
-        ..Default::default()
+    if emu_config.idle_timeout != 0 {
+        let idle_timeout = Duration::from_secs(emu_config.idle_timeout as u64);
+        if let Err(e) = stream.set_read_timeout(Some(idle_timeout)) {
+            error!("unable to set idle timeout - {:?}\n", e);
+            return;
+        }
+    }
+    // socket commands other than SPDM messages are answered on a second handle,
+    // as the responder context holds the first one.
+    let mut control_stream = match stream.try_clone() {
+        Ok(control_stream) => control_stream,
+        Err(e) => {
+            error!("unable to clone stream - {:?}\n", e);
+            return;
+        }
     };
+    let mut control_transport_encap = new_transport_encap(emu_config.transport);
+    let mut transport_encap = new_transport_encap(emu_config.transport);
 
-    let ca_file_path = if USE_ECDSA {
-        "test_key/EcP384/ca.cert.der"
-    } else {
-        "test_key/Rsa3072/ca.cert.der"
-    };
-    let ca_cert = std::fs::read(ca_file_path).expect("unable to read ca cert!");
-    let inter_file_path = if USE_ECDSA {
-        "test_key/EcP384/inter.cert.der"
-    } else {
-        "test_key/Rsa3072/inter.cert.der"
-    };
-    let inter_cert = std::fs::read(inter_file_path).expect("unable to read inter cert!");
-    let leaf_file_path = if USE_ECDSA {
-        "test_key/EcP384/end_responder.cert.der"
-    } else {
-        "test_key/Rsa3072/end_responder.cert.der"
+    let config_info = emu_config.get_responder_config_info();
+
+    let my_cert_chain_data = match emu_config.read_responder_cert_chain() {
+        Ok(my_cert_chain_data) => my_cert_chain_data,
+        Err(e) => {
+            error!("unable to read cert chain - {}\n", e);
+            return;
+        }
     };
-    let leaf_cert = std::fs::read(leaf_file_path).expect("unable to read leaf cert!");
-
-    let ca_len = ca_cert.len();
-    let inter_len = inter_cert.len();
-    let leaf_len = leaf_cert.len();
-    println!(
-        "total cert size - {:?} = {:?} + {:?} + {:?}",
-        ca_len + inter_len + leaf_len,
-        ca_len,
-        inter_len,
-        leaf_len
-    );
-    my_cert_chain_data.data_size = (ca_len + inter_len + leaf_len) as u16;
-    my_cert_chain_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
-    my_cert_chain_data.data[ca_len..(ca_len + inter_len)].copy_from_slice(inter_cert.as_ref());
-    my_cert_chain_data.data[(ca_len + inter_len)..(ca_len + inter_len + leaf_len)]
-        .copy_from_slice(leaf_cert.as_ref());
+    info!("total cert size - {:?}\n", my_cert_chain_data.data_size);
 
     let provision_info = common::SpdmProvisionInfo {
         my_cert_chain_data: Some(my_cert_chain_data),
         my_cert_chain: None,
         peer_cert_chain_data: None,
         peer_cert_chain_root_hash: None,
+        psk: emu_config.get_psk(),
+        psk_hint: emu_config.get_psk_hint(),
     };
 
-    spdmlib::crypto::asym_sign::register(ASYM_SIGN_IMPL);
-
+    let mut socket_io_transport = SocketIoTransport::new(
+        &mut stream,
+        emu_config.transport.get_socket_transport_type(),
+    );
+    let mut pldm_responder = PldmResponder {};
+    let mut time_source = EmuTimeSource::new();
     let mut context = responder::ResponderContext::new(
         &mut socket_io_transport,
-        transport_encap,
+        transport_encap.as_mut(),
         config_info,
         provision_info,
     );
+    context.common.register_time_source(&mut time_source);
+    if emu_config.transport == EmuTransport::Mctp {
+        context
+            .register_app_message_handler(
+                MctpMessageType::MctpMessageTypePldm.get_u8(),
+                &mut pldm_responder,
+            )
+            .expect("unable to register PLDM handler");
+    }
+    let cached_state = negotiated_state_cache
+        .lock()
+        .unwrap()
+        .get(&peer_ip)
+        .copied();
+    if let Some(negotiated_state) = cached_state {
+        if context.restore_negotiated_state(&negotiated_state).is_ok() {
+            info!("negotiated state restored\n");
+        }
+    }
 
     loop {
         // if failed, receieved message can't be processed. then the message will need caller to deal.
         // now caller need to deal with message in context.
-        let res = context.process_message();
-        match res {
-            Ok(spdm_result) => {
-                if spdm_result {
-                    continue;
-                } else {
-                    // send unknown spdm command
-                    return Ok(false);
-                }
-            }
-            Err((used, buffer)) => {
-                return Err((used, buffer));
+        if let Err((used, buffer)) = context.process_message() {
+            if !process_socket_message(
+                &mut control_stream,
+                control_transport_encap.as_mut(),
+                &buffer[0..used],
+                emu_config,
+            ) {
+                break;
             }
         }
     }
+
+    match context.common.cached_negotiated_state {
+        Some(negotiated_state) => {
+            negotiated_state_cache
+                .lock()
+                .unwrap()
+                .insert(peer_ip, negotiated_state);
+        }
+        None => {
+            negotiated_state_cache.lock().unwrap().remove(&peer_ip);
+        }
+    }
+
+    let _ = control_stream.shutdown(Shutdown::Both);
+    info!("connection closed!\n");
 }
 
 pub fn send_hello(
@@ -245,7 +273,7 @@ pub fn send_hello(
     transport_encap: &mut dyn SpdmTransportEncap,
     tranport_type: u32,
 ) {
-    println!("get hello");
+    info!("get hello\n");
 
     let mut payload = [0u8; 1024];
 
@@ -266,7 +294,7 @@ pub fn send_unknown(
     transport_encap: &mut dyn SpdmTransportEncap,
     transport_type: u32,
 ) {
-    println!("get unknown");
+    info!("get unknown\n");
 
     let mut payload = [0u8; 1024];
 
@@ -285,7 +313,7 @@ pub fn send_stop(
     transport_encap: &mut dyn SpdmTransportEncap,
     transport_type: u32,
 ) {
-    println!("get stop");
+    info!("get stop\n");
 
     let mut payload = [0u8; 1024];
 
@@ -305,64 +333,22 @@ pub fn send_pci_discovery(
     transport_type: u32,
     buffer: &[u8],
 ) -> bool {
-    let mut reader = Reader::init(buffer);
-    let mut unknown_message = false;
-    match PciDoeMessageHeader::read(&mut reader) {
-        Some(pcidoe_header) => {
-            match pcidoe_header.vendor_id {
-                PciDoeVendorId::PciDoeVendorIdPciSig => {}
-                _ => unknown_message = true,
-            }
-            match pcidoe_header.data_object_type {
-                PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery => {}
-                _ => unknown_message = true,
-            }
+    let discovery_responder = PciDoeDiscoveryResponder::default();
+    let mut transport_buffer = [0u8; 1024];
+    match discovery_responder.process_message(buffer, &mut transport_buffer) {
+        Ok(used) => {
+            let _buffer_size = spdm_emu::spdm_emu::send_message(
+                stream,
+                SOCKET_TRANSPORT_TYPE_PCI_DOE,
+                spdm_emu::spdm_emu::SOCKET_SPDM_COMMAND_NORMAL,
+                &transport_buffer[..used],
+            );
+            //need continue
+            true
+        }
+        Err(_) => {
+            send_unknown(stream, transport_encap, transport_type);
+            false
         }
-        None => unknown_message = true,
-    }
-
-    let payload = &mut [1u8, 0u8, 0u8, 0u8];
-
-    match u8::read(&mut reader) {
-        None => unknown_message = true,
-        Some(discovery_index) => match discovery_index {
-            0 => {
-                payload[2] = 0;
-                payload[3] = 1;
-            }
-            1 => {
-                payload[2] = 1;
-                payload[3] = 2;
-            }
-            2 => {
-                payload[2] = 2;
-                payload[3] = 0;
-            }
-            _ => unknown_message = true,
-        },
-    }
-    if unknown_message {
-        send_unknown(stream, transport_encap, transport_type);
-        return false;
     }
-
-    let payload_len = 4;
-    let mut transport_buffer = [0u8; 1024];
-    let mut writer = Writer::init(&mut transport_buffer);
-    let pcidoe_header = PciDoeMessageHeader {
-        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
-        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
-        payload_length: 4,
-    };
-    pcidoe_header.encode(&mut writer);
-    let header_size = writer.used();
-    transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(payload);
-    let _buffer_size = spdm_emu::spdm_emu::send_message(
-        stream,
-        SOCKET_TRANSPORT_TYPE_PCI_DOE,
-        spdm_emu::spdm_emu::SOCKET_SPDM_COMMAND_NORMAL,
-        &transport_buffer[..(header_size + payload_len)],
-    );
-    //need continue
-    true
 }
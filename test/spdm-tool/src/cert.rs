@@ -0,0 +1,189 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! SPDM certificate chain helpers.
+
+use spdmlib::crypto;
+use spdmlib::msgs::{SpdmBaseHashAlgo, SpdmDigestStruct};
+
+const SPDM_CERT_CHAIN_HEADER_SIZE: usize = 4;
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Split an SPDM cert chain, Length(2) Reserved(2) RootHash(H) Certificates,
+/// into the root hash and the DER encoded certificates.
+pub fn split_spdm_cert_chain(
+    cert_chain: &[u8],
+    hash_size: usize,
+) -> Result<(&[u8], &[u8]), String> {
+    if cert_chain.len() <= SPDM_CERT_CHAIN_HEADER_SIZE + hash_size {
+        return Err("cert chain too short".to_string());
+    }
+    let length = u16::from_le_bytes([cert_chain[0], cert_chain[1]]) as usize;
+    if length != cert_chain.len() {
+        return Err(format!(
+            "cert chain length {} mismatch, {} received",
+            length,
+            cert_chain.len()
+        ));
+    }
+    let certs_begin = SPDM_CERT_CHAIN_HEADER_SIZE + hash_size;
+    Ok((
+        &cert_chain[SPDM_CERT_CHAIN_HEADER_SIZE..certs_begin],
+        &cert_chain[certs_begin..],
+    ))
+}
+
+/// Split concatenated DER certificates.
+pub fn split_der_certs(certs: &[u8]) -> Result<Vec<&[u8]>, String> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset < certs.len() {
+        let size = get_der_size(&certs[offset..])
+            .ok_or_else(|| format!("invalid certificate at offset {}", offset))?;
+        result.push(&certs[offset..(offset + size)]);
+        offset += size;
+    }
+    Ok(result)
+}
+
+// Size of the leading DER SEQUENCE, tag and length included.
+fn get_der_size(der: &[u8]) -> Option<usize> {
+    if der.len() < 2 || der[0] != 0x30 {
+        return None;
+    }
+    let (header_size, length) = if der[1] & 0x80 == 0 {
+        (2, der[1] as usize)
+    } else {
+        let count = (der[1] & 0x7f) as usize;
+        if count == 0 || count > 4 || der.len() < 2 + count {
+            return None;
+        }
+        let length = der[2..(2 + count)]
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+        (2 + count, length)
+    };
+    let size = header_size.checked_add(length)?;
+    if size > der.len() {
+        None
+    } else {
+        Some(size)
+    }
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_TABLE[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// PEM encode one DER certificate.
+pub fn der_to_pem(der: &[u8]) -> String {
+    let encoded = base64_encode(der);
+    let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(core::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----\n");
+    pem
+}
+
+fn check_hash(
+    base_hash_algo: SpdmBaseHashAlgo,
+    data: &[u8],
+    expected: &[u8],
+    name: &str,
+) -> Result<(), String> {
+    let hash = crypto::hash::hash_all(base_hash_algo, data)
+        .ok_or_else(|| format!("unable to hash the {}", name))?;
+    if &hash.data[..(hash.data_size as usize)] != expected {
+        return Err(format!("{} mismatch", name));
+    }
+    Ok(())
+}
+
+/// Verify an SPDM cert chain: the root hash, the digest reported by
+/// GET_DIGESTS if given and the certificate signatures.
+pub fn verify_spdm_cert_chain(
+    base_hash_algo: SpdmBaseHashAlgo,
+    cert_chain: &[u8],
+    digest: Option<&SpdmDigestStruct>,
+) -> Result<(), String> {
+    let (root_hash, certs) = split_spdm_cert_chain(cert_chain, base_hash_algo.get_size() as usize)?;
+    let der_certs = split_der_certs(certs)?;
+    check_hash(base_hash_algo, der_certs[0], root_hash, "root hash")?;
+    if let Some(digest) = digest {
+        check_hash(
+            base_hash_algo,
+            cert_chain,
+            &digest.data[..(digest.data_size as usize)],
+            "cert chain digest",
+        )?;
+    }
+    crypto::cert_operation::verify_cert_chain(certs)
+        .map_err(|_| "cert chain signature verification fail".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_case0_der_to_pem() {
+        let pem = der_to_pem(&[0x30u8; 60]);
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "-----BEGIN CERTIFICATE-----");
+        assert_eq!(lines[1].len(), 64);
+        assert_eq!(lines[2].len(), 16);
+        assert_eq!(lines[3], "-----END CERTIFICATE-----");
+    }
+
+    #[test]
+    fn test_case0_split_der_certs() {
+        let mut certs = vec![0x30u8, 0x02, 0x01, 0x02, 0x30, 0x81, 0x80];
+        certs.extend_from_slice(&[0u8; 0x80]);
+        let der_certs = split_der_certs(&certs).unwrap();
+        assert_eq!(der_certs.len(), 2);
+        assert_eq!(der_certs[0], &[0x30u8, 0x02, 0x01, 0x02]);
+        assert_eq!(der_certs[1].len(), 0x83);
+
+        assert!(split_der_certs(&certs[..(certs.len() - 1)]).is_err());
+        assert!(split_der_certs(&[0x31u8, 0x00]).is_err());
+        assert!(split_der_certs(&[0x30u8, 0x85, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_case0_split_spdm_cert_chain() {
+        let cert_chain = [0x08u8, 0x00, 0x00, 0x00, 0xaa, 0xbb, 0x30, 0x00];
+        let (root_hash, certs) = split_spdm_cert_chain(&cert_chain, 2).unwrap();
+        assert_eq!(root_hash, &[0xaau8, 0xbb]);
+        assert_eq!(certs, &[0x30u8, 0x00]);
+
+        assert!(split_spdm_cert_chain(&cert_chain[..7], 2).is_err());
+        assert!(split_spdm_cert_chain(&cert_chain, 4).is_err());
+    }
+}
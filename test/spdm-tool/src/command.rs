@@ -0,0 +1,783 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use std::io::Write;
+
+use codec::{Codec, Reader, Writer};
+use serde_json::{json, Value};
+use spdm_emu::emu_config::{parse_hex, parse_number, EmuConfig};
+use spdmlib::config;
+use spdmlib::error::SpdmError;
+use spdmlib::msgs::*;
+use spdmlib::requester::RequesterContext;
+
+use crate::cert;
+
+pub const TOOL_USAGE: &str = "\
+[options] <command> [command options]
+
+   version                        print the SPDM versions of the responder
+   capabilities                   print the responder capabilities
+   algorithms                     print the negotiated algorithms
+   digests                        print the cert chain digest of each slot
+   certs [--slot <0~7>] [--format PEM|DER] [--out <prefix>]
+                                  dump and verify the cert chain of each slot,
+                                  DER needs --out, files are <prefix><slot>.pem|der
+   challenge                      challenge the responder with --slot_id and --meas_sum
+   measurements [--index <1~254>]
+                                  print all or one signed measurement block as JSON
+   session [--type KEY_EX|PSK] [--ops HEARTBEAT,KEY_UPDATE,END]
+                                  start a session and run the operations in order
+   vendor --standard_id <id> --vendor_id <hex> [--data <hex>] [--session NONE|KEY_EX|PSK]
+                                  send a vendor defined request and print the response";
+
+const SPDM_VENDOR_DEFINED_RESPONSE: u8 = 0x7E;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CertFormat {
+    Pem,
+    Der,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolCommand {
+    Version,
+    Capabilities,
+    Algorithms,
+    Digests,
+    Certs {
+        slot_id: Option<u8>,
+        format: CertFormat,
+        out: Option<String>,
+    },
+    Challenge,
+    Measurements {
+        index: Option<u8>,
+    },
+    Session {
+        use_psk: bool,
+        heartbeat: bool,
+        key_update: bool,
+        end: bool,
+    },
+    Vendor {
+        standard_id: u16,
+        vendor_id: Vec<u8>,
+        data: Vec<u8>,
+        /// None to send out of session, else whether to use a PSK session.
+        session: Option<bool>,
+    },
+}
+
+fn parse_session_type(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "KEY_EX" => Ok(false),
+        "PSK" => Ok(true),
+        _ => Err(format!("invalid value {} for --{}", value, name)),
+    }
+}
+
+impl ToolCommand {
+    /// Parse a command and its `--name value` options.
+    pub fn parse(command: &str, args: &[String]) -> Result<ToolCommand, String> {
+        let mut options = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unknown argument {}", arg))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            options.push((name, value.as_str()));
+        }
+        let unknown_option = |name: &str| Err(format!("unknown option --{} for {}", name, command));
+
+        let mut tool_command = match command {
+            "version" => ToolCommand::Version,
+            "capabilities" => ToolCommand::Capabilities,
+            "algorithms" => ToolCommand::Algorithms,
+            "digests" => ToolCommand::Digests,
+            "certs" => ToolCommand::Certs {
+                slot_id: None,
+                format: CertFormat::Pem,
+                out: None,
+            },
+            "challenge" => ToolCommand::Challenge,
+            "measurements" => ToolCommand::Measurements { index: None },
+            "session" => ToolCommand::Session {
+                use_psk: false,
+                heartbeat: true,
+                key_update: true,
+                end: true,
+            },
+            "vendor" => ToolCommand::Vendor {
+                standard_id: 0,
+                vendor_id: Vec::new(),
+                data: Vec::new(),
+                session: None,
+            },
+            _ => return Err(format!("unknown command {}", command)),
+        };
+
+        let mut has_standard_id = false;
+        for (name, value) in options {
+            match &mut tool_command {
+                ToolCommand::Certs {
+                    slot_id,
+                    format,
+                    out,
+                } => match name {
+                    "slot" => {
+                        let slot = parse_number(name, value)?;
+                        if slot as usize >= SPDM_MAX_SLOT_NUMBER {
+                            return Err(format!("invalid slot {}", value));
+                        }
+                        *slot_id = Some(slot as u8);
+                    }
+                    "format" => {
+                        *format = match value {
+                            "PEM" => CertFormat::Pem,
+                            "DER" => CertFormat::Der,
+                            _ => return Err(format!("invalid value {} for --format", value)),
+                        }
+                    }
+                    "out" => *out = Some(value.to_string()),
+                    _ => return unknown_option(name),
+                },
+                ToolCommand::Measurements { index } => match name {
+                    "index" => {
+                        let block_index = parse_number(name, value)?;
+                        if block_index == 0 || block_index >= 0xFF {
+                            return Err(format!("invalid index {}", value));
+                        }
+                        *index = Some(block_index as u8);
+                    }
+                    _ => return unknown_option(name),
+                },
+                ToolCommand::Session {
+                    use_psk,
+                    heartbeat,
+                    key_update,
+                    end,
+                } => match name {
+                    "type" => *use_psk = parse_session_type(name, value)?,
+                    "ops" => {
+                        *heartbeat = false;
+                        *key_update = false;
+                        *end = false;
+                        for op in value.split(',').filter(|op| !op.is_empty()) {
+                            match op {
+                                "HEARTBEAT" => *heartbeat = true,
+                                "KEY_UPDATE" => *key_update = true,
+                                "END" => *end = true,
+                                _ => return Err(format!("invalid value {} for --ops", op)),
+                            }
+                        }
+                    }
+                    _ => return unknown_option(name),
+                },
+                ToolCommand::Vendor {
+                    standard_id,
+                    vendor_id,
+                    data,
+                    session,
+                } => match name {
+                    "standard_id" => {
+                        let id = parse_number(name, value)?;
+                        if id > u16::MAX as u32 {
+                            return Err(format!("invalid standard id {}", value));
+                        }
+                        *standard_id = id as u16;
+                        has_standard_id = true;
+                    }
+                    "vendor_id" => {
+                        *vendor_id = parse_hex(name, value)?;
+                        if vendor_id.len() > u8::MAX as usize {
+                            return Err("invalid --vendor_id size".to_string());
+                        }
+                    }
+                    "data" => *data = parse_hex(name, value)?,
+                    "session" => {
+                        *session = match value {
+                            "NONE" => None,
+                            _ => Some(parse_session_type(name, value)?),
+                        }
+                    }
+                    _ => return unknown_option(name),
+                },
+                _ => return unknown_option(name),
+            }
+        }
+
+        match &tool_command {
+            ToolCommand::Certs {
+                format: CertFormat::Der,
+                out: None,
+                ..
+            } => Err("--format DER needs --out".to_string()),
+            ToolCommand::Vendor { .. } if !has_standard_id => {
+                Err("vendor needs --standard_id".to_string())
+            }
+            _ => Ok(tool_command),
+        }
+    }
+}
+
+/// Split the tool arguments, without the program name, into the emulator
+/// options before the command and the command with its options.
+pub fn parse_args(args: &[String]) -> Result<(EmuConfig, ToolCommand), String> {
+    let mut command_begin = 0;
+    while command_begin < args.len() && args[command_begin].starts_with("--") {
+        command_begin += 2;
+    }
+    if command_begin >= args.len() {
+        return Err("missing command".to_string());
+    }
+    let emu_config = EmuConfig::from_args(args[..command_begin].iter().cloned())?;
+    let tool_command = ToolCommand::parse(&args[command_begin], &args[(command_begin + 1)..])?;
+    Ok((emu_config, tool_command))
+}
+
+fn spdm_error(request: &'static str) -> impl Fn(SpdmError) -> String {
+    move |e| format!("{} fail - {:?}", request, e)
+}
+
+fn io_error(e: std::io::Error) -> String {
+    e.to_string()
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn version_to_string(version: SpdmVersion) -> String {
+    let version = version.get_u8();
+    format!("{}.{}", version >> 4, version & 0xf)
+}
+
+fn measurement_type_to_string(r#type: SpdmDmtfMeasurementType) -> String {
+    match r#type {
+        SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom => "rom".to_string(),
+        SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware => "firmware".to_string(),
+        SpdmDmtfMeasurementType::SpdmDmtfMeasurementHardwareConfig => "hardware_config".to_string(),
+        SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmwareConfig => "firmware_config".to_string(),
+        SpdmDmtfMeasurementType::SpdmDmtfMeasurementManifest => "manifest".to_string(),
+        SpdmDmtfMeasurementType::Unknown(r#type) => format!("0x{:02x}", r#type),
+    }
+}
+
+/// Render the measurement blocks of a MEASUREMENTS response.
+pub fn measurement_record_to_json(
+    slot_id: u8,
+    measurement_record: &SpdmMeasurementRecordStructure,
+) -> Value {
+    let number_of_blocks =
+        (measurement_record.number_of_blocks as usize).min(measurement_record.record.len());
+    let blocks: Vec<Value> = measurement_record.record[..number_of_blocks]
+        .iter()
+        .map(|block| {
+            let measurement = &block.measurement;
+            json!({
+                "index": block.index,
+                "measurement_specification": block.measurement_specification.bits(),
+                "type": measurement_type_to_string(measurement.r#type),
+                "representation": match measurement.representation {
+                    SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit => "raw_bit_stream",
+                    _ => "digest",
+                },
+                "value": to_hex(&measurement.value[..(measurement.value_size as usize)]),
+            })
+        })
+        .collect();
+    json!({
+        "slot_id": slot_id,
+        "number_of_blocks": number_of_blocks,
+        "blocks": blocks,
+    })
+}
+
+fn get_slot_digest(
+    peer_digests: &SpdmDigestsResponsePayload,
+    slot_id: u8,
+) -> Option<&SpdmDigestStruct> {
+    if peer_digests.slot_mask & (1 << slot_id) == 0 {
+        return None;
+    }
+    // digests are listed for the slots set in the mask only
+    let position = (peer_digests.slot_mask & ((1u8 << slot_id) - 1)).count_ones() as usize;
+    peer_digests.digests.get(position)
+}
+
+fn get_slots(slot_mask: u8) -> Vec<u8> {
+    (0..SPDM_MAX_SLOT_NUMBER as u8)
+        .filter(|slot_id| slot_mask & (1 << slot_id) != 0)
+        .collect()
+}
+
+// GET_DIGESTS and GET_CERTIFICATE as needed before a signed request.
+fn get_certificate(context: &mut RequesterContext, slot_id: u8) -> Result<(), String> {
+    context
+        .send_receive_spdm_digest()
+        .map_err(spdm_error("GET_DIGESTS"))?;
+    context
+        .send_receive_spdm_certificate(slot_id)
+        .map_err(spdm_error("GET_CERTIFICATE"))
+}
+
+fn start_session(
+    context: &mut RequesterContext,
+    emu_config: &EmuConfig,
+    use_psk: bool,
+) -> Result<u32, String> {
+    if !use_psk {
+        get_certificate(context, emu_config.slot_id)?;
+    }
+    context
+        .start_session(
+            use_psk,
+            emu_config.slot_id,
+            emu_config.measurement_summary_hash_type,
+        )
+        .map_err(spdm_error(if use_psk {
+            "PSK_EXCHANGE"
+        } else {
+            "KEY_EXCHANGE"
+        }))
+}
+
+fn print_version(context: &mut RequesterContext, output: &mut dyn Write) -> Result<(), String> {
+    let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+    let send_used = context.encode_spdm_version(&mut send_buffer);
+    context
+        .send_message(&send_buffer[..send_used])
+        .map_err(spdm_error("GET_VERSION"))?;
+
+    let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+    let used = context
+        .receive_message(&mut receive_buffer)
+        .map_err(spdm_error("GET_VERSION"))?;
+    context
+        .handle_spdm_version_response(&send_buffer[..send_used], &receive_buffer[..used])
+        .map_err(spdm_error("GET_VERSION"))?;
+
+    let mut reader = Reader::init(&receive_buffer[..used]);
+    SpdmMessageHeader::read(&mut reader).ok_or("invalid VERSION")?;
+    let version =
+        SpdmVersionResponsePayload::spdm_read(&context.common.get_decode_info(), &mut reader)
+            .ok_or("invalid VERSION")?;
+    let versions: Vec<String> = version.versions[..(version.version_number_entry_count as usize)]
+        .iter()
+        .map(|v| version_to_string(v.version))
+        .collect();
+    writeln!(output, "versions: {}", versions.join(", ")).map_err(io_error)
+}
+
+fn print_algorithms(context: &mut RequesterContext, output: &mut dyn Write) -> Result<(), String> {
+    let negotiate_info = &context.common.negotiate_info;
+    writeln!(
+        output,
+        "measurement_specification: {:?}\n\
+         measurement_hash_algo: {:?}\n\
+         base_asym_algo: {:?}\n\
+         base_hash_algo: {:?}\n\
+         dhe_algo: {:?}\n\
+         aead_algo: {:?}\n\
+         req_asym_algo: {:?}\n\
+         key_schedule_algo: {:?}",
+        negotiate_info.measurement_specification_sel,
+        negotiate_info.measurement_hash_sel,
+        negotiate_info.base_asym_sel,
+        negotiate_info.base_hash_sel,
+        negotiate_info.dhe_sel,
+        negotiate_info.aead_sel,
+        negotiate_info.req_asym_sel,
+        negotiate_info.key_schedule_sel,
+    )
+    .map_err(io_error)
+}
+
+fn print_certs(
+    context: &mut RequesterContext,
+    slot_id: Option<u8>,
+    format: CertFormat,
+    out: &Option<String>,
+    output: &mut dyn Write,
+) -> Result<(), String> {
+    context
+        .send_receive_spdm_digest()
+        .map_err(spdm_error("GET_DIGESTS"))?;
+    let peer_digests = context.common.peer_info.peer_digests;
+    let slots = match slot_id {
+        Some(slot_id) => vec![slot_id],
+        None => get_slots(peer_digests.slot_mask),
+    };
+
+    let mut verified = true;
+    for slot_id in slots {
+        context
+            .send_receive_spdm_certificate(slot_id)
+            .map_err(spdm_error("GET_CERTIFICATE"))?;
+        let base_hash_algo = context.common.negotiate_info.base_hash_sel;
+        let cert_chain = &context.common.peer_info.peer_cert_chain.cert_chain;
+        let cert_chain = &cert_chain.data[..(cert_chain.data_size as usize)];
+
+        let result = cert::verify_spdm_cert_chain(
+            base_hash_algo,
+            cert_chain,
+            get_slot_digest(&peer_digests, slot_id),
+        );
+        match &result {
+            Ok(()) => writeln!(output, "slot {}: verification pass", slot_id),
+            Err(e) => writeln!(output, "slot {}: verification fail - {}", slot_id, e),
+        }
+        .map_err(io_error)?;
+        verified &= result.is_ok();
+
+        let (_, certs) =
+            cert::split_spdm_cert_chain(cert_chain, base_hash_algo.get_size() as usize)?;
+        let dump = match format {
+            CertFormat::Pem => cert::split_der_certs(certs)?
+                .iter()
+                .map(|der| cert::der_to_pem(der))
+                .collect::<String>()
+                .into_bytes(),
+            CertFormat::Der => certs.to_vec(),
+        };
+        match out {
+            Some(prefix) => {
+                let extension = match format {
+                    CertFormat::Pem => "pem",
+                    CertFormat::Der => "der",
+                };
+                let path = format!("{}{}.{}", prefix, slot_id, extension);
+                std::fs::write(&path, &dump).map_err(|e| format!("{}: {}", path, e))?;
+                writeln!(output, "slot {}: saved to {}", slot_id, path).map_err(io_error)?;
+            }
+            None => output.write_all(&dump).map_err(io_error)?,
+        }
+    }
+
+    if verified {
+        Ok(())
+    } else {
+        Err("cert chain verification fail".to_string())
+    }
+}
+
+fn send_receive_vendor(
+    context: &mut RequesterContext,
+    session_id: Option<u32>,
+    standard_id: u16,
+    vendor_id: &[u8],
+    data: &[u8],
+    output: &mut dyn Write,
+) -> Result<(), String> {
+    if data.len() > u16::MAX as usize {
+        return Err("vendor data too large".to_string());
+    }
+    let mut send_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+    let mut writer = Writer::init(&mut send_buffer);
+    SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion11,
+        request_response_code: SpdmResponseResponseCode::SpdmRequestVendorDefinedRequest,
+    }
+    .encode(&mut writer);
+    0u8.encode(&mut writer); // param1
+    0u8.encode(&mut writer); // param2
+    standard_id.encode(&mut writer);
+    (vendor_id.len() as u8).encode(&mut writer);
+    writer
+        .extend_from_slice(vendor_id)
+        .ok_or("vendor request too large")?;
+    (data.len() as u16).encode(&mut writer);
+    writer
+        .extend_from_slice(data)
+        .ok_or("vendor request too large")?;
+    let send_used = writer.used();
+
+    let mut receive_buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+    let used = match session_id {
+        Some(session_id) => {
+            context
+                .send_secured_message(session_id, &send_buffer[..send_used], None)
+                .map_err(spdm_error("VENDOR_DEFINED_REQUEST"))?;
+            context
+                .receive_secured_message(session_id, &mut receive_buffer)
+                .map_err(spdm_error("VENDOR_DEFINED_REQUEST"))?
+        }
+        None => {
+            context
+                .send_message(&send_buffer[..send_used])
+                .map_err(spdm_error("VENDOR_DEFINED_REQUEST"))?;
+            context
+                .receive_message(&mut receive_buffer)
+                .map_err(spdm_error("VENDOR_DEFINED_REQUEST"))?
+        }
+    };
+    let response = &receive_buffer[..used];
+    writeln!(output, "response: {}", to_hex(response)).map_err(io_error)?;
+
+    // Version(1) Code(1) Param1(1) Param2(1) StandardID(2) Len(1) VendorID(Len) RespLength(2) Payload
+    if response.len() < 7 || response[1] != SPDM_VENDOR_DEFINED_RESPONSE {
+        return Err("unexpected vendor defined response".to_string());
+    }
+    let vendor_id_end = 7 + response[6] as usize;
+    if response.len() < vendor_id_end + 2 {
+        return Err("invalid vendor defined response".to_string());
+    }
+    let payload_size =
+        u16::from_le_bytes([response[vendor_id_end], response[vendor_id_end + 1]]) as usize;
+    let payload = response
+        .get((vendor_id_end + 2)..(vendor_id_end + 2 + payload_size))
+        .ok_or("invalid vendor defined response")?;
+    writeln!(
+        output,
+        "standard_id: 0x{:04x}\nvendor_id: {}\npayload: {}",
+        u16::from_le_bytes([response[4], response[5]]),
+        to_hex(&response[7..vendor_id_end]),
+        to_hex(payload)
+    )
+    .map_err(io_error)
+}
+
+/// Run the command with a requester context over any device IO and transport,
+/// writing the results to output.
+pub fn run_command(
+    context: &mut RequesterContext,
+    emu_config: &EmuConfig,
+    command: &ToolCommand,
+    output: &mut dyn Write,
+) -> Result<(), String> {
+    match command {
+        ToolCommand::Version => return print_version(context, output),
+        ToolCommand::Capabilities => {
+            context
+                .send_receive_spdm_version()
+                .map_err(spdm_error("GET_VERSION"))?;
+            context
+                .send_receive_spdm_capability()
+                .map_err(spdm_error("GET_CAPABILITIES"))?;
+            let negotiate_info = &context.common.negotiate_info;
+            return writeln!(
+                output,
+                "ct_exponent: {}\nflags: {:?}",
+                negotiate_info.rsp_ct_exponent_sel, negotiate_info.rsp_capabilities_sel
+            )
+            .map_err(io_error);
+        }
+        _ => {}
+    }
+
+    context
+        .init_connection()
+        .map_err(spdm_error("connection"))?;
+
+    match command {
+        ToolCommand::Algorithms => print_algorithms(context, output),
+        ToolCommand::Digests => {
+            context
+                .send_receive_spdm_digest()
+                .map_err(spdm_error("GET_DIGESTS"))?;
+            let peer_digests = context.common.peer_info.peer_digests;
+            for slot_id in get_slots(peer_digests.slot_mask) {
+                let digest = get_slot_digest(&peer_digests, slot_id).unwrap();
+                writeln!(
+                    output,
+                    "slot {}: {}",
+                    slot_id,
+                    to_hex(&digest.data[..(digest.data_size as usize)])
+                )
+                .map_err(io_error)?;
+            }
+            Ok(())
+        }
+        ToolCommand::Certs {
+            slot_id,
+            format,
+            out,
+        } => print_certs(context, *slot_id, *format, out, output),
+        ToolCommand::Challenge => {
+            get_certificate(context, emu_config.slot_id)?;
+            context
+                .send_receive_spdm_challenge(
+                    emu_config.slot_id,
+                    emu_config.measurement_summary_hash_type,
+                )
+                .map_err(spdm_error("CHALLENGE"))?;
+            writeln!(output, "challenge: pass").map_err(io_error)
+        }
+        ToolCommand::Measurements { index } => {
+            get_certificate(context, emu_config.slot_id)?;
+            let measurement_operation = match index {
+                Some(index) => SpdmMeasurementOperation::Unknown(*index),
+                None => SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            };
+            context
+                .send_receive_spdm_measurement(measurement_operation, emu_config.slot_id)
+                .map_err(spdm_error("GET_MEASUREMENTS"))?;
+            let json = measurement_record_to_json(
+                emu_config.slot_id,
+                &context.common.peer_info.peer_measurement_record,
+            );
+            writeln!(
+                output,
+                "{}",
+                serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?
+            )
+            .map_err(io_error)
+        }
+        ToolCommand::Session {
+            use_psk,
+            heartbeat,
+            key_update,
+            end,
+        } => {
+            let session_id = start_session(context, emu_config, *use_psk)?;
+            writeln!(output, "session 0x{:08x}: started", session_id).map_err(io_error)?;
+            if *heartbeat {
+                context
+                    .send_receive_spdm_heartbeat(session_id)
+                    .map_err(spdm_error("HEARTBEAT"))?;
+                writeln!(output, "session 0x{:08x}: heartbeat", session_id).map_err(io_error)?;
+            }
+            if *key_update {
+                context
+                    .send_receive_spdm_key_update(session_id, emu_config.key_update_operation)
+                    .map_err(spdm_error("KEY_UPDATE"))?;
+                writeln!(output, "session 0x{:08x}: key updated", session_id).map_err(io_error)?;
+            }
+            if *end {
+                context
+                    .end_session(session_id)
+                    .map_err(spdm_error("END_SESSION"))?;
+                writeln!(output, "session 0x{:08x}: ended", session_id).map_err(io_error)?;
+            }
+            Ok(())
+        }
+        ToolCommand::Vendor {
+            standard_id,
+            vendor_id,
+            data,
+            session,
+        } => {
+            let session_id = match session {
+                Some(use_psk) => Some(start_session(context, emu_config, *use_psk)?),
+                None => None,
+            };
+            let result =
+                send_receive_vendor(context, session_id, *standard_id, vendor_id, data, output);
+            if let Some(session_id) = session_id {
+                context
+                    .end_session(session_id)
+                    .map_err(spdm_error("END_SESSION"))?;
+            }
+            result
+        }
+        ToolCommand::Version | ToolCommand::Capabilities => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_case0_parse_args() {
+        let (emu_config, command) = parse_args(&args(
+            "--trans MCTP --port 4000 certs --slot 1 --format DER --out slot",
+        ))
+        .unwrap();
+        assert_eq!(emu_config.get_address(), "127.0.0.1:4000");
+        assert_eq!(
+            command,
+            ToolCommand::Certs {
+                slot_id: Some(1),
+                format: CertFormat::Der,
+                out: Some("slot".to_string())
+            }
+        );
+
+        let (_, command) = parse_args(&args("session --type PSK --ops END")).unwrap();
+        assert_eq!(
+            command,
+            ToolCommand::Session {
+                use_psk: true,
+                heartbeat: false,
+                key_update: false,
+                end: true
+            }
+        );
+
+        let (_, command) = parse_args(&args(
+            "vendor --standard_id 0x3 --vendor_id 8086 --data 0102 --session KEY_EX",
+        ))
+        .unwrap();
+        assert_eq!(
+            command,
+            ToolCommand::Vendor {
+                standard_id: 3,
+                vendor_id: vec![0x80, 0x86],
+                data: vec![0x01, 0x02],
+                session: Some(false)
+            }
+        );
+
+        let (_, command) = parse_args(&args("measurements --index 2")).unwrap();
+        assert_eq!(command, ToolCommand::Measurements { index: Some(2) });
+    }
+
+    #[test]
+    fn test_case1_parse_args() {
+        assert!(parse_args(&args("")).is_err());
+        assert!(parse_args(&args("--trans MCTP")).is_err());
+        assert!(parse_args(&args("--trans TCP version")).is_err());
+        assert!(parse_args(&args("unknown")).is_err());
+        assert!(parse_args(&args("version --slot 1")).is_err());
+        assert!(parse_args(&args("certs --slot 8")).is_err());
+        assert!(parse_args(&args("certs --format DER")).is_err());
+        assert!(parse_args(&args("measurements --index 0")).is_err());
+        assert!(parse_args(&args("session --ops FOO")).is_err());
+        assert!(parse_args(&args("vendor --vendor_id 8086")).is_err());
+        assert!(parse_args(&args("vendor --standard_id")).is_err());
+    }
+
+    #[test]
+    fn test_case0_measurement_record_to_json() {
+        let mut measurement_record = SpdmMeasurementRecordStructure {
+            number_of_blocks: 1,
+            ..Default::default()
+        };
+        measurement_record.record[0].index = 2;
+        measurement_record.record[0].measurement_specification = SpdmMeasurementSpecification::DMTF;
+        measurement_record.record[0].measurement.r#type =
+            SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware;
+        measurement_record.record[0].measurement.value_size = 2;
+        measurement_record.record[0].measurement.value[..2].copy_from_slice(&[0xab, 0xcd]);
+
+        let json = measurement_record_to_json(0, &measurement_record);
+        assert_eq!(json["number_of_blocks"], 1);
+        assert_eq!(json["blocks"][0]["index"], 2);
+        assert_eq!(json["blocks"][0]["type"], "firmware");
+        assert_eq!(json["blocks"][0]["representation"], "digest");
+        assert_eq!(json["blocks"][0]["value"], "abcd");
+    }
+
+    #[test]
+    fn test_case0_get_slot_digest() {
+        let mut peer_digests = SpdmDigestsResponsePayload {
+            slot_mask: 0b0000_0101,
+            slot_count: 2,
+            ..Default::default()
+        };
+        peer_digests.digests[1].data_size = 48;
+        assert_eq!(get_slots(peer_digests.slot_mask), vec![0, 2]);
+        assert!(get_slot_digest(&peer_digests, 1).is_none());
+        assert_eq!(get_slot_digest(&peer_digests, 2).unwrap().data_size, 48);
+    }
+}
@@ -0,0 +1,16 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! spdm-tool inspects an SPDM responder with a requester context.
+//!
+//! `run_command` works over any `SpdmDeviceIo` and `SpdmTransportEncap`;
+//! the spdm-tool binary connects to the emulator socket, e.g.
+//! `spdm-tool --trans MCTP --port 2323 certs --format PEM`.
+
+#![forbid(unsafe_code)]
+
+pub mod cert;
+pub mod command;
+
+pub use command::{parse_args, run_command, CertFormat, ToolCommand, TOOL_USAGE};
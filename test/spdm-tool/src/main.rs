@@ -0,0 +1,116 @@
+// Copyright (c) 2021 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+#![forbid(unsafe_code)]
+
+use log::LevelFilter;
+use simple_logger::SimpleLogger;
+
+use std::net::TcpStream;
+
+use mctp_transport::MctpTransportEncap;
+use pcidoe_transport::PciDoeTransportEncap;
+use spdm_emu::emu_config::{EmuTransport, EMU_USAGE};
+use spdm_emu::socket_io_transport::SocketIoTransport;
+use spdm_emu::spdm_emu::*;
+use spdm_tool::{parse_args, run_command, TOOL_USAGE};
+use spdmlib::common::{self, SpdmTransportEncap};
+use spdmlib::config;
+use spdmlib::requester;
+
+// The user may choose the log level by setting a `SPDM_LOG` environment variable.
+// Only errors are logged by default, to keep the output readable.
+fn new_logger_from_env() -> SimpleLogger {
+    let level = match std::env::var("SPDM_LOG") {
+        Ok(x) => match x.to_lowercase().as_str() {
+            "trace" => LevelFilter::Trace,
+            "debug" => LevelFilter::Debug,
+            "info" => LevelFilter::Info,
+            "warn" => LevelFilter::Warn,
+            _ => LevelFilter::Error,
+        },
+        _ => LevelFilter::Error,
+    };
+
+    SimpleLogger::new().with_level(level)
+}
+
+fn send_receive_stop(
+    stream: &mut TcpStream,
+    transport_encap: &mut dyn SpdmTransportEncap,
+    transport_type: u32,
+) {
+    let mut payload = [0u8; 1024];
+    let used = transport_encap.encap(b"", &mut payload[..], false).unwrap();
+    send_message(
+        stream,
+        transport_type,
+        SOCKET_SPDM_COMMAND_STOP,
+        &payload[..used],
+    );
+    let mut buffer = [0u8; config::MAX_SPDM_TRANSPORT_SIZE];
+    let _ = receive_message(stream, &mut buffer[..]);
+}
+
+fn main() {
+    let mut args = std::env::args();
+    let program = args.next().unwrap_or_default();
+    let args: Vec<String> = args.collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{} {}\n\n   options:\n{}", program, TOOL_USAGE, EMU_USAGE);
+        return;
+    }
+    let (emu_config, command) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{}\n\n{} {}", err, program, TOOL_USAGE);
+            std::process::exit(1);
+        }
+    };
+    new_logger_from_env().init().unwrap();
+
+    let mut socket = match TcpStream::connect(emu_config.get_address()) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("unable to connect to {} - {}", emu_config.get_address(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mctp_transport_encap = &mut MctpTransportEncap {};
+    let transport_encap: &mut dyn SpdmTransportEncap = match emu_config.transport {
+        EmuTransport::PciDoe => pcidoe_transport_encap,
+        EmuTransport::Mctp => mctp_transport_encap,
+    };
+    let transport_type = emu_config.transport.get_socket_transport_type();
+
+    // the peer cert chain is not provisioned, it is what the tool inspects.
+    let provision_info = common::SpdmProvisionInfo {
+        my_cert_chain_data: None,
+        my_cert_chain: None,
+        peer_cert_chain_data: None,
+        peer_cert_chain_root_hash: None,
+        psk: emu_config.get_psk(),
+        psk_hint: emu_config.get_psk_hint(),
+    };
+
+    let result = {
+        let socket_io_transport = &mut SocketIoTransport::new(&mut socket, transport_type);
+        let mut context = requester::RequesterContext::new(
+            socket_io_transport,
+            transport_encap,
+            emu_config.get_requester_config_info(),
+            provision_info,
+        );
+        run_command(&mut context, &emu_config, &command, &mut std::io::stdout())
+    };
+
+    send_receive_stop(&mut socket, transport_encap, transport_type);
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}